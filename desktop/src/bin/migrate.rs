@@ -0,0 +1,109 @@
+use std::path::Path;
+use std::process::exit;
+
+use magda_desktop::{cassandra, migrations};
+
+const USAGE: &str =
+    "Usage: migrate <status|dry-run|apply> <directory> <keyspace> [host] [port] [username] [password]";
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::fmt().with_env_filter("info").init();
+
+    let args: Vec<String> = std::env::args().collect();
+    let (Some(command), Some(directory), Some(keyspace)) = (args.get(1), args.get(2), args.get(3))
+    else {
+        eprintln!("{}", USAGE);
+        exit(2);
+    };
+    if !["status", "dry-run", "apply"].contains(&command.as_str()) {
+        eprintln!("{}", USAGE);
+        exit(2);
+    }
+    let host = args.get(4).map(|s| s.as_str()).unwrap_or("localhost");
+    let port: u16 = args.get(5).and_then(|p| p.parse().ok()).unwrap_or(9042);
+    let username = args.get(6).map(|s| s.as_str());
+    let password = args.get(7).map(|s| s.as_str());
+
+    let local = match migrations::load_dir(Path::new(directory)) {
+        Ok(local) => local,
+        Err(e) => {
+            eprintln!("Failed to read migrations: {}", e);
+            exit(1);
+        }
+    };
+
+    let session = match cassandra::create_session(host, port, username, password).await {
+        Ok(session) => session,
+        Err(e) => {
+            eprintln!("Failed to connect to {}:{}: {}", host, port, e);
+            exit(1);
+        }
+    };
+
+    let applied = match migrations::applied_migrations(&session, keyspace).await {
+        Ok(applied) => applied,
+        Err(e) => {
+            eprintln!("Failed to read applied migrations: {}", e);
+            exit(1);
+        }
+    };
+    let statuses = migrations::status(&local, &applied);
+    let problems = migrations::problems(&statuses);
+
+    match command.as_str() {
+        "status" => {
+            for status in &statuses {
+                let applied_at = status
+                    .applied_at
+                    .map(|at| at.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+                    .unwrap_or_default();
+                println!(
+                    "{:>6}  {:<12}  {:<40}  {}",
+                    status.version,
+                    status.state.label(),
+                    status.description,
+                    applied_at
+                );
+            }
+        }
+        "dry-run" => {
+            let pending = migrations::pending(&local, &statuses);
+            if pending.is_empty() {
+                println!("-- No pending migrations");
+            } else {
+                print!("{}", migrations::dry_run_script(keyspace, &pending));
+            }
+        }
+        _ => {
+            if problems.is_empty() {
+                match migrations::apply_pending(&session, keyspace, &local).await {
+                    Ok(report) => {
+                        println!(
+                            "Applied {} migration(s): {:?}",
+                            report.applied.len(),
+                            report.applied
+                        );
+                        if report.agreement_timeouts > 0 {
+                            println!(
+                                "Schema agreement timed out after {} statement(s)",
+                                report.agreement_timeouts
+                            );
+                        }
+                    }
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        exit(1);
+                    }
+                }
+            }
+        }
+    }
+
+    for problem in &problems {
+        eprintln!("{}", problem);
+    }
+    if !problems.is_empty() {
+        exit(1);
+    }
+}
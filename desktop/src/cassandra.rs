@@ -1,17 +1,44 @@
-use cdrs_tokio::authenticators::StaticPasswordAuthenticatorProvider;
-use cdrs_tokio::cluster::session::{SessionBuilder, TcpSessionBuilder};
-use cdrs_tokio::cluster::NodeTcpConfigBuilder;
+use cdrs_tokio::authenticators::{
+    NoneAuthenticatorProvider, SaslAuthenticatorProvider, StaticPasswordAuthenticatorProvider,
+};
+use cdrs_tokio::cluster::session::{
+    SessionBuilder, TcpSessionBuilder, DEFAULT_TRANSPORT_BUFFER_SIZE,
+};
+use cdrs_tokio::cluster::topology::NodeState;
+use cdrs_tokio::cluster::{
+    ConnectionManager, KeyspaceHolder, NodeTcpConfigBuilder, TcpConnectionManager,
+};
+use cdrs_tokio::compression::Compression;
+use cdrs_tokio::frame::message_query::BodyReqQuery;
 use cdrs_tokio::frame::message_response::ResponseBody;
+use cdrs_tokio::frame::{Envelope, Flags, Version};
+use cdrs_tokio::frame_encoding::ProtocolFrameEncodingFactory;
 use cdrs_tokio::load_balancing::RoundRobinLoadBalancingStrategy;
+use cdrs_tokio::statement::StatementParamsBuilder;
+use cdrs_tokio::transport::{CdrsTransport, TransportTcp};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use crate::components::data_grid::{ColumnInfo, QueryResult};
 use crate::error::{MagdaError, Result};
+use crate::keyspace_design::Topology;
+use crate::metrics::{
+    self, MetricsSnapshot, QuerySample, SessionMetrics, TracedLoadBalancing, TracedRetryPolicy,
+};
+use crate::roles::{redact_passwords, Grant, Role};
+use crate::schema::{
+    quote_identifier, quote_literal, AggregateDef, FunctionDef, IndexDef, KeyspaceDef,
+    KeyspaceSchema, SchemaRow, TableDef, TableOptions, TriggerDef, UserTypeDef, ViewDef,
+};
+use crate::virtual_tables::VirtualTable;
 
 /// A column in a Cassandra table schema
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct SchemaColumn {
     pub name: String,
     pub data_type: String,
@@ -21,93 +48,300 @@ pub struct SchemaColumn {
 }
 
 /// Schema information for a Cassandra table
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct TableSchema {
     pub columns: Vec<SchemaColumn>,
 }
 
-/// Validate that a string is a safe CQL identifier (keyspace or table name).
-/// Accepts unquoted identifiers: starts with letter/underscore, followed by alphanumeric/underscores.
-pub fn validate_cql_identifier(name: &str) -> Result<()> {
-    if name.is_empty() {
-        return Err(MagdaError::validation("CQL identifier cannot be empty"));
-    }
-    let first = name.chars().next().unwrap();
-    if !first.is_ascii_alphabetic() && first != '_' {
-        return Err(MagdaError::validation(format!(
-            "Invalid CQL identifier '{}': must start with a letter or underscore",
-            name
-        )));
-    }
-    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
-        return Err(MagdaError::validation(format!(
-            "Invalid CQL identifier '{}': only letters, digits, and underscores allowed",
-            name
-        )));
-    }
-    Ok(())
-}
+type TcpSession = cdrs_tokio::cluster::session::Session<
+    TransportTcp,
+    TcpConnectionManager,
+    TracedLoadBalancing<RoundRobinLoadBalancingStrategy<TransportTcp, TcpConnectionManager>>,
+>;
 
 /// Wrapper for Cassandra session
 pub struct CassandraSession {
-    inner: Arc<
-        cdrs_tokio::cluster::session::Session<
-            cdrs_tokio::transport::TransportTcp,
-            cdrs_tokio::cluster::TcpConnectionManager,
-            RoundRobinLoadBalancingStrategy<
-                cdrs_tokio::transport::TransportTcp,
-                cdrs_tokio::cluster::TcpConnectionManager,
-            >,
-        >,
-    >,
+    inner: Arc<TcpSession>,
+    version: Version,
+    metrics: Arc<SessionMetrics>,
+    /// Keyspace of user statements that name none, see [`use_keyspace`](Self::use_keyspace)
+    default_keyspace: Option<String>,
+    /// Where keyspace-scoped statements go on protocols without per-request keyspaces
+    keyspace_connection: Option<KeyspaceConnection>,
 }
 
 impl CassandraSession {
     /// Execute a query
-    pub async fn query(&self, query: &str) -> Result<cdrs_tokio::frame::Envelope> {
-        self.inner
-            .query(query)
-            .await
-            .map_err(|e| MagdaError::QueryError(format!("Query failed: {}", e)))
+    pub async fn query(&self, query: &str) -> Result<Envelope> {
+        let (result, sample) = self.send(query, None).await;
+        self.metrics.record(sample);
+        result
+    }
+
+    /// Execute a query against `keyspace` without switching the session's keyspace.
+    ///
+    /// Protocol v5 carries the keyspace on the request itself. Protocol v4
+    /// (Cassandra 3.x) has no such field, so there the statement goes over a
+    /// connection of its own that is switched with `USE` as needed.
+    pub async fn query_in_keyspace(&self, query: &str, keyspace: &str) -> Result<Envelope> {
+        let (result, sample) = self.send(query, Some(keyspace)).await;
+        self.metrics.record(sample);
+        result
+    }
+
+    /// Make `keyspace` the keyspace of user statements that name none. The
+    /// session itself is switched once here, which also checks that the
+    /// keyspace exists, and never again afterwards.
+    pub async fn use_keyspace(&mut self, keyspace: &str) -> Result<()> {
+        self.query(&format!("USE {}", quote_identifier(keyspace)))
+            .await?;
+        self.default_keyspace = Some(keyspace.to_string());
+        Ok(())
+    }
+
+    /// Send a statement and measure it. The sample is returned rather than
+    /// recorded so callers that decode the rows can fill in the row count.
+    async fn send(&self, query: &str, keyspace: Option<&str>) -> (Result<Envelope>, QuerySample) {
+        let at = Utc::now();
+        let start = Instant::now();
+        let (result, trace) = metrics::traced(async {
+            match (keyspace, &self.keyspace_connection) {
+                (Some(ks), Some(connection)) => connection.query(query, ks).await,
+                (Some(ks), None) => {
+                    let params = StatementParamsBuilder::new()
+                        .with_keyspace(ks.to_string())
+                        .build();
+                    self.inner.query_with_params(query, params).await
+                }
+                (None, _) => self.inner.query(query).await,
+            }
+        })
+        .await;
+
+        let sample = QuerySample {
+            at,
+            query: redact_passwords(query).into_owned(),
+            latency_us: start.elapsed().as_micros() as u64,
+            request_bytes: query.len(),
+            response_bytes: result.as_ref().map(|e| e.body.len()).unwrap_or(0),
+            rows: None,
+            retries: trace.retries(),
+            coordinator: trace.coordinator(),
+            success: result.is_ok(),
+        };
+        (result.map_err(MagdaError::from), sample)
+    }
+
+    /// Host ids of the nodes the driver currently sees as down
+    fn down_hosts(&self) -> HashSet<uuid::Uuid> {
+        let metadata = self.inner.cluster_metadata();
+        metadata
+            .nodes()
+            .values()
+            .filter(|node| matches!(node.state(), NodeState::Down | NodeState::ForcedDown))
+            .filter_map(|node| node.host_id())
+            .collect()
+    }
+
+    /// Native protocol version negotiated with the cluster
+    pub fn protocol_version(&self) -> Version {
+        self.version
+    }
+
+    /// Latency and traffic statistics for this session
+    pub fn metrics(&self) -> MetricsSnapshot {
+        self.metrics.snapshot()
+    }
+
+    pub fn reset_metrics(&self) {
+        self.metrics.reset();
     }
 }
 
-/// Create a new Cassandra session
+/// A connection outside the driver's pool for keyspace-scoped statements on
+/// protocol v4. The pool hands `USE` to its other connections in the
+/// background, so a statement sent right after a switch there may still run
+/// in the previous keyspace. Here the switch and the statement go over one
+/// connection, in order, under one lock.
+struct KeyspaceConnection {
+    manager: TcpConnectionManager,
+    /// Keyspace the connection is in, kept up to date by the driver
+    keyspace: Arc<KeyspaceHolder>,
+    addr: SocketAddr,
+    transport: tokio::sync::Mutex<Option<TransportTcp>>,
+}
+
+impl KeyspaceConnection {
+    fn new(addr: SocketAddr, authenticator: Authenticator) -> Self {
+        let (manager, keyspace) = connection_manager(authenticator, Version::V4);
+        Self {
+            manager,
+            keyspace,
+            addr,
+            transport: tokio::sync::Mutex::new(None),
+        }
+    }
+
+    /// Run `query` in `keyspace`, connecting first if needed. A new
+    /// connection starts in the keyspace the previous one was left in.
+    async fn query(&self, query: &str, keyspace: &str) -> cdrs_tokio::error::Result<Envelope> {
+        let mut transport = self.transport.lock().await;
+        let transport = match &mut *transport {
+            Some(transport) if !transport.is_broken() => transport,
+            slot => slot.insert(self.manager.connection(None, None, self.addr).await?),
+        };
+        if self
+            .keyspace
+            .current_keyspace()
+            .as_deref()
+            .map(String::as_str)
+            != Some(keyspace)
+        {
+            tracing::debug!("Protocol v4: switching keyspace connection to {}", keyspace);
+            let switch = format!("USE {}", quote_identifier(keyspace));
+            transport.write_envelope(&v4_query(&switch), false).await?;
+        }
+        transport.write_envelope(&v4_query(query), false).await
+    }
+}
+
+/// A protocol v4 query request with the session's default parameters
+fn v4_query(query: &str) -> Envelope {
+    let query = BodyReqQuery {
+        query: query.to_string(),
+        query_params: StatementParamsBuilder::new().build().query_params,
+    };
+    Envelope::new_query(query, Flags::empty(), Version::V4)
+}
+
+/// How long the protocol v5 handshake may take before v4 is used instead
+const PROTOCOL_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+type Authenticator = Arc<dyn SaslAuthenticatorProvider + Send + Sync>;
+
+/// Create a new Cassandra session.
+///
+/// Protocol v5 is used when the cluster accepts it since it allows
+/// per-request keyspaces; clusters that refuse it (Cassandra 3.x) are
+/// connected with protocol v4 instead.
 pub async fn create_session(
     host: &str,
     port: u16,
     username: Option<&str>,
     password: Option<&str>,
 ) -> Result<CassandraSession> {
-    tracing::info!("Creating connection to {}:{}", host, port);
+    let addr = tokio::net::lookup_host((host, port))
+        .await?
+        .next()
+        .ok_or_else(|| MagdaError::ConnectionError(format!("Could not resolve {}", host)))?;
 
-    let contact_point = format!("{}:{}", host, port);
+    let authenticator: Authenticator = match (username, password) {
+        (Some(user), Some(pass)) => {
+            tracing::info!("Using password authentication for user '{}'", user);
+            Arc::new(StaticPasswordAuthenticatorProvider::new(user, pass))
+        }
+        _ => Arc::new(NoneAuthenticatorProvider),
+    };
 
-    // Configure connection to Cassandra instance
-    let mut builder = NodeTcpConfigBuilder::new().with_contact_point(contact_point.into());
+    let version = negotiate_version(addr, authenticator.clone()).await?;
+    let mut session =
+        create_session_with_version(host, port, authenticator.clone(), version).await?;
+    if version < Version::V5 {
+        session.keyspace_connection = Some(KeyspaceConnection::new(addr, authenticator));
+    }
+    Ok(session)
+}
 
-    if let (Some(user), Some(pass)) = (username, password) {
-        tracing::info!("Using password authentication for user '{}'", user);
-        builder = builder.with_authenticator_provider(Arc::new(
-            StaticPasswordAuthenticatorProvider::new(user, pass),
-        ));
+/// Try a protocol v5 handshake with one node. A session would keep retrying
+/// a refused protocol in the background, while a single connection reports
+/// it right away; any other failure, such as bad credentials, is returned.
+async fn negotiate_version(addr: SocketAddr, authenticator: Authenticator) -> Result<Version> {
+    let (manager, _) = connection_manager(authenticator, Version::V5);
+    let probe = tokio::time::timeout(PROTOCOL_PROBE_TIMEOUT, manager.connection(None, None, addr));
+    match probe.await {
+        Ok(Ok(_)) => Ok(Version::V5),
+        Ok(Err(e)) if refuses_protocol(&e) => {
+            tracing::info!("Protocol v5 refused by {}, using v4", addr);
+            Ok(Version::V4)
+        }
+        Ok(Err(e)) => Err(MagdaError::ConnectionError(format!(
+            "Failed to connect to {}: {}",
+            addr, e
+        ))),
+        Err(_) => {
+            tracing::info!("Protocol v5 handshake with {} timed out, using v4", addr);
+            Ok(Version::V4)
+        }
+    }
+}
+
+/// Whether a handshake failed because the node does not speak the protocol
+/// version. The driver reports this as `InvalidProtocol`; the server error
+/// it comes from is matched too in case it reaches us unconverted.
+fn refuses_protocol(error: &cdrs_tokio::error::Error) -> bool {
+    match error {
+        cdrs_tokio::error::Error::InvalidProtocol(_) => true,
+        cdrs_tokio::error::Error::Server { body, .. } => body.is_bad_protocol(),
+        _ => false,
     }
+}
 
-    let config = builder
+/// A connection manager outside any session, with a keyspace holder of its own
+fn connection_manager(
+    authenticator: Authenticator,
+    version: Version,
+) -> (TcpConnectionManager, Arc<KeyspaceHolder>) {
+    let (keyspace_sender, _) = tokio::sync::watch::channel(None);
+    let keyspace = Arc::new(KeyspaceHolder::new(keyspace_sender));
+    let manager = TcpConnectionManager::new(
+        authenticator,
+        keyspace.clone(),
+        Box::new(ProtocolFrameEncodingFactory),
+        Compression::None,
+        DEFAULT_TRANSPORT_BUFFER_SIZE,
+        true,
+        version,
+    );
+    (manager, keyspace)
+}
+
+async fn create_session_with_version(
+    host: &str,
+    port: u16,
+    authenticator: Authenticator,
+    version: Version,
+) -> Result<CassandraSession> {
+    tracing::info!("Creating connection to {}:{} ({})", host, port, version);
+
+    let contact_point = format!("{}:{}", host, port);
+
+    // Configure connection to Cassandra instance
+    let config = NodeTcpConfigBuilder::new()
+        .with_contact_point(contact_point.into())
+        .with_version(version)
+        .with_authenticator_provider(authenticator)
         .build()
         .await
         .map_err(|e| MagdaError::ConnectionError(format!("Failed to build config: {}", e)))?;
 
-    // Create session with round-robin load balancing
-    let session = TcpSessionBuilder::new(RoundRobinLoadBalancingStrategy::new(), config)
-        .build()
-        .await
-        .map_err(|e| MagdaError::ConnectionError(format!("Failed to create session: {}", e)))?;
+    // Create session with round-robin load balancing, traced for metrics
+    let session = TcpSessionBuilder::new(
+        TracedLoadBalancing::new(RoundRobinLoadBalancingStrategy::new()),
+        config,
+    )
+    .with_retry_policy(Box::new(TracedRetryPolicy))
+    .build()
+    .await
+    .map_err(|e| MagdaError::ConnectionError(format!("Failed to create session: {}", e)))?;
 
     tracing::info!("Connected to Cassandra at {}:{}", host, port);
 
     Ok(CassandraSession {
         inner: Arc::new(session),
+        version,
+        metrics: Arc::new(SessionMetrics::default()),
+        default_keyspace: None,
+        keyspace_connection: None,
     })
 }
 
@@ -160,12 +394,11 @@ pub async fn list_keyspaces(session: &CassandraSession) -> Result<Vec<String>> {
 
 /// List all tables in a keyspace from the real database
 pub async fn list_tables(session: &CassandraSession, keyspace: &str) -> Result<Vec<String>> {
-    validate_cql_identifier(keyspace)?;
     tracing::info!("Listing tables for keyspace: {}", keyspace);
 
     let query = format!(
-        "SELECT table_name FROM system_schema.tables WHERE keyspace_name = '{}'",
-        keyspace
+        "SELECT table_name FROM system_schema.tables WHERE keyspace_name = {}",
+        quote_literal(keyspace)
     );
 
     let result = session.query(&query).await?;
@@ -214,58 +447,8 @@ pub async fn list_tables(session: &CassandraSession, keyspace: &str) -> Result<V
     Ok(tables)
 }
 
-/// Describe a table's columns from system_schema.columns
-pub async fn describe_table(
-    session: &CassandraSession,
-    keyspace: &str,
-    table: &str,
-) -> Result<TableSchema> {
-    validate_cql_identifier(keyspace)?;
-    validate_cql_identifier(table)?;
-    tracing::debug!("Describing table {}.{}", keyspace, table);
-
-    let query = format!(
-        "SELECT column_name, type, kind, position, clustering_order FROM system_schema.columns WHERE keyspace_name = '{}' AND table_name = '{}'",
-        keyspace, table
-    );
-
-    let result = session.query(&query).await?;
-    let mut columns = Vec::new();
-
-    if let ResponseBody::Result(cdrs_tokio::frame::message_result::ResResultBody::Rows(
-        rows_result,
-    )) = result.response_body()?
-    {
-        for row in rows_result.rows_content.iter() {
-            let get_string = |idx: usize| -> String {
-                row.get(idx)
-                    .and_then(|b| b.as_slice())
-                    .and_then(|bytes| String::from_utf8(bytes.to_vec()).ok())
-                    .unwrap_or_default()
-            };
-            let position = row
-                .get(3)
-                .and_then(|b| b.as_slice())
-                .map(|bytes| {
-                    if bytes.len() == 4 {
-                        i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
-                    } else {
-                        0
-                    }
-                })
-                .unwrap_or(0);
-
-            columns.push(SchemaColumn {
-                name: get_string(0),
-                data_type: get_string(1),
-                kind: get_string(2),
-                position,
-                clustering_order: get_string(4),
-            });
-        }
-    }
-
-    // Sort: partition_key by position, then clustering by position, then static, then regular
+/// Sort: partition_key by position, then clustering by position, then static, then regular
+fn sort_schema_columns(columns: &mut [SchemaColumn]) {
     columns.sort_by(|a, b| {
         let kind_order = |k: &str| match k {
             "partition_key" => 0,
@@ -278,31 +461,354 @@ pub async fn describe_table(
             .then(a.position.cmp(&b.position))
             .then(a.name.cmp(&b.name))
     });
+}
+
+/// Run a `system_schema` query and return its rows addressed by column name.
+/// `SELECT *` is used throughout so columns added in newer Cassandra
+/// versions are picked up without failing on older ones.
+pub(crate) async fn schema_rows(session: &CassandraSession, query: &str) -> Result<Vec<SchemaRow>> {
+    let result = session.query(query).await?;
+    let mut rows = Vec::new();
+
+    if let ResponseBody::Result(cdrs_tokio::frame::message_result::ResResultBody::Rows(
+        rows_result,
+    )) = result.response_body()?
+    {
+        let names: Vec<&String> = rows_result
+            .metadata
+            .col_specs
+            .iter()
+            .map(|c| &c.name)
+            .collect();
+        for row in rows_result.rows_content.iter() {
+            let cells = names
+                .iter()
+                .zip(row.iter())
+                .filter_map(|(name, cell)| cell.as_slice().map(|b| ((*name).clone(), b.to_vec())))
+                .collect();
+            rows.push(SchemaRow::new(cells));
+        }
+    }
+
+    Ok(rows)
+}
+
+/// Group `system_schema.columns` rows by table (or view) name
+fn group_columns(rows: &[SchemaRow]) -> HashMap<String, Vec<SchemaColumn>> {
+    let mut grouped: HashMap<String, Vec<SchemaColumn>> = HashMap::new();
+    for row in rows {
+        grouped
+            .entry(row.text("table_name").unwrap_or_default())
+            .or_default()
+            .push(SchemaColumn {
+                name: row.text("column_name").unwrap_or_default(),
+                data_type: row.text("type").unwrap_or_default(),
+                kind: row.text("kind").unwrap_or_default(),
+                position: row.int("position").unwrap_or(0),
+                clustering_order: row.text("clustering_order").unwrap_or_default(),
+            });
+    }
+    for columns in grouped.values_mut() {
+        sort_schema_columns(columns);
+    }
+    grouped
+}
+
+/// Read every schema object of a keyspace from system_schema
+pub async fn describe_keyspace(
+    session: &CassandraSession,
+    keyspace: &str,
+) -> Result<KeyspaceSchema> {
+    tracing::debug!("Describing keyspace {}", keyspace);
+
+    let select = |table: &str| {
+        format!(
+            "SELECT * FROM system_schema.{} WHERE keyspace_name = {}",
+            table,
+            quote_literal(keyspace)
+        )
+    };
+
+    let keyspace_def = schema_rows(session, &select("keyspaces"))
+        .await?
+        .first()
+        .map(KeyspaceDef::from_row)
+        .ok_or_else(|| MagdaError::SchemaError(format!("Keyspace '{}' not found", keyspace)))?;
+
+    let mut columns = group_columns(&schema_rows(session, &select("columns")).await?);
+
+    let tables = schema_rows(session, &select("tables"))
+        .await?
+        .iter()
+        .map(|row| {
+            let name = row.text("table_name").unwrap_or_default();
+            TableDef {
+                keyspace: keyspace.to_string(),
+                columns: columns.remove(&name).unwrap_or_default(),
+                name,
+                options: TableOptions::from_row(row),
+            }
+        })
+        .collect();
+
+    let views = schema_rows(session, &select("views"))
+        .await?
+        .iter()
+        .map(|row| {
+            let name = row.text("view_name").unwrap_or_default();
+            ViewDef {
+                keyspace: keyspace.to_string(),
+                base_table: row.text("base_table_name").unwrap_or_default(),
+                include_all_columns: row.boolean("include_all_columns").unwrap_or(false),
+                where_clause: row.text("where_clause").unwrap_or_default(),
+                columns: columns.remove(&name).unwrap_or_default(),
+                name,
+                options: TableOptions::from_row(row),
+            }
+        })
+        .collect();
+
+    let types = schema_rows(session, &select("types")).await?;
+    let indexes = schema_rows(session, &select("indexes")).await?;
+    let functions = schema_rows(session, &select("functions")).await?;
+    let aggregates = schema_rows(session, &select("aggregates")).await?;
+    let triggers = schema_rows(session, &select("triggers")).await?;
+
+    let schema = KeyspaceSchema {
+        keyspace: keyspace_def,
+        types: types.iter().map(UserTypeDef::from_row).collect(),
+        tables,
+        indexes: indexes.iter().map(IndexDef::from_row).collect(),
+        views,
+        functions: functions.iter().map(FunctionDef::from_row).collect(),
+        aggregates: aggregates.iter().map(AggregateDef::from_row).collect(),
+        triggers: triggers.iter().map(TriggerDef::from_row).collect(),
+    };
 
     tracing::info!(
-        "Described {}.{}: {} columns",
+        "Described keyspace {}: {} tables, {} types, {} views",
         keyspace,
-        table,
-        columns.len()
+        schema.tables.len(),
+        schema.types.len(),
+        schema.views.len()
     );
-    Ok(TableSchema { columns })
+    Ok(schema)
+}
+
+/// Every virtual table with its columns, from `system_virtual_schema`.
+/// Fails on clusters older than Cassandra 4.0, which have no virtual tables.
+pub async fn list_virtual_tables(session: &CassandraSession) -> Result<Vec<VirtualTable>> {
+    let tables = schema_rows(session, "SELECT * FROM system_virtual_schema.tables").await?;
+    let column_rows = schema_rows(session, "SELECT * FROM system_virtual_schema.columns").await?;
+
+    let mut columns: HashMap<(String, String), Vec<SchemaColumn>> = HashMap::new();
+    for row in &column_rows {
+        columns
+            .entry((
+                row.text("keyspace_name").unwrap_or_default(),
+                row.text("table_name").unwrap_or_default(),
+            ))
+            .or_default()
+            .push(SchemaColumn {
+                name: row.text("column_name").unwrap_or_default(),
+                data_type: row.text("type").unwrap_or_default(),
+                kind: row.text("kind").unwrap_or_default(),
+                position: row.int("position").unwrap_or(0),
+                clustering_order: row.text("clustering_order").unwrap_or_default(),
+            });
+    }
+
+    let mut virtual_tables: Vec<VirtualTable> = tables
+        .iter()
+        .map(|row| {
+            let keyspace = row.text("keyspace_name").unwrap_or_default();
+            let name = row.text("table_name").unwrap_or_default();
+            let mut table_columns = columns
+                .remove(&(keyspace.clone(), name.clone()))
+                .unwrap_or_default();
+            sort_schema_columns(&mut table_columns);
+            VirtualTable {
+                keyspace,
+                name,
+                comment: row.text("comment").unwrap_or_default(),
+                columns: table_columns.into_iter().map(|c| c.name).collect(),
+            }
+        })
+        .collect();
+    virtual_tables.sort_by(|a, b| (&a.keyspace, &a.name).cmp(&(&b.keyspace, &b.name)));
+
+    tracing::debug!("Found {} virtual tables", virtual_tables.len());
+    Ok(virtual_tables)
+}
+
+/// Every role visible to the session's user, from `LIST ROLES`, with
+/// memberships from `system_auth.roles` when the user may read it
+pub async fn list_roles(session: &CassandraSession) -> Result<Vec<Role>> {
+    let mut roles: Vec<Role> = schema_rows(session, "LIST ROLES")
+        .await?
+        .iter()
+        .map(Role::from_list_row)
+        .collect();
+    match schema_rows(session, "SELECT role, member_of FROM system_auth.roles").await {
+        Ok(rows) => {
+            for row in rows {
+                let name = row.text("role").unwrap_or_default();
+                if let Some(role) = roles.iter_mut().find(|r| r.name == name) {
+                    role.member_of = row.text_list("member_of");
+                }
+            }
+        }
+        Err(e) => tracing::debug!("Role memberships unavailable: {}", e),
+    }
+    roles.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(roles)
+}
+
+/// Every permission granted to the roles visible to the session's user
+pub async fn list_permissions(session: &CassandraSession) -> Result<Vec<Grant>> {
+    let rows = schema_rows(session, "LIST ALL PERMISSIONS").await?;
+    Ok(rows.iter().map(Grant::from_row).collect())
+}
+
+/// Nodes per data center, from `system.local` (the coordinator) and
+/// `system.peers` (every other node it knows of)
+pub async fn datacenters(session: &CassandraSession) -> Result<Topology> {
+    let mut topology = Topology::new();
+    for query in [
+        "SELECT data_center FROM system.local",
+        "SELECT data_center FROM system.peers",
+    ] {
+        for row in schema_rows(session, query).await? {
+            if let Some(dc) = row.text("data_center") {
+                *topology.entry(dc).or_insert(0) += 1;
+            }
+        }
+    }
+    tracing::debug!("Cluster topology: {:?}", topology);
+    Ok(topology)
+}
+
+/// Wait until every live node reports the same schema version, as drivers do
+/// after DDL so the next statement does not run against a stale schema.
+/// Peers the driver sees as down are skipped: they keep their old version
+/// until they return. Returns `false` if the nodes still disagree after
+/// `timeout`.
+pub async fn wait_for_schema_agreement(
+    session: &CassandraSession,
+    timeout: Duration,
+) -> Result<bool> {
+    let start = Instant::now();
+    loop {
+        let down = session.down_hosts();
+        let local: Option<Vec<u8>> =
+            schema_rows(session, "SELECT schema_version FROM system.local")
+                .await?
+                .first()
+                .and_then(|row| row.raw("schema_version"))
+                .map(<[u8]>::to_vec);
+        let mut disagreeing = Vec::new();
+        for row in schema_rows(
+            session,
+            "SELECT peer, host_id, schema_version FROM system.peers",
+        )
+        .await?
+        {
+            let host_id = row
+                .raw("host_id")
+                .and_then(|bytes| uuid::Uuid::from_slice(bytes).ok());
+            if host_id.is_none_or(|id| down.contains(&id)) {
+                continue;
+            }
+            if let Some(version) = row.raw("schema_version") {
+                if local.as_deref() != Some(version) {
+                    let peer = row.raw("peer").and_then(inet);
+                    disagreeing.push(peer.map_or("unknown peer".to_string(), |p| p.to_string()));
+                }
+            }
+        }
+        if disagreeing.is_empty() {
+            return Ok(true);
+        }
+        if start.elapsed() >= timeout {
+            tracing::warn!(
+                "Schema agreement not reached after {:?}: {} disagree with the coordinator",
+                timeout,
+                disagreeing.join(", ")
+            );
+            return Ok(false);
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+}
+
+/// An `inet` value: four bytes for IPv4, sixteen for IPv6
+fn inet(bytes: &[u8]) -> Option<IpAddr> {
+    match bytes.len() {
+        4 => <[u8; 4]>::try_from(bytes).ok().map(IpAddr::from),
+        16 => <[u8; 16]>::try_from(bytes).ok().map(IpAddr::from),
+        _ => None,
+    }
 }
 
 /// Execute a CQL query and return results
 pub async fn execute_query(session: &CassandraSession, query: &str) -> Result<QueryResult> {
-    let start = Instant::now();
-    tracing::debug!("Executing query: {}", query);
+    execute_query_in(session, query, None).await
+}
 
-    let result = session.query(query).await?;
+/// Execute a CQL query scoped to `keyspace`, or to the session's default
+/// keyspace when none is given, and return results
+pub async fn execute_query_in(
+    session: &CassandraSession,
+    query: &str,
+    keyspace: Option<&str>,
+) -> Result<QueryResult> {
+    let start = Instant::now();
+    let keyspace = keyspace.or(session.default_keyspace.as_deref());
+    tracing::debug!(
+        "Executing query in {:?}: {}",
+        keyspace,
+        redact_passwords(query)
+    );
 
+    let (result, mut sample) = session.send(query, keyspace).await;
     let execution_time = start.elapsed().as_millis() as u64;
 
+    let decoded = result.and_then(|envelope| decode_rows(&envelope));
+    sample.rows = decoded.as_ref().ok().map(|(_, rows)| rows.len());
+    session.metrics.record(sample);
+    let (columns, rows) = decoded?;
+    let row_count = rows.len();
+
+    // If no columns were found, create a default result
+    if columns.is_empty() {
+        tracing::info!("Query executed in {}ms, no rows returned", execution_time);
+        return Ok(QueryResult::message(
+            "Query executed successfully",
+            execution_time,
+        ));
+    }
+
+    tracing::info!(
+        "Query executed in {}ms, {} rows returned",
+        execution_time,
+        row_count
+    );
+
+    Ok(QueryResult {
+        columns,
+        rows,
+        execution_time_ms: execution_time,
+        row_count,
+    })
+}
+
+/// Extract column metadata and row values from a result envelope
+fn decode_rows(envelope: &Envelope) -> Result<(Vec<ColumnInfo>, Vec<Vec<Value>>)> {
     let mut columns = Vec::new();
     let mut rows = Vec::new();
-    let mut row_count = 0;
 
     // Process the envelope to extract data
-    if let ResponseBody::Result(res_result_body) = result.response_body()? {
+    if let ResponseBody::Result(res_result_body) = envelope.response_body()? {
         match res_result_body {
             cdrs_tokio::frame::message_result::ResResultBody::Rows(rows_result) => {
                 tracing::info!("Found {} rows in result", rows_result.rows_count);
@@ -339,8 +845,11 @@ pub async fn execute_query(session: &CassandraSession, query: &str) -> Result<Qu
                     rows.push(row_data);
                 }
 
-                row_count = rows.len();
-                tracing::debug!("Extracted {} columns and {} rows", columns.len(), row_count);
+                tracing::debug!(
+                    "Extracted {} columns and {} rows",
+                    columns.len(),
+                    rows.len()
+                );
             }
             _ => {
                 tracing::debug!("Result is not Rows type, no data to extract");
@@ -348,30 +857,7 @@ pub async fn execute_query(session: &CassandraSession, query: &str) -> Result<Qu
         }
     }
 
-    // If no columns were found, create a default result
-    if columns.is_empty() {
-        columns.push(ColumnInfo {
-            name: "result".to_string(),
-            data_type: "text".to_string(),
-        });
-        rows.push(vec![Value::String(
-            "Query executed successfully".to_string(),
-        )]);
-        row_count = 1;
-    }
-
-    tracing::info!(
-        "Query executed in {}ms, {} rows returned",
-        execution_time,
-        row_count
-    );
-
-    Ok(QueryResult {
-        columns,
-        rows,
-        execution_time_ms: execution_time,
-        row_count,
-    })
+    Ok((columns, rows))
 }
 
 /// Test the connection by executing a simple system query
@@ -544,3 +1030,32 @@ fn convert_cassandra_value(bytes: &[u8], column_type: &str) -> Value {
         Value::String(format!("0x{}", hex::encode(bytes)))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cdrs_tokio::error::Error;
+    use cdrs_tokio::frame::message_error::{ErrorBody, ErrorType};
+
+    #[test]
+    fn test_refused_protocol_is_recognised() {
+        let addr: SocketAddr = "127.0.0.1:9042".parse().unwrap();
+        assert!(refuses_protocol(&Error::InvalidProtocol(addr)));
+        let body = ErrorBody {
+            message: "Invalid or unsupported protocol version (5); \
+                      supported versions are (3/v3, 4/v4, 5/v5-beta)"
+                .to_string(),
+            ty: ErrorType::Protocol,
+        };
+        assert!(refuses_protocol(&Error::Server { body, addr }));
+
+        let body = ErrorBody {
+            message: "Provided username cassandra and/or password are incorrect".to_string(),
+            ty: ErrorType::Authentication,
+        };
+        assert!(!refuses_protocol(&Error::Server { body, addr }));
+        assert!(!refuses_protocol(&Error::General(
+            "connection refused".to_string()
+        )));
+    }
+}
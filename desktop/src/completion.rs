@@ -0,0 +1,398 @@
+//! Schema-aware editor completions: which table the statement under the
+//! cursor works on, whether the cursor sits where a column name goes, and
+//! that table's columns ranked for the spot; plus keyspace names and the
+//! objects of a keyspace for qualified names like `shop.orders`.
+
+use crate::cassandra::SchemaColumn;
+use crate::cql_tokenizer::{tokenize, Token, TokenKind};
+use crate::identifier::Identifier;
+use crate::schema::{quote_identifier, KeyspaceSchema};
+
+/// One entry of the completion list
+#[derive(Clone, Debug, PartialEq)]
+pub struct Completion {
+    /// Text inserted in place of the word being typed
+    pub text: String,
+    /// Shown next to the text, e.g. a column's type
+    pub detail: String,
+}
+
+impl Completion {
+    pub fn plain(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            detail: String::new(),
+        }
+    }
+}
+
+/// Clauses where a column name is expected
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColumnContext {
+    /// `SELECT a, b` (and `DELETE a, b`) before `FROM`
+    Select,
+    /// Conditions after `WHERE` or `IF`
+    Where,
+    /// Assignments after `SET`
+    Set,
+    OrderBy,
+    /// The column list of `INSERT INTO t (a, b)`
+    InsertColumns,
+}
+
+/// A table named in a statement, as Cassandra stores the names
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TableRef {
+    pub keyspace: Option<String>,
+    pub name: String,
+}
+
+fn is_significant(token: &Token) -> bool {
+    !matches!(token.kind, TokenKind::Whitespace | TokenKind::Comment)
+}
+
+/// Significant tokens of the statement around `cursor`
+fn statement_tokens(source: &str, cursor: usize) -> Vec<Token> {
+    let mut statement = Vec::new();
+    for token in tokenize(source).into_iter().filter(is_significant) {
+        if token.text == ";" {
+            if token.start >= cursor {
+                break;
+            }
+            statement.clear();
+        } else {
+            statement.push(token);
+        }
+    }
+    statement
+}
+
+fn is_word(token: &Token) -> bool {
+    matches!(
+        token.kind,
+        TokenKind::Keyword | TokenKind::Type | TokenKind::Function | TokenKind::Identifier
+    )
+}
+
+/// A name token as stored: unquoted words fold to lowercase
+fn stored_name(token: &Token) -> Option<String> {
+    if !is_word(token) {
+        return None;
+    }
+    Identifier::parse(&token.text)
+        .ok()
+        .map(|id| id.as_str().to_string())
+}
+
+/// The table the statement around `cursor` reads or writes: the name after
+/// `FROM`, `INTO` or `UPDATE`, optionally keyspace-qualified
+pub fn statement_table(source: &str, cursor: usize) -> Option<TableRef> {
+    let tokens = statement_tokens(source, cursor);
+    let at = tokens.iter().position(|t| {
+        t.kind == TokenKind::Keyword
+            && ["FROM", "INTO", "UPDATE"]
+                .iter()
+                .any(|kw| t.text.eq_ignore_ascii_case(kw))
+    })?;
+    let first = stored_name(tokens.get(at + 1)?)?;
+    match (tokens.get(at + 2), tokens.get(at + 3)) {
+        (Some(dot), Some(name)) if dot.text == "." => Some(TableRef {
+            keyspace: Some(first),
+            name: stored_name(name)?,
+        }),
+        _ => Some(TableRef {
+            keyspace: None,
+            name: first,
+        }),
+    }
+}
+
+/// Which column clause, if any, the word starting at `word_start` is in
+pub fn column_context(source: &str, word_start: usize) -> Option<ColumnContext> {
+    let tokens = statement_tokens(source, word_start);
+    let before: Vec<&Token> = tokens.iter().take_while(|t| t.start < word_start).collect();
+    let mut context = None;
+    let mut is_insert = false;
+    let mut after_order = false;
+
+    for token in &before {
+        if token.kind == TokenKind::Keyword {
+            let upper = token.text.to_ascii_uppercase();
+            context = match upper.as_str() {
+                "SELECT" | "DELETE" => Some(ColumnContext::Select),
+                "WHERE" | "IF" => Some(ColumnContext::Where),
+                "SET" => Some(ColumnContext::Set),
+                "BY" if after_order => Some(ColumnContext::OrderBy),
+                "INSERT" => {
+                    is_insert = true;
+                    None
+                }
+                "VALUES" => {
+                    is_insert = false;
+                    None
+                }
+                "FROM" | "INTO" | "UPDATE" | "USING" | "LIMIT" | "PER" | "ALLOW" | "GROUP"
+                | "ORDER" | "BEGIN" | "APPLY" => None,
+                // AND, IN, CONTAINS, DISTINCT... stay in the clause
+                _ => context,
+            };
+            after_order = upper == "ORDER";
+        } else {
+            after_order = false;
+            if is_insert && token.text == "(" && context.is_none() {
+                context = Some(ColumnContext::InsertColumns);
+            } else if token.text == ")" && context == Some(ColumnContext::InsertColumns) {
+                context = None;
+            }
+        }
+    }
+
+    // Right after a comparison or assignment a value is typed, not a column
+    if before
+        .last()
+        .is_some_and(|t| t.kind == TokenKind::Operator && t.text != "*")
+    {
+        return None;
+    }
+    context
+}
+
+fn kind_rank(kind: &str) -> u8 {
+    match kind {
+        "partition_key" => 0,
+        "clustering" => 1,
+        _ => 2,
+    }
+}
+
+fn kind_label(kind: &str) -> Option<&'static str> {
+    match kind {
+        "partition_key" => Some("partition key"),
+        "clustering" => Some("clustering"),
+        "static" => Some("static"),
+        _ => None,
+    }
+}
+
+/// Columns starting with `partial`, each with its type. In `WHERE` the
+/// partition key comes first, then clustering columns, as a valid
+/// restriction needs them in that order.
+pub fn column_completions(
+    columns: &[SchemaColumn],
+    context: ColumnContext,
+    partial: &str,
+) -> Vec<Completion> {
+    let partial = partial.to_lowercase();
+    let mut matching: Vec<&SchemaColumn> = columns
+        .iter()
+        .filter(|c| c.name.to_lowercase().starts_with(&partial))
+        .collect();
+    if context == ColumnContext::Where {
+        // Stable, so each group keeps its schema order
+        matching.sort_by_key(|c| kind_rank(&c.kind));
+    }
+    matching
+        .into_iter()
+        .map(|c| Completion {
+            text: quote_identifier(&c.name),
+            detail: match kind_label(&c.kind) {
+                Some(label) => format!("{} · {}", c.data_type, label),
+                None => c.data_type.clone(),
+            },
+        })
+        .collect()
+}
+
+fn starts_with_ignore_case(name: &str, partial: &str) -> bool {
+    name.to_lowercase().starts_with(&partial.to_lowercase())
+}
+
+/// Keyspaces starting with `partial`, for a name that may be qualified next
+pub fn keyspace_completions(keyspaces: &[String], partial: &str) -> Vec<Completion> {
+    keyspaces
+        .iter()
+        .filter(|ks| starts_with_ignore_case(ks, partial))
+        .map(|ks| Completion {
+            text: quote_identifier(ks),
+            detail: "keyspace".to_string(),
+        })
+        .collect()
+}
+
+/// Tables, materialized views and user types of a keyspace starting with
+/// `partial`, completing the name after `keyspace.`
+pub fn keyspace_object_completions(schema: &KeyspaceSchema, partial: &str) -> Vec<Completion> {
+    let tables = schema.tables.iter().map(|t| (&t.name, "table"));
+    let views = schema.views.iter().map(|v| (&v.name, "materialized view"));
+    let types = schema.types.iter().map(|t| (&t.name, "type"));
+    tables
+        .chain(views)
+        .chain(types)
+        .filter(|(name, _)| starts_with_ignore_case(name, partial))
+        .map(|(name, kind)| Completion {
+            text: quote_identifier(name),
+            detail: kind.to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{column, keyspace, table};
+
+    /// Context of the word ending at `|` in `source`
+    fn context_at(source: &str) -> Option<ColumnContext> {
+        let cursor = source.find('|').unwrap();
+        let text = source.replace('|', "");
+        let word = crate::cql_tokenizer::word_at_cursor(&text, cursor);
+        column_context(&text, word.start)
+    }
+
+    #[test]
+    fn test_statement_table() {
+        let source = "SELECT * FROM other;\nSELECT na FROM Shop.\"Orders\" WHERE id = 1;";
+        assert_eq!(
+            statement_table(source, 30),
+            Some(TableRef {
+                keyspace: Some("shop".to_string()),
+                name: "Orders".to_string()
+            })
+        );
+        assert_eq!(
+            statement_table(source, 5),
+            Some(TableRef {
+                keyspace: None,
+                name: "other".to_string()
+            })
+        );
+        assert_eq!(
+            statement_table("UPDATE users SET name = 'x'", 20).map(|t| t.name),
+            Some("users".to_string())
+        );
+        assert_eq!(
+            statement_table("INSERT INTO ks.events (id", 24).map(|t| t.name),
+            Some("events".to_string())
+        );
+        assert_eq!(statement_table("SELECT now() FROM", 17), None);
+    }
+
+    #[test]
+    fn test_column_context() {
+        assert_eq!(
+            context_at("SELECT na| FROM users"),
+            Some(ColumnContext::Select)
+        );
+        assert_eq!(
+            context_at("SELECT id, na| FROM users"),
+            Some(ColumnContext::Select)
+        );
+        assert_eq!(context_at("SELECT id FROM us|"), None);
+        assert_eq!(
+            context_at("SELECT * FROM users WHERE id = 1 AND na|"),
+            Some(ColumnContext::Where)
+        );
+        assert_eq!(context_at("SELECT * FROM users WHERE id = na|"), None);
+        assert_eq!(
+            context_at("SELECT * FROM users WHERE id IN (1, 2) AND na|"),
+            Some(ColumnContext::Where)
+        );
+        assert_eq!(
+            context_at("UPDATE users SET na| WHERE id = 1"),
+            Some(ColumnContext::Set)
+        );
+        assert_eq!(
+            context_at("SELECT * FROM t WHERE a = 1 ORDER BY ts|"),
+            Some(ColumnContext::OrderBy)
+        );
+        assert_eq!(
+            context_at("INSERT INTO users (id, na|) VALUES (1, 'x')"),
+            Some(ColumnContext::InsertColumns)
+        );
+        assert_eq!(
+            context_at("INSERT INTO users (id, name) VALUES (1, na|"),
+            None
+        );
+        assert_eq!(context_at("SELECT * FROM t LIMIT 1; SEL|"), None);
+    }
+
+    #[test]
+    fn test_column_completions_rank_keys_in_where() {
+        let columns = vec![
+            column("tenant_id", "uuid", "partition_key", 0),
+            column("day", "date", "partition_key", 0),
+            column("ts", "timestamp", "clustering", 0),
+            column("total", "decimal", "regular", 0),
+            column("Tag", "text", "static", 0),
+        ];
+        let names =
+            |items: Vec<Completion>| -> Vec<String> { items.into_iter().map(|c| c.text).collect() };
+        assert_eq!(
+            names(column_completions(&columns, ColumnContext::Select, "t")),
+            vec!["tenant_id", "ts", "total", "\"Tag\""]
+        );
+
+        let mut shuffled = columns.clone();
+        shuffled.reverse();
+        let completions = column_completions(&shuffled, ColumnContext::Where, "");
+        assert_eq!(
+            names(completions.clone()),
+            vec!["day", "tenant_id", "ts", "\"Tag\"", "total"]
+        );
+        assert_eq!(completions[1].detail, "uuid · partition key");
+        assert_eq!(completions[4].detail, "decimal");
+    }
+
+    #[test]
+    fn test_keyspace_and_object_completions() {
+        use crate::schema::{TableOptions, UserTypeDef, ViewDef};
+
+        let keyspaces = vec![
+            "orders_ks".to_string(),
+            "Ops".to_string(),
+            "shop".to_string(),
+        ];
+        let found = keyspace_completions(&keyspaces, "o");
+        assert_eq!(
+            found.iter().map(|c| c.text.as_str()).collect::<Vec<_>>(),
+            vec!["orders_ks", "\"Ops\""]
+        );
+        assert_eq!(found[0].detail, "keyspace");
+
+        let mut schema = keyspace(
+            "orders_ks",
+            vec![
+                table("orders_ks", "orders", Vec::new()),
+                table("orders_ks", "Refunds", Vec::new()),
+            ],
+        );
+        schema.types.push(UserTypeDef {
+            keyspace: "orders_ks".to_string(),
+            name: "order_line".to_string(),
+            fields: Vec::new(),
+        });
+        schema.views.push(ViewDef {
+            keyspace: "orders_ks".to_string(),
+            name: "orders_by_day".to_string(),
+            base_table: "orders".to_string(),
+            include_all_columns: true,
+            where_clause: String::new(),
+            columns: Vec::new(),
+            options: TableOptions::default(),
+        });
+        let found = keyspace_object_completions(&schema, "");
+        assert_eq!(
+            found
+                .iter()
+                .map(|c| (c.text.as_str(), c.detail.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("orders", "table"),
+                ("\"Refunds\"", "table"),
+                ("orders_by_day", "materialized view"),
+                ("order_line", "type"),
+            ]
+        );
+        assert_eq!(keyspace_object_completions(&schema, "ORDERS_").len(), 1);
+    }
+}
@@ -1,6 +1,15 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
 use dioxus::prelude::*;
 
-use crate::cql_tokenizer;
+use crate::completion::{self, Completion};
+use crate::cql_parser;
+use crate::cql_tokenizer::{self, Mark};
+use crate::diagnostics::{self, SchemaContext};
+use crate::identifier::Identifier;
+use crate::schema::{quote_identifier, KeyspaceSchema};
+use crate::schema_lint::Severity;
 use crate::state::AppState;
 
 const MAX_SUGGESTIONS: usize = 12;
@@ -15,24 +24,128 @@ fn is_table_context(keyword: &str) -> bool {
 pub fn CodeEditor(
     value: Signal<String>,
     on_execute: EventHandler<String>,
-    is_executing: Signal<bool>,
+    is_executing: ReadSignal<bool>,
 ) -> Element {
     let app_state = use_context::<Signal<AppState>>();
 
     // Autocomplete state
-    let mut suggestions: Signal<Vec<String>> = use_signal(Vec::new);
+    let mut suggestions: Signal<Vec<Completion>> = use_signal(Vec::new);
     let mut selected_idx: Signal<usize> = use_signal(|| 0);
     let mut ac_visible: Signal<bool> = use_signal(|| false);
     let mut ac_word_start: Signal<usize> = use_signal(|| 0);
 
-    // Highlighted HTML — recomputes reactively whenever `value` changes
+    // Connection and keyspace of the active tab
+    let tab_context = use_memo(move || {
+        let state = app_state.read();
+        let active_id = *state.active_editor_tab.read();
+        state
+            .editor_tabs
+            .read()
+            .iter()
+            .find(|t| t.id == active_id)
+            .map(|t| (t.connection_id, t.keyspace.clone()))
+            .unwrap_or_default()
+    });
+
+    // Schema the diagnostics check names against: the cluster's keyspaces,
+    // and the schema of each keyspace the script uses once it shows up
+    let mut default_keyspace: Signal<Option<String>> = use_signal(|| None);
+    let mut keyspace_names: Signal<Option<Vec<String>>> = use_signal(|| None);
+    let mut schemas: Signal<HashMap<String, Arc<KeyspaceSchema>>> = use_signal(HashMap::new);
+
+    // Start over on another tab or connection, and after schema changes
+    use_effect(move || {
+        let _ = app_state.read().schema_version.read();
+        let _ = app_state.read().connection_status.read();
+        let (tab_connection, tab_keyspace) = tab_context();
+        let connection_manager = app_state.read().connection_manager.clone();
+        spawn(async move {
+            let connection = match tab_connection {
+                Some(id) => connection_manager.get_connection(id).await,
+                None => connection_manager.get_active_connection().await,
+            };
+            let (keyspace, names) = match connection {
+                Some(conn) => (
+                    conn.tab_keyspace(tab_keyspace).await,
+                    conn.list_keyspaces().await.ok(),
+                ),
+                None => (None, None),
+            };
+            schemas.set(HashMap::new());
+            default_keyspace.set(keyspace);
+            keyspace_names.set(names);
+        });
+    });
+
+    let parsed = use_memo(move || cql_parser::parse(&value.read()));
+
+    // Load the schema of keyspaces the script starts using
+    use_effect(move || {
+        let Some(names) = keyspace_names.read().clone() else {
+            return;
+        };
+        let wanted: Vec<String> =
+            diagnostics::referenced_keyspaces(&parsed.read(), default_keyspace.read().as_deref())
+                .into_iter()
+                .filter(|ks| names.contains(ks) && !schemas.peek().contains_key(ks))
+                .collect();
+        if wanted.is_empty() {
+            return;
+        }
+        let tab_connection = tab_context.peek().0;
+        let connection_manager = app_state.read().connection_manager.clone();
+        spawn(async move {
+            let connection = match tab_connection {
+                Some(id) => connection_manager.get_connection(id).await,
+                None => connection_manager.get_active_connection().await,
+            };
+            let Some(conn) = connection else {
+                return;
+            };
+            for keyspace in wanted {
+                if let Ok(schema) = conn.keyspace_schema(&keyspace).await {
+                    schemas.write().insert(keyspace, schema);
+                }
+            }
+        });
+    });
+
+    let script_diagnostics = use_memo(move || {
+        let schemas = schemas.read();
+        let keyspaces = keyspace_names.read();
+        let keyspace = default_keyspace.read();
+        let context = SchemaContext {
+            default_keyspace: keyspace.as_deref(),
+            keyspaces: keyspaces.as_deref(),
+            schemas: &schemas,
+        };
+        diagnostics::diagnose(&value.read(), &parsed.read(), &context)
+    });
+
+    // Highlighted HTML with diagnostics underlined — recomputes reactively
+    // whenever `value` or the diagnostics change
     let highlighted = use_memo(move || {
         let src = value.read().clone();
         let tokens = cql_tokenizer::tokenize(&src);
-        cql_tokenizer::to_highlighted_html(&tokens)
+        let diagnostics = script_diagnostics.read();
+        let marks: Vec<Mark> = diagnostics
+            .iter()
+            .map(|d| Mark {
+                start: d.span.start,
+                end: d.span.end,
+                class: match d.severity {
+                    Severity::Error => "cql-mark-error",
+                    Severity::Warning | Severity::Info => "cql-mark-warning",
+                },
+                message: &d.message,
+            })
+            .collect();
+        cql_tokenizer::to_highlighted_html_marked(&tokens, &marks)
     });
 
-    // Set up scroll sync once on mount
+    // Set up scroll sync once on mount. The overlay doesn't take pointer
+    // events, so the textarea shows the message of the marks under the
+    // pointer as its tooltip.
     use_effect(move || {
         spawn(async move {
             let _ = document::eval(
@@ -45,6 +158,22 @@ pub fn CodeEditor(
                             pre.scrollTop = ta.scrollTop;
                             pre.scrollLeft = ta.scrollLeft;
                         });
+                        ta.addEventListener('mousemove', function(e) {
+                            const messages = [];
+                            for (const mark of pre.querySelectorAll('.cql-mark')) {
+                                for (const r of mark.getClientRects()) {
+                                    if (e.clientX >= r.left && e.clientX <= r.right
+                                        && e.clientY >= r.top && e.clientY <= r.bottom
+                                        && !messages.includes(mark.dataset.message)) {
+                                        messages.push(mark.dataset.message);
+                                    }
+                                }
+                            }
+                            const title = messages.join('\n');
+                            if (ta.title !== title) {
+                                ta.title = title;
+                            }
+                        });
                     }
                 })();
                 "#,
@@ -55,6 +184,15 @@ pub fn CodeEditor(
     // Fetch cursor position and compute suggestions
     let compute_suggestions = move |text: String| {
         let connection_manager = app_state.read().connection_manager.clone();
+        let active_id = *app_state.read().active_editor_tab.peek();
+        let (tab_connection, tab_keyspace) = app_state
+            .read()
+            .editor_tabs
+            .peek()
+            .iter()
+            .find(|t| t.id == active_id)
+            .map(|t| (t.connection_id, t.keyspace.clone()))
+            .unwrap_or_default();
         spawn(async move {
             // Get cursor position via dioxus.send() + recv() (the documented pattern).
             // Fallback to end-of-text if eval fails (common case: user types at the end).
@@ -71,8 +209,21 @@ pub fn CodeEditor(
                 }
             };
 
-            let (partial, word_start) = cql_tokenizer::word_at_cursor(&text, cursor);
-            if partial.len() < 2 {
+            let word = cql_tokenizer::word_at_cursor(&text, cursor);
+            let (partial, word_start) = (word.partial, word.start);
+            // After `keyspace.` the keyspace's tables, views and types are
+            // listed straight away
+            let qualifier = word
+                .qualifier
+                .and_then(|q| Identifier::parse(q).ok())
+                .map(|id| id.as_str().to_string());
+            // Column names complete from the first letter, everything else from the second
+            let column_ctx = if partial.is_empty() || qualifier.is_some() {
+                None
+            } else {
+                completion::column_context(&text, word_start)
+            };
+            if partial.len() < 2 && column_ctx.is_none() && qualifier.is_none() {
                 ac_visible.set(false);
                 return;
             }
@@ -83,30 +234,73 @@ pub fn CodeEditor(
             let prev_kw = cql_tokenizer::keyword_before_cursor(&text, cursor);
             let in_table_ctx = prev_kw.as_deref().is_some_and(is_table_context);
 
-            let mut items: Vec<String> = Vec::new();
-
-            if in_table_ctx {
-                // Try to get table names from the active connection
-                if let Some(conn) = connection_manager.get_active_connection().await {
-                    if let Some(ks) = conn.resolve_keyspace().await {
-                        if let Ok(tables) = conn.list_tables(&ks).await {
-                            let upper_partial = partial.to_ascii_uppercase();
-                            items = tables
-                                .into_iter()
-                                .filter(|t| t.to_ascii_uppercase().starts_with(&upper_partial))
-                                .take(MAX_SUGGESTIONS)
-                                .collect();
+            let mut items: Vec<Completion> = Vec::new();
+
+            if in_table_ctx || column_ctx.is_some() || qualifier.is_some() {
+                // Look up schema on the tab's connection and keyspace
+                let connection = match tab_connection {
+                    Some(id) => connection_manager.get_connection(id).await,
+                    None => connection_manager.get_active_connection().await,
+                };
+                if let Some(conn) = connection {
+                    let keyspace = match tab_keyspace {
+                        Some(ks) => Some(ks),
+                        None => conn.resolve_keyspace().await,
+                    };
+                    if let Some(ks) = &qualifier {
+                        if let Ok(schema) = conn.keyspace_schema(ks).await {
+                            items = completion::keyspace_object_completions(&schema, partial);
+                            items.truncate(MAX_SUGGESTIONS);
+                        }
+                    } else if in_table_ctx {
+                        if let Some(ks) = keyspace {
+                            if let Ok(tables) = conn.list_tables(&ks).await {
+                                let upper_partial = partial.to_ascii_uppercase();
+                                // Case-sensitive names are inserted quoted
+                                items = tables
+                                    .into_iter()
+                                    .filter(|t| t.to_ascii_uppercase().starts_with(&upper_partial))
+                                    .take(MAX_SUGGESTIONS)
+                                    .map(|t| Completion::plain(quote_identifier(&t)))
+                                    .collect();
+                            }
+                        }
+                        // Keyspaces follow, to be qualified with a dot
+                        if let Ok(keyspaces) = conn.list_keyspaces().await {
+                            let room = MAX_SUGGESTIONS - items.len();
+                            items.extend(
+                                completion::keyspace_completions(&keyspaces, partial)
+                                    .into_iter()
+                                    .take(room),
+                            );
+                        }
+                    } else if let (Some(context), Some(table)) =
+                        (column_ctx, completion::statement_table(&text, cursor))
+                    {
+                        if let Some(ks) = table.keyspace.or(keyspace) {
+                            if let Ok(schema) = conn.describe_table(&ks, &table.name).await {
+                                items = completion::column_completions(
+                                    &schema.columns,
+                                    context,
+                                    partial,
+                                );
+                                items.truncate(MAX_SUGGESTIONS);
+                            }
                         }
                     }
                 }
             }
 
-            // Fall back to (or supplement with) keyword/type/function completions
-            if items.is_empty() {
-                items = cql_tokenizer::suggest_completions(partial, MAX_SUGGESTIONS)
+            // Fall back to keyword/type/function completions, or fill up the
+            // column list with them (e.g. FROM after a SELECT list)
+            if (items.is_empty() || !in_table_ctx) && qualifier.is_none() && partial.len() >= 2 {
+                let room = MAX_SUGGESTIONS - items.len();
+                let keywords: Vec<Completion> = cql_tokenizer::suggest_completions(partial, room)
                     .into_iter()
-                    .map(String::from)
+                    .filter(|kw| !items.iter().any(|c| c.text.eq_ignore_ascii_case(kw)))
+                    .map(Completion::plain)
                     .collect();
+                items.extend(keywords);
             }
 
             if items.is_empty() {
@@ -199,7 +393,7 @@ pub fn CodeEditor(
                     let idx = *selected_idx.read();
                     let item = suggestions.read().get(idx).cloned();
                     if let Some(item) = item {
-                        apply_completion(item);
+                        apply_completion(item.text);
                     }
                 }
                 Key::Escape => {
@@ -242,17 +436,20 @@ pub fn CodeEditor(
 
                     for (i, item) in suggestions.read().iter().enumerate() {
                         div {
-                            key: "{item}",
+                            key: "{item.detail}:{item.text}",
                             class: if i == *selected_idx.read() { "ac-item ac-item-selected" } else { "ac-item" },
                             // mousedown fires before blur, so the textarea keeps focus
                             onmousedown: {
-                                let item = item.clone();
+                                let text = item.text.clone();
                                 move |e: Event<MouseData>| {
                                     e.prevent_default();
-                                    apply_completion(item.clone());
+                                    apply_completion(text.clone());
                                 }
                             },
-                            "{item}"
+                            span { class: "ac-text", "{item.text}" }
+                            if !item.detail.is_empty() {
+                                span { class: "ac-detail", "{item.detail}" }
+                            }
                         }
                     }
                 }
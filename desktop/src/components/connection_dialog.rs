@@ -1,4 +1,6 @@
 use crate::connection::ConnectionConfig;
+use crate::identifier::Identifier;
+use crate::schema::quote_identifier;
 use dioxus::prelude::*;
 
 #[component]
@@ -40,12 +42,16 @@ pub fn ConnectionDialog(
             .and_then(|c| c.password.clone())
             .unwrap_or_default()
     });
+    // Shown as typed in CQL, so case-sensitive names appear quoted
     let mut keyspace = use_signal(|| {
         existing
             .as_ref()
-            .and_then(|c| c.keyspace.clone())
+            .and_then(|c| c.keyspace.as_deref())
+            .map(quote_identifier)
             .unwrap_or_default()
     });
+    let mut snapshot_on_connect =
+        use_signal(|| existing.as_ref().is_some_and(|c| c.snapshot_on_connect));
     let mut validation_error = use_signal(|| None::<String>);
 
     let title = if is_edit { "Edit Connection" } else { "New Connection" };
@@ -137,6 +143,16 @@ pub fn ConnectionDialog(
                             oninput: move |e| keyspace.set(e.value())
                         }
                     }
+
+                    label {
+                        class: "form-checkbox",
+                        input {
+                            r#type: "checkbox",
+                            checked: *snapshot_on_connect.read(),
+                            onchange: move |e| snapshot_on_connect.set(e.checked()),
+                        }
+                        "Snapshot schema on connect"
+                    }
                 }
 
                 // Validation error message
@@ -177,12 +193,17 @@ pub fn ConnectionDialog(
                             };
                             // Validate keyspace if provided
                             let ks = keyspace.read().trim().to_string();
-                            if !ks.is_empty() {
-                                if let Err(e) = crate::cassandra::validate_cql_identifier(&ks) {
-                                    validation_error.set(Some(format!("Invalid keyspace: {}", e)));
-                                    return;
+                            let ks = if ks.is_empty() {
+                                None
+                            } else {
+                                match Identifier::parse(&ks) {
+                                    Ok(id) => Some(id.as_str().to_string()),
+                                    Err(e) => {
+                                        validation_error.set(Some(format!("Invalid keyspace: {}", e)));
+                                        return;
+                                    }
                                 }
-                            }
+                            };
 
                             validation_error.set(None);
 
@@ -201,15 +222,14 @@ pub fn ConnectionDialog(
                                 )
                             };
                             config.port = port_num;
+                            config.snapshot_on_connect = *snapshot_on_connect.read();
 
                             if !username.read().is_empty() {
                                 config.username = Some(username.read().clone());
                                 config.password = Some(password.read().clone());
                             }
 
-                            if !ks.is_empty() {
-                                config.keyspace = Some(ks);
-                            }
+                            config.keyspace = ks;
 
                             on_save.call(config);
                         },
@@ -1,7 +1,11 @@
 use dioxus::prelude::*;
 use serde_json::Value;
 
+use super::error_panel::ErrorPanel;
+use crate::error::ErrorReport;
+use crate::schema::qualified_name;
 use crate::state::{AppState, DEFAULT_PAGE_SIZE};
+use crate::virtual_tables::{self, VirtualTable};
 
 #[derive(Clone, Debug)]
 pub struct ColumnInfo {
@@ -17,42 +21,76 @@ pub struct QueryResult {
     pub row_count: usize,
 }
 
+impl QueryResult {
+    /// Single-cell result for statements that return no rows
+    pub fn message(text: impl Into<String>, execution_time_ms: u64) -> Self {
+        Self {
+            columns: vec![ColumnInfo {
+                name: "result".to_string(),
+                data_type: "text".to_string(),
+            }],
+            rows: vec![vec![Value::String(text.into())]],
+            execution_time_ms,
+            row_count: 1,
+        }
+    }
+}
+
 #[component]
-pub fn DataGrid(#[props(optional)] table_name: Option<String>) -> Element {
+pub fn DataGrid(
+    #[props(optional)] keyspace: Option<String>,
+    #[props(optional)] table_name: Option<String>,
+) -> Element {
     let app_state = use_context::<Signal<AppState>>();
     let mut query_result = use_signal(|| None::<QueryResult>);
     let mut selected_row = use_signal(|| None::<usize>);
     let mut loading = use_signal(|| false);
+    let mut load_error = use_signal(|| None::<ErrorReport>);
+    // Set while a virtual table is shown; those are read whole, not paged
+    let mut virtual_table = use_signal(|| None::<VirtualTable>);
 
     // Load table data when table_name changes
-    use_effect(use_reactive!(|table_name| {
+    use_effect(use_reactive!(|keyspace, table_name| {
         // Clear previous results when table changes
         query_result.set(None);
+        load_error.set(None);
+        virtual_table.set(None);
 
         if let Some(ref table) = table_name {
             loading.set(true);
             let table_clone = table.clone();
+            let keyspace = keyspace.clone();
             spawn(async move {
                 let cm = app_state.read().connection_manager.clone();
                 if let Some(connection) = cm.get_active_connection().await {
-                    if let Some(keyspace) = connection.resolve_keyspace().await {
-                        // Validate identifiers before interpolating into CQL
-                        if let Err(e) = crate::cassandra::validate_cql_identifier(&keyspace) {
-                            tracing::error!("Invalid keyspace name: {}", e);
-                            query_result.set(None);
-                            loading.set(false);
-                            return;
-                        }
-                        if let Err(e) = crate::cassandra::validate_cql_identifier(&table_clone) {
-                            tracing::error!("Invalid table name: {}", e);
-                            query_result.set(None);
-                            loading.set(false);
-                            return;
-                        }
-                        let query = format!(
-                            "SELECT * FROM {}.{} LIMIT {}",
-                            keyspace, table_clone, DEFAULT_PAGE_SIZE
-                        );
+                    let keyspace = match keyspace {
+                        Some(ks) => Some(ks),
+                        None => connection.resolve_keyspace().await,
+                    };
+                    if let Some(keyspace) = keyspace {
+                        let query = if virtual_tables::is_virtual_keyspace(&keyspace) {
+                            let table = connection
+                                .virtual_tables()
+                                .await
+                                .unwrap_or_default()
+                                .into_iter()
+                                .find(|t| t.keyspace == keyspace && t.name == table_clone)
+                                .unwrap_or_else(|| VirtualTable {
+                                    keyspace: keyspace.clone(),
+                                    name: table_clone.clone(),
+                                    comment: String::new(),
+                                    columns: Vec::new(),
+                                });
+                            let query = table.browse_query();
+                            virtual_table.set(Some(table));
+                            query
+                        } else {
+                            format!(
+                                "SELECT * FROM {} LIMIT {}",
+                                qualified_name(&keyspace, &table_clone),
+                                DEFAULT_PAGE_SIZE
+                            )
+                        };
                         match connection.execute_query(&query).await {
                             Ok(result) => {
                                 tracing::info!(
@@ -70,6 +108,7 @@ pub fn DataGrid(#[props(optional)] table_name: Option<String>) -> Element {
                                     e
                                 );
                                 query_result.set(None);
+                                load_error.set(Some(ErrorReport::from(&e)));
                             }
                         }
                     } else {
@@ -144,6 +183,21 @@ pub fn DataGrid(#[props(optional)] table_name: Option<String>) -> Element {
                 }
             }
 
+            if let Some(table) = virtual_table.read().as_ref() {
+                div {
+                    class: "virtual-view-header",
+                    h4 { "{table.title()}" }
+                    span { class: "virtual-view-source", "{table.keyspace}.{table.name}" }
+                    if !table.description().is_empty() {
+                        p { class: "form-hint", "{table.description()}" }
+                    }
+                }
+            }
+
+            if let Some(report) = load_error.read().as_ref() {
+                ErrorPanel { report: report.clone() }
+            }
+
             // Table container
             div {
                 class: "table-container",
@@ -217,25 +271,36 @@ pub fn DataGrid(#[props(optional)] table_name: Option<String>) -> Element {
                 }
             }
 
-            // Pagination
-            div {
-                class: "pagination",
-
-                button {
-                    class: "btn-icon",
-                    disabled: true,
-                    "◀"
+            // Pagination; virtual tables are local to the coordinator and
+            // shown in full
+            if virtual_table.read().is_some() {
+                div {
+                    class: "pagination",
+                    span {
+                        class: "page-info",
+                        "All rows from the coordinator node"
+                    }
                 }
+            } else {
+                div {
+                    class: "pagination",
 
-                span {
-                    class: "page-info",
-                    "Page 1 of 1"
-                }
+                    button {
+                        class: "btn-icon",
+                        disabled: true,
+                        "◀"
+                    }
+
+                    span {
+                        class: "page-info",
+                        "Page 1 of 1"
+                    }
 
-                button {
-                    class: "btn-icon",
-                    disabled: true,
-                    "▶"
+                    button {
+                        class: "btn-icon",
+                        disabled: true,
+                        "▶"
+                    }
                 }
             }
         }
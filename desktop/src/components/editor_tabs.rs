@@ -0,0 +1,269 @@
+use dioxus::prelude::*;
+use uuid::Uuid;
+
+use crate::config::save_editor_tabs;
+use crate::connection::ConnectionConfig;
+use crate::state::{AppState, EditorTab};
+
+/// First free "Query N" title among the open tabs
+fn next_tab_title(tabs: &[EditorTab]) -> String {
+    (1..)
+        .map(|n| format!("Query {}", n))
+        .find(|title| !tabs.iter().any(|t| &t.title == title))
+        .unwrap_or_default()
+}
+
+/// Tab strip above the query editor: switch, open and close editor tabs,
+/// and bind the active tab to one of the connected clusters.
+#[component]
+pub fn EditorTabBar() -> Element {
+    let app_state = use_context::<Signal<AppState>>();
+    let mut tabs = app_state.read().editor_tabs;
+    let mut active_tab_id = app_state.read().active_editor_tab;
+    let mut executions = app_state.read().tab_executions;
+    let mut query_text = app_state.read().query_text;
+    let connection_status = app_state.read().connection_status;
+    let mut connected = use_signal(Vec::<ConnectionConfig>::new);
+    let mut keyspaces = use_signal(Vec::<String>::new);
+
+    // Only the active tab's binding matters for the keyspace list, not its text
+    let tab_connection = use_memo(move || {
+        let id = *active_tab_id.read();
+        tabs.read()
+            .iter()
+            .find(|t| t.id == id)
+            .and_then(|t| t.connection_id)
+    });
+
+    // Reload the list of connected clusters whenever a connection opens or closes
+    use_effect(move || {
+        let _ = connection_status.read();
+        let cm = app_state.read().connection_manager.clone();
+        spawn(async move {
+            let mut list = Vec::new();
+            for config in cm.get_configs().await {
+                if cm.is_connected(config.id).await {
+                    list.push(config);
+                }
+            }
+            connected.set(list);
+        });
+    });
+
+    // Keyspaces offered by the selector follow the connection the tab runs against
+    use_effect(move || {
+        let _ = connection_status.read();
+        let connection_id = *tab_connection.read();
+        let cm = app_state.read().connection_manager.clone();
+        spawn(async move {
+            let connection = match connection_id {
+                Some(id) => cm.get_connection(id).await,
+                None => cm.get_active_connection().await,
+            };
+            let list = match connection {
+                Some(conn) => conn.list_keyspaces().await.unwrap_or_else(|e| {
+                    tracing::error!("Failed to list keyspaces: {}", e);
+                    Vec::new()
+                }),
+                None => Vec::new(),
+            };
+            keyspaces.set(list);
+        });
+    });
+
+    // Copy the editor buffer into the tab being left so no keystroke is lost
+    let mut flush_active = move || {
+        let id = *active_tab_id.peek();
+        let text = query_text.peek().clone();
+        if let Some(tab) = tabs.write().iter_mut().find(|t| t.id == id) {
+            tab.query = text;
+        }
+    };
+
+    let mut switch_to = move |id: Uuid| {
+        if *active_tab_id.peek() == id {
+            return;
+        }
+        flush_active();
+        let text = tabs
+            .peek()
+            .iter()
+            .find(|t| t.id == id)
+            .map(|t| t.query.clone())
+            .unwrap_or_default();
+        active_tab_id.set(id);
+        query_text.set(text);
+        save_editor_tabs(&tabs.peek(), id);
+    };
+
+    let open_tab = move |_| {
+        flush_active();
+        let current = *active_tab_id.peek();
+        let mut tab = EditorTab::new(next_tab_title(&tabs.peek()));
+        if let Some(source) = tabs.peek().iter().find(|t| t.id == current) {
+            tab.connection_id = source.connection_id;
+            tab.keyspace = source.keyspace.clone();
+        }
+        let id = tab.id;
+        tabs.write().push(tab);
+        active_tab_id.set(id);
+        query_text.set(String::new());
+        save_editor_tabs(&tabs.peek(), id);
+    };
+
+    let mut close_tab = move |id: Uuid| {
+        if let Some(execution) = executions.write().remove(&id) {
+            if let Some(task) = execution.task {
+                task.cancel();
+            }
+        }
+
+        let index = tabs.peek().iter().position(|t| t.id == id);
+        let Some(index) = index else {
+            return;
+        };
+        tabs.write().remove(index);
+        if tabs.peek().is_empty() {
+            tabs.write().push(EditorTab::new("Query 1"));
+        }
+
+        if *active_tab_id.peek() == id {
+            let next = tabs.peek()[index.min(tabs.peek().len() - 1)].clone();
+            active_tab_id.set(next.id);
+            query_text.set(next.query);
+        }
+        save_editor_tabs(&tabs.peek(), *active_tab_id.peek());
+    };
+
+    let mut bind_connection = move |value: String| {
+        let id = *active_tab_id.peek();
+        let connection_id = Uuid::parse_str(&value).ok();
+        let keyspace = connected
+            .peek()
+            .iter()
+            .find(|c| Some(c.id) == connection_id)
+            .and_then(|c| c.keyspace.clone());
+        if let Some(tab) = tabs.write().iter_mut().find(|t| t.id == id) {
+            tab.connection_id = connection_id;
+            tab.keyspace = keyspace;
+        }
+        save_editor_tabs(&tabs.peek(), id);
+    };
+
+    let mut select_keyspace = move |value: String| {
+        let id = *active_tab_id.peek();
+        if let Some(tab) = tabs.write().iter_mut().find(|t| t.id == id) {
+            tab.keyspace = if value.is_empty() { None } else { Some(value) };
+        }
+        save_editor_tabs(&tabs.peek(), id);
+    };
+
+    let active_id = *active_tab_id.read();
+    let tab_keyspace = tabs
+        .read()
+        .iter()
+        .find(|t| t.id == active_id)
+        .and_then(|t| t.keyspace.clone())
+        .unwrap_or_default();
+    let bound_connection = tabs
+        .read()
+        .iter()
+        .find(|t| t.id == active_id)
+        .and_then(|t| t.connection_id)
+        .map(|id| id.to_string())
+        .unwrap_or_default();
+    let running: Vec<Uuid> = executions
+        .read()
+        .iter()
+        .filter(|(_, e)| e.is_running)
+        .map(|(id, _)| *id)
+        .collect();
+
+    rsx! {
+        div {
+            class: "editor-tabs",
+
+            div {
+                class: "editor-tab-list",
+
+                for tab in tabs.read().iter() {
+                    {
+                        let id = tab.id;
+                        let tooltip = match (&tab.keyspace, tab.connection_id) {
+                            (Some(ks), _) => format!("{} — keyspace {}", tab.title, ks),
+                            (None, Some(_)) => format!("{} — bound connection", tab.title),
+                            (None, None) => format!("{} — active connection", tab.title),
+                        };
+                        rsx! {
+                            div {
+                                key: "{tab.id}",
+                                class: if id == active_id { "editor-tab active" } else { "editor-tab" },
+                                title: "{tooltip}",
+                                onclick: move |_| switch_to(id),
+
+                                if running.contains(&id) {
+                                    span { class: "editor-tab-running", "●" }
+                                }
+
+                                span { class: "editor-tab-title", "{tab.title}" }
+
+                                button {
+                                    class: "editor-tab-close",
+                                    title: "Close tab",
+                                    onclick: move |e| {
+                                        e.stop_propagation();
+                                        close_tab(id);
+                                    },
+                                    "×"
+                                }
+                            }
+                        }
+                    }
+                }
+
+                button {
+                    class: "editor-tab-new",
+                    title: "New query tab",
+                    onclick: open_tab,
+                    "+"
+                }
+            }
+
+            select {
+                class: "select-keyspace editor-tab-connection",
+                title: "Connection used by this tab",
+                value: "{bound_connection}",
+                onchange: move |e| bind_connection(e.value()),
+
+                option { value: "", "Active connection" }
+                for config in connected.read().iter() {
+                    option {
+                        key: "{config.id}",
+                        value: "{config.id}",
+                        "{config.name}"
+                    }
+                }
+            }
+
+            select {
+                class: "select-keyspace editor-tab-connection",
+                title: "Keyspace used by this tab (USE ks switches it too)",
+                value: "{tab_keyspace}",
+                onchange: move |e| select_keyspace(e.value()),
+
+                option { value: "", "Default keyspace" }
+                // Keep a tracked keyspace selectable even before the list loads
+                if !tab_keyspace.is_empty() && !keyspaces.read().contains(&tab_keyspace) {
+                    option { value: "{tab_keyspace}", "{tab_keyspace}" }
+                }
+                for ks in keyspaces.read().iter() {
+                    option {
+                        key: "{ks}",
+                        value: "{ks}",
+                        "{ks}"
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use dioxus::prelude::*;
+
+use crate::error::ErrorReport;
+
+/// Error box explaining a failed request: title, plain-language explanation,
+/// protocol details (code, consistency, replica counts) and the raw server message.
+#[component]
+pub fn ErrorPanel(report: ErrorReport) -> Element {
+    rsx! {
+        div {
+            class: "query-error",
+
+            div { class: "error-header", "{report.title}" }
+
+            if !report.explanation.is_empty() {
+                div { class: "error-explanation", "{report.explanation}" }
+            }
+
+            if !report.details.is_empty() {
+                div {
+                    class: "error-details",
+                    for (label, value) in report.details.iter() {
+                        div {
+                            key: "{label}",
+                            class: "error-detail",
+                            span { class: "error-detail-label", "{label}" }
+                            span { class: "error-detail-value", "{value}" }
+                        }
+                    }
+                }
+            }
+
+            div { class: "error-message", "{report.message}" }
+        }
+    }
+}
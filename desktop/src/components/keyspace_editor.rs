@@ -0,0 +1,373 @@
+use std::collections::BTreeMap;
+
+use dioxus::prelude::*;
+
+use crate::cql_tokenizer;
+use crate::keyspace_design::{KeyspaceDesign, Replication, Topology};
+use crate::schema::KeyspaceDef;
+use crate::state::{AppState, ConsoleCategory, StatusLevel};
+
+/// Replication strategy choice in the form
+#[derive(Clone, Copy, PartialEq)]
+enum Strategy {
+    NetworkTopology,
+    Simple,
+}
+
+/// Replication factor rows for the form: every data center of the cluster,
+/// plus any the design replicates to that the cluster does not know about
+fn factor_rows(design: &KeyspaceDesign, topology: &Topology) -> Vec<(String, String)> {
+    let mut rows: BTreeMap<String, String> = topology
+        .keys()
+        .map(|dc| (dc.clone(), "0".to_string()))
+        .collect();
+    if let Replication::NetworkTopology(factors) = &design.replication {
+        for (dc, rf) in factors {
+            rows.insert(dc.clone(), rf.to_string());
+        }
+    }
+    rows.into_iter().collect()
+}
+
+/// Wizard creating a keyspace, or altering `existing`, with replication set
+/// per data center of the active connection's cluster
+#[component]
+pub fn KeyspaceEditorDialog(
+    #[props(default)] existing: Option<KeyspaceDef>,
+    on_close: EventHandler<()>,
+    on_saved: EventHandler<String>,
+) -> Element {
+    let app_state = use_context::<Signal<AppState>>();
+    let initial = existing.as_ref().map(KeyspaceDesign::from_def);
+    let editable = !matches!(initial, Some(None));
+    let initial = initial.flatten();
+
+    let mut topology = use_signal(Topology::new);
+    let mut topology_error = use_signal(|| None::<String>);
+    let mut name = use_signal({
+        let initial = initial.clone();
+        move || initial.map(|d| d.name).unwrap_or_default()
+    });
+    let mut strategy = use_signal({
+        let initial = initial.clone();
+        move || match initial.map(|d| d.replication) {
+            Some(Replication::Simple(_)) => Strategy::Simple,
+            _ => Strategy::NetworkTopology,
+        }
+    });
+    let mut simple_rf = use_signal({
+        let initial = initial.clone();
+        move || match initial.map(|d| d.replication) {
+            Some(Replication::Simple(rf)) => rf.to_string(),
+            _ => "1".to_string(),
+        }
+    });
+    let mut factors = use_signal(Vec::<(String, String)>::new);
+    let mut durable_writes = use_signal({
+        let initial = initial.clone();
+        move || initial.is_none_or(|d| d.durable_writes)
+    });
+    let mut saving = use_signal(|| false);
+    let mut save_error = use_signal(|| None::<String>);
+
+    // Read the cluster's data centers and fill in replication factors
+    use_effect({
+        let initial = initial.clone();
+        move || {
+            let initial = initial.clone();
+            spawn(async move {
+                let cm = app_state.read().connection_manager.clone();
+                let Some(conn) = cm.get_active_connection().await else {
+                    topology_error.set(Some("No active connection".to_string()));
+                    return;
+                };
+                let found = match conn.datacenters().await {
+                    Ok(found) => found,
+                    Err(e) => {
+                        topology_error.set(Some(format!("Failed to read data centers: {}", e)));
+                        Topology::new()
+                    }
+                };
+                // Without a known topology, new keyspaces start out as SimpleStrategy
+                if initial.is_none() && found.is_empty() {
+                    strategy.set(Strategy::Simple);
+                }
+                let design = initial.unwrap_or_else(|| KeyspaceDesign::new(&found));
+                factors.set(factor_rows(&design, &found));
+                topology.set(found);
+            });
+        }
+    });
+
+    // The keyspace as edited, or why a replication factor is invalid
+    let design = use_memo(move || -> Result<KeyspaceDesign, String> {
+        let replication = match *strategy.read() {
+            Strategy::Simple => Replication::Simple(
+                simple_rf
+                    .read()
+                    .trim()
+                    .parse()
+                    .map_err(|_| "The replication factor must be a number".to_string())?,
+            ),
+            Strategy::NetworkTopology => {
+                let mut per_dc = BTreeMap::new();
+                for (dc, rf) in factors.read().iter() {
+                    let rf: u32 = rf.trim().parse().map_err(|_| {
+                        format!("The replication factor for {} must be a number", dc)
+                    })?;
+                    // Data centers left out of the map get no replicas
+                    if rf > 0 {
+                        per_dc.insert(dc.clone(), rf);
+                    }
+                }
+                Replication::NetworkTopology(per_dc)
+            }
+        };
+        Ok(KeyspaceDesign {
+            name: name.read().trim().to_string(),
+            replication,
+            durable_writes: *durable_writes.read(),
+        })
+    });
+
+    let problems = use_memo(move || match design.read().as_ref() {
+        Ok(design) => design.validate(),
+        Err(e) => vec![e.clone()],
+    });
+
+    let warnings = use_memo({
+        let existing = existing.clone();
+        move || {
+            let Ok(design) = design.read().clone() else {
+                return Vec::new();
+            };
+            let mut warnings = design.warnings(&topology.read());
+            if let Some(current) = existing.as_ref() {
+                if design.needs_repair(current, &topology.read()) {
+                    warnings.push(
+                        "New replicas only receive existing data after running a full repair"
+                            .to_string(),
+                    );
+                }
+            }
+            warnings
+        }
+    });
+
+    let statement = use_memo({
+        let existing = existing.clone();
+        move || {
+            let design = design.read();
+            let design = design.as_ref().ok()?;
+            match existing.as_ref() {
+                Some(current) => design.alter_cql(current),
+                None => Some(design.create_cql()),
+            }
+        }
+    });
+
+    let save = move |_| {
+        let Some(cql) = statement.read().clone() else {
+            return;
+        };
+        if !problems.read().is_empty() {
+            return;
+        }
+        let keyspace = name.read().trim().to_string();
+        saving.set(true);
+        save_error.set(None);
+        spawn(async move {
+            let cm = app_state.read().connection_manager.clone();
+            let Some(conn) = cm.get_active_connection().await else {
+                save_error.set(Some("No active connection".to_string()));
+                saving.set(false);
+                return;
+            };
+            match conn.execute_query(&cql).await {
+                Ok(_) => {
+                    let state = app_state.read();
+                    AppState::console_push(
+                        state.console_log,
+                        state.status_message,
+                        StatusLevel::Success,
+                        ConsoleCategory::Query,
+                        format!("Saved keyspace {}: {}", keyspace, cql),
+                    );
+                    saving.set(false);
+                    on_saved.call(keyspace);
+                }
+                Err(e) => {
+                    save_error.set(Some(format!("Failed to save keyspace: {}", e)));
+                    saving.set(false);
+                }
+            }
+        });
+    };
+
+    let title = match &existing {
+        Some(ks) => format!("Keyspace {}", ks.name),
+        None => "New Keyspace".to_string(),
+    };
+    let dc_count = topology.read().len();
+    let node_count: usize = topology.read().values().sum();
+
+    rsx! {
+        div {
+            class: "modal-backdrop",
+            onclick: move |_| on_close.call(()),
+
+            div {
+                class: "modal modal-wide",
+                onclick: move |e| e.stop_propagation(),
+
+                div {
+                    class: "modal-header",
+                    h2 { "{title}" }
+                    button {
+                        class: "btn-close",
+                        onclick: move |_| on_close.call(()),
+                        "x"
+                    }
+                }
+
+                div {
+                    class: "modal-body",
+
+                    if !editable {
+                        p { class: "form-hint", "This keyspace's replication strategy is managed by Cassandra and cannot be edited." }
+                    } else {
+                        if existing.is_none() {
+                            div {
+                                class: "form-group",
+                                label { "Keyspace name" }
+                                input {
+                                    r#type: "text",
+                                    value: "{name.read()}",
+                                    placeholder: "e.g. shop",
+                                    oninput: move |e| name.set(e.value()),
+                                }
+                            }
+                        }
+
+                        if let Some(error) = topology_error.read().as_ref() {
+                            p { class: "form-hint", "{error}" }
+                        } else {
+                            p { class: "form-hint", "Cluster: {node_count} node(s) in {dc_count} data center(s)" }
+                        }
+
+                        div {
+                            class: "form-group",
+                            label { "Replication strategy" }
+                            select {
+                                value: if *strategy.read() == Strategy::Simple { "simple" } else { "network" },
+                                onchange: move |e| {
+                                    strategy.set(if e.value() == "simple" { Strategy::Simple } else { Strategy::NetworkTopology })
+                                },
+                                option { value: "network", "NetworkTopologyStrategy" }
+                                option { value: "simple", "SimpleStrategy (development)" }
+                            }
+                        }
+
+                        if *strategy.read() == Strategy::Simple {
+                            div {
+                                class: "form-group",
+                                label { "Replication factor" }
+                                input {
+                                    r#type: "number",
+                                    min: "1",
+                                    value: "{simple_rf.read()}",
+                                    oninput: move |e| simple_rf.set(e.value()),
+                                }
+                            }
+                        } else {
+                            div {
+                                class: "form-group",
+                                label { "Replication factor per data center" }
+                                div {
+                                    class: "map-editor",
+                                    for (i, (dc, rf)) in factors.read().iter().enumerate() {
+                                        div {
+                                            key: "{dc}",
+                                            class: "map-editor-row",
+                                            span {
+                                                class: "keyspace-editor-dc",
+                                                "{dc}"
+                                                if let Some(nodes) = topology.read().get(dc) {
+                                                    span { class: "keyspace-editor-nodes", " ({nodes} node(s))" }
+                                                } else {
+                                                    span { class: "keyspace-editor-nodes", " (not in cluster)" }
+                                                }
+                                            }
+                                            input {
+                                                r#type: "number",
+                                                min: "0",
+                                                value: "{rf}",
+                                                oninput: move |e| factors.write()[i].1 = e.value(),
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+
+                        label {
+                            class: "form-checkbox",
+                            input {
+                                r#type: "checkbox",
+                                checked: *durable_writes.read(),
+                                onchange: move |e| durable_writes.set(e.checked()),
+                            }
+                            "Durable writes"
+                        }
+
+                        for warning in warnings.read().iter() {
+                            p { class: "keyspace-editor-warning", "⚠ {warning}" }
+                        }
+
+                        match statement.read().as_ref() {
+                            Some(cql) => rsx! {
+                                pre {
+                                    class: "schema-ddl",
+                                    dangerous_inner_html: cql_tokenizer::to_highlighted_html(&cql_tokenizer::tokenize(cql))
+                                }
+                            },
+                            None if existing.is_some() && problems.read().is_empty() => rsx! {
+                                p { class: "form-hint", "No changes" }
+                            },
+                            None => rsx! {},
+                        }
+                    }
+                }
+
+                if let Some(problem) = problems.read().first() {
+                    div { class: "form-error", "{problem}" }
+                } else if let Some(error) = save_error.read().as_ref() {
+                    div { class: "form-error", "{error}" }
+                }
+
+                div {
+                    class: "modal-footer",
+
+                    button {
+                        class: "btn btn-secondary",
+                        onclick: move |_| on_close.call(()),
+                        "Cancel"
+                    }
+
+                    button {
+                        class: "btn btn-primary",
+                        disabled: !editable || statement.read().is_none() || !problems.read().is_empty() || *saving.read(),
+                        onclick: save,
+                        if *saving.read() {
+                            "Saving..."
+                        } else if existing.is_some() {
+                            "Alter keyspace"
+                        } else {
+                            "Create keyspace"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use std::time::Duration;
+
+use dioxus::prelude::*;
+use uuid::Uuid;
+
+use crate::metrics::{format_bytes, format_latency, MetricsSnapshot, MetricsSummary};
+use crate::state::AppState;
+
+/// How often the panel re-reads the session statistics
+const REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Statistics of one open connection, as shown in the panel
+#[derive(Clone, PartialEq)]
+struct ConnectionMetrics {
+    id: Uuid,
+    name: String,
+    snapshot: MetricsSnapshot,
+}
+
+/// Per-connection and per-node latency histograms collected by the driver
+#[component]
+pub fn MetricsPanel() -> Element {
+    let app_state = use_context::<Signal<AppState>>();
+    let mut connections = use_signal(Vec::<ConnectionMetrics>::new);
+
+    use_future(move || async move {
+        loop {
+            let cm = app_state.read().connection_manager.clone();
+            let mut list: Vec<ConnectionMetrics> = cm
+                .get_active_connections()
+                .await
+                .iter()
+                .map(|conn| ConnectionMetrics {
+                    id: conn.id,
+                    name: conn.config.name.clone(),
+                    snapshot: conn.metrics(),
+                })
+                .collect();
+            list.sort_by(|a, b| a.name.cmp(&b.name));
+            if *connections.peek() != list {
+                connections.set(list);
+            }
+            tokio::time::sleep(REFRESH_INTERVAL).await;
+        }
+    });
+
+    let reset = move |id: Uuid| {
+        let cm = app_state.read().connection_manager.clone();
+        spawn(async move {
+            if let Some(conn) = cm.get_connection(id).await {
+                conn.reset_metrics();
+                // Show the cleared statistics without waiting for the next tick
+                if let Some(entry) = connections.write().iter_mut().find(|c| c.id == id) {
+                    entry.snapshot = conn.metrics();
+                }
+            }
+        });
+    };
+
+    rsx! {
+        div {
+            class: "metrics-panel",
+
+            div {
+                class: "metrics-header",
+                h3 { "Driver Metrics" }
+                span { class: "metrics-hint", "Latencies measured per request, including retries" }
+            }
+
+            if connections.read().is_empty() {
+                div {
+                    class: "empty-state",
+                    "No open connections. Connect to a cluster to collect query statistics."
+                }
+            }
+
+            for conn in connections.read().iter() {
+                {
+                    let id = conn.id;
+                    let overall = conn.snapshot.overall.clone();
+                    rsx! {
+                        div {
+                            key: "{conn.id}",
+                            class: "metrics-connection",
+
+                            div {
+                                class: "metrics-connection-header",
+                                h4 { "{conn.name}" }
+                                button {
+                                    class: "btn btn-small",
+                                    onclick: move |_| reset(id),
+                                    "Reset"
+                                }
+                            }
+
+                            SummaryCards { summary: overall }
+
+                            if !conn.snapshot.nodes.is_empty() {
+                                h5 { class: "metrics-section-title", "Per node" }
+                                table {
+                                    class: "results-table metrics-table",
+                                    thead {
+                                        tr {
+                                            th { "Node" }
+                                            th { "Queries" }
+                                            th { "Errors" }
+                                            th { "Retries" }
+                                            th { "p50" }
+                                            th { "p95" }
+                                            th { "p99" }
+                                            th { "Max" }
+                                        }
+                                    }
+                                    tbody {
+                                        for (node, stats) in conn.snapshot.nodes.iter() {
+                                            tr {
+                                                key: "{node}",
+                                                td { "{node}" }
+                                                td { "{stats.queries}" }
+                                                td { "{stats.errors}" }
+                                                td { "{stats.retries}" }
+                                                td { {format_latency(stats.p50_us)} }
+                                                td { {format_latency(stats.p95_us)} }
+                                                td { {format_latency(stats.p99_us)} }
+                                                td { {format_latency(stats.max_us)} }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+
+                            if !conn.snapshot.recent.is_empty() {
+                                h5 { class: "metrics-section-title", "Recent requests" }
+                                table {
+                                    class: "results-table metrics-table",
+                                    thead {
+                                        tr {
+                                            th { "Time" }
+                                            th { "Statement" }
+                                            th { "Latency" }
+                                            th { "Rows" }
+                                            th { "Response" }
+                                            th { "Retries" }
+                                            th { "Coordinator" }
+                                        }
+                                    }
+                                    tbody {
+                                        for (i, sample) in conn.snapshot.recent.iter().enumerate() {
+                                            tr {
+                                                key: "{i}",
+                                                class: if !sample.success { "metrics-row-error" },
+                                                td { {sample.at.with_timezone(&chrono::Local).format("%H:%M:%S").to_string()} }
+                                                td { class: "metrics-query", title: "{sample.query}", "{sample.query}" }
+                                                td { {format_latency(sample.latency_us)} }
+                                                td {
+                                                    {sample.rows.map(|n| n.to_string()).unwrap_or_else(|| "—".to_string())}
+                                                }
+                                                td { {format_bytes(sample.response_bytes as u64)} }
+                                                td { "{sample.retries}" }
+                                                td {
+                                                    {sample.coordinator.as_ref().map(|n| n.to_string()).unwrap_or_else(|| "—".to_string())}
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn SummaryCards(summary: MetricsSummary) -> Element {
+    let cards = [
+        ("Queries", summary.queries.to_string()),
+        ("Errors", summary.errors.to_string()),
+        ("Retries", summary.retries.to_string()),
+        ("Rows", summary.rows.to_string()),
+        ("Sent", format_bytes(summary.request_bytes)),
+        ("Received", format_bytes(summary.response_bytes)),
+        ("Mean", format_latency(summary.mean_us)),
+        ("p50", format_latency(summary.p50_us)),
+        ("p95", format_latency(summary.p95_us)),
+        ("p99", format_latency(summary.p99_us)),
+        ("Max", format_latency(summary.max_us)),
+    ];
+
+    rsx! {
+        div {
+            class: "metrics-cards",
+            for (label, value) in cards {
+                div {
+                    key: "{label}",
+                    class: "metrics-card",
+                    span { class: "metrics-card-label", "{label}" }
+                    span { class: "metrics-card-value", "{value}" }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,279 @@
+use std::path::PathBuf;
+
+use dioxus::prelude::*;
+
+use crate::cql_tokenizer;
+use crate::migrations::{self, Migration, MigrationState, MigrationStatus};
+use crate::schema::qualified_name;
+use crate::state::{AppState, ConsoleCategory, StatusLevel};
+
+fn state_class(state: MigrationState) -> &'static str {
+    match state {
+        MigrationState::Applied => "migration-applied",
+        MigrationState::Pending => "migration-pending",
+        MigrationState::Modified | MigrationState::OutOfOrder => "migration-problem",
+        MigrationState::Missing => "migration-missing",
+    }
+}
+
+/// Shows which migrations of a directory are applied to `keyspace`, previews
+/// the pending ones as a script and applies them in order
+#[component]
+pub fn MigrationRunnerDialog(
+    keyspace: String,
+    on_close: EventHandler<()>,
+    on_applied: EventHandler<()>,
+) -> Element {
+    let app_state = use_context::<Signal<AppState>>();
+    let mut directory = use_signal(|| None::<PathBuf>);
+    let mut files = use_signal(Vec::<Migration>::new);
+    let mut statuses = use_signal(Vec::<MigrationStatus>::new);
+    let mut load_error = use_signal(|| None::<String>);
+    let mut loading = use_signal(|| false);
+    let mut applying = use_signal(|| false);
+    let mut show_script = use_signal(|| false);
+    let mut refresh = use_signal(|| 0u32);
+
+    // Read the directory and the tracking table
+    use_effect({
+        let keyspace = keyspace.clone();
+        move || {
+            let _ = *refresh.read();
+            let Some(dir) = directory.read().clone() else {
+                return;
+            };
+            let keyspace = keyspace.clone();
+            loading.set(true);
+            load_error.set(None);
+            spawn(async move {
+                let local = match migrations::load_dir(&dir) {
+                    Ok(local) => local,
+                    Err(e) => {
+                        load_error.set(Some(format!("Failed to read migrations: {}", e)));
+                        files.set(Vec::new());
+                        statuses.set(Vec::new());
+                        loading.set(false);
+                        return;
+                    }
+                };
+                let cm = app_state.read().connection_manager.clone();
+                match cm.get_active_connection().await {
+                    Some(conn) => match conn.migration_status(&keyspace, &local).await {
+                        Ok(found) => statuses.set(found),
+                        Err(e) => load_error
+                            .set(Some(format!("Failed to read applied migrations: {}", e))),
+                    },
+                    None => load_error.set(Some("No active connection".to_string())),
+                }
+                files.set(local);
+                loading.set(false);
+            });
+        }
+    });
+
+    let problems = use_memo(move || migrations::problems(&statuses.read()));
+
+    let script = use_memo({
+        let keyspace = keyspace.clone();
+        move || {
+            let local = files.read();
+            let pending = migrations::pending(&local, &statuses.read());
+            (!pending.is_empty()).then(|| migrations::dry_run_script(&keyspace, &pending))
+        }
+    });
+
+    let pending_count = statuses
+        .read()
+        .iter()
+        .filter(|s| s.state == MigrationState::Pending)
+        .count();
+
+    let choose_directory = move |_| {
+        spawn(async move {
+            if let Some(folder) = rfd::AsyncFileDialog::new().pick_folder().await {
+                directory.set(Some(folder.path().to_path_buf()));
+            }
+        });
+    };
+
+    let apply = {
+        let keyspace = keyspace.clone();
+        move |_| {
+            let keyspace = keyspace.clone();
+            let local = files.read().clone();
+            applying.set(true);
+            spawn(async move {
+                let cm = app_state.read().connection_manager.clone();
+                let (level, message) = match cm.get_active_connection().await {
+                    None => (StatusLevel::Error, "No active connection".to_string()),
+                    Some(conn) => match conn.apply_migrations(&keyspace, &local).await {
+                        Ok(report) if report.agreement_timeouts > 0 => (
+                            StatusLevel::Info,
+                            format!(
+                                "Applied {} migration(s) to {}; schema agreement timed out {} time(s)",
+                                report.applied.len(),
+                                keyspace,
+                                report.agreement_timeouts
+                            ),
+                        ),
+                        Ok(report) => (
+                            StatusLevel::Success,
+                            format!(
+                                "Applied {} migration(s) to {}",
+                                report.applied.len(),
+                                keyspace
+                            ),
+                        ),
+                        Err(e) => (StatusLevel::Error, format!("Migration failed: {}", e)),
+                    },
+                };
+                let state = app_state.read();
+                AppState::console_push(
+                    state.console_log,
+                    state.status_message,
+                    level,
+                    ConsoleCategory::Query,
+                    message,
+                );
+                applying.set(false);
+                *refresh.write() += 1;
+                on_applied.call(());
+            });
+        }
+    };
+
+    let directory_label = directory
+        .read()
+        .as_ref()
+        .map(|d| d.display().to_string())
+        .unwrap_or_else(|| "No directory selected".to_string());
+    let tracking_table = qualified_name(&keyspace, migrations::TRACKING_TABLE);
+
+    rsx! {
+        div {
+            class: "modal-backdrop",
+            onclick: move |_| on_close.call(()),
+
+            div {
+                class: "modal modal-wide",
+                onclick: move |e| e.stop_propagation(),
+
+                div {
+                    class: "modal-header",
+                    h2 { "Migrations for {keyspace}" }
+                    button {
+                        class: "btn-close",
+                        onclick: move |_| on_close.call(()),
+                        "x"
+                    }
+                }
+
+                div {
+                    class: "modal-body",
+
+                    div {
+                        class: "migration-directory",
+                        span { class: "migration-directory-path", "{directory_label}" }
+                        button { class: "btn btn-small", onclick: choose_directory, "Choose directory..." }
+                        button {
+                            class: "btn btn-small",
+                            disabled: directory.read().is_none() || *loading.read(),
+                            onclick: move |_| *refresh.write() += 1,
+                            "Reload"
+                        }
+                    }
+                    p {
+                        class: "form-hint",
+                        "Files named <version>_<description>.cql are applied in version order and recorded in {tracking_table}."
+                    }
+
+                    if *loading.read() {
+                        div { class: "loading-indicator", "Reading migrations..." }
+                    } else if !statuses.read().is_empty() {
+                        table {
+                            class: "results-table migration-status",
+                            thead {
+                                tr {
+                                    th { "Version" }
+                                    th { "Description" }
+                                    th { "State" }
+                                    th { "Applied at" }
+                                }
+                            }
+                            tbody {
+                                for status in statuses.read().iter() {
+                                    tr {
+                                        key: "{status.version}",
+                                        td { "{status.version}" }
+                                        td { "{status.description}" }
+                                        td { span { class: state_class(status.state), "{status.state.label()}" } }
+                                        td {
+                                            {status.applied_at
+                                                .map(|at| at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S").to_string())
+                                                .unwrap_or_default()}
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    } else if directory.read().is_some() && load_error.read().is_none() {
+                        div { class: "empty-state", "No migrations found" }
+                    }
+
+                    if let Some(text) = script.read().as_ref() {
+                        label {
+                            class: "form-checkbox",
+                            input {
+                                r#type: "checkbox",
+                                checked: *show_script.read(),
+                                onchange: move |e| show_script.set(e.checked()),
+                            }
+                            "Show dry run"
+                        }
+                        if *show_script.read() {
+                            pre {
+                                class: "schema-ddl",
+                                dangerous_inner_html: cql_tokenizer::to_highlighted_html(&cql_tokenizer::tokenize(text))
+                            }
+                        }
+                    }
+                }
+
+                if let Some(error) = load_error.read().as_ref() {
+                    div { class: "form-error", "{error}" }
+                } else if !problems.read().is_empty() {
+                    div {
+                        class: "form-error",
+                        ul {
+                            class: "table-designer-problems",
+                            for problem in problems.read().iter() {
+                                li { "{problem}" }
+                            }
+                        }
+                    }
+                }
+
+                div {
+                    class: "modal-footer",
+
+                    button {
+                        class: "btn btn-secondary",
+                        onclick: move |_| on_close.call(()),
+                        "Close"
+                    }
+
+                    button {
+                        class: "btn btn-primary",
+                        disabled: pending_count == 0 || !problems.read().is_empty() || *applying.read() || *loading.read(),
+                        onclick: apply,
+                        if *applying.read() {
+                            "Applying..."
+                        } else {
+                            "Apply {pending_count} pending"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -2,9 +2,22 @@ pub mod code_editor;
 pub mod connection_dialog;
 pub mod console_panel;
 pub mod data_grid;
+pub mod editor_tabs;
+pub mod error_panel;
+pub mod keyspace_editor;
+pub mod metrics_panel;
+pub mod migration_runner;
 pub mod query_editor;
+pub mod schema_compare;
+pub mod schema_export;
+pub mod schema_history;
+pub mod schema_search;
 pub mod schema_viewer;
+pub mod script_results;
+pub mod security_panel;
 pub mod sidebar;
 pub mod statusbar;
+pub mod table_designer;
+pub mod table_options;
 pub mod variables_panel;
 pub mod workspace;
@@ -4,9 +4,14 @@ use crate::config::save_saved_queries;
 use crate::state::{AppState, SavedQuery};
 
 #[component]
-pub fn QueryEditor(on_execute: EventHandler<String>, is_executing: Signal<bool>) -> Element {
+pub fn QueryEditor(
+    on_execute: EventHandler<String>,
+    on_cancel: EventHandler<()>,
+    is_executing: ReadSignal<bool>,
+) -> Element {
     let mut app_state = use_context::<Signal<AppState>>();
     let mut query_text = app_state.read().query_text;
+    let mut stop_on_error = app_state.read().stop_on_error;
     let mut show_save_input = use_signal(|| false);
     let mut save_name = use_signal(String::new);
 
@@ -43,6 +48,14 @@ pub fn QueryEditor(on_execute: EventHandler<String>, is_executing: Signal<bool>)
                     }
                 }
 
+                if *is_executing.read() {
+                    button {
+                        class: "btn btn-danger",
+                        onclick: move |_| on_cancel.call(()),
+                        "Cancel"
+                    }
+                }
+
                 button {
                     class: "btn",
                     onclick: move |_| query_text.set(String::new()),
@@ -108,6 +121,17 @@ pub fn QueryEditor(on_execute: EventHandler<String>, is_executing: Signal<bool>)
                     }
                 }
 
+                label {
+                    class: "form-checkbox",
+                    title: "Stop a multi-statement script at its first failing statement",
+                    input {
+                        r#type: "checkbox",
+                        checked: *stop_on_error.read(),
+                        onchange: move |e| stop_on_error.set(e.checked()),
+                    }
+                    "Stop on error"
+                }
+
                 div {
                     class: "query-shortcuts",
                     span {
@@ -0,0 +1,338 @@
+use dioxus::prelude::*;
+use uuid::Uuid;
+
+use super::schema_history::SchemaHistory;
+use crate::cql_tokenizer;
+use crate::schema_diff::{diff_keyspaces, migration_script, ChangeKind, SchemaChange};
+use crate::state::{ActiveTab, AppState, ConsoleCategory, EditorTab, StatusLevel};
+
+/// An open connection that can be compared
+#[derive(Clone, PartialEq)]
+struct ConnectionChoice {
+    id: Uuid,
+    name: String,
+}
+
+/// What the compare tab compares
+#[derive(Clone, Copy, PartialEq)]
+enum CompareMode {
+    /// Two live keyspaces
+    Keyspaces,
+    /// Two stored snapshots of one connection
+    Snapshots,
+}
+
+/// Compare live keyspaces, or stored snapshots of one connection
+#[component]
+pub fn SchemaComparePanel() -> Element {
+    let mut mode = use_signal(|| CompareMode::Keyspaces);
+
+    rsx! {
+        div {
+            class: "schema-compare",
+
+            div {
+                class: "schema-view-toggle schema-compare-mode",
+                button {
+                    class: if *mode.read() == CompareMode::Keyspaces { "btn btn-small active" } else { "btn btn-small" },
+                    onclick: move |_| mode.set(CompareMode::Keyspaces),
+                    "Keyspaces"
+                }
+                button {
+                    class: if *mode.read() == CompareMode::Snapshots { "btn btn-small active" } else { "btn btn-small" },
+                    onclick: move |_| mode.set(CompareMode::Snapshots),
+                    "Snapshots"
+                }
+            }
+
+            match *mode.read() {
+                CompareMode::Keyspaces => rsx! { KeyspaceComparison {} },
+                CompareMode::Snapshots => rsx! { SchemaHistory {} },
+            }
+        }
+    }
+}
+
+/// Compare a keyspace against another, possibly on a different cluster, and
+/// generate the CQL that brings the target in line with the source
+#[component]
+fn KeyspaceComparison() -> Element {
+    let app_state = use_context::<Signal<AppState>>();
+    let mut connections = use_signal(Vec::<ConnectionChoice>::new);
+    let source_connection = use_signal(|| None::<Uuid>);
+    let source_keyspace = use_signal(|| None::<String>);
+    let target_connection = use_signal(|| None::<Uuid>);
+    let target_keyspace = use_signal(|| None::<String>);
+    let mut include_drops = use_signal(|| false);
+    let mut changes = use_signal(|| None::<Vec<SchemaChange>>);
+    let mut comparing = use_signal(|| false);
+    let mut error = use_signal(|| None::<String>);
+
+    use_effect(move || {
+        let cm = app_state.read().connection_manager.clone();
+        spawn(async move {
+            let mut list: Vec<ConnectionChoice> = cm
+                .get_active_connections()
+                .await
+                .iter()
+                .map(|conn| ConnectionChoice {
+                    id: conn.id,
+                    name: conn.config.name.clone(),
+                })
+                .collect();
+            list.sort_by(|a, b| a.name.cmp(&b.name));
+            connections.set(list);
+        });
+    });
+
+    let compare = move |_| {
+        let (Some(src_id), Some(src_ks), Some(dst_id), Some(dst_ks)) = (
+            *source_connection.read(),
+            source_keyspace.read().clone(),
+            *target_connection.read(),
+            target_keyspace.read().clone(),
+        ) else {
+            return;
+        };
+        comparing.set(true);
+        error.set(None);
+        changes.set(None);
+        spawn(async move {
+            let cm = app_state.read().connection_manager.clone();
+            let (Some(src), Some(dst)) = (
+                cm.get_connection(src_id).await,
+                cm.get_connection(dst_id).await,
+            ) else {
+                error.set(Some("Connection is not open".to_string()));
+                comparing.set(false);
+                return;
+            };
+            let result = match src.describe_keyspace(&src_ks).await {
+                Ok(source) => dst
+                    .describe_keyspace(&dst_ks)
+                    .await
+                    .map(|target| diff_keyspaces(&source, &target)),
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(diff) => changes.set(Some(diff)),
+                Err(e) => error.set(Some(format!("Failed to read schema: {}", e))),
+            }
+            comparing.set(false);
+        });
+    };
+
+    let script = use_memo(move || {
+        changes
+            .read()
+            .as_ref()
+            .map(|c| migration_script(c, *include_drops.read()))
+    });
+
+    let copy_script = move |_| {
+        let Some(text) = script.read().clone() else {
+            return;
+        };
+        let js = format!(
+            "navigator.clipboard.writeText({});",
+            serde_json::to_string(&text).unwrap_or_default()
+        );
+        let _ = document::eval(&js);
+        let state = app_state.read();
+        AppState::console_push(
+            state.console_log,
+            state.status_message,
+            StatusLevel::Info,
+            ConsoleCategory::System,
+            "Copied migration script to clipboard",
+        );
+    };
+
+    let open_script = move |_| {
+        let Some(text) = script.read().clone() else {
+            return;
+        };
+        let ks = target_keyspace.read().clone();
+        let mut tab = EditorTab::new(format!("Sync {}", ks.as_deref().unwrap_or("schema")));
+        tab.connection_id = *target_connection.read();
+        tab.keyspace = ks;
+        let state = app_state.read();
+        AppState::open_in_new_tab(
+            state.editor_tabs,
+            state.active_editor_tab,
+            state.query_text,
+            tab,
+            text,
+        );
+        state.active_tab.clone().set(ActiveTab::Query);
+    };
+
+    let ready = source_keyspace.read().is_some() && target_keyspace.read().is_some();
+
+    rsx! {
+        div {
+            div {
+                class: "schema-compare-header",
+
+                KeyspacePicker {
+                    label: "Source",
+                    connections: connections.read().clone(),
+                    connection: source_connection,
+                    keyspace: source_keyspace,
+                }
+                span { class: "schema-compare-arrow", "→" }
+                KeyspacePicker {
+                    label: "Target",
+                    connections: connections.read().clone(),
+                    connection: target_connection,
+                    keyspace: target_keyspace,
+                }
+
+                button {
+                    class: "btn btn-small btn-primary",
+                    disabled: !ready || *comparing.read(),
+                    onclick: compare,
+                    if *comparing.read() { "Comparing..." } else { "Compare" }
+                }
+            }
+
+            if let Some(err) = error.read().as_ref() {
+                div { class: "empty-state", "{err}" }
+            }
+
+            if connections.read().is_empty() {
+                div { class: "empty-state", "Connect to a cluster to compare schemas" }
+            } else if let Some(diff) = changes.read().as_ref() {
+                if diff.is_empty() {
+                    div { class: "empty-state", "The schemas are identical" }
+                } else {
+                    SchemaChangesTable { changes: diff.clone() }
+
+                    div {
+                        class: "schema-ddl-toolbar",
+                        label {
+                            class: "form-checkbox",
+                            input {
+                                r#type: "checkbox",
+                                checked: *include_drops.read(),
+                                onchange: move |e| include_drops.set(e.checked()),
+                            }
+                            "Include DROP statements"
+                        }
+                        button { class: "btn btn-small", onclick: copy_script, "Copy" }
+                        button { class: "btn btn-small btn-primary", onclick: open_script, "Open in editor" }
+                    }
+                    if let Some(text) = script.read().as_ref() {
+                        pre {
+                            class: "schema-ddl",
+                            dangerous_inner_html: cql_tokenizer::to_highlighted_html(&cql_tokenizer::tokenize(text))
+                        }
+                    }
+                }
+            } else if !*comparing.read() {
+                div {
+                    class: "empty-state",
+                    "Pick a source and a target keyspace. The generated script brings the target in line with the source."
+                }
+            }
+        }
+    }
+}
+
+/// One row per difference, coloured by kind
+#[component]
+pub fn SchemaChangesTable(changes: Vec<SchemaChange>) -> Element {
+    rsx! {
+        table {
+            class: "results-table schema-compare-changes",
+            thead {
+                tr {
+                    th { "" }
+                    th { "Object" }
+                    th { "Name" }
+                    th { "Difference" }
+                }
+            }
+            tbody {
+                for (i, change) in changes.iter().enumerate() {
+                    tr {
+                        key: "{i}",
+                        class: match change.kind {
+                            ChangeKind::Added => "schema-change-added",
+                            ChangeKind::Removed => "schema-change-removed",
+                            ChangeKind::Changed => "schema-change-changed",
+                        },
+                        td { class: "schema-change-symbol", "{change.kind.symbol()}" }
+                        td { "{change.object.label()}" }
+                        td { span { class: "column-name", "{change.name}" } }
+                        td { "{change.detail}" }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Connection and keyspace selectors for one side of the comparison
+#[component]
+fn KeyspacePicker(
+    label: &'static str,
+    connections: Vec<ConnectionChoice>,
+    connection: Signal<Option<Uuid>>,
+    keyspace: Signal<Option<String>>,
+) -> Element {
+    let app_state = use_context::<Signal<AppState>>();
+    let mut keyspaces = use_signal(Vec::<String>::new);
+
+    use_effect(move || {
+        let id = *connection.read();
+        keyspaces.set(Vec::new());
+        keyspace.set(None);
+        let Some(id) = id else {
+            return;
+        };
+        let cm = app_state.read().connection_manager.clone();
+        spawn(async move {
+            if let Some(conn) = cm.get_connection(id).await {
+                if let Ok(ks) = conn.list_keyspaces().await {
+                    keyspaces.set(ks);
+                }
+            }
+        });
+    });
+
+    let selected_connection = connection
+        .read()
+        .as_ref()
+        .map(Uuid::to_string)
+        .unwrap_or_default();
+
+    rsx! {
+        div {
+            class: "schema-compare-side",
+            span { class: "schema-compare-label", "{label}" }
+            select {
+                class: "select-keyspace",
+                value: "{selected_connection}",
+                onchange: move |e| connection.set(Uuid::parse_str(&e.value()).ok()),
+                option { value: "", "Connection..." }
+                for conn in connections.iter() {
+                    option { value: "{conn.id}", "{conn.name}" }
+                }
+            }
+            select {
+                class: "select-keyspace",
+                value: "{keyspace.read().as_deref().unwrap_or(\"\")}",
+                disabled: connection.read().is_none(),
+                onchange: move |e| {
+                    let v = if e.value().is_empty() { None } else { Some(e.value()) };
+                    keyspace.set(v);
+                },
+                option { value: "", "Keyspace..." }
+                for ks in keyspaces.read().iter() {
+                    option { value: "{ks}", "{ks}" }
+                }
+            }
+        }
+    }
+}
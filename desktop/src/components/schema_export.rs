@@ -0,0 +1,161 @@
+use dioxus::prelude::*;
+use uuid::Uuid;
+
+use crate::schema::export_script;
+use crate::state::{AppState, ConsoleCategory, StatusLevel};
+
+/// Export the schema of a connection, or of one of its keyspaces, to a `.cql` file
+#[component]
+pub fn SchemaExportDialog(
+    connection_id: Uuid,
+    #[props(default)] keyspace: Option<String>,
+    on_close: EventHandler<()>,
+) -> Element {
+    let app_state = use_context::<Signal<AppState>>();
+    let mut include_system = use_signal(|| false);
+    let mut exporting = use_signal(|| false);
+    let mut export_error = use_signal(|| None::<String>);
+
+    let title = match &keyspace {
+        Some(ks) => format!("Export Keyspace {}", ks),
+        None => "Export Schema".to_string(),
+    };
+
+    let export = {
+        let keyspace = keyspace.clone();
+        move |_| {
+            let keyspace = keyspace.clone();
+            let include_system = *include_system.read();
+            exporting.set(true);
+            export_error.set(None);
+            spawn(async move {
+                let cm = app_state.read().connection_manager.clone();
+                let Some(conn) = cm.get_connection(connection_id).await else {
+                    export_error.set(Some("Connection is not open".to_string()));
+                    exporting.set(false);
+                    return;
+                };
+
+                let schemas = match &keyspace {
+                    Some(ks) => conn.describe_keyspace(ks).await.map(|s| vec![s]),
+                    None => conn.describe_keyspaces(include_system).await,
+                };
+                let schemas = match schemas {
+                    Ok(s) => s,
+                    Err(e) => {
+                        export_error.set(Some(format!("Failed to read schema: {}", e)));
+                        exporting.set(false);
+                        return;
+                    }
+                };
+
+                let source = match &keyspace {
+                    Some(ks) => format!("{} / {}", conn.config.name, ks),
+                    None => conn.config.name.clone(),
+                };
+                let script = export_script(&source, &schemas);
+                let file_name = format!("{}.cql", keyspace.as_deref().unwrap_or("schema"));
+
+                if let Some(path) = rfd::AsyncFileDialog::new()
+                    .set_file_name(&file_name)
+                    .add_filter("CQL", &["cql"])
+                    .save_file()
+                    .await
+                {
+                    let console_log = app_state.read().console_log;
+                    let status_message = app_state.read().status_message;
+                    match tokio::fs::write(path.path(), script.as_bytes()).await {
+                        Ok(_) => {
+                            AppState::console_push(
+                                console_log,
+                                status_message,
+                                StatusLevel::Success,
+                                ConsoleCategory::System,
+                                format!(
+                                    "Exported {} keyspace(s) to {}",
+                                    schemas.len(),
+                                    path.path().display()
+                                ),
+                            );
+                            exporting.set(false);
+                            on_close.call(());
+                            return;
+                        }
+                        Err(e) => {
+                            tracing::error!("Failed to write schema export: {}", e);
+                            export_error.set(Some(format!("Failed to write file: {}", e)));
+                        }
+                    }
+                }
+                exporting.set(false);
+            });
+        }
+    };
+
+    rsx! {
+        div {
+            class: "modal-backdrop",
+            onclick: move |_| on_close.call(()),
+
+            div {
+                class: "modal",
+                onclick: move |e| e.stop_propagation(),
+
+                div {
+                    class: "modal-header",
+                    h2 { "{title}" }
+                    button {
+                        class: "btn-close",
+                        onclick: move |_| on_close.call(()),
+                        "x"
+                    }
+                }
+
+                div {
+                    class: "modal-body",
+
+                    p {
+                        class: "form-hint",
+                        "Writes a CQL script that recreates the schema: keyspaces, then types, functions, tables, indexes and materialized views."
+                    }
+
+                    if keyspace.is_none() {
+                        label {
+                            class: "form-checkbox",
+                            input {
+                                r#type: "checkbox",
+                                checked: *include_system.read(),
+                                onchange: move |e| include_system.set(e.checked()),
+                            }
+                            "Include system keyspaces"
+                        }
+                    }
+                }
+
+                if let Some(error) = export_error.read().as_ref() {
+                    div {
+                        class: "form-error",
+                        "{error}"
+                    }
+                }
+
+                div {
+                    class: "modal-footer",
+
+                    button {
+                        class: "btn btn-secondary",
+                        onclick: move |_| on_close.call(()),
+                        "Cancel"
+                    }
+
+                    button {
+                        class: "btn btn-primary",
+                        disabled: *exporting.read(),
+                        onclick: export,
+                        if *exporting.read() { "Exporting..." } else { "Export" }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,241 @@
+use dioxus::prelude::*;
+use uuid::Uuid;
+
+use super::schema_compare::SchemaChangesTable;
+use crate::connection::ConnectionConfig;
+use crate::schema_diff::ChangeKind;
+use crate::schema_snapshots::{
+    diff_snapshots, snapshot_connection, KeyspaceChanges, SnapshotInfo, SnapshotStore,
+};
+use crate::state::{AppState, ConsoleCategory, StatusLevel};
+
+fn format_taken_at(info: &SnapshotInfo) -> String {
+    info.taken_at
+        .with_timezone(&chrono::Local)
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string()
+}
+
+fn keyspace_heading(keyspace: &KeyspaceChanges) -> String {
+    let note = match keyspace.kind {
+        ChangeKind::Added => " (created)",
+        ChangeKind::Removed => " (dropped)",
+        ChangeKind::Changed => "",
+    };
+    format!("{} {}{}", keyspace.kind.symbol(), keyspace.keyspace, note)
+}
+
+/// Browse the stored schema snapshots of a connection and diff any two of them
+#[component]
+pub fn SchemaHistory() -> Element {
+    let app_state = use_context::<Signal<AppState>>();
+    let mut configs = use_signal(Vec::<ConnectionConfig>::new);
+    let mut connection = use_signal(|| None::<Uuid>);
+    let mut snapshots = use_signal(Vec::<SnapshotInfo>::new);
+    // Indices into `snapshots`, which is sorted newest first
+    let mut older = use_signal(|| None::<usize>);
+    let mut newer = use_signal(|| None::<usize>);
+    let mut refresh = use_signal(|| 0u32);
+    let mut taking = use_signal(|| false);
+
+    use_effect(move || {
+        let cm = app_state.read().connection_manager.clone();
+        spawn(async move {
+            let mut list = cm.get_configs().await;
+            list.sort_by(|a, b| a.name.cmp(&b.name));
+            // Start on the active connection
+            if connection.peek().is_none() {
+                let active = cm.get_active_connection().await.map(|c| c.id);
+                connection.set(active.or_else(|| list.first().map(|c| c.id)));
+            }
+            configs.set(list);
+        });
+    });
+
+    // Reload the history when the connection changes or a snapshot is taken
+    use_effect(move || {
+        let _ = *refresh.read();
+        let list = match (*connection.read(), SnapshotStore::open()) {
+            (Some(id), Some(store)) => store.list(id),
+            _ => Vec::new(),
+        };
+        newer.set((!list.is_empty()).then_some(0));
+        older.set((list.len() > 1).then_some(1));
+        snapshots.set(list);
+    });
+
+    let diff = use_memo(move || -> Option<Result<Vec<KeyspaceChanges>, String>> {
+        let (Some(o), Some(n)) = (*older.read(), *newer.read()) else {
+            return None;
+        };
+        let store = SnapshotStore::open()?;
+        let list = snapshots.read();
+        let load = |i: usize| {
+            let info = list.get(i).ok_or("Snapshot not found")?;
+            store.load(&info.path).map_err(|e| e.to_string())
+        };
+        Some(load(o).and_then(|older| load(n).map(|newer| diff_snapshots(&older, &newer))))
+    });
+
+    let take_snapshot = move |_| {
+        let Some(id) = *connection.read() else {
+            return;
+        };
+        taking.set(true);
+        spawn(async move {
+            let cm = app_state.read().connection_manager.clone();
+            let (level, message) = match (cm.get_connection(id).await, SnapshotStore::open()) {
+                (None, _) => (
+                    StatusLevel::Error,
+                    "Connect to take a schema snapshot".to_string(),
+                ),
+                (_, None) => (
+                    StatusLevel::Error,
+                    "No config directory to store snapshots in".to_string(),
+                ),
+                (Some(conn), Some(store)) => match snapshot_connection(&store, &conn).await {
+                    Ok(Some(_)) => (
+                        StatusLevel::Success,
+                        format!("Stored schema snapshot of {}", conn.config.name),
+                    ),
+                    Ok(None) => (
+                        StatusLevel::Info,
+                        format!(
+                            "Schema of {} unchanged since the last snapshot",
+                            conn.config.name
+                        ),
+                    ),
+                    Err(e) => (
+                        StatusLevel::Error,
+                        format!("Failed to snapshot schema: {}", e),
+                    ),
+                },
+            };
+            let state = app_state.read();
+            AppState::console_push(
+                state.console_log,
+                state.status_message,
+                level,
+                ConsoleCategory::System,
+                message,
+            );
+            taking.set(false);
+            *refresh.write() += 1;
+        });
+    };
+
+    let selected_connection = connection
+        .read()
+        .as_ref()
+        .map(Uuid::to_string)
+        .unwrap_or_default();
+    let snapshot_on_connect = configs
+        .read()
+        .iter()
+        .find(|c| Some(c.id) == *connection.read())
+        .is_some_and(|c| c.snapshot_on_connect);
+
+    rsx! {
+        div {
+            div {
+                class: "schema-compare-header",
+
+                div {
+                    class: "schema-compare-side",
+                    span { class: "schema-compare-label", "Connection" }
+                    select {
+                        class: "select-keyspace",
+                        value: "{selected_connection}",
+                        onchange: move |e| connection.set(Uuid::parse_str(&e.value()).ok()),
+                        option { value: "", "Connection..." }
+                        for config in configs.read().iter() {
+                            option { value: "{config.id}", "{config.name}" }
+                        }
+                    }
+                }
+
+                SnapshotPicker { label: "From", snapshots: snapshots.read().clone(), selected: older }
+                span { class: "schema-compare-arrow", "→" }
+                SnapshotPicker { label: "To", snapshots: snapshots.read().clone(), selected: newer }
+
+                button {
+                    class: "btn btn-small",
+                    disabled: connection.read().is_none() || *taking.read(),
+                    onclick: take_snapshot,
+                    if *taking.read() { "Snapshotting..." } else { "Snapshot now" }
+                }
+            }
+
+            if connection.read().is_some() && !snapshot_on_connect {
+                p {
+                    class: "form-hint",
+                    "Enable \"Snapshot schema on connect\" in the connection settings to record the schema every time you connect."
+                }
+            }
+
+            match diff.read().as_ref() {
+                None if connection.read().is_none() => rsx! {
+                    div { class: "empty-state", "Pick a connection to browse its schema snapshots" }
+                },
+                None => rsx! {
+                    div {
+                        class: "empty-state",
+                        "{snapshots.read().len()} snapshot(s) stored. Pick two to see what changed between them."
+                    }
+                },
+                Some(Err(e)) => rsx! {
+                    div { class: "empty-state", "Failed to read snapshot: {e}" }
+                },
+                Some(Ok(changes)) if changes.is_empty() => rsx! {
+                    div { class: "empty-state", "The schema did not change between these snapshots" }
+                },
+                Some(Ok(changes)) => rsx! {
+                    for keyspace in changes.iter() {
+                        div {
+                            key: "{keyspace.keyspace}",
+                            class: "schema-history-keyspace",
+                            h3 {
+                                class: match keyspace.kind {
+                                    ChangeKind::Added => "schema-change-added",
+                                    ChangeKind::Removed => "schema-change-removed",
+                                    ChangeKind::Changed => "schema-change-changed",
+                                },
+                                "{keyspace_heading(keyspace)}"
+                            }
+                            if !keyspace.changes.is_empty() {
+                                SchemaChangesTable { changes: keyspace.changes.clone() }
+                            }
+                        }
+                    }
+                },
+            }
+        }
+    }
+}
+
+/// Selector for one side of a snapshot comparison
+#[component]
+fn SnapshotPicker(
+    label: &'static str,
+    snapshots: Vec<SnapshotInfo>,
+    selected: Signal<Option<usize>>,
+) -> Element {
+    let value = selected.read().map(|i| i.to_string()).unwrap_or_default();
+
+    rsx! {
+        div {
+            class: "schema-compare-side",
+            span { class: "schema-compare-label", "{label}" }
+            select {
+                class: "select-keyspace",
+                value: "{value}",
+                disabled: snapshots.is_empty(),
+                onchange: move |e| selected.set(e.value().parse().ok()),
+                option { value: "", "Snapshot..." }
+                for (i, info) in snapshots.iter().enumerate() {
+                    option { value: "{i}", "{format_taken_at(info)}" }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use std::sync::Arc;
+
+use dioxus::prelude::*;
+
+use crate::schema::{KeyspaceSchema, SchemaObject};
+use crate::schema_search::{self, SearchHit, MAX_HITS};
+use crate::state::AppState;
+
+/// Search box over the cached schema of every keyspace. Picking a hit calls
+/// `on_select` with its keyspace and, unless it is a keyspace, its object.
+#[component]
+pub fn SchemaSearch(on_select: EventHandler<(String, Option<SchemaObject>)>) -> Element {
+    let app_state = use_context::<Signal<AppState>>();
+    let mut term = use_signal(String::new);
+    let mut include_system = use_signal(|| false);
+    let mut schemas = use_signal(Vec::<Arc<KeyspaceSchema>>::new);
+    let mut loading = use_signal(|| false);
+    let mut load_error = use_signal(|| None::<String>);
+
+    let searching = use_memo(move || !term.read().trim().is_empty());
+
+    // Read every keyspace once the user starts searching; later searches
+    // run against the connection's schema cache
+    use_effect(move || {
+        let _ = app_state.read().schema_version.read();
+        let include_system = *include_system.read();
+        if !*searching.read() {
+            return;
+        }
+        loading.set(true);
+        spawn(async move {
+            let cm = app_state.read().connection_manager.clone();
+            match cm.get_active_connection().await {
+                Some(conn) => match conn.keyspace_schemas(include_system).await {
+                    Ok(found) => {
+                        schemas.set(found);
+                        load_error.set(None);
+                    }
+                    Err(e) => load_error.set(Some(format!("Failed to read schema: {}", e))),
+                },
+                None => load_error.set(Some("No active connection".to_string())),
+            }
+            loading.set(false);
+        });
+    });
+
+    let hits = use_memo(move || {
+        schema_search::search(schemas.read().iter().map(|s| s.as_ref()), &term.read())
+    });
+
+    let mut pick = move |hit: SearchHit| {
+        term.set(String::new());
+        on_select.call((hit.keyspace, hit.object));
+    };
+
+    rsx! {
+        div {
+            class: "schema-search",
+
+            input {
+                r#type: "search",
+                class: "schema-search-input",
+                placeholder: "Search tables, columns, types...",
+                value: "{term.read()}",
+                oninput: move |e| term.set(e.value()),
+                onkeydown: move |e| {
+                    if e.key() == Key::Escape {
+                        term.set(String::new());
+                    } else if e.key() == Key::Enter {
+                        let first = hits.read().first().cloned();
+                        if let Some(hit) = first {
+                            pick(hit);
+                        }
+                    }
+                },
+            }
+
+            if *searching.read() {
+                div {
+                    class: "schema-search-results",
+
+                    label {
+                        class: "form-checkbox",
+                        input {
+                            r#type: "checkbox",
+                            checked: *include_system.read(),
+                            onchange: move |e| include_system.set(e.checked()),
+                        }
+                        "Include system keyspaces"
+                    }
+
+                    if let Some(error) = load_error.read().as_ref() {
+                        div { class: "form-error", "{error}" }
+                    } else if *loading.read() && schemas.read().is_empty() {
+                        div { class: "loading-indicator", "Reading schema..." }
+                    } else if hits.read().is_empty() {
+                        div { class: "empty-state", "No matches" }
+                    }
+
+                    for hit in hits.read().iter() {
+                        div {
+                            key: "{hit.keyspace}:{hit.kind.label()}:{hit.label}",
+                            class: "schema-search-hit",
+                            onclick: {
+                                let hit = hit.clone();
+                                move |_| pick(hit.clone())
+                            },
+                            span { class: "schema-search-kind", "{hit.kind.label()}" }
+                            span { class: "schema-search-name", "{hit.path()}" }
+                            if !hit.detail.is_empty() {
+                                span { class: "schema-search-detail", "{hit.detail}" }
+                            }
+                        }
+                    }
+
+                    if hits.read().len() == MAX_HITS {
+                        div { class: "form-hint", "Showing the first {MAX_HITS} matches" }
+                    }
+                }
+            }
+        }
+    }
+}
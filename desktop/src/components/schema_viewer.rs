@@ -1,7 +1,26 @@
+use std::sync::Arc;
+
 use dioxus::prelude::*;
 
-use crate::cassandra::TableSchema;
-use crate::state::AppState;
+use super::keyspace_editor::KeyspaceEditorDialog;
+use super::migration_runner::MigrationRunnerDialog;
+use super::schema_search::SchemaSearch;
+use super::table_designer::TableDesignerDialog;
+use super::table_options::TableOptionsPanel;
+use crate::cassandra::SchemaColumn;
+use crate::cql_tokenizer;
+use crate::schema::{qualified_name, KeyspaceDef, KeyspaceSchema, SchemaObject};
+use crate::schema_diagram;
+use crate::schema_lint::{self, Finding};
+use crate::state::{ActiveTab, AppState, ConsoleCategory, EditorTab, StatusLevel};
+
+/// What the schema viewer shows for the selection
+#[derive(Clone, Copy, PartialEq)]
+enum SchemaView {
+    Details,
+    Cql,
+    Diagram,
+}
 
 #[component]
 pub fn SchemaViewer() -> Element {
@@ -9,14 +28,19 @@ pub fn SchemaViewer() -> Element {
 
     let mut keyspaces = use_signal(Vec::<String>::new);
     let mut selected_keyspace = use_signal(|| None::<String>);
-    let mut tables = use_signal(Vec::<String>::new);
-    let mut selected_table = use_signal(|| None::<String>);
-    let mut schema = use_signal(|| None::<TableSchema>);
+    let mut selected_object = use_signal(|| None::<SchemaObject>);
     let mut loading = use_signal(|| false);
     let mut error = use_signal(|| None::<String>);
+    let mut view_mode = use_signal(|| SchemaView::Details);
+    let mut keyspace_schema = use_signal(|| None::<Arc<KeyspaceSchema>>);
+    let mut designing = use_signal(|| false);
+    // `Some(None)` creates a keyspace, `Some(Some(def))` alters `def`
+    let mut keyspace_editor = use_signal(|| None::<Option<KeyspaceDef>>);
+    let mut migrating = use_signal(|| false);
 
-    // Load keyspaces on mount
+    // Load keyspaces on mount and when the schema changes
     use_effect(move || {
+        let _ = app_state.read().schema_version.read();
         let cm = app_state.read().connection_manager.clone();
         spawn(async move {
             if let Some(conn) = cm.get_active_connection().await {
@@ -25,8 +49,10 @@ pub fn SchemaViewer() -> Element {
                 match conn.list_keyspaces().await {
                     Ok(ks) => {
                         keyspaces.set(ks);
-                        if let Some(default_ks) = resolved {
-                            selected_keyspace.set(Some(default_ks));
+                        if selected_keyspace.peek().is_none() {
+                            if let Some(default_ks) = resolved {
+                                selected_keyspace.set(Some(default_ks));
+                            }
                         }
                     }
                     Err(e) => error.set(Some(format!("Failed to load keyspaces: {}", e))),
@@ -35,62 +61,182 @@ pub fn SchemaViewer() -> Element {
         });
     });
 
-    // Load tables when keyspace changes
+    // Reveal an object picked elsewhere, e.g. in the sidebar
     use_effect(move || {
-        let ks = selected_keyspace.read().clone();
-        let cm = app_state.read().connection_manager.clone();
-        // Reset downstream state
-        selected_table.set(None);
-        schema.set(None);
-        tables.set(Vec::new());
+        let mut target = app_state.read().schema_target;
+        let Some((ks, object)) = target.read().clone() else {
+            return;
+        };
+        target.set(None);
+        selected_keyspace.set(Some(ks));
+        selected_object.set(Some(object));
+    });
 
-        if let Some(ks) = ks {
-            spawn(async move {
-                if let Some(conn) = cm.get_active_connection().await {
-                    match conn.list_tables(&ks).await {
-                        Ok(t) => tables.set(t),
-                        Err(e) => error.set(Some(format!("Failed to load tables: {}", e))),
-                    }
+    // Load the keyspace definition when the keyspace or the schema changes
+    use_effect(move || {
+        let _ = app_state.read().schema_version.read();
+        let Some(ks) = selected_keyspace.read().clone() else {
+            keyspace_schema.set(None);
+            return;
+        };
+        if keyspace_schema
+            .peek()
+            .as_ref()
+            .is_some_and(|s| s.keyspace.name != ks)
+        {
+            keyspace_schema.set(None);
+        }
+        let cm = app_state.read().connection_manager.clone();
+        loading.set(true);
+        error.set(None);
+        spawn(async move {
+            if let Some(conn) = cm.get_active_connection().await {
+                match conn.keyspace_schema(&ks).await {
+                    Ok(s) => keyspace_schema.set(Some(s)),
+                    Err(e) => error.set(Some(format!("Failed to describe keyspace: {}", e))),
                 }
-            });
+            }
+            loading.set(false);
+        });
+    });
+
+    // DESCRIBE for the selected object, DESCRIBE KEYSPACE otherwise
+    let ddl = use_memo(move || {
+        let schema = keyspace_schema.read();
+        let schema = schema.as_ref()?;
+        match selected_object.read().as_ref() {
+            Some(object) => schema.describe(object),
+            None => Some(schema.to_cql()),
         }
     });
 
-    // Load schema when table changes
-    use_effect(move || {
+    let copy_ddl = move |_| {
+        let Some(text) = ddl.read().clone() else {
+            return;
+        };
+        let js = format!(
+            "navigator.clipboard.writeText({});",
+            serde_json::to_string(&text).unwrap_or_default()
+        );
+        let _ = document::eval(&js);
+        let state = app_state.read();
+        AppState::console_push(
+            state.console_log,
+            state.status_message,
+            StatusLevel::Info,
+            ConsoleCategory::System,
+            "Copied DDL to clipboard",
+        );
+    };
+
+    let open_ddl = move |_| {
+        let Some(text) = ddl.read().clone() else {
+            return;
+        };
         let ks = selected_keyspace.read().clone();
-        let tbl = selected_table.read().clone();
-        let cm = app_state.read().connection_manager.clone();
-        schema.set(None);
+        let title = match (selected_object.read().as_ref(), ks.as_ref()) {
+            (Some(object), _) => format!("{} DDL", object.name()),
+            (None, Some(ks)) => format!("{} DDL", ks),
+            _ => "DDL".to_string(),
+        };
+        let mut tab = EditorTab::new(title);
+        tab.keyspace = ks;
+        let state = app_state.read();
+        AppState::open_in_new_tab(
+            state.editor_tabs,
+            state.active_editor_tab,
+            state.query_text,
+            tab,
+            text,
+        );
+        state.active_tab.clone().set(ActiveTab::Query);
+    };
 
-        if let (Some(ks), Some(tbl)) = (ks, tbl) {
-            loading.set(true);
-            error.set(None);
-            spawn(async move {
-                if let Some(conn) = cm.get_active_connection().await {
-                    match conn.describe_table(&ks, &tbl).await {
-                        Ok(s) => schema.set(Some(s)),
-                        Err(e) => error.set(Some(format!("Failed to describe table: {}", e))),
-                    }
-                }
-                loading.set(false);
-            });
-        }
+    // Diagram of the whole keyspace, as standalone SVG
+    let diagram = use_memo(move || {
+        let schema = keyspace_schema.read();
+        schema.as_ref().map(|s| schema_diagram::layout(s).to_svg())
     });
 
+    let export_diagram = move |_| {
+        let Some(svg) = diagram.read().clone() else {
+            return;
+        };
+        let file_name = format!(
+            "{}.svg",
+            selected_keyspace.read().as_deref().unwrap_or("keyspace")
+        );
+        spawn(async move {
+            let Some(path) = rfd::AsyncFileDialog::new()
+                .set_file_name(&file_name)
+                .add_filter("SVG", &["svg"])
+                .save_file()
+                .await
+            else {
+                return;
+            };
+            let (level, message) = match tokio::fs::write(path.path(), svg.as_bytes()).await {
+                Ok(_) => (
+                    StatusLevel::Success,
+                    format!("Exported diagram to {}", path.path().display()),
+                ),
+                Err(e) => (
+                    StatusLevel::Error,
+                    format!("Failed to write {}: {}", path.path().display(), e),
+                ),
+            };
+            let state = app_state.read();
+            AppState::console_push(
+                state.console_log,
+                state.status_message,
+                level,
+                ConsoleCategory::System,
+                message,
+            );
+        });
+    };
+
+    let select_object = move |object: SchemaObject| selected_object.set(Some(object));
+
+    // The connection dropped its cached schema after the change; reload it
+    let reload_schema = move |_| {
+        let mut schema_version = app_state.read().schema_version;
+        *schema_version.write() += 1;
+    };
+
+    let objects = keyspace_schema
+        .read()
+        .as_ref()
+        .map(|s| s.objects())
+        .unwrap_or_default();
+    let selected_key = selected_object
+        .read()
+        .as_ref()
+        .map(SchemaObject::key)
+        .unwrap_or_default();
+
     rsx! {
         div {
             class: "schema-viewer",
 
-            // Header with keyspace + table selectors
+            // Header with search, keyspace + object selectors
             div {
                 class: "schema-header",
 
+                SchemaSearch {
+                    on_select: move |(ks, object): (String, Option<SchemaObject>)| {
+                        selected_keyspace.set(Some(ks));
+                        selected_object.set(object);
+                        view_mode.set(SchemaView::Details);
+                    }
+                }
+
                 select {
                     class: "select-keyspace",
                     value: "{selected_keyspace.read().as_deref().unwrap_or(\"\")}",
                     onchange: move |e| {
                         let v = if e.value().is_empty() { None } else { Some(e.value()) };
+                        selected_object.set(None);
                         selected_keyspace.set(v);
                     },
                     option { value: "", "Select keyspace..." }
@@ -102,17 +248,66 @@ pub fn SchemaViewer() -> Element {
                 if selected_keyspace.read().is_some() {
                     select {
                         class: "select-keyspace",
-                        value: "{selected_table.read().as_deref().unwrap_or(\"\")}",
-                        onchange: move |e| {
-                            let v = if e.value().is_empty() { None } else { Some(e.value()) };
-                            selected_table.set(v);
-                        },
-                        option { value: "", "Select table..." }
-                        for t in tables.read().iter() {
-                            option { value: "{t}", "{t}" }
+                        value: "{selected_key}",
+                        onchange: move |e| selected_object.set(SchemaObject::from_key(&e.value())),
+                        option { value: "", "Select object..." }
+                        for (label, group) in group_objects(&objects) {
+                            optgroup {
+                                key: "{label}",
+                                label: "{label}",
+                                for object in group {
+                                    option { key: "{object.key()}", value: "{object.key()}", {object_label(object)} }
+                                }
+                            }
                         }
                     }
                 }
+
+                button {
+                    class: "btn btn-small",
+                    onclick: move |_| keyspace_editor.set(Some(None)),
+                    "New keyspace"
+                }
+
+                if let Some(schema) = keyspace_schema.read().as_ref() {
+                    button {
+                        class: "btn btn-small",
+                        onclick: {
+                            let keyspace = schema.keyspace.clone();
+                            move |_| keyspace_editor.set(Some(Some(keyspace.clone())))
+                        },
+                        "Edit keyspace"
+                    }
+                    button {
+                        class: "btn btn-small",
+                        onclick: move |_| designing.set(true),
+                        "New table"
+                    }
+                    button {
+                        class: "btn btn-small",
+                        onclick: move |_| migrating.set(true),
+                        "Migrations"
+                    }
+                }
+
+                div {
+                    class: "schema-view-toggle",
+                    button {
+                        class: if *view_mode.read() == SchemaView::Details { "btn btn-small active" } else { "btn btn-small" },
+                        onclick: move |_| view_mode.set(SchemaView::Details),
+                        "Details"
+                    }
+                    button {
+                        class: if *view_mode.read() == SchemaView::Cql { "btn btn-small active" } else { "btn btn-small" },
+                        onclick: move |_| view_mode.set(SchemaView::Cql),
+                        "CQL"
+                    }
+                    button {
+                        class: if *view_mode.read() == SchemaView::Diagram { "btn btn-small active" } else { "btn btn-small" },
+                        onclick: move |_| view_mode.set(SchemaView::Diagram),
+                        "Diagram"
+                    }
+                }
             }
 
             // Error display
@@ -125,46 +320,494 @@ pub fn SchemaViewer() -> Element {
                 div { class: "loading-indicator", "Loading schema..." }
             }
 
-            // Column details table
-            if let Some(table_schema) = schema.read().as_ref() {
-                if table_schema.columns.is_empty() {
-                    div { class: "empty-state", "No columns found" }
-                } else {
-                    table {
-                        class: "results-table",
-                        thead {
+            if *view_mode.read() == SchemaView::Diagram {
+                if let Some(svg) = diagram.read().as_ref() {
+                    div {
+                        class: "schema-ddl-toolbar",
+                        span { class: "schema-diagram-legend", "K partition key · C↑/C↓ clustering · S static" }
+                        button { class: "btn btn-small btn-primary", onclick: export_diagram, "Export SVG" }
+                    }
+                    div {
+                        class: "schema-diagram",
+                        dangerous_inner_html: "{svg}"
+                    }
+                } else if !*loading.read() && selected_keyspace.read().is_none() {
+                    div { class: "empty-state", "Select a keyspace to view its diagram" }
+                }
+            } else if *view_mode.read() == SchemaView::Cql {
+                if let Some(text) = ddl.read().as_ref() {
+                    div {
+                        class: "schema-ddl-toolbar",
+                        button { class: "btn btn-small", onclick: copy_ddl, "Copy" }
+                        button { class: "btn btn-small btn-primary", onclick: open_ddl, "Open in editor" }
+                    }
+                    pre {
+                        class: "schema-ddl",
+                        dangerous_inner_html: cql_tokenizer::to_highlighted_html(&cql_tokenizer::tokenize(text))
+                    }
+                } else if !*loading.read() && selected_keyspace.read().is_none() {
+                    div { class: "empty-state", "Select a keyspace to view its CQL definition" }
+                }
+            } else if let (Some(schema), Some(object)) = (keyspace_schema.read().as_ref(), selected_object.read().as_ref()) {
+                ObjectDetails {
+                    schema: schema.clone(),
+                    object: object.clone(),
+                    on_select: select_object,
+                    on_altered: reload_schema,
+                }
+            } else if let Some(schema) = keyspace_schema.read().as_ref() {
+                FindingsList {
+                    findings: schema_lint::lint_keyspace(schema, &history_queries(app_state)),
+                    on_select: select_object,
+                }
+            } else if !*loading.read() && error.read().is_none() {
+                div { class: "empty-state", "Select a keyspace and an object to view its schema" }
+            }
+
+            if let Some(existing) = keyspace_editor.read().clone() {
+                KeyspaceEditorDialog {
+                    existing,
+                    on_close: move |_| keyspace_editor.set(None),
+                    on_saved: move |keyspace: String| {
+                        keyspace_editor.set(None);
+                        selected_object.set(None);
+                        selected_keyspace.set(Some(keyspace));
+                        reload_schema(());
+                    },
+                }
+            }
+
+            if *migrating.read() {
+                if let Some(keyspace) = selected_keyspace.read().clone() {
+                    MigrationRunnerDialog {
+                        keyspace,
+                        on_close: move |_| migrating.set(false),
+                        on_applied: reload_schema,
+                    }
+                }
+            }
+
+            if *designing.read() {
+                if let Some(schema) = keyspace_schema.read().as_ref() {
+                    TableDesignerDialog {
+                        schema: schema.clone(),
+                        on_close: move |_| designing.set(false),
+                        on_created: move |table: String| {
+                            designing.set(false);
+                            view_mode.set(SchemaView::Details);
+                            selected_object.set(Some(SchemaObject::Table(table)));
+                            reload_schema(());
+                        },
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Objects split into labelled groups, skipping empty kinds
+fn group_objects(objects: &[SchemaObject]) -> Vec<(&'static str, Vec<&SchemaObject>)> {
+    let mut groups: Vec<(&'static str, Vec<&SchemaObject>)> = Vec::new();
+    for object in objects {
+        let label = object.group_label();
+        match groups.last_mut() {
+            Some((last, group)) if *last == label => group.push(object),
+            _ => groups.push((label, vec![object])),
+        }
+    }
+    groups
+}
+
+fn object_label(object: &SchemaObject) -> String {
+    match object {
+        SchemaObject::Trigger { table, name } => format!("{} ({})", name, table),
+        other => other.name().to_string(),
+    }
+}
+
+/// Structured view of one keyspace object, with links to related objects
+#[component]
+fn ObjectDetails(
+    schema: Arc<KeyspaceSchema>,
+    object: SchemaObject,
+    on_select: EventHandler<SchemaObject>,
+    on_altered: EventHandler<()>,
+) -> Element {
+    let app_state = use_context::<Signal<AppState>>();
+    let keyspace = schema.keyspace.name.clone();
+    let missing = rsx! {
+        div { class: "empty-state", "{object.kind_label()} {object.name()} no longer exists" }
+    };
+
+    let body = match &object {
+        SchemaObject::Table(name) => {
+            let Some(table) = schema.table(name) else {
+                return missing;
+            };
+            let mut related: Vec<SchemaObject> = schema
+                .indexes
+                .iter()
+                .filter(|i| &i.table == name)
+                .map(|i| SchemaObject::Index(i.name.clone()))
+                .collect();
+            related.extend(
+                schema
+                    .views
+                    .iter()
+                    .filter(|v| &v.base_table == name)
+                    .map(|v| SchemaObject::View(v.name.clone())),
+            );
+            related.extend(
+                schema
+                    .triggers
+                    .iter()
+                    .filter(|t| &t.table == name)
+                    .map(|t| SchemaObject::Trigger {
+                        table: t.table.clone(),
+                        name: t.name.clone(),
+                    }),
+            );
+            let used_types: Vec<SchemaObject> = schema
+                .types
+                .iter()
+                .filter(|ty| schema.type_usages(&ty.name).contains(&object))
+                .map(|ty| SchemaObject::Type(ty.name.clone()))
+                .collect();
+            let findings = schema_lint::lint_table(&schema, table, &history_queries(app_state));
+            rsx! {
+                ColumnsTable { columns: table.columns.clone() }
+                FindingsList { findings, on_select: None }
+                TableOptionsPanel {
+                    options: table.options.clone(),
+                    alter: format!("ALTER TABLE {}", qualified_name(&keyspace, name)),
+                    on_altered,
+                }
+                if !used_types.is_empty() {
+                    ObjectLinks { title: "Uses types", objects: used_types, on_select }
+                }
+                if !related.is_empty() {
+                    ObjectLinks { title: "Indexes, views and triggers", objects: related, on_select }
+                }
+            }
+        }
+        SchemaObject::View(name) => {
+            let Some(view) = schema.view(name) else {
+                return missing;
+            };
+            rsx! {
+                dl {
+                    class: "schema-properties",
+                    dt { "Base table" }
+                    dd {
+                        ObjectLink { object: SchemaObject::Table(view.base_table.clone()), on_select }
+                    }
+                    dt { "Filter" }
+                    dd { code { "{view.where_clause}" } }
+                    dt { "Selection" }
+                    dd { if view.include_all_columns { "all columns" } else { "listed columns" } }
+                }
+                ColumnsTable { columns: view.columns.clone() }
+                TableOptionsPanel {
+                    options: view.options.clone(),
+                    alter: format!("ALTER MATERIALIZED VIEW {}", qualified_name(&keyspace, name)),
+                    on_altered,
+                }
+            }
+        }
+        SchemaObject::Type(name) => {
+            let Some(ty) = schema.user_type(name) else {
+                return missing;
+            };
+            let usages = schema.type_usages(name);
+            rsx! {
+                table {
+                    class: "results-table",
+                    thead {
+                        tr {
+                            th { div { class: "column-header", span { class: "column-name", "Field" } } }
+                            th { div { class: "column-header", span { class: "column-name", "Type" } } }
+                        }
+                    }
+                    tbody {
+                        for (field, field_type) in ty.fields.iter() {
                             tr {
-                                th { div { class: "column-header", span { class: "column-name", "Column" } } }
-                                th { div { class: "column-header", span { class: "column-name", "Type" } } }
-                                th { div { class: "column-header", span { class: "column-name", "Kind" } } }
-                                th { div { class: "column-header", span { class: "column-name", "Order" } } }
+                                key: "{field}",
+                                td { span { class: "column-name", "{field}" } }
+                                td { span { class: "column-type", "{field_type}" } }
                             }
                         }
-                        tbody {
-                            for col in table_schema.columns.iter() {
-                                tr {
-                                    key: "{col.name}",
-                                    class: match col.kind.as_str() {
-                                        "partition_key" => "schema-row-partition",
-                                        "clustering" => "schema-row-clustering",
-                                        _ => "",
-                                    },
-                                    td { span { class: "column-name", "{col.name}" } }
-                                    td { span { class: "column-type", "{col.data_type}" } }
-                                    td { "{col.kind}" }
-                                    td {
-                                        if col.kind == "clustering" {
-                                            "{col.clustering_order}"
-                                        }
-                                    }
-                                }
+                    }
+                }
+                if usages.is_empty() {
+                    div { class: "schema-hint", "Not used by any table or type" }
+                } else {
+                    ObjectLinks { title: "Used by", objects: usages, on_select }
+                }
+            }
+        }
+        SchemaObject::Index(name) => {
+            let Some(index) = schema.index(name) else {
+                return missing;
+            };
+            let kind = match index.class_name() {
+                Some(class) if class.ends_with("StorageAttachedIndex") => "SAI".to_string(),
+                Some(class) if class.ends_with("SASIIndex") => "SASI".to_string(),
+                Some(class) => class.to_string(),
+                None => "Secondary index".to_string(),
+            };
+            let extra: Vec<(String, String)> = index
+                .options
+                .iter()
+                .filter(|(k, _)| k.as_str() != "target" && k.as_str() != "class_name")
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect();
+            rsx! {
+                dl {
+                    class: "schema-properties",
+                    dt { "Table" }
+                    dd {
+                        ObjectLink { object: SchemaObject::Table(index.table.clone()), on_select }
+                    }
+                    dt { "Target" }
+                    dd { code { "{index.target()}" } }
+                    dt { "Implementation" }
+                    dd { "{kind}" }
+                    for (option, value) in extra {
+                        dt { key: "{option}", "{option}" }
+                        dd { "{value}" }
+                    }
+                }
+            }
+        }
+        SchemaObject::Function(signature) => {
+            let Some(function) = schema.function(signature) else {
+                return missing;
+            };
+            let arguments: Vec<String> = function
+                .argument_names
+                .iter()
+                .zip(&function.argument_types)
+                .map(|(name, ty)| format!("{} {}", name, ty))
+                .collect();
+            rsx! {
+                dl {
+                    class: "schema-properties",
+                    dt { "Arguments" }
+                    dd { code { {arguments.join(", ")} } }
+                    dt { "Returns" }
+                    dd { code { "{function.return_type}" } }
+                    dt { "Language" }
+                    dd { "{function.language}" }
+                    dt { "Null input" }
+                    dd { if function.called_on_null_input { "called" } else { "returns null" } }
+                }
+                pre { class: "schema-ddl", "{function.body}" }
+            }
+        }
+        SchemaObject::Aggregate(signature) => {
+            let Some(aggregate) = schema.aggregate(signature) else {
+                return missing;
+            };
+            // The state function takes the state followed by the aggregate's arguments
+            let mut state_args = vec![aggregate.state_type.clone()];
+            state_args.extend(aggregate.argument_types.iter().cloned());
+            let state_func = SchemaObject::Function(format!(
+                "{}({})",
+                aggregate.state_func,
+                state_args.join(", ")
+            ));
+            let final_func = aggregate
+                .final_func
+                .as_ref()
+                .map(|f| SchemaObject::Function(format!("{}({})", f, aggregate.state_type)));
+            rsx! {
+                dl {
+                    class: "schema-properties",
+                    dt { "Arguments" }
+                    dd { code { {aggregate.argument_types.join(", ")} } }
+                    dt { "State function" }
+                    dd {
+                        if schema.describe(&state_func).is_some() {
+                            ObjectLink { object: state_func.clone(), on_select }
+                        } else {
+                            code { "{aggregate.state_func}" }
+                        }
+                    }
+                    dt { "State type" }
+                    dd { code { "{aggregate.state_type}" } }
+                    if let Some(final_func) = final_func {
+                        dt { "Final function" }
+                        dd {
+                            if schema.describe(&final_func).is_some() {
+                                ObjectLink { object: final_func.clone(), on_select }
+                            } else {
+                                code { {final_func.name().to_string()} }
                             }
                         }
                     }
+                    if let Some(initcond) = aggregate.initcond.as_ref() {
+                        dt { "Initial state" }
+                        dd { code { "{initcond}" } }
+                    }
+                    dt { "Returns" }
+                    dd { code { "{aggregate.return_type}" } }
+                }
+            }
+        }
+        SchemaObject::Trigger { table, name } => {
+            let Some(trigger) = schema.trigger(table, name) else {
+                return missing;
+            };
+            rsx! {
+                dl {
+                    class: "schema-properties",
+                    dt { "Table" }
+                    dd {
+                        ObjectLink { object: SchemaObject::Table(trigger.table.clone()), on_select }
+                    }
+                    dt { "Class" }
+                    dd { code { "{trigger.class_name()}" } }
                 }
-            } else if !*loading.read() && selected_table.read().is_none() && error.read().is_none() {
-                div { class: "empty-state", "Select a keyspace and table to view its schema" }
             }
         }
+    };
+
+    rsx! {
+        div {
+            class: "schema-object",
+            div {
+                class: "schema-object-title",
+                span { class: "schema-object-kind", "{object.kind_label()}" }
+                h3 { {object_label(&object)} }
+            }
+            {body}
+        }
+    }
+}
+
+/// Statements from the query history, for lint rules that look at access
+/// patterns
+fn history_queries(app_state: Signal<AppState>) -> Vec<String> {
+    let history = app_state.read().query_history;
+    let queries = history
+        .read()
+        .iter()
+        .map(|item| item.query.clone())
+        .collect();
+    queries
+}
+
+/// Data model findings, most severe first. With `on_select` the table name
+/// of each finding links to it (keyspace-wide list).
+#[component]
+fn FindingsList(findings: Vec<Finding>, on_select: Option<EventHandler<SchemaObject>>) -> Element {
+    let title = if on_select.is_some() {
+        "Data model findings in this keyspace"
+    } else {
+        "Data model findings"
+    };
+    rsx! {
+        div {
+            class: "schema-related",
+            h5 { class: "schema-related-title", "{title}" }
+            if findings.is_empty() {
+                div { class: "schema-hint", "No issues found" }
+            }
+            for (i, finding) in findings.into_iter().enumerate() {
+                div {
+                    key: "{i}",
+                    class: "schema-finding schema-finding-{finding.severity.label()}",
+                    div {
+                        class: "schema-finding-header",
+                        span { class: "schema-finding-severity", "{finding.severity.label()}" }
+                        if let Some(on_select) = on_select {
+                            ObjectLink { object: SchemaObject::Table(finding.table.clone()), on_select }
+                        }
+                        span { class: "schema-finding-message", "{finding.message}" }
+                    }
+                    details {
+                        class: "schema-finding-explanation",
+                        summary { "{finding.rule}" }
+                        p { "{finding.explanation}" }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn ColumnsTable(columns: Vec<SchemaColumn>) -> Element {
+    if columns.is_empty() {
+        return rsx! {
+            div { class: "empty-state", "No columns found" }
+        };
+    }
+    rsx! {
+        table {
+            class: "results-table",
+            thead {
+                tr {
+                    th { div { class: "column-header", span { class: "column-name", "Column" } } }
+                    th { div { class: "column-header", span { class: "column-name", "Type" } } }
+                    th { div { class: "column-header", span { class: "column-name", "Kind" } } }
+                    th { div { class: "column-header", span { class: "column-name", "Order" } } }
+                }
+            }
+            tbody {
+                for col in columns.iter() {
+                    tr {
+                        key: "{col.name}",
+                        class: match col.kind.as_str() {
+                            "partition_key" => "schema-row-partition",
+                            "clustering" => "schema-row-clustering",
+                            _ => "",
+                        },
+                        td { span { class: "column-name", "{col.name}" } }
+                        td { span { class: "column-type", "{col.data_type}" } }
+                        td { "{col.kind}" }
+                        td {
+                            if col.kind == "clustering" {
+                                "{col.clustering_order}"
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn ObjectLinks(
+    title: &'static str,
+    objects: Vec<SchemaObject>,
+    on_select: EventHandler<SchemaObject>,
+) -> Element {
+    rsx! {
+        div {
+            class: "schema-related",
+            h5 { class: "schema-related-title", "{title}" }
+            div {
+                class: "schema-related-list",
+                for object in objects {
+                    ObjectLink { key: "{object.key()}", object, on_select }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn ObjectLink(object: SchemaObject, on_select: EventHandler<SchemaObject>) -> Element {
+    let label = object_label(&object);
+    let kind = object.kind_label();
+    rsx! {
+        button {
+            class: "schema-link",
+            title: "{kind}",
+            onclick: move |_| on_select.call(object.clone()),
+            "{label}"
+        }
     }
 }
@@ -0,0 +1,120 @@
+use dioxus::prelude::*;
+use uuid::Uuid;
+
+use crate::state::{AppState, StatementState};
+
+/// Longest statement text shown on one line of the list
+const MAX_STATEMENT_CHARS: usize = 120;
+
+/// A statement on one line, shortened for the list
+fn one_line(statement: &str) -> String {
+    let line = statement.split_whitespace().collect::<Vec<_>>().join(" ");
+    if line.chars().count() > MAX_STATEMENT_CHARS {
+        let cut: String = line.chars().take(MAX_STATEMENT_CHARS).collect();
+        format!("{}…", cut)
+    } else {
+        line
+    }
+}
+
+/// Status class, icon and outcome text of a statement
+fn describe(state: &StatementState) -> (&'static str, &'static str, String) {
+    match state {
+        StatementState::Pending => ("script-pending", "○", "Pending".to_string()),
+        StatementState::Running => ("script-running", "◌", "Running…".to_string()),
+        StatementState::Done(result) => (
+            "script-done",
+            "✓",
+            match result.row_count {
+                1 => "1 row".to_string(),
+                n => format!("{} rows", n),
+            },
+        ),
+        StatementState::Failed(report) => ("script-failed", "✗", report.message.clone()),
+        StatementState::Skipped => ("script-skipped", "–", "Not run".to_string()),
+    }
+}
+
+/// Statements of a script run in an editor tab with their outcome and
+/// timing. Picking a finished statement shows its result or error below.
+#[component]
+pub fn ScriptResults(tab_id: Uuid) -> Element {
+    let app_state = use_context::<Signal<AppState>>();
+    let mut executions = app_state.read().tab_executions;
+
+    let execution = executions.read().get(&tab_id).cloned().unwrap_or_default();
+    let runs = execution.script;
+    let selected = execution.selected_statement;
+    let done = runs
+        .iter()
+        .filter(|r| matches!(r.state, StatementState::Done(_)))
+        .count();
+    let failed = runs
+        .iter()
+        .filter(|r| matches!(r.state, StatementState::Failed(_)))
+        .count();
+    let total_ms: u64 = runs.iter().map(|r| r.execution_time_ms).sum();
+
+    let mut select = move |index: usize| {
+        let mut executions = executions.write();
+        let Some(execution) = executions.get_mut(&tab_id) else {
+            return;
+        };
+        let (result, error) = match execution.script.get(index).map(|r| &r.state) {
+            Some(StatementState::Done(result)) => (Some(result.clone()), None),
+            Some(StatementState::Failed(report)) => (None, Some(report.clone())),
+            _ => return,
+        };
+        execution.selected_statement = Some(index);
+        execution.result = result;
+        execution.error = error;
+        execution.current_page = 1;
+    };
+
+    rsx! {
+        div {
+            class: "script-results",
+
+            div {
+                class: "script-results-summary",
+                span { "Script: {done} of {runs.len()} statements succeeded" }
+                if failed > 0 {
+                    span { class: "script-failed", "{failed} failed" }
+                }
+                span { class: "script-time", "{total_ms}ms" }
+            }
+
+            div {
+                class: "script-statements",
+                for (index, run) in runs.iter().enumerate() {
+                    {
+                        let (class, icon, outcome) = describe(&run.state);
+                        let finished = matches!(
+                            run.state,
+                            StatementState::Done(_) | StatementState::Failed(_)
+                        );
+                        rsx! {
+                            div {
+                                key: "{index}",
+                                class: if selected == Some(index) { "script-statement selected" } else { "script-statement" },
+                                title: "{run.statement}",
+                                onclick: move |_| {
+                                    if finished {
+                                        select(index);
+                                    }
+                                },
+                                span { class: "script-status {class}", "{icon}" }
+                                span { class: "script-index", "{index + 1}" }
+                                span { class: "script-text", "{one_line(&run.statement)}" }
+                                span { class: "script-outcome {class}", "{outcome}" }
+                                if finished {
+                                    span { class: "script-time", "{run.execution_time_ms}ms" }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
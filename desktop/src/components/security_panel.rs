@@ -0,0 +1,609 @@
+use dioxus::prelude::*;
+
+use crate::cql_tokenizer;
+use crate::identifier::Identifier;
+use crate::roles::{self, Grant, Resource, Role, RoleDesign, PERMISSIONS};
+use crate::state::{AppState, ConsoleCategory, StatusLevel};
+
+#[derive(Clone, Copy, PartialEq)]
+enum GrantsView {
+    ByRole,
+    ByResource,
+}
+
+/// Resource kinds offered by the grant form, as `(value, label)`
+const RESOURCE_KINDS: [(&str, &str); 7] = [
+    ("all_keyspaces", "All keyspaces"),
+    ("keyspace", "Keyspace"),
+    ("table", "Table"),
+    ("all_roles", "All roles"),
+    ("role", "Role"),
+    ("all_functions", "All functions"),
+    ("keyspace_functions", "Functions in keyspace"),
+];
+
+fn parse_name(text: &str, what: &str) -> Result<String, String> {
+    if text.trim().is_empty() {
+        return Err(format!("Enter a {} name", what));
+    }
+    Identifier::parse(text)
+        .map(|id| id.as_str().to_string())
+        .map_err(|e| e.to_string())
+}
+
+/// The resource picked in the grant form
+fn form_resource(kind: &str, keyspace: &str, name: &str) -> Result<Resource, String> {
+    Ok(match kind {
+        "keyspace" => Resource::Keyspace(parse_name(keyspace, "keyspace")?),
+        "table" => Resource::Table(
+            parse_name(keyspace, "keyspace")?,
+            parse_name(name, "table")?,
+        ),
+        "all_roles" => Resource::AllRoles,
+        "role" => Resource::Role(parse_name(name, "role")?),
+        "all_functions" => Resource::AllFunctions,
+        "keyspace_functions" => Resource::KeyspaceFunctions(parse_name(keyspace, "keyspace")?),
+        _ => Resource::AllKeyspaces,
+    })
+}
+
+/// Roles with their flags and memberships, the permissions granted to them,
+/// and forms generating `CREATE ROLE`, `GRANT` and `REVOKE` statements
+#[component]
+pub fn SecurityPanel() -> Element {
+    let app_state = use_context::<Signal<AppState>>();
+    let mut role_list = use_signal(Vec::<Role>::new);
+    let mut grants = use_signal(Vec::<Grant>::new);
+    let mut load_error = use_signal(|| None::<String>);
+    let mut loading = use_signal(|| false);
+    let mut refresh = use_signal(|| 0u32);
+    let mut view = use_signal(|| GrantsView::ByRole);
+    // `Some(None)` creates a role, `Some(Some(role))` alters it
+    let mut role_editor = use_signal(|| None::<Option<Role>>);
+    let mut confirm_drop = use_signal(|| None::<String>);
+
+    let mut grant_role = use_signal(String::new);
+    let mut grant_permission = use_signal(|| "SELECT".to_string());
+    let mut resource_kind = use_signal(|| "keyspace".to_string());
+    let mut resource_keyspace = use_signal(String::new);
+    let mut resource_name = use_signal(String::new);
+
+    use_effect(move || {
+        let _ = *refresh.read();
+        let _ = app_state.read().schema_version.read();
+        loading.set(true);
+        spawn(async move {
+            let cm = app_state.read().connection_manager.clone();
+            let Some(conn) = cm.get_active_connection().await else {
+                load_error.set(Some("No active connection".to_string()));
+                role_list.set(Vec::new());
+                grants.set(Vec::new());
+                loading.set(false);
+                return;
+            };
+            let mut errors = Vec::new();
+            match conn.roles().await {
+                Ok(found) => role_list.set(found),
+                Err(e) => {
+                    errors.push(format!("Failed to list roles: {}", e));
+                    role_list.set(Vec::new());
+                }
+            }
+            match conn.permissions().await {
+                Ok(found) => grants.set(found),
+                Err(e) => {
+                    errors.push(format!("Failed to list permissions: {}", e));
+                    grants.set(Vec::new());
+                }
+            }
+            load_error.set((!errors.is_empty()).then(|| errors.join("; ")));
+            loading.set(false);
+        });
+    });
+
+    // Run a generated statement, log it as `shown` (passwords masked) and
+    // reload roles and grants
+    let run = move |cql: String, shown: String| {
+        spawn(async move {
+            let cm = app_state.read().connection_manager.clone();
+            let (level, message) = match cm.get_active_connection().await {
+                None => (StatusLevel::Error, "No active connection".to_string()),
+                Some(conn) => match conn.execute_query(&cql).await {
+                    Ok(_) => (StatusLevel::Success, shown),
+                    Err(e) => (StatusLevel::Error, format!("{} failed: {}", shown, e)),
+                },
+            };
+            let state = app_state.read();
+            AppState::console_push(
+                state.console_log,
+                state.status_message,
+                level,
+                ConsoleCategory::Query,
+                message,
+            );
+            *refresh.write() += 1;
+        });
+    };
+
+    // GRANT and REVOKE statements for the form, or why it is incomplete
+    let grant_statements = use_memo(move || -> Result<(String, String), String> {
+        let role = grant_role.read().clone();
+        if role.is_empty() {
+            return Err("Choose a role".to_string());
+        }
+        let resource = form_resource(
+            &resource_kind.read(),
+            &resource_keyspace.read(),
+            &resource_name.read(),
+        )?;
+        let permission = grant_permission.read().clone();
+        if let Some(problem) = resource.check_permission(&permission) {
+            return Err(problem);
+        }
+        Ok((
+            roles::grant_cql(&permission, &resource, &role),
+            roles::revoke_cql(&permission, &resource, &role),
+        ))
+    });
+
+    let kind = resource_kind.read().clone();
+    let needs_keyspace = matches!(kind.as_str(), "keyspace" | "table" | "keyspace_functions");
+    let name_label = match kind.as_str() {
+        "table" => Some("Table"),
+        "role" => Some("Role"),
+        _ => None,
+    };
+    let grouped = match *view.read() {
+        GrantsView::ByRole => roles::grants_by_role(&grants.read()),
+        GrantsView::ByResource => roles::grants_by_resource(&grants.read()),
+    };
+
+    rsx! {
+        div {
+            class: "security-panel",
+
+            div {
+                class: "security-header",
+                h3 { "Roles" }
+                if *loading.read() {
+                    span { class: "loading-indicator", "⟳" }
+                }
+                button {
+                    class: "btn btn-small",
+                    disabled: *loading.read(),
+                    onclick: move |_| *refresh.write() += 1,
+                    "Refresh"
+                }
+                button {
+                    class: "btn btn-small btn-primary",
+                    onclick: move |_| role_editor.set(Some(None)),
+                    "New role"
+                }
+            }
+
+            if let Some(error) = load_error.read().as_ref() {
+                div {
+                    class: "form-error",
+                    "{error}"
+                    p { class: "form-hint", "Roles and permissions require PasswordAuthenticator and CassandraAuthorizer." }
+                }
+            }
+
+            table {
+                class: "results-table security-roles",
+                thead {
+                    tr {
+                        th { "Role" }
+                        th { "Login" }
+                        th { "Superuser" }
+                        th { "Member of" }
+                        th {}
+                    }
+                }
+                tbody {
+                    for role in role_list.read().iter() {
+                        tr {
+                            key: "{role.name}",
+                            td { class: "security-role-name", "{role.name}" }
+                            td { if role.can_login { "yes" } else { "no" } }
+                            td { if role.is_superuser { "yes" } else { "no" } }
+                            td { "{role.member_of.join(\", \")}" }
+                            td {
+                                class: "security-role-actions",
+                                if confirm_drop.read().as_ref() == Some(&role.name) {
+                                    span {
+                                        class: "confirm-delete",
+                                        span { class: "confirm-label", "Drop role?" }
+                                        button {
+                                            class: "btn-small btn-danger",
+                                            onclick: {
+                                                let name = role.name.clone();
+                                                move |_| {
+                                                    confirm_drop.set(None);
+                                                    let cql = roles::drop_role_cql(&name);
+                                                    run(cql.clone(), cql);
+                                                }
+                                            },
+                                            "Yes"
+                                        }
+                                        button {
+                                            class: "btn-small",
+                                            onclick: move |_| confirm_drop.set(None),
+                                            "No"
+                                        }
+                                    }
+                                } else {
+                                    button {
+                                        class: "btn-icon-small",
+                                        title: "Alter role",
+                                        onclick: {
+                                            let role = role.clone();
+                                            move |_| role_editor.set(Some(Some(role.clone())))
+                                        },
+                                        "✎"
+                                    }
+                                    button {
+                                        class: "btn-icon-small btn-danger-text",
+                                        title: "Drop role",
+                                        onclick: {
+                                            let name = role.name.clone();
+                                            move |_| confirm_drop.set(Some(name.clone()))
+                                        },
+                                        "✕"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            // Grant form
+            div {
+                class: "security-header",
+                h3 { "Grant or revoke" }
+            }
+            div {
+                class: "security-grant-form",
+
+                select {
+                    value: "{grant_permission.read()}",
+                    onchange: move |e| grant_permission.set(e.value()),
+                    for permission in PERMISSIONS {
+                        option { value: "{permission}", "{permission}" }
+                    }
+                }
+                span { "on" }
+                select {
+                    value: "{resource_kind.read()}",
+                    onchange: move |e| resource_kind.set(e.value()),
+                    for (value, label) in RESOURCE_KINDS {
+                        option { value: "{value}", "{label}" }
+                    }
+                }
+                if needs_keyspace {
+                    input {
+                        r#type: "text",
+                        placeholder: "keyspace",
+                        value: "{resource_keyspace.read()}",
+                        oninput: move |e| resource_keyspace.set(e.value()),
+                    }
+                }
+                if let Some(label) = name_label {
+                    input {
+                        r#type: "text",
+                        placeholder: "{label.to_lowercase()}",
+                        value: "{resource_name.read()}",
+                        oninput: move |e| resource_name.set(e.value()),
+                    }
+                }
+                span { "to" }
+                select {
+                    value: "{grant_role.read()}",
+                    onchange: move |e| grant_role.set(e.value()),
+                    option { value: "", "Select a role..." }
+                    for role in role_list.read().iter() {
+                        option { value: "{role.name}", "{role.name}" }
+                    }
+                }
+            }
+
+            match grant_statements.read().clone() {
+                Ok((grant, revoke)) => rsx! {
+                    pre {
+                        class: "schema-ddl",
+                        dangerous_inner_html: cql_tokenizer::to_highlighted_html(&cql_tokenizer::tokenize(&grant))
+                    }
+                    div {
+                        class: "security-grant-actions",
+                        button {
+                            class: "btn btn-small btn-primary",
+                            onclick: move |_| run(grant.clone(), grant.clone()),
+                            "Grant"
+                        }
+                        button {
+                            class: "btn btn-small",
+                            onclick: move |_| run(revoke.clone(), revoke.clone()),
+                            "Revoke"
+                        }
+                    }
+                },
+                Err(problem) => rsx! {
+                    p { class: "form-hint", "{problem}" }
+                },
+            }
+
+            // Existing grants
+            div {
+                class: "security-header",
+                h3 { "Permissions" }
+                div {
+                    class: "schema-view-toggle",
+                    button {
+                        class: if *view.read() == GrantsView::ByRole { "btn btn-small active" } else { "btn btn-small" },
+                        onclick: move |_| view.set(GrantsView::ByRole),
+                        "By role"
+                    }
+                    button {
+                        class: if *view.read() == GrantsView::ByResource { "btn btn-small active" } else { "btn btn-small" },
+                        onclick: move |_| view.set(GrantsView::ByResource),
+                        "By resource"
+                    }
+                }
+            }
+
+            if grouped.is_empty() && load_error.read().is_none() {
+                div { class: "empty-state", "No permissions granted" }
+            }
+
+            for (group, entries) in grouped.into_iter() {
+                div {
+                    key: "{group}",
+                    class: "security-grants",
+                    h4 { "{group}" }
+                    table {
+                        class: "results-table",
+                        tbody {
+                            for (entry, permissions) in entries.into_iter() {
+                                tr {
+                                    key: "{entry}",
+                                    td { class: "security-grant-target", "{entry}" }
+                                    td {
+                                        for permission in permissions.into_iter() {
+                                            {
+                                                let grant = match *view.read() {
+                                                    GrantsView::ByRole => Grant {
+                                                        role: group.clone(),
+                                                        resource: entry.clone(),
+                                                        permission: permission.clone(),
+                                                    },
+                                                    GrantsView::ByResource => Grant {
+                                                        role: entry.clone(),
+                                                        resource: group.clone(),
+                                                        permission: permission.clone(),
+                                                    },
+                                                };
+                                                let revoke = grant.revoke_cql();
+                                                rsx! {
+                                                    span {
+                                                        key: "{permission}",
+                                                        class: "security-permission",
+                                                        "{permission}"
+                                                        if let Some(cql) = revoke {
+                                                            button {
+                                                                class: "btn-icon-small btn-danger-text",
+                                                                title: "{cql}",
+                                                                onclick: move |_| run(cql.clone(), cql.clone()),
+                                                                "✕"
+                                                            }
+                                                        }
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            if let Some(existing) = role_editor.read().clone() {
+                RoleEditorDialog {
+                    existing,
+                    on_close: move |_| role_editor.set(None),
+                    on_save: move |(cql, shown): (String, String)| {
+                        role_editor.set(None);
+                        run(cql, shown);
+                    },
+                }
+            }
+        }
+    }
+}
+
+/// Form creating a role, or altering `existing`'s password and flags
+#[component]
+fn RoleEditorDialog(
+    #[props(default)] existing: Option<Role>,
+    on_close: EventHandler<()>,
+    /// The statement to run and its display form with the password masked
+    on_save: EventHandler<(String, String)>,
+) -> Element {
+    let initial = existing
+        .as_ref()
+        .map(RoleDesign::from_role)
+        .unwrap_or_default();
+    let mut name = use_signal(|| initial.name.clone());
+    let mut password = use_signal(String::new);
+    let mut login = use_signal(|| initial.login);
+    let mut superuser = use_signal(|| initial.superuser);
+
+    let design = use_memo({
+        let existing = existing.clone();
+        move || -> Result<RoleDesign, String> {
+            let name = match existing.as_ref() {
+                Some(role) => role.name.clone(),
+                None if name.read().trim().is_empty() => String::new(),
+                None => Identifier::parse(&name.read())
+                    .map(|id| id.as_str().to_string())
+                    .map_err(|e| e.to_string())?,
+            };
+            Ok(RoleDesign {
+                name,
+                password: password.read().clone(),
+                login: *login.read(),
+                superuser: *superuser.read(),
+            })
+        }
+    });
+
+    let problems = use_memo({
+        let existing = existing.clone();
+        move || match design.read().as_ref() {
+            Ok(design) => design.validate(existing.as_ref()),
+            Err(e) => vec![e.clone()],
+        }
+    });
+
+    let statement = use_memo({
+        let existing = existing.clone();
+        move || {
+            let design = design.read();
+            let design = design.as_ref().ok()?;
+            match existing.as_ref() {
+                Some(current) => design.alter_cql(current),
+                None => Some(design.create_cql()),
+            }
+        }
+    });
+
+    // The statement as shown, without the password
+    let preview = use_memo({
+        let existing = existing.clone();
+        move || {
+            let mut design = design.read().clone().ok()?;
+            if !design.password.is_empty() {
+                design.password = "********".to_string();
+            }
+            match existing.as_ref() {
+                Some(current) => design.alter_cql(current),
+                None => Some(design.create_cql()),
+            }
+        }
+    });
+
+    let title = match &existing {
+        Some(role) => format!("Role {}", role.name),
+        None => "New Role".to_string(),
+    };
+
+    rsx! {
+        div {
+            class: "modal-backdrop",
+            onclick: move |_| on_close.call(()),
+
+            div {
+                class: "modal",
+                onclick: move |e| e.stop_propagation(),
+
+                div {
+                    class: "modal-header",
+                    h2 { "{title}" }
+                    button {
+                        class: "btn-close",
+                        onclick: move |_| on_close.call(()),
+                        "x"
+                    }
+                }
+
+                div {
+                    class: "modal-body",
+
+                    if existing.is_none() {
+                        div {
+                            class: "form-group",
+                            label { "Role name" }
+                            input {
+                                r#type: "text",
+                                value: "{name.read()}",
+                                placeholder: "e.g. analyst",
+                                oninput: move |e| name.set(e.value()),
+                            }
+                        }
+                    }
+
+                    div {
+                        class: "form-group",
+                        label { if existing.is_some() { "New password" } else { "Password" } }
+                        input {
+                            r#type: "password",
+                            value: "{password.read()}",
+                            placeholder: if existing.is_some() { "unchanged" } else { "" },
+                            oninput: move |e| password.set(e.value()),
+                        }
+                    }
+
+                    label {
+                        class: "form-checkbox",
+                        input {
+                            r#type: "checkbox",
+                            checked: *login.read(),
+                            onchange: move |e| login.set(e.checked()),
+                        }
+                        "Can log in"
+                    }
+
+                    label {
+                        class: "form-checkbox",
+                        input {
+                            r#type: "checkbox",
+                            checked: *superuser.read(),
+                            onchange: move |e| superuser.set(e.checked()),
+                        }
+                        "Superuser"
+                    }
+
+                    match preview.read().as_ref() {
+                        Some(cql) => rsx! {
+                            pre {
+                                class: "schema-ddl",
+                                dangerous_inner_html: cql_tokenizer::to_highlighted_html(&cql_tokenizer::tokenize(cql))
+                            }
+                        },
+                        None if existing.is_some() && problems.read().is_empty() => rsx! {
+                            p { class: "form-hint", "No changes" }
+                        },
+                        None => rsx! {},
+                    }
+                }
+
+                if let Some(problem) = problems.read().first() {
+                    div { class: "form-error", "{problem}" }
+                }
+
+                div {
+                    class: "modal-footer",
+
+                    button {
+                        class: "btn btn-secondary",
+                        onclick: move |_| on_close.call(()),
+                        "Cancel"
+                    }
+
+                    button {
+                        class: "btn btn-primary",
+                        disabled: statement.read().is_none() || !problems.read().is_empty(),
+                        onclick: move |_| {
+                            if let (Some(cql), Some(shown)) = (statement.read().clone(), preview.read().clone()) {
+                                on_save.call((cql, shown));
+                            }
+                        },
+                        if existing.is_some() { "Alter role" } else { "Create role" }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -3,7 +3,9 @@ use uuid::Uuid;
 
 use crate::config::save_saved_queries;
 use crate::connection::ConnectionConfig;
+use crate::schema::SchemaObject;
 use crate::state::{ActiveTab, AppState, ConsoleCategory, StatusLevel};
+use crate::virtual_tables::VirtualTable;
 
 #[component]
 pub fn Sidebar() -> Element {
@@ -12,6 +14,8 @@ pub fn Sidebar() -> Element {
     let mut selected_connection = use_signal(|| None::<Uuid>);
     let mut show_connection_dialog = use_signal(|| false);
     let mut editing_connection = use_signal(|| None::<ConnectionConfig>);
+    // Connection (and optionally keyspace) whose schema is being exported
+    let mut export_target = use_signal(|| None::<(Uuid, Option<String>)>);
 
     // Load connections on mount
     use_effect(move || {
@@ -65,6 +69,7 @@ pub fn Sidebar() -> Element {
                             editing_connection.set(Some(config));
                             show_connection_dialog.set(true);
                         },
+                        on_export: move |id: Uuid| export_target.set(Some((id, None))),
                         on_delete: move |id: Uuid| {
                             spawn(async move {
                                 let cm = app_state.read().connection_manager.clone();
@@ -91,7 +96,8 @@ pub fn Sidebar() -> Element {
             if let Some(selected_id) = *selected_connection.read() {
                 if let Some(selected_conn) = connections.read().iter().find(|c| c.id == selected_id) {
                     TablesSection {
-                        connection_name: selected_conn.name.clone()
+                        connection_name: selected_conn.name.clone(),
+                        on_export: move |target| export_target.set(Some(target)),
                     }
                 }
             }
@@ -99,6 +105,14 @@ pub fn Sidebar() -> Element {
             // Saved queries section
             SavedQueriesSection {}
 
+            if let Some((connection_id, keyspace)) = export_target.read().clone() {
+                super::schema_export::SchemaExportDialog {
+                    connection_id,
+                    keyspace,
+                    on_close: move |_| export_target.set(None),
+                }
+            }
+
             // Connection dialog (new or edit)
             if *show_connection_dialog.read() {
                 super::connection_dialog::ConnectionDialog {
@@ -148,6 +162,7 @@ fn ConnectionItem(
     on_select: EventHandler<Uuid>,
     on_edit: EventHandler<ConnectionConfig>,
     on_delete: EventHandler<Uuid>,
+    on_export: EventHandler<Uuid>,
 ) -> Element {
     let mut app_state = use_context::<Signal<AppState>>();
     let mut is_connected = use_signal(|| false);
@@ -252,6 +267,16 @@ fn ConnectionItem(
                 }
 
                 if *is_connected.read() {
+                    button {
+                        class: "btn-icon-small",
+                        title: "Export schema",
+                        onclick: move |e| {
+                            e.stop_propagation();
+                            on_export.call(connection.id);
+                        },
+                        "⇩"
+                    }
+
                     button {
                         class: "btn-small btn-danger",
                         onclick: move |e| {
@@ -333,40 +358,84 @@ fn ConnectionItem(
     }
 }
 
+/// Sidebar icon for a keyspace object
+fn object_icon(object: &SchemaObject) -> &'static str {
+    match object {
+        SchemaObject::Table(_) => "📋",
+        SchemaObject::View(_) => "👁",
+        SchemaObject::Type(_) => "🧩",
+        SchemaObject::Index(_) => "🔎",
+        SchemaObject::Function(_) => "ƒ",
+        SchemaObject::Aggregate(_) => "Σ",
+        SchemaObject::Trigger { .. } => "⚡",
+    }
+}
+
 #[component]
-fn TablesSection(connection_name: String) -> Element {
+fn TablesSection(
+    connection_name: String,
+    on_export: EventHandler<(Uuid, Option<String>)>,
+) -> Element {
     let mut app_state = use_context::<Signal<AppState>>();
-    let mut tables = use_signal(Vec::<String>::new);
-    let mut selected_table = use_signal(|| None::<String>);
+    let mut objects = use_signal(Vec::<SchemaObject>::new);
+    let mut tables_connection = use_signal(|| None::<Uuid>);
+    let mut tables_keyspace = use_signal(|| None::<String>);
+    let mut selected_object = use_signal(|| None::<SchemaObject>);
+    let mut virtual_tables = use_signal(Vec::<VirtualTable>::new);
+    let mut show_virtual = use_signal(|| false);
     let mut loading = use_signal(|| false);
 
     use_effect(move || {
+        let _ = app_state.read().schema_version.read();
         loading.set(true);
         spawn(async move {
             let cm = app_state.read().connection_manager.clone();
             if let Some(conn) = cm.get_active_connection().await {
-                if let Some(keyspace) = conn.resolve_keyspace().await {
-                    match conn.list_tables(&keyspace).await {
-                        Ok(t) => tables.set(t),
+                tables_connection.set(Some(conn.id));
+                let resolved = conn.resolve_keyspace().await;
+                tables_keyspace.set(resolved.clone());
+                if let Some(keyspace) = resolved {
+                    match conn.keyspace_schema(&keyspace).await {
+                        Ok(schema) => objects.set(schema.objects()),
                         Err(e) => {
-                            tracing::error!(
-                                "Failed to list tables for keyspace '{}': {}",
-                                keyspace,
-                                e
-                            );
-                            tables.set(Vec::new());
+                            tracing::error!("Failed to describe keyspace '{}': {}", keyspace, e);
+                            objects.set(Vec::new());
                         }
                     }
                 } else {
-                    tables.set(Vec::new());
+                    objects.set(Vec::new());
+                }
+                // Cassandra 3.x has no virtual tables; the group is just left out
+                match conn.virtual_tables().await {
+                    Ok(found) => virtual_tables.set(found),
+                    Err(e) => {
+                        tracing::debug!("No virtual tables available: {}", e);
+                        virtual_tables.set(Vec::new());
+                    }
                 }
             } else {
-                tables.set(Vec::new());
+                objects.set(Vec::new());
+                virtual_tables.set(Vec::new());
             }
             loading.set(false);
         });
     });
 
+    // Drop the connection's cached schema and read it again everywhere
+    let refresh = move |_| {
+        let Some(id) = *tables_connection.read() else {
+            return;
+        };
+        spawn(async move {
+            let cm = app_state.read().connection_manager.clone();
+            if let Some(conn) = cm.get_connection(id).await {
+                conn.refresh_schema(None);
+                let mut schema_version = app_state.read().schema_version;
+                *schema_version.write() += 1;
+            }
+        });
+    };
+
     rsx! {
         div {
             class: "tables-section",
@@ -376,7 +445,7 @@ fn TablesSection(connection_name: String) -> Element {
                 class: "tables-header",
                 h4 {
                     class: "tables-title",
-                    "Tables"
+                    "Schema"
                 }
 
                 if *loading.read() {
@@ -385,45 +454,138 @@ fn TablesSection(connection_name: String) -> Element {
                         "⟳"
                     }
                 }
+
+                if tables_connection.read().is_some() && !*loading.read() {
+                    button {
+                        class: "btn-icon-small",
+                        title: "Refresh schema",
+                        onclick: refresh,
+                        "↻"
+                    }
+                }
+
+                if let (Some(id), Some(keyspace)) = (*tables_connection.read(), tables_keyspace.read().clone()) {
+                    button {
+                        class: "btn-icon-small",
+                        title: "Export keyspace schema",
+                        onclick: move |_| on_export.call((id, Some(keyspace.clone()))),
+                        "⇩"
+                    }
+                }
             }
 
-            // Tables list
+            // Tables, views and the other keyspace objects
             div {
                 class: "tables-list",
 
-                if tables.read().is_empty() && !*loading.read() {
+                if objects.read().is_empty() && !*loading.read() {
                     div {
                         class: "empty-tables",
                         "No tables found"
                     }
                 } else {
-                    for (idx, table) in tables.read().iter().enumerate() {
+                    for (idx, object) in objects.read().iter().enumerate() {
                         div {
-                            key: "{idx}",
-                            class: if selected_table.read().as_ref() == Some(table) {
-                                "table-item selected"
-                            } else {
-                                "table-item"
-                            },
-                            onclick: {
-                                let table = table.clone();
-                                move |_| {
-                                    selected_table.set(Some(table.clone()));
-                                    // Also update global state
-                                    app_state.write().selected_table.set(Some(table.clone()));
-                                    // Switch to Data tab to show the table data
-                                    app_state.write().active_tab.set(crate::state::ActiveTab::Data);
+                            key: "{object.key()}",
+                            if idx > 0 && object.group_label() != objects.read()[idx - 1].group_label() {
+                                div {
+                                    class: "tables-subtitle",
+                                    "{object.group_label()}"
+                                }
+                            }
+                            div {
+                                class: if selected_object.read().as_ref() == Some(object) {
+                                    "table-item selected"
+                                } else {
+                                    "table-item"
+                                },
+                                title: "{object.kind_label()}",
+                                onclick: {
+                                    let object = object.clone();
+                                    move |_| {
+                                        selected_object.set(Some(object.clone()));
+                                        let keyspace = tables_keyspace.read().clone();
+                                        match &object {
+                                            // Tables and views open in the Data tab
+                                            SchemaObject::Table(table) | SchemaObject::View(table) => {
+                                                app_state.write().data_keyspace.set(keyspace);
+                                                app_state.write().selected_table.set(Some(table.clone()));
+                                                app_state.write().active_tab.set(ActiveTab::Data);
+                                            }
+                                            // Everything else opens in the schema viewer
+                                            _ => {
+                                                if let Some(keyspace) = keyspace {
+                                                    app_state.write().schema_target.set(Some((keyspace, object.clone())));
+                                                    app_state.write().active_tab.set(ActiveTab::Schema);
+                                                }
+                                            }
+                                        }
+                                    }
+                                },
+
+                                span {
+                                    class: "table-icon",
+                                    {object_icon(object)}
                                 }
-                            },
 
-                            span {
-                                class: "table-icon",
-                                "📋"
+                                span {
+                                    class: "table-name",
+                                    {object.name().to_string()}
+                                }
                             }
+                        }
+                    }
+                }
+            }
+
+            // Virtual tables (Cassandra 4.0+), opened whole in the Data tab
+            if !virtual_tables.read().is_empty() {
+                div {
+                    class: "virtual-tables",
 
-                            span {
-                                class: "table-name",
-                                "{table}"
+                    div {
+                        class: "tables-subtitle virtual-tables-toggle",
+                        onclick: move |_| {
+                            let open = *show_virtual.read();
+                            show_virtual.set(!open);
+                        },
+                        if *show_virtual.read() { "▾ Virtual tables" } else { "▸ Virtual tables" }
+                    }
+
+                    if *show_virtual.read() {
+                        for (idx, table) in virtual_tables.read().iter().enumerate() {
+                            div {
+                                key: "{table.keyspace}.{table.name}",
+                                if idx == 0 || table.keyspace != virtual_tables.read()[idx - 1].keyspace {
+                                    div {
+                                        class: "virtual-keyspace",
+                                        "{table.keyspace}"
+                                    }
+                                }
+                                div {
+                                    class: "table-item",
+                                    title: "{table.keyspace}.{table.name}: {table.description()}",
+                                    onclick: {
+                                        let keyspace = table.keyspace.clone();
+                                        let name = table.name.clone();
+                                        move |_| {
+                                            selected_object.set(None);
+                                            app_state.write().data_keyspace.set(Some(keyspace.clone()));
+                                            app_state.write().selected_table.set(Some(name.clone()));
+                                            app_state.write().active_tab.set(ActiveTab::Data);
+                                        }
+                                    },
+
+                                    span {
+                                        class: "table-icon",
+                                        "◌"
+                                    }
+
+                                    span {
+                                        class: "table-name",
+                                        "{table.title()}"
+                                    }
+                                }
                             }
                         }
                     }
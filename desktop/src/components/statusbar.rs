@@ -1,5 +1,7 @@
-use crate::state::{AppState, StatusLevel};
+use crate::metrics::{format_latency, MetricsSummary};
+use crate::state::{ActiveTab, AppState, StatusLevel};
 use dioxus::prelude::*;
+use std::time::Duration;
 
 #[component]
 pub fn StatusBar() -> Element {
@@ -8,6 +10,23 @@ pub fn StatusBar() -> Element {
     let status_message = app_state.read().status_message;
     let console_visible = *app_state.read().console_visible.read();
     let entry_count = app_state.read().console_log.read().len();
+    let mut latency = use_signal(|| None::<MetricsSummary>);
+
+    // Poll the active connection's driver statistics
+    use_future(move || async move {
+        loop {
+            let cm = app_state.read().connection_manager.clone();
+            let summary = cm
+                .get_active_connection()
+                .await
+                .map(|conn| conn.metrics().overall)
+                .filter(|summary| summary.queries > 0);
+            if *latency.peek() != summary {
+                latency.set(summary);
+            }
+            tokio::time::sleep(Duration::from_secs(2)).await;
+        }
+    });
 
     let display_text = connection_status
         .read()
@@ -57,6 +76,17 @@ pub fn StatusBar() -> Element {
 
             div {
                 class: "status-right",
+                if let Some(summary) = latency.read().as_ref() {
+                    span {
+                        class: "status-item status-latency",
+                        title: "Driver latency on the active connection (p50 / p95 / p99)",
+                        onclick: move |e| {
+                            e.stop_propagation();
+                            app_state.read().active_tab.clone().set(ActiveTab::Metrics);
+                        },
+                        "{format_latency(summary.p50_us)} / {format_latency(summary.p95_us)} / {format_latency(summary.p99_us)} · {summary.queries} queries"
+                    }
+                }
                 button {
                     class: "status-button",
                     onclick: move |e| {
@@ -0,0 +1,331 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use dioxus::prelude::*;
+
+use crate::cql_parser::NATIVE_TYPES;
+use crate::cql_tokenizer;
+use crate::schema::KeyspaceSchema;
+use crate::state::{AppState, ConsoleCategory, StatusLevel};
+use crate::table_design::{ColumnRole, DesignColumn, TableDesign, COLLECTION_TYPES};
+
+/// Compaction strategies offered for new tables; empty keeps the default
+const COMPACTION_STRATEGIES: &[&str] = &[
+    "",
+    "SizeTieredCompactionStrategy",
+    "LeveledCompactionStrategy",
+    "TimeWindowCompactionStrategy",
+    "UnifiedCompactionStrategy",
+];
+
+/// Parse an optional numeric option; empty leaves it unset
+fn parse_option(name: &str, text: &str) -> Result<Option<i32>, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+    match text.parse::<i32>() {
+        Ok(v) if v >= 0 => Ok(Some(v)),
+        _ => Err(format!("{} must be a non-negative number", name)),
+    }
+}
+
+/// Form for designing a new table in `schema`'s keyspace, with a live
+/// `CREATE TABLE` preview, validated before it is run
+#[component]
+pub fn TableDesignerDialog(
+    schema: Arc<KeyspaceSchema>,
+    on_close: EventHandler<()>,
+    on_created: EventHandler<String>,
+) -> Element {
+    let app_state = use_context::<Signal<AppState>>();
+    let keyspace = schema.keyspace.name.clone();
+    let mut name = use_signal(String::new);
+    let mut columns = use_signal(|| {
+        vec![
+            DesignColumn::new("id", "uuid", ColumnRole::PartitionKey),
+            DesignColumn::new("", "text", ColumnRole::Regular),
+        ]
+    });
+    let mut compaction = use_signal(String::new);
+    let mut gc_grace = use_signal(String::new);
+    let mut ttl = use_signal(String::new);
+    let mut comment = use_signal(String::new);
+    let mut creating = use_signal(|| false);
+    let mut create_error = use_signal(|| None::<String>);
+
+    // The design as edited, or why its options are invalid
+    let design = use_memo(move || -> Result<TableDesign, String> {
+        let mut design = TableDesign::new(&keyspace);
+        design.name = name.read().clone();
+        design.columns = columns.read().clone();
+        design.options.gc_grace_seconds = parse_option("gc_grace_seconds", &gc_grace.read())?;
+        design.options.default_time_to_live = parse_option("default_time_to_live", &ttl.read())?;
+        design.options.comment = comment.read().clone();
+        let class = compaction.read().clone();
+        if !class.is_empty() {
+            design.options.compaction = BTreeMap::from([("class".to_string(), class)]);
+        }
+        Ok(design)
+    });
+
+    let problems = use_memo({
+        let schema = schema.clone();
+        move || match design.read().as_ref() {
+            Ok(design) => design.validate(&schema.types),
+            Err(e) => vec![e.clone()],
+        }
+    });
+
+    let preview = use_memo(move || design.read().as_ref().ok().map(TableDesign::to_cql));
+
+    let create = move |_| {
+        let Some(cql) = preview.read().clone() else {
+            return;
+        };
+        if !problems.read().is_empty() {
+            return;
+        }
+        let table = name.read().trim().to_string();
+        creating.set(true);
+        create_error.set(None);
+        spawn(async move {
+            let cm = app_state.read().connection_manager.clone();
+            let Some(conn) = cm.get_active_connection().await else {
+                create_error.set(Some("No active connection".to_string()));
+                creating.set(false);
+                return;
+            };
+            match conn.execute_query(&cql).await {
+                Ok(_) => {
+                    let state = app_state.read();
+                    AppState::console_push(
+                        state.console_log,
+                        state.status_message,
+                        StatusLevel::Success,
+                        ConsoleCategory::Query,
+                        format!("Created table {}", table),
+                    );
+                    creating.set(false);
+                    on_created.call(table);
+                }
+                Err(e) => {
+                    create_error.set(Some(format!("Failed to create table: {}", e)));
+                    creating.set(false);
+                }
+            }
+        });
+    };
+
+    let user_types: Vec<String> = schema.types.iter().map(|t| t.name.clone()).collect();
+    let column_count = columns.read().len();
+
+    rsx! {
+        div {
+            class: "modal-backdrop",
+            onclick: move |_| on_close.call(()),
+
+            div {
+                class: "modal modal-wide",
+                onclick: move |e| e.stop_propagation(),
+
+                div {
+                    class: "modal-header",
+                    h2 { "New Table in {schema.keyspace.name}" }
+                    button {
+                        class: "btn-close",
+                        onclick: move |_| on_close.call(()),
+                        "x"
+                    }
+                }
+
+                div {
+                    class: "modal-body",
+
+                    div {
+                        class: "form-group",
+                        label { "Table name" }
+                        input {
+                            r#type: "text",
+                            value: "{name.read()}",
+                            placeholder: "e.g. events_by_device",
+                            oninput: move |e| name.set(e.value()),
+                        }
+                    }
+
+                    div {
+                        class: "form-group",
+                        label { "Columns" }
+                        div {
+                            class: "table-designer-columns",
+                            for (i, column) in columns.read().iter().enumerate() {
+                                div {
+                                    key: "{i}",
+                                    class: "table-designer-row",
+                                    input {
+                                        r#type: "text",
+                                        value: "{column.name}",
+                                        placeholder: "column",
+                                        oninput: move |e| columns.write()[i].name = e.value(),
+                                    }
+                                    input {
+                                        r#type: "text",
+                                        value: "{column.data_type}",
+                                        list: "table-designer-types",
+                                        placeholder: "type",
+                                        oninput: move |e| columns.write()[i].data_type = e.value(),
+                                    }
+                                    select {
+                                        value: "{column.role.key()}",
+                                        onchange: move |e| {
+                                            if let Some(role) = ColumnRole::from_key(&e.value()) {
+                                                columns.write()[i].role = role;
+                                            }
+                                        },
+                                        for role in ColumnRole::ALL {
+                                            option { value: "{role.key()}", "{role.label()}" }
+                                        }
+                                    }
+                                    button {
+                                        class: "btn-icon-small",
+                                        title: "Move up",
+                                        disabled: i == 0,
+                                        onclick: move |_| columns.write().swap(i - 1, i),
+                                        "↑"
+                                    }
+                                    button {
+                                        class: "btn-icon-small",
+                                        title: "Move down",
+                                        disabled: i + 1 == column_count,
+                                        onclick: move |_| columns.write().swap(i, i + 1),
+                                        "↓"
+                                    }
+                                    button {
+                                        class: "btn-icon-small",
+                                        title: "Remove",
+                                        onclick: move |_| {
+                                            columns.write().remove(i);
+                                        },
+                                        "x"
+                                    }
+                                }
+                            }
+                            datalist {
+                                id: "table-designer-types",
+                                for ty in NATIVE_TYPES.iter() {
+                                    option { value: "{ty}" }
+                                }
+                                for ty in COLLECTION_TYPES.iter() {
+                                    option { value: "{ty}" }
+                                }
+                                for ty in user_types.iter() {
+                                    option { value: "frozen<{ty}>" }
+                                    option { value: "{ty}" }
+                                }
+                            }
+                            button {
+                                class: "btn btn-small",
+                                onclick: move |_| {
+                                    columns.write().push(DesignColumn::new("", "text", ColumnRole::Regular))
+                                },
+                                "Add column"
+                            }
+                        }
+                        p { class: "form-hint", "Key columns are used in the order listed" }
+                    }
+
+                    div {
+                        class: "form-row",
+                        div {
+                            class: "form-group",
+                            label { "Compaction" }
+                            select {
+                                value: "{compaction.read()}",
+                                onchange: move |e| compaction.set(e.value()),
+                                for class in COMPACTION_STRATEGIES.iter() {
+                                    option {
+                                        value: "{class}",
+                                        if class.is_empty() { "Default" } else { "{class}" }
+                                    }
+                                }
+                            }
+                        }
+                        div {
+                            class: "form-group",
+                            label { "Comment" }
+                            input {
+                                r#type: "text",
+                                value: "{comment.read()}",
+                                oninput: move |e| comment.set(e.value()),
+                            }
+                        }
+                    }
+
+                    div {
+                        class: "form-row",
+                        div {
+                            class: "form-group",
+                            label { "gc_grace_seconds" }
+                            input {
+                                r#type: "number",
+                                min: "0",
+                                value: "{gc_grace.read()}",
+                                placeholder: "864000",
+                                oninput: move |e| gc_grace.set(e.value()),
+                            }
+                        }
+                        div {
+                            class: "form-group",
+                            label { "default_time_to_live" }
+                            input {
+                                r#type: "number",
+                                min: "0",
+                                value: "{ttl.read()}",
+                                placeholder: "0",
+                                oninput: move |e| ttl.set(e.value()),
+                            }
+                        }
+                    }
+
+                    if let Some(cql) = preview.read().as_ref() {
+                        pre {
+                            class: "schema-ddl",
+                            dangerous_inner_html: cql_tokenizer::to_highlighted_html(&cql_tokenizer::tokenize(cql))
+                        }
+                    }
+                }
+
+                if !problems.read().is_empty() {
+                    div {
+                        class: "form-error",
+                        ul {
+                            class: "table-designer-problems",
+                            for problem in problems.read().iter() {
+                                li { "{problem}" }
+                            }
+                        }
+                    }
+                } else if let Some(error) = create_error.read().as_ref() {
+                    div { class: "form-error", "{error}" }
+                }
+
+                div {
+                    class: "modal-footer",
+
+                    button {
+                        class: "btn btn-secondary",
+                        onclick: move |_| on_close.call(()),
+                        "Cancel"
+                    }
+
+                    button {
+                        class: "btn btn-primary",
+                        disabled: !problems.read().is_empty() || *creating.read(),
+                        onclick: create,
+                        if *creating.read() { "Creating..." } else { "Create table" }
+                    }
+                }
+            }
+        }
+    }
+}
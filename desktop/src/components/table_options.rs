@@ -0,0 +1,559 @@
+use std::collections::BTreeMap;
+
+use dioxus::prelude::*;
+
+use crate::cql_tokenizer;
+use crate::schema::TableOptions;
+use crate::state::{AppState, ConsoleCategory, StatusLevel};
+
+/// Compaction strategies offered in the editor, by short name
+const COMPACTION_STRATEGIES: &[&str] = &[
+    "SizeTieredCompactionStrategy",
+    "LeveledCompactionStrategy",
+    "TimeWindowCompactionStrategy",
+    "UnifiedCompactionStrategy",
+];
+
+/// Compressors offered in the editor, by short name
+const COMPRESSORS: &[&str] = &[
+    "LZ4Compressor",
+    "ZstdCompressor",
+    "SnappyCompressor",
+    "DeflateCompressor",
+];
+
+/// Last segment of a Java class name
+fn short_class(class: &str) -> &str {
+    class.rsplit('.').next().unwrap_or(class)
+}
+
+/// `864000` → `10d`, `3600` → `1h`, `90` → `1m 30s`
+fn format_seconds(seconds: i32) -> String {
+    if seconds <= 0 {
+        return seconds.to_string();
+    }
+    let units = [("d", 86_400), ("h", 3_600), ("m", 60), ("s", 1)];
+    let mut rest = seconds;
+    let mut parts = Vec::new();
+    for (unit, size) in units {
+        if rest >= size {
+            parts.push(format!("{}{}", rest / size, unit));
+            rest %= size;
+        }
+    }
+    parts.join(" ")
+}
+
+/// Map options without the `class` entry, which is shown separately
+fn parameters(map: &BTreeMap<String, String>) -> Vec<(String, String)> {
+    map.iter()
+        .filter(|(k, _)| k.as_str() != "class")
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect()
+}
+
+/// Structured view of a table's `WITH` options
+#[component]
+pub fn TableOptionsPanel(
+    options: TableOptions,
+    /// Statement prefix for changes, e.g. `ALTER TABLE ks.t`
+    alter: String,
+    on_altered: EventHandler<()>,
+) -> Element {
+    let mut editing = use_signal(|| false);
+
+    let compaction = options
+        .compaction
+        .get("class")
+        .map(|c| short_class(c).to_string())
+        .unwrap_or_else(|| "—".to_string());
+    let compression_enabled = options
+        .compression
+        .get("enabled")
+        .is_none_or(|enabled| enabled != "false")
+        && !options.compression.is_empty();
+    let compression = match options.compression.get("class") {
+        Some(class) if compression_enabled => short_class(class).to_string(),
+        _ => "disabled".to_string(),
+    };
+    let caching = if options.caching.is_empty() {
+        "—".to_string()
+    } else {
+        format!(
+            "keys: {}, rows per partition: {}",
+            options.caching.get("keys").map_or("—", String::as_str),
+            options
+                .caching
+                .get("rows_per_partition")
+                .map_or("—", String::as_str)
+        )
+    };
+    let optional = |value: Option<String>| value.unwrap_or_else(|| "—".to_string());
+
+    rsx! {
+        div {
+            class: "schema-related",
+            div {
+                class: "table-options-header",
+                h5 { class: "schema-related-title", "Options" }
+                button {
+                    class: "btn btn-small",
+                    onclick: move |_| editing.set(true),
+                    "Edit"
+                }
+            }
+
+            dl {
+                class: "schema-properties",
+                dt { "Compaction" }
+                dd { code { "{compaction}" } }
+                for (key, value) in parameters(&options.compaction) {
+                    dt { key: "compaction-{key}", class: "table-options-param", "{key}" }
+                    dd { code { "{value}" } }
+                }
+                dt { "Compression" }
+                dd { code { "{compression}" } }
+                if compression_enabled {
+                    for (key, value) in parameters(&options.compression) {
+                        dt { key: "compression-{key}", class: "table-options-param", "{key}" }
+                        dd { code { "{value}" } }
+                    }
+                }
+                dt { "Caching" }
+                dd { "{caching}" }
+                dt { "gc_grace_seconds" }
+                dd {
+                    {optional(options.gc_grace_seconds.map(|s| format!("{} ({})", s, format_seconds(s))))}
+                }
+                dt { "default_time_to_live" }
+                dd {
+                    {optional(options.default_time_to_live.map(|s| {
+                        if s == 0 { "0 (no expiry)".to_string() } else { format!("{} ({})", s, format_seconds(s)) }
+                    }))}
+                }
+                dt { "bloom_filter_fp_chance" }
+                dd { {optional(options.bloom_filter_fp_chance.map(|v| v.to_string()))} }
+                dt { "speculative_retry" }
+                dd { {optional(options.speculative_retry.clone())} }
+                if !options.comment.is_empty() {
+                    dt { "comment" }
+                    dd { "{options.comment}" }
+                }
+            }
+        }
+
+        if *editing.read() {
+            TableOptionsDialog {
+                options: options.clone(),
+                alter: alter.clone(),
+                on_close: move |_| editing.set(false),
+                on_altered: move |_| {
+                    editing.set(false);
+                    on_altered.call(());
+                },
+            }
+        }
+    }
+}
+
+/// Editable key/value rows of a map option such as `compaction`
+#[component]
+fn MapEditor(
+    rows: Signal<Vec<(String, String)>>,
+    /// Suggestions for the `class` value
+    classes: &'static [&'static str],
+) -> Element {
+    let list_id = format!("classes-{}", classes.first().copied().unwrap_or("none"));
+    rsx! {
+        div {
+            class: "map-editor",
+            for (i, (key, value)) in rows.read().iter().enumerate() {
+                div {
+                    key: "{i}",
+                    class: "map-editor-row",
+                    input {
+                        r#type: "text",
+                        value: "{key}",
+                        placeholder: "option",
+                        oninput: move |e| rows.write()[i].0 = e.value(),
+                    }
+                    input {
+                        r#type: "text",
+                        value: "{value}",
+                        list: if key == "class" { "{list_id}" },
+                        placeholder: "value",
+                        oninput: move |e| rows.write()[i].1 = e.value(),
+                    }
+                    button {
+                        class: "btn-icon-small",
+                        title: "Remove",
+                        onclick: move |_| {
+                            rows.write().remove(i);
+                        },
+                        "x"
+                    }
+                }
+            }
+            datalist {
+                id: "{list_id}",
+                for class in classes.iter() {
+                    option { value: "{class}" }
+                }
+            }
+            button {
+                class: "btn btn-small",
+                onclick: move |_| rows.write().push((String::new(), String::new())),
+                "Add option"
+            }
+        }
+    }
+}
+
+fn map_rows(map: &BTreeMap<String, String>) -> Vec<(String, String)> {
+    let mut rows: Vec<(String, String)> = map
+        .iter()
+        .map(|(k, v)| {
+            let value = if k == "class" { short_class(v) } else { v };
+            (k.clone(), value.to_string())
+        })
+        .collect();
+    // `class` first, as it decides which other options apply
+    rows.sort_by_key(|(k, _)| k != "class");
+    rows
+}
+
+fn rows_map(rows: &[(String, String)]) -> BTreeMap<String, String> {
+    rows.iter()
+        .filter(|(k, _)| !k.trim().is_empty())
+        .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+        .collect()
+}
+
+/// Parse an optional numeric field, keeping the current value when empty
+fn parse_field<T: std::str::FromStr>(
+    name: &str,
+    text: &str,
+    current: Option<T>,
+) -> Result<Option<T>, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(current);
+    }
+    text.parse()
+        .map(Some)
+        .map_err(|_| format!("{} must be a number", name))
+}
+
+/// Edit form producing and running `ALTER TABLE ... WITH`
+#[component]
+fn TableOptionsDialog(
+    options: TableOptions,
+    alter: String,
+    on_close: EventHandler<()>,
+    on_altered: EventHandler<()>,
+) -> Element {
+    let app_state = use_context::<Signal<AppState>>();
+    let current = options.clone();
+    let mut gc_grace = use_signal(|| {
+        options
+            .gc_grace_seconds
+            .map(|v| v.to_string())
+            .unwrap_or_default()
+    });
+    let mut ttl = use_signal(|| {
+        options
+            .default_time_to_live
+            .map(|v| v.to_string())
+            .unwrap_or_default()
+    });
+    let mut bloom = use_signal(|| {
+        options
+            .bloom_filter_fp_chance
+            .map(|v| v.to_string())
+            .unwrap_or_default()
+    });
+    let mut speculative_retry =
+        use_signal(|| options.speculative_retry.clone().unwrap_or_default());
+    let mut comment = use_signal(|| options.comment.clone());
+    let mut caching_keys = use_signal(|| options.caching.get("keys").cloned().unwrap_or_default());
+    let mut caching_rows = use_signal(|| {
+        options
+            .caching
+            .get("rows_per_partition")
+            .cloned()
+            .unwrap_or_default()
+    });
+    let compaction = use_signal(|| map_rows(&options.compaction));
+    let compression = use_signal(|| map_rows(&options.compression));
+    let mut applying = use_signal(|| false);
+    let mut apply_error = use_signal(|| None::<String>);
+
+    // The options as edited, or why they are invalid
+    let desired = use_memo(move || -> Result<TableOptions, String> {
+        let mut desired = current.clone();
+        desired.gc_grace_seconds = parse_field(
+            "gc_grace_seconds",
+            &gc_grace.read(),
+            current.gc_grace_seconds,
+        )?;
+        desired.default_time_to_live = parse_field(
+            "default_time_to_live",
+            &ttl.read(),
+            current.default_time_to_live,
+        )?;
+        desired.bloom_filter_fp_chance = parse_field(
+            "bloom_filter_fp_chance",
+            &bloom.read(),
+            current.bloom_filter_fp_chance,
+        )?;
+        if let Some(chance) = desired.bloom_filter_fp_chance {
+            if !(chance > 0.0 && chance <= 1.0) {
+                return Err("bloom_filter_fp_chance must be in (0, 1]".to_string());
+            }
+        }
+        if desired.gc_grace_seconds.is_some_and(|v| v < 0)
+            || desired.default_time_to_live.is_some_and(|v| v < 0)
+        {
+            return Err("Durations cannot be negative".to_string());
+        }
+        let retry = speculative_retry.read().trim().to_string();
+        if !retry.is_empty() {
+            desired.speculative_retry = Some(retry);
+        }
+        desired.comment = comment.read().clone();
+        if !current.caching.is_empty() {
+            desired
+                .caching
+                .insert("keys".to_string(), caching_keys.read().trim().to_string());
+            desired.caching.insert(
+                "rows_per_partition".to_string(),
+                caching_rows.read().trim().to_string(),
+            );
+        }
+        desired.compaction = rows_map(&compaction.read());
+        if !desired.compaction.contains_key("class") {
+            return Err("compaction needs a class".to_string());
+        }
+        desired.compression = rows_map(&compression.read());
+        // Unchanged maps are compared with the fully qualified class names
+        // the cluster reports
+        for (edited, original) in [
+            (&mut desired.compaction, &current.compaction),
+            (&mut desired.compression, &current.compression),
+        ] {
+            if let (Some(new), Some(old)) = (edited.get("class"), original.get("class")) {
+                if short_class(old) == new {
+                    edited.insert("class".to_string(), old.clone());
+                }
+            }
+        }
+        Ok(desired)
+    });
+
+    let statement = use_memo({
+        let alter = alter.clone();
+        let options = options.clone();
+        move || {
+            desired
+                .read()
+                .as_ref()
+                .ok()
+                .and_then(|d| options.alter_cql(&alter, d))
+        }
+    });
+
+    let apply = move |_| {
+        let Some(cql) = statement.read().clone() else {
+            return;
+        };
+        applying.set(true);
+        apply_error.set(None);
+        spawn(async move {
+            let cm = app_state.read().connection_manager.clone();
+            let Some(conn) = cm.get_active_connection().await else {
+                apply_error.set(Some("No active connection".to_string()));
+                applying.set(false);
+                return;
+            };
+            let console_log = app_state.read().console_log;
+            let status_message = app_state.read().status_message;
+            match conn.execute_query(&cql).await {
+                Ok(_) => {
+                    AppState::console_push(
+                        console_log,
+                        status_message,
+                        StatusLevel::Success,
+                        ConsoleCategory::Query,
+                        format!("Altered table options: {}", cql.replace('\n', " ")),
+                    );
+                    applying.set(false);
+                    on_altered.call(());
+                }
+                Err(e) => {
+                    apply_error.set(Some(format!("Failed to alter table: {}", e)));
+                    applying.set(false);
+                }
+            }
+        });
+    };
+
+    let caching_supported = !options.caching.is_empty();
+
+    rsx! {
+        div {
+            class: "modal-backdrop",
+            onclick: move |_| on_close.call(()),
+
+            div {
+                class: "modal modal-wide",
+                onclick: move |e| e.stop_propagation(),
+
+                div {
+                    class: "modal-header",
+                    h2 { "Table Options" }
+                    button {
+                        class: "btn-close",
+                        onclick: move |_| on_close.call(()),
+                        "x"
+                    }
+                }
+
+                div {
+                    class: "modal-body",
+
+                    div {
+                        class: "form-group",
+                        label { "Compaction" }
+                        MapEditor { rows: compaction, classes: COMPACTION_STRATEGIES }
+                    }
+
+                    div {
+                        class: "form-group",
+                        label { "Compression" }
+                        MapEditor { rows: compression, classes: COMPRESSORS }
+                    }
+
+                    div {
+                        class: "form-row",
+                        div {
+                            class: "form-group",
+                            label { "gc_grace_seconds" }
+                            input {
+                                r#type: "number",
+                                min: "0",
+                                value: "{gc_grace.read()}",
+                                oninput: move |e| gc_grace.set(e.value()),
+                            }
+                        }
+                        div {
+                            class: "form-group",
+                            label { "default_time_to_live" }
+                            input {
+                                r#type: "number",
+                                min: "0",
+                                value: "{ttl.read()}",
+                                oninput: move |e| ttl.set(e.value()),
+                            }
+                        }
+                    }
+
+                    div {
+                        class: "form-row",
+                        div {
+                            class: "form-group",
+                            label { "bloom_filter_fp_chance" }
+                            input {
+                                r#type: "number",
+                                step: "0.001",
+                                value: "{bloom.read()}",
+                                oninput: move |e| bloom.set(e.value()),
+                            }
+                        }
+                        div {
+                            class: "form-group",
+                            label { "speculative_retry" }
+                            input {
+                                r#type: "text",
+                                value: "{speculative_retry.read()}",
+                                placeholder: "99p, 50ms, ALWAYS, NONE",
+                                oninput: move |e| speculative_retry.set(e.value()),
+                            }
+                        }
+                    }
+
+                    if caching_supported {
+                        div {
+                            class: "form-row",
+                            div {
+                                class: "form-group",
+                                label { "Caching: keys" }
+                                select {
+                                    value: "{caching_keys.read()}",
+                                    onchange: move |e| caching_keys.set(e.value()),
+                                    option { value: "ALL", "ALL" }
+                                    option { value: "NONE", "NONE" }
+                                }
+                            }
+                            div {
+                                class: "form-group",
+                                label { "Caching: rows per partition" }
+                                input {
+                                    r#type: "text",
+                                    value: "{caching_rows.read()}",
+                                    placeholder: "NONE, ALL or a number",
+                                    oninput: move |e| caching_rows.set(e.value()),
+                                }
+                            }
+                        }
+                    }
+
+                    div {
+                        class: "form-group",
+                        label { "Comment" }
+                        input {
+                            r#type: "text",
+                            value: "{comment.read()}",
+                            oninput: move |e| comment.set(e.value()),
+                        }
+                    }
+
+                    match (desired.read().as_ref(), statement.read().as_ref()) {
+                        (Err(_), _) => rsx! {},
+                        (Ok(_), Some(cql)) => rsx! {
+                            pre {
+                                class: "schema-ddl",
+                                dangerous_inner_html: cql_tokenizer::to_highlighted_html(&cql_tokenizer::tokenize(cql))
+                            }
+                        },
+                        (Ok(_), None) => rsx! {
+                            p { class: "form-hint", "No changes" }
+                        },
+                    }
+                }
+
+                if let Err(error) = desired.read().as_ref() {
+                    div { class: "form-error", "{error}" }
+                } else if let Some(error) = apply_error.read().as_ref() {
+                    div { class: "form-error", "{error}" }
+                }
+
+                div {
+                    class: "modal-footer",
+
+                    button {
+                        class: "btn btn-secondary",
+                        onclick: move |_| on_close.call(()),
+                        "Cancel"
+                    }
+
+                    button {
+                        class: "btn btn-primary",
+                        disabled: statement.read().is_none() || *applying.read(),
+                        onclick: apply,
+                        if *applying.read() { "Applying..." } else { "Apply" }
+                    }
+                }
+            }
+        }
+    }
+}
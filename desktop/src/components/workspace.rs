@@ -1,10 +1,30 @@
+use dioxus::core::{spawn_forever, Task};
 use dioxus::prelude::*;
 
 use super::data_grid::DataGrid;
+use super::editor_tabs::EditorTabBar;
+use super::error_panel::ErrorPanel;
+use super::metrics_panel::MetricsPanel;
 use super::query_editor::QueryEditor;
+use super::schema_compare::SchemaComparePanel;
 use super::schema_viewer::SchemaViewer;
+use super::script_results::ScriptResults;
+use super::security_panel::SecurityPanel;
 use super::variables_panel::VariablesPanel;
-use crate::state::{ActiveTab, AppState, ConsoleCategory, QueryVariable, StatusLevel, DEFAULT_PAGE_SIZE};
+use crate::components::data_grid::QueryResult;
+use crate::config::save_editor_tabs;
+use crate::connection::CassandraConnection;
+use crate::cql_tokenizer;
+use crate::error::{ErrorReport, MagdaError, Result};
+use crate::roles::redact_passwords;
+use crate::state::{
+    ActiveTab, AppState, ConsoleCategory, QueryExecution, QueryVariable, StatementRun,
+    StatementState, StatusLevel, DEFAULT_PAGE_SIZE,
+};
+use crate::virtual_tables::is_virtual_keyspace;
+
+/// Idle time after the last keystroke before editor tabs are written to disk
+const SAVE_TABS_DELAY: std::time::Duration = std::time::Duration::from_millis(800);
 
 fn substitute_variables(query: &str, vars: &[QueryVariable]) -> String {
     let mut result = query.to_string();
@@ -16,6 +36,213 @@ fn substitute_variables(query: &str, vars: &[QueryVariable]) -> String {
     result
 }
 
+/// Handle `USE ks` typed in an editor tab: check the keyspace exists, then
+/// record it on the tab so later queries run there.
+async fn switch_tab_keyspace(
+    connection: &CassandraConnection,
+    app_state: Signal<AppState>,
+    tab_id: uuid::Uuid,
+    keyspace: String,
+) -> Result<QueryResult> {
+    let start = std::time::Instant::now();
+    // Virtual keyspaces are not listed in system_schema
+    let keyspaces = connection.list_keyspaces().await?;
+    if !keyspaces.contains(&keyspace) && !is_virtual_keyspace(&keyspace) {
+        return Err(MagdaError::InvalidQuery(format!(
+            "Keyspace '{}' does not exist",
+            keyspace
+        )));
+    }
+
+    let mut editor_tabs = app_state.read().editor_tabs;
+    let active = *app_state.read().active_editor_tab.peek();
+    if let Some(tab) = editor_tabs.write().iter_mut().find(|t| t.id == tab_id) {
+        tab.keyspace = Some(keyspace.clone());
+    }
+    save_editor_tabs(&editor_tabs.peek(), active);
+
+    Ok(QueryResult::message(
+        format!("Now using keyspace {}", keyspace),
+        start.elapsed().as_millis() as u64,
+    ))
+}
+
+/// Run one statement on the tab's connection, bumping the schema version
+/// after a schema change
+async fn execute_statement(
+    connection: &CassandraConnection,
+    app_state: Signal<AppState>,
+    tab_id: uuid::Uuid,
+    keyspace: Option<&str>,
+    statement: &str,
+) -> Result<QueryResult> {
+    let result = match cql_tokenizer::parse_use_statement(statement) {
+        // USE only switches this tab's keyspace; the shared session is left alone
+        Some(ks) => switch_tab_keyspace(connection, app_state, tab_id, ks).await,
+        None => connection.execute_query_in(statement, keyspace).await,
+    };
+    if result.is_ok() && cql_tokenizer::is_schema_change(statement) {
+        let mut schema_version = app_state.read().schema_version;
+        *schema_version.write() += 1;
+    }
+    result
+}
+
+fn set_statement_state(
+    app_state: Signal<AppState>,
+    tab_id: uuid::Uuid,
+    index: usize,
+    state: StatementState,
+    execution_time_ms: u64,
+) {
+    let mut executions = app_state.read().tab_executions;
+    let mut executions = executions.write();
+    if let Some(run) = executions
+        .get_mut(&tab_id)
+        .and_then(|e| e.script.get_mut(index))
+    {
+        run.state = state;
+        run.execution_time_ms = execution_time_ms;
+    }
+}
+
+/// Run the statements of a script one after another, recording each outcome
+/// on the tab's execution as it completes. A `USE` applies to the
+/// statements after it. Returns the outcome to show: the first failure,
+/// else the last statement's result.
+async fn run_script(
+    connection: &CassandraConnection,
+    app_state: Signal<AppState>,
+    tab_id: uuid::Uuid,
+    mut keyspace: Option<String>,
+    statements: &[String],
+    stop_on_error: bool,
+    query: String,
+) -> std::result::Result<QueryResult, ErrorReport> {
+    let console_log = app_state.read().console_log;
+    let status_msg = app_state.read().status_message;
+    let script_start = std::time::Instant::now();
+    let mut shown: Option<(usize, std::result::Result<QueryResult, ErrorReport>)> = None;
+    let mut failures = 0;
+    let mut executed = 0;
+
+    for (index, statement) in statements.iter().enumerate() {
+        set_statement_state(app_state, tab_id, index, StatementState::Running, 0);
+        AppState::console_push(
+            console_log,
+            status_msg,
+            StatusLevel::Info,
+            ConsoleCategory::Query,
+            format!(
+                "Executing ({}/{}): {}",
+                index + 1,
+                statements.len(),
+                redact_passwords(statement)
+            ),
+        );
+        let start = std::time::Instant::now();
+        let result = execute_statement(
+            connection,
+            app_state,
+            tab_id,
+            keyspace.as_deref(),
+            statement,
+        )
+        .await;
+        let elapsed = start.elapsed().as_millis() as u64;
+        executed += 1;
+
+        let outcome = match result {
+            Ok(result) => {
+                if let Some(ks) = cql_tokenizer::parse_use_statement(statement) {
+                    keyspace = Some(ks);
+                }
+                AppState::console_push(
+                    console_log,
+                    status_msg,
+                    StatusLevel::Success,
+                    ConsoleCategory::Query,
+                    format!("{} rows in {}ms", result.row_count, elapsed),
+                );
+                Ok(result)
+            }
+            Err(e) => {
+                let error_msg = format!("Statement {} failed: {}", index + 1, e);
+                tracing::error!("{}", error_msg);
+                AppState::console_push(
+                    console_log,
+                    status_msg,
+                    StatusLevel::Error,
+                    ConsoleCategory::Query,
+                    error_msg,
+                );
+                failures += 1;
+                Err(ErrorReport::from(&e))
+            }
+        };
+        let failed = outcome.is_err();
+        // Show the first failure, else the latest result
+        if !matches!(shown, Some((_, Err(_)))) {
+            shown = Some((index, outcome.clone()));
+        }
+        let state = match outcome {
+            Ok(result) => StatementState::Done(result),
+            Err(report) => StatementState::Failed(report),
+        };
+        set_statement_state(app_state, tab_id, index, state, elapsed);
+        if failed && stop_on_error {
+            break;
+        }
+    }
+
+    let mut executions = app_state.read().tab_executions;
+    if let Some(execution) = executions.write().get_mut(&tab_id) {
+        for run in execution.script.iter_mut().skip(executed) {
+            run.state = StatementState::Skipped;
+        }
+        execution.selected_statement = shown.as_ref().map(|(index, _)| *index);
+    }
+
+    let total_ms = script_start.elapsed().as_millis() as u64;
+    let (level, summary) = if failures == 0 {
+        (
+            StatusLevel::Success,
+            format!("Script: {} statements in {}ms", executed, total_ms),
+        )
+    } else {
+        (
+            StatusLevel::Error,
+            format!(
+                "Script: {} of {} statements failed, {} not run",
+                failures,
+                statements.len(),
+                statements.len() - executed
+            ),
+        )
+    };
+    AppState::console_push(
+        console_log,
+        status_msg,
+        level,
+        ConsoleCategory::Query,
+        summary,
+    );
+    AppState::add_to_history(
+        app_state.read().query_history,
+        query,
+        failures == 0,
+        total_ms,
+    );
+
+    match shown {
+        Some((_, outcome)) => outcome,
+        None => Err(ErrorReport::plain(
+            "Empty script",
+            "The script has no statements",
+        )),
+    }
+}
+
 #[component]
 pub fn Workspace() -> Element {
     let app_state = use_context::<Signal<AppState>>();
@@ -47,6 +274,12 @@ pub fn Workspace() -> Element {
                     onclick: move |_| active_tab.set(ActiveTab::Schema)
                 }
 
+                TabButton {
+                    label: "Compare",
+                    is_active: *active_tab.read() == ActiveTab::Compare,
+                    onclick: move |_| active_tab.set(ActiveTab::Compare)
+                }
+
                 TabButton {
                     label: "History",
                     is_active: *active_tab.read() == ActiveTab::History,
@@ -58,6 +291,18 @@ pub fn Workspace() -> Element {
                     is_active: *active_tab.read() == ActiveTab::Variables,
                     onclick: move |_| active_tab.set(ActiveTab::Variables)
                 }
+
+                TabButton {
+                    label: "Metrics",
+                    is_active: *active_tab.read() == ActiveTab::Metrics,
+                    onclick: move |_| active_tab.set(ActiveTab::Metrics)
+                }
+
+                TabButton {
+                    label: "Security",
+                    is_active: *active_tab.read() == ActiveTab::Security,
+                    onclick: move |_| active_tab.set(ActiveTab::Security)
+                }
             }
 
             // Tab content
@@ -74,11 +319,20 @@ pub fn Workspace() -> Element {
                     ActiveTab::Schema => rsx! {
                         SchemaViewer {}
                     },
+                    ActiveTab::Compare => rsx! {
+                        SchemaComparePanel {}
+                    },
                     ActiveTab::History => rsx! {
                         HistoryWorkspace {}
                     },
                     ActiveTab::Variables => rsx! {
                         VariablesPanel {}
+                    },
+                    ActiveTab::Metrics => rsx! {
+                        MetricsPanel {}
+                    },
+                    ActiveTab::Security => rsx! {
+                        SecurityPanel {}
                     }
                 }
             }
@@ -99,74 +353,168 @@ fn TabButton(label: &'static str, is_active: bool, onclick: EventHandler<MouseEv
 
 #[component]
 fn QueryWorkspace() -> Element {
-    let mut app_state = use_context::<Signal<AppState>>();
+    let app_state = use_context::<Signal<AppState>>();
+    let mut editor_tabs = app_state.read().editor_tabs;
+    let active_tab_id = app_state.read().active_editor_tab;
+    let mut executions = app_state.read().tab_executions;
+    let query_text = app_state.read().query_text;
 
-    // Full cached result from the last query execution
-    let mut cached_result = use_signal(|| None::<crate::components::data_grid::QueryResult>);
-    let mut query_error = use_signal(|| None::<String>);
-    let mut is_executing = use_signal(|| false);
-    let mut current_page = use_signal(|| 1u32);
-    let mut original_query = use_signal(String::new);
+    let mut pending_save = use_signal(|| None::<Task>);
+
+    // Mirror the editor buffer into the active tab and persist it once typing
+    // pauses; switching, closing and running tabs save straight away
+    use_effect(move || {
+        let text = query_text.read().clone();
+        let id = *active_tab_id.read();
+        let changed = editor_tabs
+            .peek()
+            .iter()
+            .any(|t| t.id == id && t.query != text);
+        if changed {
+            if let Some(tab) = editor_tabs.write().iter_mut().find(|t| t.id == id) {
+                tab.query = text;
+            }
+            if let Some(task) = pending_save.take() {
+                task.cancel();
+            }
+            pending_save.set(Some(spawn_forever(async move {
+                tokio::time::sleep(SAVE_TABS_DELAY).await;
+                save_editor_tabs(&editor_tabs.peek(), *active_tab_id.peek());
+                pending_save.set(None);
+            })));
+        }
+    });
+
+    let is_executing = use_memo(move || {
+        executions
+            .read()
+            .get(&*active_tab_id.read())
+            .is_some_and(|e| e.is_running)
+    });
 
     let page_size = DEFAULT_PAGE_SIZE as usize;
+    let active_id = *active_tab_id.read();
+    let execution = executions
+        .read()
+        .get(&active_id)
+        .cloned()
+        .unwrap_or_default();
+    let current_page = execution.current_page.max(1);
+    let query_error = execution.error.clone();
 
     // Derive the current page slice from cache
-    let page_rows = {
-        let cached = cached_result.read();
-        if let Some(ref result) = *cached {
-            let start = ((*current_page.read() as usize) - 1) * page_size;
-            let end = (start + page_size).min(result.rows.len());
-            if start < result.rows.len() {
-                Some((
-                    result.columns.clone(),
-                    result.rows[start..end].to_vec(),
-                    result.execution_time_ms,
-                    result.row_count,
-                ))
-            } else {
-                Some((
-                    result.columns.clone(),
-                    vec![],
-                    result.execution_time_ms,
-                    result.row_count,
-                ))
-            }
+    let page_rows = execution.result.as_ref().map(|result| {
+        let start = ((current_page as usize) - 1) * page_size;
+        let end = (start + page_size).min(result.rows.len());
+        let rows = if start < result.rows.len() {
+            result.rows[start..end].to_vec()
         } else {
-            None
-        }
-    };
+            vec![]
+        };
+        (
+            result.columns.clone(),
+            rows,
+            result.execution_time_ms,
+            result.row_count,
+        )
+    });
 
-    let total_pages = {
-        let cached = cached_result.read();
-        cached
-            .as_ref()
-            .map(|r| {
-                let total = r.rows.len();
-                total.div_ceil(page_size).max(1) as u32
-            })
-            .unwrap_or(1)
+    let total_pages = execution
+        .result
+        .as_ref()
+        .map(|r| {
+            let total = r.rows.len();
+            total.div_ceil(page_size).max(1) as u32
+        })
+        .unwrap_or(1);
+
+    let mut set_page = move |page: u32| {
+        let id = *active_tab_id.peek();
+        if let Some(execution) = executions.write().get_mut(&id) {
+            execution.current_page = page;
+        }
     };
 
-    // Execute query: fetch all rows once and cache them
-    let mut run_query = move |query: String| {
-        original_query.set(query.clone());
-        current_page.set(1);
-        is_executing.set(true);
-        query_error.set(None);
-        cached_result.set(None);
+    // Execute query for the active tab: fetch all rows once and cache them.
+    // Runs detached from this component so other tabs (and other workspace
+    // views) stay usable while it is in flight.
+    let run_query = move |query: String| {
+        let tab_id = *active_tab_id.peek();
+        let (connection_id, keyspace) = editor_tabs
+            .peek()
+            .iter()
+            .find(|t| t.id == tab_id)
+            .map(|t| (t.connection_id, t.keyspace.clone()))
+            .unwrap_or_default();
 
         // Substitute variables before execution, keep original for history
         let vars = app_state.read().query_variables.read().clone();
         let substituted = substitute_variables(&query, &vars);
+        // A script of several statements runs them one by one
+        let statements = cql_tokenizer::split_statements(&substituted);
+        let is_script = statements.len() > 1;
+        let stop_on_error = *app_state.read().stop_on_error.peek();
+        save_editor_tabs(&editor_tabs.peek(), tab_id);
+
+        executions.write().insert(
+            tab_id,
+            QueryExecution {
+                is_running: true,
+                current_query: Some(query.clone()),
+                started_at: Some(chrono::Utc::now()),
+                current_page: 1,
+                script: if is_script {
+                    statements.iter().cloned().map(StatementRun::new).collect()
+                } else {
+                    Vec::new()
+                },
+                ..Default::default()
+            },
+        );
 
-        spawn(async move {
+        let task = spawn_forever(async move {
             let cm = app_state.read().connection_manager.clone();
             let console_log = app_state.read().console_log;
             let status_msg = app_state.read().status_message;
-            if let Some(connection) = cm.get_active_connection().await {
-                tracing::debug!("Executing query: {}", substituted);
-                AppState::console_push(console_log, status_msg, StatusLevel::Info, ConsoleCategory::Query, format!("Executing: {}", substituted));
-                match connection.execute_query(&substituted).await {
+            let connection = match connection_id {
+                Some(id) => cm.get_connection(id).await,
+                None => cm.get_active_connection().await,
+            };
+            // The keyspace the editor checked the statements against
+            let keyspace = match connection.as_ref() {
+                Some(connection) => connection.tab_keyspace(keyspace).await,
+                None => keyspace,
+            };
+
+            let outcome = if let Some(connection) = connection.as_ref().filter(|_| is_script) {
+                run_script(
+                    connection,
+                    app_state,
+                    tab_id,
+                    keyspace,
+                    &statements,
+                    stop_on_error,
+                    query,
+                )
+                .await
+            } else if let Some(connection) = connection {
+                tracing::debug!("Executing query: {}", redact_passwords(&substituted));
+                AppState::console_push(
+                    console_log,
+                    status_msg,
+                    StatusLevel::Info,
+                    ConsoleCategory::Query,
+                    format!("Executing: {}", redact_passwords(&substituted)),
+                );
+                let result = execute_statement(
+                    &connection,
+                    app_state,
+                    tab_id,
+                    keyspace.as_deref(),
+                    &substituted,
+                )
+                .await;
+                match result {
                     Ok(result) => {
                         let execution_time = result.execution_time_ms;
                         tracing::info!(
@@ -174,56 +522,129 @@ fn QueryWorkspace() -> Element {
                             result.row_count,
                             execution_time
                         );
-                        AppState::console_push(console_log, status_msg, StatusLevel::Success, ConsoleCategory::Query, format!("{} rows in {}ms", result.row_count, execution_time));
-                        cached_result.set(Some(result));
-
-                        // Store original query with placeholders in history
-                        let history_item = crate::state::QueryHistoryItem {
-                            id: uuid::Uuid::new_v4(),
-                            query: query.clone(),
-                            success: true,
-                            execution_time_ms: execution_time,
-                            executed_at: chrono::Utc::now(),
-                        };
-                        app_state.write().query_history.write().push(history_item);
+                        AppState::console_push(
+                            console_log,
+                            status_msg,
+                            StatusLevel::Success,
+                            ConsoleCategory::Query,
+                            format!("{} rows in {}ms", result.row_count, execution_time),
+                        );
+                        AppState::add_to_history(
+                            app_state.read().query_history,
+                            query.clone(),
+                            true,
+                            execution_time,
+                        );
+                        Ok(result)
                     }
                     Err(e) => {
                         let error_msg = format!("Query failed: {}", e);
                         tracing::error!("{}", error_msg);
-                        AppState::console_push(console_log, status_msg, StatusLevel::Error, ConsoleCategory::Query, error_msg.clone());
-                        query_error.set(Some(error_msg));
-
-                        let history_item = crate::state::QueryHistoryItem {
-                            id: uuid::Uuid::new_v4(),
-                            query: query.clone(),
-                            success: false,
-                            execution_time_ms: 0,
-                            executed_at: chrono::Utc::now(),
-                        };
-                        app_state.write().query_history.write().push(history_item);
+                        AppState::console_push(
+                            console_log,
+                            status_msg,
+                            StatusLevel::Error,
+                            ConsoleCategory::Query,
+                            error_msg,
+                        );
+                        AppState::add_to_history(
+                            app_state.read().query_history,
+                            query.clone(),
+                            false,
+                            0,
+                        );
+                        Err(ErrorReport::from(&e))
                     }
                 }
             } else {
-                let msg = "No active connection available".to_string();
-                AppState::console_push(console_log, status_msg, StatusLevel::Error, ConsoleCategory::Query, msg.clone());
-                query_error.set(Some(msg));
+                let msg = if connection_id.is_some() {
+                    "The connection bound to this tab is not connected".to_string()
+                } else {
+                    "No active connection available".to_string()
+                };
+                AppState::console_push(
+                    console_log,
+                    status_msg,
+                    StatusLevel::Error,
+                    ConsoleCategory::Query,
+                    msg.clone(),
+                );
+                Err(ErrorReport::plain("No connection", msg))
+            };
+
+            // The tab may have been closed while the query was running
+            if let Some(execution) = executions.write().get_mut(&tab_id) {
+                execution.is_running = false;
+                execution.task = None;
+                // A script statement may have been picked meanwhile
+                match outcome {
+                    Ok(result) => {
+                        execution.result = Some(result);
+                        execution.error = None;
+                    }
+                    Err(report) => {
+                        execution.result = None;
+                        execution.error = Some(report);
+                    }
+                }
             }
-            is_executing.set(false);
         });
+
+        if let Some(execution) = executions.write().get_mut(&tab_id) {
+            if execution.is_running {
+                execution.task = Some(task);
+            }
+        }
+    };
+
+    let cancel_query = move |_| {
+        let tab_id = *active_tab_id.peek();
+        let cancelled = match executions.write().get_mut(&tab_id) {
+            Some(execution) if execution.is_running => {
+                if let Some(task) = execution.task.take() {
+                    task.cancel();
+                }
+                execution.is_running = false;
+                for run in execution.script.iter_mut() {
+                    if matches!(run.state, StatementState::Pending | StatementState::Running) {
+                        run.state = StatementState::Skipped;
+                    }
+                }
+                execution.result = None;
+                execution.error = Some(ErrorReport::plain(
+                    "Cancelled",
+                    "The query was abandoned by the client. The server may still finish executing it.",
+                ));
+                true
+            }
+            _ => false,
+        };
+        if cancelled {
+            let console_log = app_state.read().console_log;
+            let status_msg = app_state.read().status_message;
+            AppState::console_push(
+                console_log,
+                status_msg,
+                StatusLevel::Info,
+                ConsoleCategory::Query,
+                "Query cancelled",
+            );
+        }
     };
 
     rsx! {
         div {
             class: "query-workspace",
 
-            // Query editor at the top
+            // Editor tabs and query editor at the top
             div {
                 class: "query-editor-container",
+                EditorTabBar {}
                 QueryEditor {
+                    key: "{active_id}",
                     is_executing: is_executing,
-                    on_execute: move |query: String| {
-                        run_query(query);
-                    }
+                    on_execute: run_query,
+                    on_cancel: cancel_query,
                 }
             }
 
@@ -231,12 +652,12 @@ fn QueryWorkspace() -> Element {
             div {
                 class: "query-results-container",
 
-                if let Some(error) = query_error.read().as_ref() {
-                    div {
-                        class: "query-error",
-                        div { class: "error-header", "Error" }
-                        div { class: "error-message", "{error}" }
-                    }
+                if !execution.script.is_empty() {
+                    ScriptResults { tab_id: active_id }
+                }
+
+                if let Some(report) = query_error {
+                    ErrorPanel { report }
                 } else if let Some((columns, rows, exec_time, total_rows)) = page_rows {
                     div {
                         class: "query-results",
@@ -249,7 +670,7 @@ fn QueryWorkspace() -> Element {
                                 if total_pages > 1 {
                                     span {
                                         class: "pagination-hint",
-                                        " (page {current_page.read()} of {total_pages})"
+                                        " (page {current_page} of {total_pages})"
                                     }
                                 }
                             }
@@ -259,7 +680,8 @@ fn QueryWorkspace() -> Element {
                                 button {
                                     class: "btn-small",
                                     onclick: move |_| {
-                                        if let Some(ref result) = *cached_result.read() {
+                                        let id = *active_tab_id.peek();
+                                        if let Some(result) = executions.peek().get(&id).and_then(|e| e.result.as_ref()) {
                                             let csv = super::data_grid::export_to_csv(result);
                                             spawn(async move {
                                                 if let Some(path) = rfd::AsyncFileDialog::new()
@@ -285,25 +707,23 @@ fn QueryWorkspace() -> Element {
 
                                     button {
                                         class: "btn btn-secondary",
-                                        disabled: *current_page.read() <= 1,
+                                        disabled: current_page <= 1,
                                         onclick: move |_| {
-                                            let p = *current_page.read();
-                                            if p > 1 { current_page.set(p - 1); }
+                                            if current_page > 1 { set_page(current_page - 1); }
                                         },
                                         "Previous"
                                     }
 
                                     span {
                                         class: "page-info",
-                                        "Page {current_page.read()} / {total_pages}"
+                                        "Page {current_page} / {total_pages}"
                                     }
 
                                     button {
                                         class: "btn btn-secondary",
-                                        disabled: *current_page.read() >= total_pages,
+                                        disabled: current_page >= total_pages,
                                         onclick: move |_| {
-                                            let p = *current_page.read();
-                                            if p < total_pages { current_page.set(p + 1); }
+                                            if current_page < total_pages { set_page(current_page + 1); }
                                         },
                                         "Next"
                                     }
@@ -355,7 +775,7 @@ fn QueryWorkspace() -> Element {
                             }
                         }
                     }
-                } else if *is_executing.read() {
+                } else if execution.is_running {
                     div {
                         class: "executing-indicator",
                         "Executing query..."
@@ -378,21 +798,52 @@ fn QueryWorkspace() -> Element {
 #[component]
 fn DataWorkspace() -> Element {
     let mut app_state = use_context::<Signal<AppState>>();
-    let selected_table = app_state.read().selected_table;
+    let mut selected_table = app_state.read().selected_table;
+    let mut data_keyspace = app_state.read().data_keyspace;
+    let mut keyspaces = use_signal(Vec::<String>::new);
+    let mut virtual_keyspaces = use_signal(Vec::<String>::new);
     let mut tables = use_signal(Vec::<String>::new);
 
+    // Load keyspaces on mount, defaulting to the connection's keyspace
     use_effect(move || {
+        let _ = app_state.read().schema_version.read();
         spawn(async move {
             let cm = app_state.read().connection_manager.clone();
             if let Some(conn) = cm.get_active_connection().await {
-                if let Some(keyspace) = conn.resolve_keyspace().await {
-                    match conn.list_tables(&keyspace).await {
-                        Ok(t) => tables.set(t),
-                        Err(e) => tracing::error!("Failed to load tables: {}", e),
-                    }
-                } else {
-                    tables.set(Vec::new());
+                match conn.list_keyspaces().await {
+                    Ok(ks) => keyspaces.set(ks),
+                    Err(e) => tracing::error!("Failed to load keyspaces: {}", e),
+                }
+                // Not in system_schema, and absent before Cassandra 4.0
+                let mut found: Vec<String> = conn
+                    .virtual_tables()
+                    .await
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|t| t.keyspace)
+                    .collect();
+                found.dedup();
+                virtual_keyspaces.set(found);
+                if data_keyspace.peek().is_none() {
+                    data_keyspace.set(conn.resolve_keyspace().await);
+                }
+            }
+        });
+    });
+
+    // Load tables whenever the browsed keyspace or the schema changes
+    use_effect(move || {
+        let keyspace = data_keyspace.read().clone();
+        let _ = app_state.read().schema_version.read();
+        spawn(async move {
+            let cm = app_state.read().connection_manager.clone();
+            if let (Some(conn), Some(keyspace)) = (cm.get_active_connection().await, keyspace) {
+                match conn.list_tables(&keyspace).await {
+                    Ok(t) => tables.set(t),
+                    Err(e) => tracing::error!("Failed to load tables: {}", e),
                 }
+            } else {
+                tables.set(Vec::new());
             }
         });
     });
@@ -408,6 +859,37 @@ fn DataWorkspace() -> Element {
                 div {
                     class: "table-selector",
 
+                    select {
+                        class: "select-keyspace",
+                        value: "{data_keyspace.read().as_deref().unwrap_or(\"\")}",
+                        onchange: move |e| {
+                            let value = if e.value().is_empty() { None } else { Some(e.value()) };
+                            selected_table.set(None);
+                            data_keyspace.set(value);
+                        },
+
+                        option { value: "", "Select a keyspace..." }
+
+                        for ks in keyspaces.read().iter() {
+                            option {
+                                value: "{ks}",
+                                "{ks}"
+                            }
+                        }
+
+                        if !virtual_keyspaces.read().is_empty() {
+                            optgroup {
+                                label: "Virtual",
+                                for ks in virtual_keyspaces.read().iter() {
+                                    option {
+                                        value: "{ks}",
+                                        "{ks}"
+                                    }
+                                }
+                            }
+                        }
+                    }
+
                     select {
                         class: "select-table",
                         value: "{selected_table.read().as_ref().unwrap_or(&String::new())}",
@@ -448,6 +930,7 @@ fn DataWorkspace() -> Element {
 
             // Data grid
             DataGrid {
+                keyspace: data_keyspace.read().clone(),
                 table_name: selected_table.read().clone()
             }
         }
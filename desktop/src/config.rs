@@ -6,7 +6,7 @@ use tracing::{debug, info};
 
 use crate::connection::ConnectionConfig;
 use crate::error::{MagdaError, Result};
-use crate::state::{QueryVariable, SavedQuery};
+use crate::state::{EditorTab, QueryVariable, SavedQuery};
 
 /// Application configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -206,6 +206,54 @@ pub fn save_saved_queries(queries: &[SavedQuery]) {
     }
 }
 
+/// Wrapper for TOML serialization of editor tabs
+#[derive(Debug, Serialize, Deserialize)]
+struct EditorTabsFile {
+    active: Option<uuid::Uuid>,
+    tabs: Vec<EditorTab>,
+}
+
+fn editor_tabs_file_path() -> Option<PathBuf> {
+    ProjectDirs::from("com", "magda", "Magda")
+        .map(|dirs| dirs.config_dir().join("editor_tabs.toml"))
+}
+
+/// Load editor tabs and the id of the last active tab from disk.
+pub fn load_editor_tabs() -> (Vec<EditorTab>, Option<uuid::Uuid>) {
+    let Some(path) = editor_tabs_file_path() else {
+        return (Vec::new(), None);
+    };
+    let Ok(content) = fs::read_to_string(&path) else {
+        return (Vec::new(), None);
+    };
+    toml::from_str::<EditorTabsFile>(&content)
+        .map(|f| (f.tabs, f.active))
+        .unwrap_or_default()
+}
+
+/// Save editor tabs to disk.
+pub fn save_editor_tabs(tabs: &[EditorTab], active: uuid::Uuid) {
+    let Some(path) = editor_tabs_file_path() else {
+        tracing::warn!("Could not determine config directory for editor tabs");
+        return;
+    };
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    let file = EditorTabsFile {
+        active: Some(active),
+        tabs: tabs.to_vec(),
+    };
+    match toml::to_string_pretty(&file) {
+        Ok(content) => {
+            if let Err(e) = fs::write(&path, content) {
+                tracing::warn!("Failed to save editor tabs: {}", e);
+            }
+        }
+        Err(e) => tracing::warn!("Failed to serialize editor tabs: {}", e),
+    }
+}
+
 /// User preferences
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserPreferences {
@@ -267,4 +315,23 @@ mod tests {
         assert_eq!(config.recent_queries.len(), 2);
         assert_eq!(config.recent_queries[0], "SELECT * FROM users");
     }
+
+    #[test]
+    fn test_editor_tabs_serialization() {
+        let mut tab = EditorTab::new("Query 1");
+        tab.query = "SELECT * FROM users;".to_string();
+        tab.keyspace = Some("app".to_string());
+        let file = EditorTabsFile {
+            active: Some(tab.id),
+            tabs: vec![tab.clone(), EditorTab::new("Query 2")],
+        };
+
+        let toml_str = toml::to_string_pretty(&file).unwrap();
+        let deserialized: EditorTabsFile = toml::from_str(&toml_str).unwrap();
+
+        assert_eq!(deserialized.active, Some(tab.id));
+        assert_eq!(deserialized.tabs.len(), 2);
+        assert_eq!(deserialized.tabs[0], tab);
+        assert_eq!(deserialized.tabs[1].connection_id, None);
+    }
 }
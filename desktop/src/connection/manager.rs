@@ -7,13 +7,40 @@ use uuid::Uuid;
 
 use super::{CassandraConnection, ConnectionConfig};
 use crate::error::{MagdaError, Result};
+use crate::identifier::Identifier;
+use crate::schema_snapshots::{snapshot_connection, SnapshotStore};
+
+/// Current format of the connections file. Version 1 stores keyspaces as
+/// Cassandra stores them; earlier files held them as typed CQL, where `MyKs`
+/// meant `myks`.
+const CONNECTIONS_VERSION: u32 = 1;
 
 /// Wrapper for TOML serialization (TOML requires a root table)
 #[derive(serde::Serialize, serde::Deserialize)]
 struct SavedConnections {
+    /// Format the file was written in; older files are migrated on load
+    #[serde(default)]
+    version: u32,
     connections: Vec<ConnectionConfig>,
 }
 
+impl SavedConnections {
+    /// Bring connections written by an older version up to date
+    fn migrate(&mut self) {
+        if self.version < 1 {
+            // Keyspaces were saved as typed and sent unquoted in `USE`
+            for connection in &mut self.connections {
+                if let Some(keyspace) = connection.keyspace.as_mut() {
+                    if let Ok(id) = Identifier::parse(keyspace) {
+                        *keyspace = id.as_str().to_string();
+                    }
+                }
+            }
+        }
+        self.version = CONNECTIONS_VERSION;
+    }
+}
+
 /// Get the path to the connections config file
 fn connections_file_path() -> Option<PathBuf> {
     directories::ProjectDirs::from("com", "magda", "Magda")
@@ -31,6 +58,7 @@ fn persist_configs(configs: &[ConnectionConfig]) {
     }
 
     let saved = SavedConnections {
+        version: CONNECTIONS_VERSION,
         connections: configs.to_vec(),
     };
     match toml::to_string_pretty(&saved) {
@@ -53,12 +81,20 @@ fn load_persisted_configs() -> Vec<ConnectionConfig> {
 
     match std::fs::read_to_string(&path) {
         Ok(content) => match toml::from_str::<SavedConnections>(&content) {
-            Ok(saved) => {
+            Ok(mut saved) => {
                 info!(
                     "Loaded {} saved connections from {:?}",
                     saved.connections.len(),
                     path
                 );
+                if saved.version < CONNECTIONS_VERSION {
+                    info!(
+                        "Migrating saved connections from version {} to {}",
+                        saved.version, CONNECTIONS_VERSION
+                    );
+                    saved.migrate();
+                    persist_configs(&saved.connections);
+                }
                 saved.connections
             }
             Err(e) => {
@@ -182,8 +218,9 @@ impl ConnectionManager {
         // Test the connection
         connection.test().await?;
 
+        let connection = Arc::new(connection);
         let mut connections = self.connections.write().await;
-        connections.insert(id, Arc::new(connection));
+        connections.insert(id, connection.clone());
 
         // Always set as active connection when connecting
         let mut active = self.active_connection_id.write().await;
@@ -191,6 +228,18 @@ impl ConnectionManager {
         debug!("Set {} as active connection", id);
 
         info!("Successfully connected to {}", config.name);
+
+        if config.snapshot_on_connect {
+            if let Some(store) = SnapshotStore::open() {
+                tokio::spawn(async move {
+                    match snapshot_connection(&store, &connection).await {
+                        Ok(Some(path)) => info!("Stored schema snapshot {:?}", path),
+                        Ok(None) => debug!("Schema unchanged since the last snapshot"),
+                        Err(e) => warn!("Failed to snapshot schema: {}", e),
+                    }
+                });
+            }
+        }
         Ok(())
     }
 
@@ -297,6 +346,37 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_legacy_keyspaces_are_migrated() {
+        // Files written before versioning have no `version` key
+        let mut connections = Vec::new();
+        for keyspace in ["MyKs", "\"Mixed\""] {
+            let mut connection = ConnectionConfig::new("local", "localhost");
+            connection.keyspace = Some(keyspace.to_string());
+            connections.push(connection);
+        }
+        let current = toml::to_string(&SavedConnections {
+            version: CONNECTIONS_VERSION,
+            connections,
+        })
+        .unwrap();
+        let unversioned: Vec<&str> = current
+            .lines()
+            .filter(|line| !line.starts_with("version"))
+            .collect();
+        let mut legacy: SavedConnections = toml::from_str(&unversioned.join("\n")).unwrap();
+        assert_eq!(legacy.version, 0);
+
+        legacy.migrate();
+        let keyspaces: Vec<_> = legacy
+            .connections
+            .iter()
+            .map(|c| c.keyspace.as_deref())
+            .collect();
+        assert_eq!(keyspaces, vec![Some("myks"), Some("Mixed")]);
+        assert_eq!(legacy.version, CONNECTIONS_VERSION);
+    }
+
     #[tokio::test]
     async fn test_connection_manager_config_operations() {
         let manager = new_in_memory();
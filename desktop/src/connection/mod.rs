@@ -4,8 +4,10 @@ use uuid::Uuid;
 use crate::error::{MagdaError, Result};
 
 pub mod manager;
+pub mod schema_cache;
 
 pub use manager::ConnectionManager;
+pub use schema_cache::SchemaCache;
 
 /// Connection configuration for a Cassandra cluster
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -22,6 +24,9 @@ pub struct ConnectionConfig {
     pub request_timeout_ms: u64,
     pub status_color: String,
     pub tag: String,
+    /// Store a schema snapshot every time this connection is opened
+    #[serde(default)]
+    pub snapshot_on_connect: bool,
 }
 
 impl ConnectionConfig {
@@ -40,6 +45,7 @@ impl ConnectionConfig {
             request_timeout_ms: 12000,
             status_color: "#808080".to_string(),
             tag: String::new(),
+            snapshot_on_connect: false,
         }
     }
 
@@ -80,6 +86,7 @@ pub struct CassandraConnection {
     pub config: ConnectionConfig,
     connected_at: chrono::DateTime<chrono::Utc>,
     session: Option<crate::cassandra::CassandraSession>,
+    schema: SchemaCache,
 }
 
 impl CassandraConnection {
@@ -88,7 +95,7 @@ impl CassandraConnection {
         config.validate()?;
 
         // Create session using our cassandra module
-        let session = crate::cassandra::create_session(
+        let mut session = crate::cassandra::create_session(
             &config.host,
             config.port,
             config.username.as_deref(),
@@ -98,9 +105,8 @@ impl CassandraConnection {
 
         // Set the active keyspace if configured
         if let Some(ref keyspace) = config.keyspace {
-            crate::cassandra::validate_cql_identifier(keyspace)?;
             tracing::info!("Setting active keyspace to: {}", keyspace);
-            session.query(&format!("USE {}", keyspace)).await?;
+            session.use_keyspace(keyspace).await?;
         }
 
         Ok(Self {
@@ -108,6 +114,7 @@ impl CassandraConnection {
             config: config.clone(),
             connected_at: chrono::Utc::now(),
             session: Some(session),
+            schema: SchemaCache::default(),
         })
     }
 
@@ -120,22 +127,43 @@ impl CassandraConnection {
         }
     }
 
-    /// List all keyspaces
+    /// List all keyspaces (cached)
     pub async fn list_keyspaces(&self) -> Result<Vec<String>> {
-        if let Some(ref session) = self.session {
-            crate::cassandra::list_keyspaces(session).await
-        } else {
-            Err(MagdaError::ConnectionError("No active session".to_string()))
-        }
+        let Some(ref session) = self.session else {
+            return Err(MagdaError::ConnectionError("No active session".to_string()));
+        };
+        self.schema
+            .keyspaces(|| crate::cassandra::list_keyspaces(session))
+            .await
     }
 
-    /// List all tables in a keyspace
+    /// List all tables in a keyspace. Names come from the cached schema when
+    /// it is loaded, otherwise from one `system_schema.tables` query.
     pub async fn list_tables(&self, keyspace: &str) -> Result<Vec<String>> {
-        if let Some(ref session) = self.session {
-            crate::cassandra::list_tables(session, keyspace).await
-        } else {
-            Err(MagdaError::ConnectionError("No active session".to_string()))
+        if crate::virtual_tables::is_virtual_keyspace(keyspace) {
+            let tables = self.virtual_tables().await?;
+            return Ok(tables
+                .into_iter()
+                .filter(|t| t.keyspace == keyspace)
+                .map(|t| t.name)
+                .collect());
+        }
+        if let Some(schema) = self.schema.cached(keyspace) {
+            return Ok(schema.tables.iter().map(|t| t.name.clone()).collect());
         }
+        let Some(ref session) = self.session else {
+            return Err(MagdaError::ConnectionError("No active session".to_string()));
+        };
+        crate::cassandra::list_tables(session, keyspace).await
+    }
+
+    /// Virtual tables of the cluster, read afresh each time as they are few.
+    /// Errors on Cassandra 3.x, which has no virtual tables.
+    pub async fn virtual_tables(&self) -> Result<Vec<crate::virtual_tables::VirtualTable>> {
+        let Some(ref session) = self.session else {
+            return Err(MagdaError::ConnectionError("No active session".to_string()));
+        };
+        crate::cassandra::list_virtual_tables(session).await
     }
 
     /// Resolve the keyspace to use: configured keyspace, or first non-system keyspace found.
@@ -152,28 +180,192 @@ impl CassandraConnection {
         }
     }
 
-    /// Describe a table's schema (columns, types, keys)
+    /// Keyspace an editor tab works in: its own, else [`resolve_keyspace`](Self::resolve_keyspace).
+    /// Running a tab's statements and checking them in the editor both go
+    /// through here so they agree on the keyspace.
+    pub async fn tab_keyspace(&self, tab_keyspace: Option<String>) -> Option<String> {
+        match tab_keyspace {
+            Some(keyspace) => Some(keyspace),
+            None => self.resolve_keyspace().await,
+        }
+    }
+
+    /// Describe a table's or materialized view's schema (columns, types, keys)
     pub async fn describe_table(
         &self,
         keyspace: &str,
         table: &str,
     ) -> Result<crate::cassandra::TableSchema> {
+        let schema = self.keyspace_schema(keyspace).await?;
+        let columns = match (schema.table(table), schema.view(table)) {
+            (Some(t), _) => t.columns.clone(),
+            (None, Some(v)) => v.columns.clone(),
+            (None, None) => {
+                return Err(MagdaError::SchemaError(format!(
+                    "Table '{}.{}' not found",
+                    keyspace, table
+                )))
+            }
+        };
+        Ok(crate::cassandra::TableSchema { columns })
+    }
+
+    /// Every schema object of a keyspace, from the cache when loaded before
+    pub async fn keyspace_schema(
+        &self,
+        keyspace: &str,
+    ) -> Result<std::sync::Arc<crate::schema::KeyspaceSchema>> {
+        let Some(ref session) = self.session else {
+            return Err(MagdaError::ConnectionError("No active session".to_string()));
+        };
+        self.schema
+            .keyspace(keyspace, || {
+                crate::cassandra::describe_keyspace(session, keyspace)
+            })
+            .await
+    }
+
+    /// Read every schema object of a keyspace (tables, types, indexes, views,
+    /// functions) straight from the cluster, refreshing the cached copy
+    pub async fn describe_keyspace(&self, keyspace: &str) -> Result<crate::schema::KeyspaceSchema> {
         if let Some(ref session) = self.session {
-            crate::cassandra::describe_table(session, keyspace, table).await
+            let schema = crate::cassandra::describe_keyspace(session, keyspace).await?;
+            self.schema.store(schema.clone());
+            Ok(schema)
         } else {
             Err(MagdaError::ConnectionError("No active session".to_string()))
         }
     }
 
+    /// Drop cached schema for `keyspace`, or all of it, so it is read again
+    pub fn refresh_schema(&self, keyspace: Option<&str>) {
+        tracing::debug!("Invalidating schema cache for {:?}", keyspace);
+        self.schema.invalidate(keyspace);
+    }
+
+    /// Read the schema of every keyspace, optionally leaving out system keyspaces
+    pub async fn describe_keyspaces(
+        &self,
+        include_system: bool,
+    ) -> Result<Vec<crate::schema::KeyspaceSchema>> {
+        let names = self.sorted_keyspaces(include_system).await?;
+        let mut schemas = Vec::with_capacity(names.len());
+        for keyspace in names {
+            schemas.push(self.describe_keyspace(&keyspace).await?);
+        }
+        Ok(schemas)
+    }
+
+    /// Schema of every keyspace, from the cache where it was loaded before
+    pub async fn keyspace_schemas(
+        &self,
+        include_system: bool,
+    ) -> Result<Vec<std::sync::Arc<crate::schema::KeyspaceSchema>>> {
+        let names = self.sorted_keyspaces(include_system).await?;
+        let mut schemas = Vec::with_capacity(names.len());
+        for keyspace in names {
+            schemas.push(self.keyspace_schema(&keyspace).await?);
+        }
+        Ok(schemas)
+    }
+
+    /// Keyspace names in order, optionally leaving out system keyspaces
+    async fn sorted_keyspaces(&self, include_system: bool) -> Result<Vec<String>> {
+        let mut names = self.list_keyspaces().await?;
+        names.retain(|ks| include_system || !crate::schema::is_system_keyspace(ks));
+        names.sort();
+        Ok(names)
+    }
+
+    /// Nodes per data center of the cluster
+    pub async fn datacenters(&self) -> Result<crate::keyspace_design::Topology> {
+        let Some(ref session) = self.session else {
+            return Err(MagdaError::ConnectionError("No active session".to_string()));
+        };
+        crate::cassandra::datacenters(session).await
+    }
+
+    /// Roles of the cluster; fails when authentication is disabled
+    pub async fn roles(&self) -> Result<Vec<crate::roles::Role>> {
+        let Some(ref session) = self.session else {
+            return Err(MagdaError::ConnectionError("No active session".to_string()));
+        };
+        crate::cassandra::list_roles(session).await
+    }
+
+    /// Permissions granted to every role; fails when authorization is disabled
+    pub async fn permissions(&self) -> Result<Vec<crate::roles::Grant>> {
+        let Some(ref session) = self.session else {
+            return Err(MagdaError::ConnectionError("No active session".to_string()));
+        };
+        crate::cassandra::list_permissions(session).await
+    }
+
+    /// Applied and pending migrations of `keyspace`
+    pub async fn migration_status(
+        &self,
+        keyspace: &str,
+        local: &[crate::migrations::Migration],
+    ) -> Result<Vec<crate::migrations::MigrationStatus>> {
+        let Some(ref session) = self.session else {
+            return Err(MagdaError::ConnectionError("No active session".to_string()));
+        };
+        let applied = crate::migrations::applied_migrations(session, keyspace).await?;
+        Ok(crate::migrations::status(local, &applied))
+    }
+
+    /// Apply the pending migrations of `local` to `keyspace`, in order
+    pub async fn apply_migrations(
+        &self,
+        keyspace: &str,
+        local: &[crate::migrations::Migration],
+    ) -> Result<crate::migrations::MigrationReport> {
+        let Some(ref session) = self.session else {
+            return Err(MagdaError::ConnectionError("No active session".to_string()));
+        };
+        let result = crate::migrations::apply_pending(session, keyspace, local).await;
+        // Even a failed run may have changed the schema
+        self.refresh_schema(None);
+        result
+    }
+
     /// Execute a CQL query and return results
     pub async fn execute_query(
         &self,
         query: &str,
     ) -> Result<crate::components::data_grid::QueryResult> {
+        self.execute_query_in(query, None).await
+    }
+
+    /// Execute a CQL query scoped to `keyspace` without changing the session's keyspace
+    pub async fn execute_query_in(
+        &self,
+        query: &str,
+        keyspace: Option<&str>,
+    ) -> Result<crate::components::data_grid::QueryResult> {
+        let Some(ref session) = self.session else {
+            return Err(MagdaError::ConnectionError("No active session".to_string()));
+        };
+        let result = crate::cassandra::execute_query_in(session, query, keyspace).await?;
+        // DDL may target any keyspace; everything is reloaded lazily
+        if crate::cql_tokenizer::is_schema_change(query) {
+            self.refresh_schema(None);
+        }
+        Ok(result)
+    }
+
+    /// Latency and traffic statistics collected since connecting
+    pub fn metrics(&self) -> crate::metrics::MetricsSnapshot {
+        self.session
+            .as_ref()
+            .map(|session| session.metrics())
+            .unwrap_or_default()
+    }
+
+    /// Clear the collected statistics
+    pub fn reset_metrics(&self) {
         if let Some(ref session) = self.session {
-            crate::cassandra::execute_query(session, query).await
-        } else {
-            Err(MagdaError::ConnectionError("No active session".to_string()))
+            session.reset_metrics();
         }
     }
 
@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::OnceCell;
+use tracing::debug;
+
+use crate::error::Result;
+use crate::schema::KeyspaceSchema;
+
+/// A value loaded at most once, shared by everyone waiting for it
+type Slot<T> = Arc<OnceCell<T>>;
+
+/// Per-connection cache of `system_schema`: the keyspace names and, for each
+/// keyspace read so far, its full definition. Entries load on first use;
+/// concurrent readers of a missing entry wait for a single load. Invalidated
+/// entries load again on next use.
+#[derive(Default)]
+pub struct SchemaCache {
+    keyspaces: Mutex<Slot<Vec<String>>>,
+    schemas: Mutex<HashMap<String, Slot<Arc<KeyspaceSchema>>>>,
+}
+
+impl SchemaCache {
+    /// Keyspace names, from `load` the first time
+    pub async fn keyspaces<F, Fut>(&self, load: F) -> Result<Vec<String>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Vec<String>>>,
+    {
+        let slot = match self.keyspaces.lock() {
+            Ok(slot) => slot.clone(),
+            Err(_) => return load().await,
+        };
+        slot.get_or_try_init(load).await.cloned()
+    }
+
+    /// Definition of `keyspace`, from `load` the first time
+    pub async fn keyspace<F, Fut>(&self, keyspace: &str, load: F) -> Result<Arc<KeyspaceSchema>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<KeyspaceSchema>>,
+    {
+        let slot = match self.schemas.lock() {
+            Ok(mut schemas) => schemas.entry(keyspace.to_string()).or_default().clone(),
+            Err(_) => return load().await.map(Arc::new),
+        };
+        slot.get_or_try_init(|| async {
+            debug!("Loading schema of keyspace {} into cache", keyspace);
+            load().await.map(Arc::new)
+        })
+        .await
+        .cloned()
+    }
+
+    /// Definition of `keyspace` if it is already loaded
+    pub fn cached(&self, keyspace: &str) -> Option<Arc<KeyspaceSchema>> {
+        let schemas = self.schemas.lock().ok()?;
+        schemas.get(keyspace)?.get().cloned()
+    }
+
+    /// Replace the cached definition with a freshly read one
+    pub fn store(&self, schema: KeyspaceSchema) -> Arc<KeyspaceSchema> {
+        let schema = Arc::new(schema);
+        if let Ok(mut schemas) = self.schemas.lock() {
+            schemas.insert(
+                schema.keyspace.name.clone(),
+                Arc::new(OnceCell::new_with(Some(schema.clone()))),
+            );
+        }
+        schema
+    }
+
+    /// Forget `keyspace`, or everything when `None`. The keyspace list is
+    /// always dropped, since the change may have created or dropped one.
+    pub fn invalidate(&self, keyspace: Option<&str>) {
+        if let Ok(mut keyspaces) = self.keyspaces.lock() {
+            *keyspaces = Slot::default();
+        }
+        if let Ok(mut schemas) = self.schemas.lock() {
+            match keyspace {
+                Some(keyspace) => {
+                    schemas.remove(keyspace);
+                }
+                None => schemas.clear(),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::test_fixtures::keyspace;
+
+    #[tokio::test]
+    async fn test_keyspace_loaded_once_until_invalidated() {
+        let cache = SchemaCache::default();
+        let loads = AtomicUsize::new(0);
+        let load = || async {
+            loads.fetch_add(1, Ordering::SeqCst);
+            Ok(keyspace("shop", Vec::new()))
+        };
+
+        let (a, b) = tokio::join!(cache.keyspace("shop", load), cache.keyspace("shop", load));
+        assert_eq!(a.unwrap().keyspace.name, "shop");
+        assert_eq!(b.unwrap().keyspace.name, "shop");
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+        assert!(cache.cached("shop").is_some());
+
+        cache.invalidate(Some("shop"));
+        assert!(cache.cached("shop").is_none());
+        cache.keyspace("shop", load).await.unwrap();
+        assert_eq!(loads.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_failed_load_is_not_cached() {
+        let cache = SchemaCache::default();
+        let failed = cache
+            .keyspaces(|| async { Err(crate::error::MagdaError::QueryError("timeout".into())) })
+            .await;
+        assert!(failed.is_err());
+
+        let names = cache
+            .keyspaces(|| async { Ok(vec!["shop".to_string()]) })
+            .await
+            .unwrap();
+        assert_eq!(names, vec!["shop"]);
+    }
+}
@@ -0,0 +1,767 @@
+//! Syntax tree of CQL statements.
+//!
+//! Nodes keep the byte [`Span`] of the source they were parsed from, so
+//! editor features can map them back to the text. Names hold their stored
+//! form (see [`Identifier`]); numbers keep their written text, as CQL
+//! integers may be arbitrarily large.
+
+use std::fmt;
+
+use crate::identifier::Identifier;
+
+/// Byte range `start..end` in the parsed source
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    /// From the start of `self` to the end of `other`
+    pub fn to(self, other: Span) -> Span {
+        Span::new(self.start, other.end.max(self.end))
+    }
+
+    /// Whether a cursor at `offset` touches the span, including its end
+    pub fn contains(&self, offset: usize) -> bool {
+        self.start <= offset && offset <= self.end
+    }
+}
+
+/// A name as written in the statement
+#[derive(Clone, Debug, PartialEq)]
+pub struct Name {
+    pub ident: Identifier,
+    pub span: Span,
+}
+
+impl Name {
+    /// The stored name, as compared against `system_schema`
+    pub fn as_str(&self) -> &str {
+        self.ident.as_str()
+    }
+
+    /// Whether the name is a `{{variable}}` filled in before execution
+    pub fn is_template(&self) -> bool {
+        self.as_str().starts_with("{{")
+    }
+}
+
+/// `keyspace.name`, or `name` in the session's keyspace
+#[derive(Clone, Debug, PartialEq)]
+pub struct QualifiedName {
+    pub keyspace: Option<Name>,
+    pub name: Name,
+}
+
+impl QualifiedName {
+    pub fn span(&self) -> Span {
+        match &self.keyspace {
+            Some(keyspace) => keyspace.span.to(self.name.span),
+            None => self.name.span,
+        }
+    }
+}
+
+impl fmt::Display for QualifiedName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.keyspace {
+            Some(keyspace) => write!(f, "{}.{}", keyspace.ident, self.name.ident),
+            None => write!(f, "{}", self.name.ident),
+        }
+    }
+}
+
+/// A column, field or parameter type
+#[derive(Clone, Debug, PartialEq)]
+pub enum CqlType {
+    /// Built-in type, lowercase, e.g. `text`
+    Native(String),
+    List(Box<CqlType>),
+    Set(Box<CqlType>),
+    Map(Box<CqlType>, Box<CqlType>),
+    Tuple(Vec<CqlType>),
+    Frozen(Box<CqlType>),
+    Vector(Box<CqlType>, usize),
+    UserDefined(QualifiedName),
+    /// A Java class name given as a string
+    Custom(String),
+}
+
+/// Written the way `system_schema` stores types, e.g. `map<text, int>`
+impl fmt::Display for CqlType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |types: &[&CqlType]| {
+            types
+                .iter()
+                .map(|t| t.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match self {
+            CqlType::Native(name) => f.write_str(name),
+            CqlType::List(item) => write!(f, "list<{}>", item),
+            CqlType::Set(item) => write!(f, "set<{}>", item),
+            CqlType::Map(key, value) => write!(f, "map<{}>", join(&[key, value])),
+            CqlType::Tuple(items) => {
+                write!(f, "tuple<{}>", join(&items.iter().collect::<Vec<_>>()))
+            }
+            CqlType::Frozen(inner) => write!(f, "frozen<{}>", inner),
+            CqlType::Vector(item, size) => write!(f, "vector<{}, {}>", item, size),
+            CqlType::UserDefined(name) => write!(f, "{}", name.name.ident),
+            CqlType::Custom(class) => write!(f, "'{}'", class),
+        }
+    }
+}
+
+/// A constant
+#[derive(Clone, Debug, PartialEq)]
+pub enum Literal {
+    /// String contents with `''` unescaped; also `$$` bodies
+    String(String),
+    Integer(String),
+    Float(String),
+    Boolean(bool),
+    Uuid(String),
+    /// `0x...`
+    Blob(String),
+    /// `1h30m`
+    Duration(String),
+    NaN,
+    Infinity,
+    Null,
+}
+
+/// Arithmetic between terms; `+` and `-` also add to and remove from
+/// collections
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArithOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+}
+
+/// A value or an expression producing one
+#[derive(Clone, Debug, PartialEq)]
+pub enum Term {
+    Literal {
+        value: Literal,
+        span: Span,
+    },
+    /// `?` or `:name`
+    BindMarker {
+        name: Option<Name>,
+        span: Span,
+    },
+    /// `{{name}}`, filled in before execution
+    Variable {
+        name: String,
+        span: Span,
+    },
+    Column(Name),
+    FunctionCall {
+        name: QualifiedName,
+        args: Vec<Term>,
+        span: Span,
+    },
+    /// The `*` in `COUNT(*)`
+    Wildcard(Span),
+    List {
+        items: Vec<Term>,
+        span: Span,
+    },
+    Set {
+        items: Vec<Term>,
+        span: Span,
+    },
+    Map {
+        entries: Vec<(Term, Term)>,
+        span: Span,
+    },
+    /// `(a, b)`, also the value list of `IN (...)`
+    Tuple {
+        items: Vec<Term>,
+        span: Span,
+    },
+    /// `{street: 'x', zip: 1}`
+    UserType {
+        fields: Vec<(Name, Term)>,
+        span: Span,
+    },
+    /// `CAST(term AS type)`
+    Cast {
+        term: Box<Term>,
+        cql_type: CqlType,
+        span: Span,
+    },
+    /// `(type) term`
+    TypeHint {
+        cql_type: CqlType,
+        term: Box<Term>,
+        span: Span,
+    },
+    Binary {
+        op: ArithOp,
+        left: Box<Term>,
+        right: Box<Term>,
+    },
+    /// `base[key]`, a map value or list element
+    Element {
+        base: Box<Term>,
+        key: Box<Term>,
+        span: Span,
+    },
+    /// `base.field` of a user type
+    Field {
+        base: Box<Term>,
+        field: Name,
+    },
+}
+
+impl Term {
+    pub fn span(&self) -> Span {
+        match self {
+            Term::Literal { span, .. }
+            | Term::BindMarker { span, .. }
+            | Term::Variable { span, .. }
+            | Term::FunctionCall { span, .. }
+            | Term::Wildcard(span)
+            | Term::List { span, .. }
+            | Term::Set { span, .. }
+            | Term::Map { span, .. }
+            | Term::Tuple { span, .. }
+            | Term::UserType { span, .. }
+            | Term::Cast { span, .. }
+            | Term::TypeHint { span, .. }
+            | Term::Element { span, .. } => *span,
+            Term::Column(name) => name.span,
+            Term::Binary { left, right, .. } => left.span().to(right.span()),
+            Term::Field { base, field } => base.span().to(field.span),
+        }
+    }
+
+    /// The column a term refers to, through element and field access
+    pub fn column(&self) -> Option<&Name> {
+        match self {
+            Term::Column(name) => Some(name),
+            Term::Element { base, .. } | Term::Field { base, .. } => base.column(),
+            _ => None,
+        }
+    }
+}
+
+/// Comparison in a `WHERE` or `IF` clause
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    In,
+    Contains,
+    ContainsKey,
+    Like,
+    IsNotNull,
+}
+
+impl Operator {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Operator::Eq => "=",
+            Operator::Ne => "!=",
+            Operator::Lt => "<",
+            Operator::Le => "<=",
+            Operator::Gt => ">",
+            Operator::Ge => ">=",
+            Operator::In => "IN",
+            Operator::Contains => "CONTAINS",
+            Operator::ContainsKey => "CONTAINS KEY",
+            Operator::Like => "LIKE",
+            Operator::IsNotNull => "IS NOT NULL",
+        }
+    }
+}
+
+/// `left op right`; `right` is absent for `IS NOT NULL`. `left` is a column
+/// (possibly with element or field access), a tuple of columns or `TOKEN(...)`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Relation {
+    pub left: Term,
+    pub op: Operator,
+    pub right: Option<Term>,
+    pub span: Span,
+}
+
+/// The `IF` clause of a conditional write
+#[derive(Clone, Debug, PartialEq)]
+pub enum Condition {
+    Exists(Span),
+    NotExists(Span),
+    Relations(Vec<Relation>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum UsingOption {
+    Ttl(Term),
+    Timestamp(Term),
+    Timeout(Term),
+}
+
+/// `column [ASC | DESC]`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Ordering {
+    pub column: Name,
+    pub descending: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Selector {
+    pub term: Term,
+    pub alias: Option<Name>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Selection {
+    /// `*`
+    All(Span),
+    Columns(Vec<Selector>),
+}
+
+/// Empty selection, left by a selection that failed to parse
+impl Default for Selection {
+    fn default() -> Self {
+        Selection::Columns(Vec::new())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SelectStatement {
+    pub json: bool,
+    pub distinct: bool,
+    pub selection: Selection,
+    pub table: QualifiedName,
+    pub where_clause: Vec<Relation>,
+    pub group_by: Vec<Name>,
+    pub order_by: Vec<Ordering>,
+    pub per_partition_limit: Option<Term>,
+    pub limit: Option<Term>,
+    pub allow_filtering: bool,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum InsertValues {
+    /// `(a, b) VALUES (1, 2)`
+    Columns {
+        columns: Vec<Name>,
+        values: Vec<Term>,
+    },
+    /// `JSON '{...}' [DEFAULT UNSET]`
+    Json { value: Term, default_unset: bool },
+}
+
+impl Default for InsertValues {
+    fn default() -> Self {
+        InsertValues::Columns {
+            columns: Vec::new(),
+            values: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct InsertStatement {
+    pub table: QualifiedName,
+    pub values: InsertValues,
+    pub if_not_exists: bool,
+    pub using: Vec<UsingOption>,
+    pub span: Span,
+}
+
+/// How an assignment changes the column
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AssignOp {
+    /// `c = v`
+    Set,
+    /// `c = c + v` or `c += v`: counter increment or collection append
+    Add,
+    /// `c = v + c`: list prepend
+    Prepend,
+    /// `c = c - v` or `c -= v`
+    Subtract,
+}
+
+/// One `SET` item: `column[key] = value` or `column.field = value` among
+/// the plain forms
+#[derive(Clone, Debug, PartialEq)]
+pub struct Assignment {
+    pub column: Name,
+    pub key: Option<Term>,
+    pub field: Option<Name>,
+    pub op: AssignOp,
+    pub value: Term,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct UpdateStatement {
+    pub table: QualifiedName,
+    pub using: Vec<UsingOption>,
+    pub assignments: Vec<Assignment>,
+    pub where_clause: Vec<Relation>,
+    pub condition: Option<Condition>,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeleteStatement {
+    /// Columns (or elements and fields) to delete; empty for whole rows
+    pub columns: Vec<Term>,
+    pub table: QualifiedName,
+    pub using: Vec<UsingOption>,
+    pub where_clause: Vec<Relation>,
+    pub condition: Option<Condition>,
+    pub span: Span,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BatchKind {
+    Logged,
+    Unlogged,
+    Counter,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BatchStatement {
+    pub kind: BatchKind,
+    pub using: Vec<UsingOption>,
+    pub statements: Vec<Statement>,
+    pub span: Span,
+}
+
+/// `name = value` in a `WITH` clause
+#[derive(Clone, Debug, PartialEq)]
+pub struct Property {
+    pub name: Name,
+    pub value: Term,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TableOption {
+    Property(Property),
+    ClusteringOrder(Vec<Ordering>),
+    CompactStorage,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColumnDefinition {
+    pub name: Name,
+    pub cql_type: CqlType,
+    pub is_static: bool,
+    /// Declared with an inline `PRIMARY KEY`
+    pub primary_key: bool,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PrimaryKey {
+    pub partition_key: Vec<Name>,
+    pub clustering: Vec<Name>,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CreateKeyspace {
+    pub name: Name,
+    pub if_not_exists: bool,
+    pub options: Vec<Property>,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AlterKeyspace {
+    pub name: Name,
+    pub if_exists: bool,
+    pub options: Vec<Property>,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CreateTable {
+    pub name: QualifiedName,
+    pub if_not_exists: bool,
+    pub columns: Vec<ColumnDefinition>,
+    /// A separate `PRIMARY KEY (...)` definition
+    pub primary_key: Option<PrimaryKey>,
+    pub options: Vec<TableOption>,
+    pub span: Span,
+}
+
+impl CreateTable {
+    /// Partition key and clustering columns, from the `PRIMARY KEY`
+    /// definition or a column declared `PRIMARY KEY`
+    pub fn key_columns(&self) -> (Vec<&Name>, Vec<&Name>) {
+        match &self.primary_key {
+            Some(key) => (
+                key.partition_key.iter().collect(),
+                key.clustering.iter().collect(),
+            ),
+            None => (
+                self.columns
+                    .iter()
+                    .filter(|c| c.primary_key)
+                    .map(|c| &c.name)
+                    .collect(),
+                Vec::new(),
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum AlterTableAction {
+    Add(Vec<ColumnDefinition>),
+    Drop(Vec<Name>),
+    Rename(Vec<(Name, Name)>),
+    AlterType(Name, CqlType),
+    With(Vec<TableOption>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AlterTable {
+    pub name: QualifiedName,
+    pub if_exists: bool,
+    pub action: AlterTableAction,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CreateType {
+    pub name: QualifiedName,
+    pub if_not_exists: bool,
+    pub fields: Vec<(Name, CqlType)>,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum AlterTypeAction {
+    Add(Name, CqlType),
+    Rename(Vec<(Name, Name)>),
+    AlterType(Name, CqlType),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AlterType {
+    pub name: QualifiedName,
+    pub if_exists: bool,
+    pub action: AlterTypeAction,
+    pub span: Span,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IndexTargetKind {
+    Values,
+    Keys,
+    Entries,
+    Full,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CreateIndex {
+    pub name: Option<Name>,
+    pub if_not_exists: bool,
+    pub custom: bool,
+    pub table: QualifiedName,
+    pub column: Name,
+    /// `KEYS(c)` and the like; `Values` for a bare column
+    pub target: IndexTargetKind,
+    /// Index class after `USING`, e.g. `sai`
+    pub using: Option<String>,
+    pub options: Vec<Property>,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CreateMaterializedView {
+    pub name: QualifiedName,
+    pub if_not_exists: bool,
+    pub selection: Selection,
+    pub base_table: QualifiedName,
+    pub where_clause: Vec<Relation>,
+    pub primary_key: PrimaryKey,
+    pub options: Vec<TableOption>,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AlterMaterializedView {
+    pub name: QualifiedName,
+    pub if_exists: bool,
+    pub options: Vec<TableOption>,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CreateFunction {
+    pub or_replace: bool,
+    pub if_not_exists: bool,
+    pub name: QualifiedName,
+    pub params: Vec<(Name, CqlType)>,
+    pub called_on_null_input: bool,
+    pub return_type: CqlType,
+    pub language: String,
+    pub body: String,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CreateAggregate {
+    pub or_replace: bool,
+    pub if_not_exists: bool,
+    pub name: QualifiedName,
+    pub arg_types: Vec<CqlType>,
+    pub state_function: Name,
+    pub state_type: CqlType,
+    pub final_function: Option<Name>,
+    pub initial_condition: Option<Term>,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CreateTrigger {
+    pub name: Name,
+    pub if_not_exists: bool,
+    pub table: QualifiedName,
+    pub class: String,
+    pub span: Span,
+}
+
+/// What a `DROP` statement removes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ObjectKind {
+    Keyspace,
+    Table,
+    Type,
+    Index,
+    MaterializedView,
+    Function,
+    Aggregate,
+    Trigger,
+}
+
+impl ObjectKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ObjectKind::Keyspace => "keyspace",
+            ObjectKind::Table => "table",
+            ObjectKind::Type => "type",
+            ObjectKind::Index => "index",
+            ObjectKind::MaterializedView => "materialized view",
+            ObjectKind::Function => "function",
+            ObjectKind::Aggregate => "aggregate",
+            ObjectKind::Trigger => "trigger",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DropStatement {
+    pub kind: ObjectKind,
+    pub name: QualifiedName,
+    pub if_exists: bool,
+    /// Argument types picking one overload of a function or aggregate
+    pub signature: Option<Vec<CqlType>>,
+    /// The table of a trigger
+    pub table: Option<QualifiedName>,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Statement {
+    Select(SelectStatement),
+    Insert(InsertStatement),
+    Update(UpdateStatement),
+    Delete(DeleteStatement),
+    Batch(BatchStatement),
+    Use {
+        keyspace: Name,
+        span: Span,
+    },
+    Truncate {
+        table: QualifiedName,
+        span: Span,
+    },
+    CreateKeyspace(CreateKeyspace),
+    AlterKeyspace(AlterKeyspace),
+    CreateTable(CreateTable),
+    AlterTable(AlterTable),
+    CreateType(CreateType),
+    AlterType(AlterType),
+    CreateIndex(CreateIndex),
+    CreateMaterializedView(CreateMaterializedView),
+    AlterMaterializedView(AlterMaterializedView),
+    CreateFunction(CreateFunction),
+    CreateAggregate(CreateAggregate),
+    CreateTrigger(CreateTrigger),
+    Drop(DropStatement),
+    /// Statements kept as text: roles, permissions, `DESCRIBE`, `LIST`.
+    /// `keyword` is the leading keyword, uppercase.
+    Other {
+        keyword: String,
+        span: Span,
+    },
+}
+
+impl Statement {
+    pub fn span(&self) -> Span {
+        match self {
+            Statement::Select(s) => s.span,
+            Statement::Insert(s) => s.span,
+            Statement::Update(s) => s.span,
+            Statement::Delete(s) => s.span,
+            Statement::Batch(s) => s.span,
+            Statement::Use { span, .. }
+            | Statement::Truncate { span, .. }
+            | Statement::Other { span, .. } => *span,
+            Statement::CreateKeyspace(s) => s.span,
+            Statement::AlterKeyspace(s) => s.span,
+            Statement::CreateTable(s) => s.span,
+            Statement::AlterTable(s) => s.span,
+            Statement::CreateType(s) => s.span,
+            Statement::AlterType(s) => s.span,
+            Statement::CreateIndex(s) => s.span,
+            Statement::CreateMaterializedView(s) => s.span,
+            Statement::AlterMaterializedView(s) => s.span,
+            Statement::CreateFunction(s) => s.span,
+            Statement::CreateAggregate(s) => s.span,
+            Statement::CreateTrigger(s) => s.span,
+            Statement::Drop(s) => s.span,
+        }
+    }
+
+    /// The table a data statement reads or writes, or a schema statement
+    /// creates or changes
+    pub fn table(&self) -> Option<&QualifiedName> {
+        match self {
+            Statement::Select(s) => Some(&s.table),
+            Statement::Insert(s) => Some(&s.table),
+            Statement::Update(s) => Some(&s.table),
+            Statement::Delete(s) => Some(&s.table),
+            Statement::Truncate { table, .. } => Some(table),
+            Statement::CreateTable(s) => Some(&s.name),
+            Statement::AlterTable(s) => Some(&s.name),
+            Statement::CreateIndex(s) => Some(&s.table),
+            Statement::CreateMaterializedView(s) => Some(&s.base_table),
+            Statement::Drop(s) if s.kind == ObjectKind::Table => Some(&s.name),
+            _ => None,
+        }
+    }
+}
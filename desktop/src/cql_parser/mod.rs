@@ -0,0 +1,360 @@
+//! CQL parser producing a typed syntax tree.
+//!
+//! Covers the data statements (`SELECT`, `INSERT`, `UPDATE`, `DELETE`,
+//! `BATCH`) and schema statements for keyspaces, tables, types, indexes,
+//! materialized views, functions, aggregates and triggers. Role, permission
+//! and `DESCRIBE` statements are recognised but kept as text.
+//!
+//! Parsing never stops at the first error: a broken clause is skipped up to
+//! the next clause keyword and a broken statement up to its `;`, so an
+//! editor gets every error of a script and the structure around them.
+
+mod ast;
+mod parser;
+
+use std::fmt;
+
+pub use ast::*;
+pub use parser::NATIVE_TYPES;
+
+/// A syntax error at a byte range of the source
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    pub message: String,
+    pub span: Span,
+}
+
+impl ParseError {
+    pub fn new(message: impl Into<String>, span: Span) -> Self {
+        Self {
+            message: message.into(),
+            span,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Statements of a script and the syntax errors found in it. A statement
+/// with errors in some clauses is kept with those clauses left empty.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ParsedScript {
+    pub statements: Vec<Statement>,
+    pub errors: Vec<ParseError>,
+}
+
+impl ParsedScript {
+    /// The statement under a cursor at byte `offset`
+    pub fn statement_at(&self, offset: usize) -> Option<&Statement> {
+        self.statements.iter().find(|s| s.span().contains(offset))
+    }
+}
+
+/// Parse every statement of `source`
+pub fn parse(source: &str) -> ParsedScript {
+    let (statements, errors) = parser::Parser::new(source).parse_script();
+    ParsedScript { statements, errors }
+}
+
+/// Parse `source` as exactly one statement, failing on its first error
+pub fn parse_statement(source: &str) -> Result<Statement, ParseError> {
+    let ParsedScript {
+        mut statements,
+        errors,
+    } = parse(source);
+    if let Some(error) = errors.into_iter().next() {
+        return Err(error);
+    }
+    match statements.len() {
+        0 => Err(ParseError::new(
+            "Expected a statement",
+            Span::new(source.len(), source.len()),
+        )),
+        1 => Ok(statements.remove(0)),
+        _ => Err(ParseError::new(
+            "Expected a single statement",
+            statements[1].span(),
+        )),
+    }
+}
+
+/// Parse a type as written in a column definition or stored in
+/// `system_schema`, e.g. `frozen<map<text, int>>`
+pub fn parse_type(source: &str) -> Result<CqlType, ParseError> {
+    parser::Parser::new(source).parse_type()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cql_tokenizer::split_statements;
+
+    fn select(source: &str) -> SelectStatement {
+        match parse_statement(source) {
+            Ok(Statement::Select(select)) => select,
+            other => panic!("not a SELECT: {:?}", other),
+        }
+    }
+
+    fn text(source: &str, span: Span) -> &str {
+        &source[span.start..span.end]
+    }
+
+    #[test]
+    fn test_corpus_parses_without_errors() {
+        let corpus = include_str!("corpus.cql");
+        let parsed = parse(corpus);
+        if let Some(error) = parsed.errors.first() {
+            let line = corpus[..error.span.start].lines().count();
+            panic!("line {}: {}", line, error.message);
+        }
+        assert_eq!(parsed.statements.len(), split_statements(corpus).len());
+        for (statement, expected) in parsed.statements.iter().zip(split_statements(corpus)) {
+            assert_eq!(text(corpus, statement.span()), expected);
+        }
+    }
+
+    #[test]
+    fn test_select_structure_and_spans() {
+        let source = "SELECT id, \"Total\" AS t, COUNT(*) FROM shop.orders \
+                      WHERE tenant = ? AND day IN ('a', 'b') AND ts >= -5 \
+                      ORDER BY ts DESC LIMIT 10 ALLOW FILTERING";
+        let select = select(source);
+        assert_eq!(select.table.to_string(), "shop.orders");
+        assert_eq!(text(source, select.table.span()), "shop.orders");
+
+        let Selection::Columns(selectors) = &select.selection else {
+            panic!("expected columns");
+        };
+        assert_eq!(selectors.len(), 3);
+        assert_eq!(selectors[1].term.column().unwrap().as_str(), "Total");
+        assert_eq!(selectors[1].alias.as_ref().unwrap().as_str(), "t");
+        assert!(matches!(
+            &selectors[2].term,
+            Term::FunctionCall { args, .. } if matches!(args[..], [Term::Wildcard(_)])
+        ));
+
+        let ops: Vec<Operator> = select.where_clause.iter().map(|r| r.op).collect();
+        assert_eq!(ops, vec![Operator::Eq, Operator::In, Operator::Ge]);
+        assert_eq!(
+            text(source, select.where_clause[1].span),
+            "day IN ('a', 'b')"
+        );
+        assert_eq!(
+            select.where_clause[2].right,
+            Some(Term::Literal {
+                value: Literal::Integer("-5".to_string()),
+                span: Span::new(source.find("-5").unwrap(), source.find("-5").unwrap() + 2),
+            })
+        );
+        assert!(select.order_by[0].descending);
+        assert!(select.allow_filtering);
+        assert_eq!(text(source, select.span), source);
+    }
+
+    #[test]
+    fn test_update_assignments() {
+        let source = "UPDATE t USING TTL 60 SET n = n + 1, tags = tags - {'x'}, \
+                      log = ['a'] + log, m['k'] = 'v', addr.zip = 1, total = 2 * 3 \
+                      WHERE id = 1 IF EXISTS";
+        let Ok(Statement::Update(update)) = parse_statement(source) else {
+            panic!("not an UPDATE");
+        };
+        let ops: Vec<AssignOp> = update.assignments.iter().map(|a| a.op).collect();
+        assert_eq!(
+            ops,
+            vec![
+                AssignOp::Add,
+                AssignOp::Subtract,
+                AssignOp::Prepend,
+                AssignOp::Set,
+                AssignOp::Set,
+                AssignOp::Set
+            ]
+        );
+        assert!(update.assignments[3].key.is_some());
+        assert_eq!(
+            update.assignments[4].field.as_ref().unwrap().as_str(),
+            "zip"
+        );
+        assert!(matches!(update.using[..], [UsingOption::Ttl(_)]));
+        assert!(matches!(update.condition, Some(Condition::Exists(_))));
+    }
+
+    #[test]
+    fn test_create_table_and_types() {
+        let source = "CREATE TABLE IF NOT EXISTS shop.events (\
+                      tenant uuid, day date, ts timeuuid, \"Payload\" map<text, frozen<list<int>>>, \
+                      owner frozen<shop.person>, emb vector<float, 3>, note text static, \
+                      PRIMARY KEY ((tenant, day), ts)) \
+                      WITH CLUSTERING ORDER BY (ts DESC) AND comment = 'x'";
+        let Ok(Statement::CreateTable(table)) = parse_statement(source) else {
+            panic!("not a CREATE TABLE");
+        };
+        assert!(table.if_not_exists);
+        let types: Vec<String> = table
+            .columns
+            .iter()
+            .map(|c| c.cql_type.to_string())
+            .collect();
+        assert_eq!(
+            types,
+            vec![
+                "uuid",
+                "date",
+                "timeuuid",
+                "map<text, frozen<list<int>>>",
+                "frozen<person>",
+                "vector<float, 3>",
+                "text"
+            ]
+        );
+        assert_eq!(table.columns[3].name.as_str(), "Payload");
+        assert!(table.columns[6].is_static);
+        let (partition, clustering) = table.key_columns();
+        assert_eq!(
+            partition.iter().map(|n| n.as_str()).collect::<Vec<_>>(),
+            vec!["tenant", "day"]
+        );
+        assert_eq!(clustering[0].as_str(), "ts");
+        assert!(matches!(
+            &table.options[..],
+            [TableOption::ClusteringOrder(order), TableOption::Property(_)] if order[0].descending
+        ));
+
+        let Ok(Statement::CreateTable(inline)) =
+            parse_statement("CREATE TABLE users (id uuid PRIMARY KEY, name text)")
+        else {
+            panic!("not a CREATE TABLE");
+        };
+        assert_eq!(inline.key_columns().0[0].as_str(), "id");
+
+        assert_eq!(
+            parse_type("frozen<map<text, list<int>>>").map(|t| t.to_string()),
+            Ok("frozen<map<text, list<int>>>".to_string())
+        );
+        assert!(parse_type("list<int> x").is_err());
+        assert!(parse_type("vector<float, 0>").is_err());
+    }
+
+    #[test]
+    fn test_batch_and_literals() {
+        let source = "BEGIN UNLOGGED BATCH USING TIMESTAMP 1\n\
+                      INSERT INTO t (id, at, raw, d, f, u) VALUES \
+                      (123e4567-e89b-12d3-a456-426614174000, '2024-01-01', 0xcafe, 1h30m, 1.5e3, {name: 'x'});\n\
+                      DELETE tags['a'] FROM t WHERE id = :id;\n\
+                      APPLY BATCH";
+        let Ok(Statement::Batch(batch)) = parse_statement(source) else {
+            panic!("not a BATCH");
+        };
+        assert_eq!(batch.kind, BatchKind::Unlogged);
+        assert_eq!(batch.statements.len(), 2);
+        let Statement::Insert(insert) = &batch.statements[0] else {
+            panic!("not an INSERT");
+        };
+        let InsertValues::Columns { values, .. } = &insert.values else {
+            panic!("not a column list");
+        };
+        let literals: Vec<&Literal> = values
+            .iter()
+            .filter_map(|v| match v {
+                Term::Literal { value, .. } => Some(value),
+                _ => None,
+            })
+            .collect();
+        assert!(matches!(
+            literals[..],
+            [
+                Literal::Uuid(_),
+                Literal::String(_),
+                Literal::Blob(_),
+                Literal::Duration(_),
+                Literal::Float(_)
+            ]
+        ));
+        assert!(matches!(values[5], Term::UserType { .. }));
+        let Statement::Delete(delete) = &batch.statements[1] else {
+            panic!("not a DELETE");
+        };
+        assert!(matches!(delete.columns[0], Term::Element { .. }));
+        assert!(matches!(
+            &delete.where_clause[0].right,
+            Some(Term::BindMarker { name: Some(n), .. }) if n.as_str() == "id"
+        ));
+    }
+
+    #[test]
+    fn test_errors_are_recovered() {
+        let source = "SELECT a, FROM users WHERE id = 1 LIMIT 5;\n\
+                      SELEC * FROM t;\n\
+                      INSERT INTO users (id) VALUES (1;\n\
+                      UPDATE users SET name = 'x' WHERE;\n\
+                      DELETE FROM users WHERE id = 1";
+        let parsed = parse(source);
+        let messages: Vec<&str> = parsed.errors.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Expected a value, found `FROM`",
+                "Unknown statement `SELEC`",
+                "Expected `)`, found end of statement",
+                "Expected a column name, found end of statement",
+            ]
+        );
+        assert_eq!(text(source, parsed.errors[1].span), "SELEC");
+
+        // The statements around the errors keep their structure
+        let kinds: Vec<&str> = parsed
+            .statements
+            .iter()
+            .map(|s| match s {
+                Statement::Select(_) => "select",
+                Statement::Insert(_) => "insert",
+                Statement::Update(_) => "update",
+                Statement::Delete(_) => "delete",
+                _ => "other",
+            })
+            .collect();
+        assert_eq!(kinds, vec!["select", "insert", "update", "delete"]);
+        let Statement::Select(first) = &parsed.statements[0] else {
+            unreachable!()
+        };
+        assert_eq!(first.where_clause.len(), 1);
+        assert!(first.limit.is_some());
+
+        let offset = source.find("DELETE").unwrap() + 3;
+        assert!(matches!(
+            parsed.statement_at(offset),
+            Some(Statement::Delete(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_statement_requires_one_statement() {
+        assert!(parse_statement("USE shop").is_ok());
+        assert_eq!(
+            parse_statement("USE a; USE b").unwrap_err().message,
+            "Expected a single statement"
+        );
+        assert_eq!(parse("SELECT").errors.len(), 1);
+        assert_eq!(
+            parse_statement("-- nothing").unwrap_err().message,
+            "Expected a statement"
+        );
+        assert_eq!(
+            parse_statement("SELECT * FROM t WHERE key = 1 AND ttl > 2")
+                .map(|s| s.table().cloned().map(|t| t.to_string())),
+            Ok(Some("t".to_string()))
+        );
+        assert_eq!(
+            parse_statement("SELECT * FROM from").unwrap_err().message,
+            "Expected a table name, found `from`"
+        );
+    }
+}
@@ -0,0 +1,1764 @@
+//! Recursive descent over the significant tokens of [`cql_tokenizer`].
+//!
+//! Keywords are matched by text, so non-reserved ones (`key`, `ttl`, `type`)
+//! still work as names. A failed clause is recorded and skipped up to the
+//! next clause keyword, keeping the rest of the statement; a failed
+//! statement is skipped up to its `;`.
+//!
+//! [`cql_tokenizer`]: crate::cql_tokenizer
+
+use super::ast::*;
+use super::ParseError;
+use crate::cql_tokenizer::{tokenize, Token, TokenKind};
+use crate::identifier::Identifier;
+use crate::schema::is_reserved_keyword;
+
+type PResult<T> = std::result::Result<T, ParseError>;
+
+/// Native CQL types, sorted for binary search
+pub static NATIVE_TYPES: &[&str] = &[
+    "ascii",
+    "bigint",
+    "blob",
+    "boolean",
+    "counter",
+    "date",
+    "decimal",
+    "double",
+    "duration",
+    "float",
+    "inet",
+    "int",
+    "smallint",
+    "text",
+    "time",
+    "timestamp",
+    "timeuuid",
+    "tinyint",
+    "uuid",
+    "varchar",
+    "varint",
+];
+
+/// Clauses a `SELECT` can resume at after an error
+const SELECT_CLAUSES: &[&str] = &["FROM", "WHERE", "GROUP", "ORDER", "PER", "LIMIT", "ALLOW"];
+
+fn span_of(token: &Token) -> Span {
+    Span::new(token.start, token.start + token.text.len())
+}
+
+fn is_quoted(token: &Token) -> bool {
+    token.kind == TokenKind::Identifier && token.text.starts_with('"')
+}
+
+/// An unquoted word, keyword or not
+fn is_word(token: &Token) -> bool {
+    matches!(
+        token.kind,
+        TokenKind::Keyword | TokenKind::Type | TokenKind::Function | TokenKind::Identifier
+    ) && !is_quoted(token)
+}
+
+fn is_name(token: &Token) -> bool {
+    is_word(token) || is_quoted(token) || token.kind == TokenKind::Variable
+}
+
+fn unescape_string(text: &str) -> Option<String> {
+    if let Some(body) = text.strip_prefix("$$") {
+        return body.strip_suffix("$$").map(str::to_string);
+    }
+    let inner = text.strip_prefix('\'')?.strip_suffix('\'')?;
+    Some(inner.replace("''", "'"))
+}
+
+fn number_literal(text: &str) -> Literal {
+    let unsigned = text.trim_start_matches('-');
+    if unsigned.len() == 36 && unsigned.contains('-') {
+        Literal::Uuid(text.to_string())
+    } else if unsigned.starts_with("0x") || unsigned.starts_with("0X") {
+        Literal::Blob(text.to_string())
+    } else if unsigned.contains(['.', 'e', 'E']) {
+        Literal::Float(text.to_string())
+    } else if unsigned.chars().any(|c| c.is_ascii_alphabetic()) {
+        Literal::Duration(text.to_string())
+    } else {
+        Literal::Integer(text.to_string())
+    }
+}
+
+pub(super) struct Parser<'a> {
+    source: &'a str,
+    tokens: Vec<Token>,
+    pos: usize,
+    errors: Vec<ParseError>,
+}
+
+impl<'a> Parser<'a> {
+    pub(super) fn new(source: &'a str) -> Self {
+        let tokens = tokenize(source)
+            .into_iter()
+            .filter(|t| !matches!(t.kind, TokenKind::Whitespace | TokenKind::Comment))
+            .collect();
+        Self {
+            source,
+            tokens,
+            pos: 0,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Every statement of the source, and every error met on the way
+    pub(super) fn parse_script(mut self) -> (Vec<Statement>, Vec<ParseError>) {
+        let mut statements = Vec::new();
+        loop {
+            while self.eat_symbol(";") {}
+            if self.pos >= self.tokens.len() {
+                break;
+            }
+            let is_batch = self.at_word("BEGIN");
+            match self.statement() {
+                Ok(statement) => {
+                    statements.push(statement);
+                    if !self.at_statement_end() {
+                        let error = self.error("end of statement");
+                        self.push_error(error);
+                        self.skip_statement(false);
+                    }
+                }
+                Err(error) => {
+                    self.push_error(error);
+                    self.skip_statement(is_batch);
+                }
+            }
+        }
+        (statements, self.errors)
+    }
+
+    /// The whole source as one type, e.g. a column type from `system_schema`
+    pub(super) fn parse_type(mut self) -> PResult<CqlType> {
+        let cql_type = self.cql_type()?;
+        match self.peek() {
+            None => Ok(cql_type),
+            Some(_) => Err(self.error("end of type")),
+        }
+    }
+
+    // ── Token access ───────────────────────────────────────────
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_at(&self, n: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + n)
+    }
+
+    /// Start of the current token, or the end of the source
+    fn start(&self) -> usize {
+        self.peek().map_or(self.source.len(), |t| t.start)
+    }
+
+    /// End of the last consumed token
+    fn prev_end(&self) -> usize {
+        self.pos
+            .checked_sub(1)
+            .and_then(|i| self.tokens.get(i))
+            .map_or(0, |t| span_of(t).end)
+    }
+
+    fn finish(&self, start: usize) -> Span {
+        Span::new(start, self.prev_end().max(start))
+    }
+
+    fn at_statement_end(&self) -> bool {
+        self.peek().is_none_or(|t| t.text == ";")
+    }
+
+    fn at_word(&self, keyword: &str) -> bool {
+        self.at_word_n(0, keyword)
+    }
+
+    fn at_word_n(&self, n: usize, keyword: &str) -> bool {
+        self.peek_at(n)
+            .is_some_and(|t| is_word(t) && t.text.eq_ignore_ascii_case(keyword))
+    }
+
+    fn at_words(&self, keywords: &[&str]) -> bool {
+        keywords
+            .iter()
+            .enumerate()
+            .all(|(n, kw)| self.at_word_n(n, kw))
+    }
+
+    fn eat_word(&mut self, keyword: &str) -> bool {
+        let found = self.at_word(keyword);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn eat_words(&mut self, keywords: &[&str]) -> bool {
+        let found = self.at_words(keywords);
+        if found {
+            self.pos += keywords.len();
+        }
+        found
+    }
+
+    fn expect_word(&mut self, keyword: &str) -> PResult<()> {
+        if self.eat_word(keyword) {
+            Ok(())
+        } else {
+            Err(self.error(keyword))
+        }
+    }
+
+    fn expect_words(&mut self, keywords: &[&str]) -> PResult<()> {
+        keywords.iter().try_for_each(|kw| self.expect_word(kw))
+    }
+
+    fn at_symbol(&self, symbol: &str) -> bool {
+        self.at_symbol_n(0, symbol)
+    }
+
+    fn at_symbol_n(&self, n: usize, symbol: &str) -> bool {
+        self.peek_at(n).is_some_and(|t| {
+            !matches!(t.kind, TokenKind::String | TokenKind::Identifier) && t.text == symbol
+        })
+    }
+
+    fn eat_symbol(&mut self, symbol: &str) -> bool {
+        let found = self.at_symbol(symbol);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> PResult<()> {
+        if self.eat_symbol(symbol) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("`{}`", symbol)))
+        }
+    }
+
+    /// "Expected `expected`, found ..." at the current token
+    fn error(&self, expected: &str) -> ParseError {
+        match self.peek() {
+            Some(t) if t.text != ";" => ParseError::new(
+                format!("Expected {}, found `{}`", expected, t.text),
+                span_of(t),
+            ),
+            _ => {
+                let end = self.prev_end();
+                ParseError::new(
+                    format!("Expected {}, found end of statement", expected),
+                    Span::new(end, end),
+                )
+            }
+        }
+    }
+
+    /// Record an error, unless one was already reported at the same place
+    /// (a clause cut short at the end of the statement fails the next too)
+    fn push_error(&mut self, error: ParseError) {
+        if self
+            .errors
+            .last()
+            .is_none_or(|last| last.span.start != error.span.start)
+        {
+            self.errors.push(error);
+        }
+    }
+
+    /// Skip the rest of the statement; a batch ends at `APPLY BATCH` rather
+    /// than at the `;` of its first inner statement
+    fn skip_statement(&mut self, in_batch: bool) {
+        while self.pos < self.tokens.len() {
+            if in_batch && self.eat_words(&["APPLY", "BATCH"]) {
+                return;
+            }
+            if !in_batch && self.at_symbol(";") {
+                return;
+            }
+            self.pos += 1;
+        }
+    }
+
+    /// Run `clause`; on error record it, skip to one of `resume_at` (or the
+    /// end of the statement) and carry on with an empty clause
+    fn recover<T: Default>(
+        &mut self,
+        resume_at: &[&str],
+        clause: impl FnOnce(&mut Self) -> PResult<T>,
+    ) -> T {
+        match clause(self) {
+            Ok(value) => value,
+            Err(error) => {
+                self.push_error(error);
+                while !self.at_statement_end() && !resume_at.iter().any(|kw| self.at_word(kw)) {
+                    self.pos += 1;
+                }
+                T::default()
+            }
+        }
+    }
+
+    /// `item {, item}`
+    fn comma_list<T>(&mut self, mut item: impl FnMut(&mut Self) -> PResult<T>) -> PResult<Vec<T>> {
+        let mut items = vec![item(self)?];
+        while self.eat_symbol(",") {
+            items.push(item(self)?);
+        }
+        Ok(items)
+    }
+
+    /// `( item {, item} )`, possibly empty
+    fn paren_list<T>(&mut self, item: impl FnMut(&mut Self) -> PResult<T>) -> PResult<Vec<T>> {
+        self.expect_symbol("(")?;
+        if self.eat_symbol(")") {
+            return Ok(Vec::new());
+        }
+        let items = self.comma_list(item)?;
+        self.expect_symbol(")")?;
+        Ok(items)
+    }
+
+    // ── Names and types ────────────────────────────────────────
+
+    fn name_token(&mut self, what: &str, allow_reserved: bool) -> PResult<Name> {
+        let Some(token) = self.peek().cloned() else {
+            return Err(self.error(what));
+        };
+        let span = span_of(&token);
+        let ident = if token.kind == TokenKind::Variable {
+            Identifier::new(token.text.clone())
+        } else if is_quoted(&token)
+            || (is_word(&token) && (allow_reserved || !is_reserved_keyword(&token.text)))
+        {
+            Identifier::parse(&token.text)
+                .map_err(|_| ParseError::new("Unterminated quoted name", span))?
+        } else {
+            return Err(self.error(what));
+        };
+        self.pos += 1;
+        Ok(Name { ident, span })
+    }
+
+    /// A name; reserved keywords only when quoted
+    fn name(&mut self, what: &str) -> PResult<Name> {
+        self.name_token(what, false)
+    }
+
+    /// A name where keywords cannot be confused with one, e.g. an option
+    fn any_name(&mut self, what: &str) -> PResult<Name> {
+        self.name_token(what, true)
+    }
+
+    fn qualified_name(&mut self, what: &str) -> PResult<QualifiedName> {
+        let first = self.name(what)?;
+        if self.eat_symbol(".") {
+            let name = self.name(what)?;
+            Ok(QualifiedName {
+                keyspace: Some(first),
+                name,
+            })
+        } else {
+            Ok(QualifiedName {
+                keyspace: None,
+                name: first,
+            })
+        }
+    }
+
+    fn string(&mut self, what: &str) -> PResult<String> {
+        match self.peek() {
+            Some(t) if t.kind == TokenKind::String => {
+                let span = span_of(t);
+                let value = unescape_string(&t.text)
+                    .ok_or_else(|| ParseError::new("Unterminated string", span))?;
+                self.pos += 1;
+                Ok(value)
+            }
+            _ => Err(self.error(what)),
+        }
+    }
+
+    fn cql_type(&mut self) -> PResult<CqlType> {
+        let Some(token) = self.peek().cloned() else {
+            return Err(self.error("a type"));
+        };
+        if token.kind == TokenKind::String {
+            return self.string("a type").map(CqlType::Custom);
+        }
+        if !is_word(&token) {
+            return self.qualified_name("a type").map(CqlType::UserDefined);
+        }
+        let lower = token.text.to_ascii_lowercase();
+        let generic = self.at_symbol_n(1, "<");
+        let cql_type = match lower.as_str() {
+            "frozen" if generic => {
+                self.pos += 2;
+                CqlType::Frozen(Box::new(self.cql_type()?))
+            }
+            "list" if generic => {
+                self.pos += 2;
+                CqlType::List(Box::new(self.cql_type()?))
+            }
+            "set" if generic => {
+                self.pos += 2;
+                CqlType::Set(Box::new(self.cql_type()?))
+            }
+            "map" if generic => {
+                self.pos += 2;
+                let key = self.cql_type()?;
+                self.expect_symbol(",")?;
+                CqlType::Map(Box::new(key), Box::new(self.cql_type()?))
+            }
+            "tuple" if generic => {
+                self.pos += 2;
+                CqlType::Tuple(self.comma_list(Self::cql_type)?)
+            }
+            "vector" if generic => {
+                self.pos += 2;
+                let item = self.cql_type()?;
+                self.expect_symbol(",")?;
+                let size = match self.peek() {
+                    Some(t) if t.kind == TokenKind::Number => {
+                        t.text.parse::<usize>().ok().filter(|&n| n > 0)
+                    }
+                    _ => None,
+                };
+                let Some(size) = size else {
+                    return Err(self.error("a vector size"));
+                };
+                self.pos += 1;
+                CqlType::Vector(Box::new(item), size)
+            }
+            _ if NATIVE_TYPES.binary_search(&lower.as_str()).is_ok() => {
+                self.pos += 1;
+                return Ok(CqlType::Native(lower));
+            }
+            _ => return self.qualified_name("a type").map(CqlType::UserDefined),
+        };
+        self.expect_symbol(">")?;
+        Ok(cql_type)
+    }
+
+    // ── Terms ──────────────────────────────────────────────────
+
+    fn term(&mut self) -> PResult<Term> {
+        let mut left = self.product()?;
+        loop {
+            let op = if self.at_symbol("+") {
+                ArithOp::Add
+            } else if self.at_symbol("-") {
+                ArithOp::Sub
+            } else {
+                return Ok(left);
+            };
+            self.pos += 1;
+            let right = self.product()?;
+            left = Term::Binary {
+                op,
+                left: Box::new(left),
+                right: Box::new(right),
+            };
+        }
+    }
+
+    fn product(&mut self) -> PResult<Term> {
+        let mut left = self.primary("a value")?;
+        loop {
+            let op = if self.at_symbol("*") {
+                ArithOp::Mul
+            } else if self.at_symbol("/") {
+                ArithOp::Div
+            } else if self.at_symbol("%") {
+                ArithOp::Mod
+            } else {
+                return Ok(left);
+            };
+            self.pos += 1;
+            let right = self.primary("a value")?;
+            left = Term::Binary {
+                op,
+                left: Box::new(left),
+                right: Box::new(right),
+            };
+        }
+    }
+
+    fn primary(&mut self, what: &str) -> PResult<Term> {
+        let Some(token) = self.peek().cloned() else {
+            return Err(self.error(what));
+        };
+        let start = token.start;
+        let span = span_of(&token);
+        match token.kind {
+            TokenKind::String => {
+                let value = self.string(what)?;
+                return Ok(Term::Literal {
+                    value: Literal::String(value),
+                    span,
+                });
+            }
+            TokenKind::Number => {
+                self.pos += 1;
+                return Ok(Term::Literal {
+                    value: number_literal(&token.text),
+                    span,
+                });
+            }
+            TokenKind::Variable => {
+                self.pos += 1;
+                return Ok(Term::Variable {
+                    name: token.text,
+                    span,
+                });
+            }
+            _ => {}
+        }
+
+        if !is_name(&token) {
+            return match token.text.as_str() {
+                "?" => {
+                    self.pos += 1;
+                    Ok(Term::BindMarker { name: None, span })
+                }
+                ":" => {
+                    self.pos += 1;
+                    let name = self.any_name("a bind marker name")?;
+                    Ok(Term::BindMarker {
+                        span: span.to(name.span),
+                        name: Some(name),
+                    })
+                }
+                "-" => self.negative(what),
+                "[" => {
+                    self.pos += 1;
+                    let items = if self.at_symbol("]") {
+                        Vec::new()
+                    } else {
+                        self.comma_list(Self::term)?
+                    };
+                    self.expect_symbol("]")?;
+                    Ok(Term::List {
+                        items,
+                        span: self.finish(start),
+                    })
+                }
+                "{" => self.braces(),
+                "(" => self.parenthesized(),
+                _ => Err(self.error(what)),
+            };
+        }
+
+        if is_word(&token) {
+            let upper = token.text.to_ascii_uppercase();
+            let constant = match upper.as_str() {
+                "TRUE" => Some(Literal::Boolean(true)),
+                "FALSE" => Some(Literal::Boolean(false)),
+                "NULL" => Some(Literal::Null),
+                "NAN" => Some(Literal::NaN),
+                "INFINITY" => Some(Literal::Infinity),
+                _ => None,
+            };
+            if let Some(value) = constant {
+                self.pos += 1;
+                return Ok(Term::Literal { value, span });
+            }
+            if upper == "CAST" && self.at_symbol_n(1, "(") {
+                self.pos += 2;
+                let term = self.term()?;
+                self.expect_word("AS")?;
+                let cql_type = self.cql_type()?;
+                self.expect_symbol(")")?;
+                return Ok(Term::Cast {
+                    term: Box::new(term),
+                    cql_type,
+                    span: self.finish(start),
+                });
+            }
+        }
+
+        // Function call, maybe keyspace-qualified
+        if self.at_symbol_n(1, "(") {
+            let name = self.any_name("a function name")?;
+            return self.call(
+                QualifiedName {
+                    keyspace: None,
+                    name,
+                },
+                start,
+            );
+        }
+        if self.at_symbol_n(1, ".")
+            && self.peek_at(2).is_some_and(is_name)
+            && self.at_symbol_n(3, "(")
+        {
+            let keyspace = self.name("a keyspace name")?;
+            self.pos += 1;
+            let name = self.any_name("a function name")?;
+            return self.call(
+                QualifiedName {
+                    keyspace: Some(keyspace),
+                    name,
+                },
+                start,
+            );
+        }
+
+        let mut term = Term::Column(self.name(what)?);
+        loop {
+            if self.eat_symbol("[") {
+                let key = self.term()?;
+                self.expect_symbol("]")?;
+                term = Term::Element {
+                    base: Box::new(term),
+                    key: Box::new(key),
+                    span: self.finish(start),
+                };
+            } else if self.at_symbol(".") && self.peek_at(1).is_some_and(is_name) {
+                self.pos += 1;
+                let field = self.any_name("a field name")?;
+                term = Term::Field {
+                    base: Box::new(term),
+                    field,
+                };
+            } else {
+                return Ok(term);
+            }
+        }
+    }
+
+    /// `-5`, `-1.5`, `-Infinity`
+    fn negative(&mut self, what: &str) -> PResult<Term> {
+        let start = self.start();
+        self.pos += 1;
+        let value = match self.peek() {
+            Some(t) if t.kind == TokenKind::Number => number_literal(&format!("-{}", t.text)),
+            Some(t) if is_word(t) && t.text.eq_ignore_ascii_case("INFINITY") => {
+                Literal::Float("-Infinity".to_string())
+            }
+            Some(t) if is_word(t) && t.text.eq_ignore_ascii_case("NAN") => Literal::NaN,
+            _ => return Err(self.error(what)),
+        };
+        self.pos += 1;
+        Ok(Term::Literal {
+            value,
+            span: self.finish(start),
+        })
+    }
+
+    fn call(&mut self, name: QualifiedName, start: usize) -> PResult<Term> {
+        let args = if self.at_symbol_n(1, "*") && self.at_symbol_n(2, ")") {
+            self.pos += 1;
+            let star = span_of(self.peek().expect("checked above"));
+            self.pos += 2;
+            vec![Term::Wildcard(star)]
+        } else {
+            self.paren_list(Self::term)?
+        };
+        Ok(Term::FunctionCall {
+            name,
+            args,
+            span: self.finish(start),
+        })
+    }
+
+    /// Map, set or user type literal
+    fn braces(&mut self) -> PResult<Term> {
+        let start = self.start();
+        self.expect_symbol("{")?;
+        if self.eat_symbol("}") {
+            return Ok(Term::Map {
+                entries: Vec::new(),
+                span: self.finish(start),
+            });
+        }
+
+        let is_udt =
+            self.peek().is_some_and(|t| is_word(t) || is_quoted(t)) && self.at_symbol_n(1, ":");
+        if is_udt {
+            let fields = self.comma_list(|p| {
+                let name = p.any_name("a field name")?;
+                p.expect_symbol(":")?;
+                Ok((name, p.term()?))
+            })?;
+            self.expect_symbol("}")?;
+            return Ok(Term::UserType {
+                fields,
+                span: self.finish(start),
+            });
+        }
+
+        let first = self.term()?;
+        if self.eat_symbol(":") {
+            let mut entries = vec![(first, self.term()?)];
+            while self.eat_symbol(",") {
+                let key = self.term()?;
+                self.expect_symbol(":")?;
+                entries.push((key, self.term()?));
+            }
+            self.expect_symbol("}")?;
+            return Ok(Term::Map {
+                entries,
+                span: self.finish(start),
+            });
+        }
+        let mut items = vec![first];
+        while self.eat_symbol(",") {
+            items.push(self.term()?);
+        }
+        self.expect_symbol("}")?;
+        Ok(Term::Set {
+            items,
+            span: self.finish(start),
+        })
+    }
+
+    /// Tuple, `(type) term` hint, or a term in parentheses
+    fn parenthesized(&mut self) -> PResult<Term> {
+        let start = self.start();
+        let is_hint = self.peek_at(1).is_some_and(|t| {
+            is_word(t)
+                && NATIVE_TYPES
+                    .binary_search(&t.text.to_ascii_lowercase().as_str())
+                    .is_ok()
+        }) && self.at_symbol_n(2, ")")
+            && self.peek_at(3).is_some_and(|t| {
+                matches!(
+                    t.kind,
+                    TokenKind::String | TokenKind::Number | TokenKind::Variable
+                ) || matches!(t.text.as_str(), "?" | ":" | "[" | "{" | "-")
+            });
+        if is_hint {
+            self.pos += 1;
+            let cql_type = self.cql_type()?;
+            self.expect_symbol(")")?;
+            let term = self.primary("a value")?;
+            return Ok(Term::TypeHint {
+                cql_type,
+                term: Box::new(term),
+                span: self.finish(start),
+            });
+        }
+
+        let mut items = self.paren_list(Self::term)?;
+        if items.len() == 1 {
+            return Ok(items.remove(0));
+        }
+        Ok(Term::Tuple {
+            items,
+            span: self.finish(start),
+        })
+    }
+
+    // ── Clauses ────────────────────────────────────────────────
+
+    fn relation(&mut self) -> PResult<Relation> {
+        let start = self.start();
+        let left = if self.at_symbol("(") {
+            let columns = self.paren_list(|p| p.name("a column name").map(Term::Column))?;
+            Term::Tuple {
+                items: columns,
+                span: self.finish(start),
+            }
+        } else {
+            self.primary("a column name")?
+        };
+
+        let op = match self.peek() {
+            Some(t) if is_word(t) => match t.text.to_ascii_uppercase().as_str() {
+                "IN" => Operator::In,
+                "CONTAINS" if self.at_word_n(1, "KEY") => {
+                    self.pos += 1;
+                    Operator::ContainsKey
+                }
+                "CONTAINS" => Operator::Contains,
+                "LIKE" => Operator::Like,
+                "IS" => {
+                    self.pos += 1;
+                    self.expect_words(&["NOT", "NULL"])?;
+                    return Ok(Relation {
+                        left,
+                        op: Operator::IsNotNull,
+                        right: None,
+                        span: self.finish(start),
+                    });
+                }
+                _ => return Err(self.error("an operator such as `=` or `IN`")),
+            },
+            Some(t) if t.kind == TokenKind::Operator => match t.text.as_str() {
+                "=" => Operator::Eq,
+                "!=" => Operator::Ne,
+                "<" => Operator::Lt,
+                "<=" => Operator::Le,
+                ">" => Operator::Gt,
+                ">=" => Operator::Ge,
+                _ => return Err(self.error("an operator such as `=` or `IN`")),
+            },
+            _ => return Err(self.error("an operator such as `=` or `IN`")),
+        };
+        self.pos += 1;
+
+        let right = if op == Operator::In && self.at_symbol("(") {
+            let values_start = self.start();
+            let items = self.paren_list(Self::term)?;
+            Term::Tuple {
+                items,
+                span: self.finish(values_start),
+            }
+        } else {
+            self.term()?
+        };
+        Ok(Relation {
+            left,
+            op,
+            right: Some(right),
+            span: self.finish(start),
+        })
+    }
+
+    /// `relation {AND relation}`
+    fn relations(&mut self) -> PResult<Vec<Relation>> {
+        let mut relations = vec![self.relation()?];
+        while self.eat_word("AND") {
+            relations.push(self.relation()?);
+        }
+        Ok(relations)
+    }
+
+    /// `IF EXISTS` or `IF relation {AND relation}`, after `IF`
+    fn condition(&mut self) -> PResult<Condition> {
+        let start = self.start();
+        if self.eat_word("EXISTS") {
+            return Ok(Condition::Exists(self.finish(start)));
+        }
+        if self.eat_words(&["NOT", "EXISTS"]) {
+            return Ok(Condition::NotExists(self.finish(start)));
+        }
+        self.relations().map(Condition::Relations)
+    }
+
+    /// Options after `USING`
+    fn using_options(&mut self) -> PResult<Vec<UsingOption>> {
+        let mut options = Vec::new();
+        loop {
+            let option = if self.eat_word("TTL") {
+                UsingOption::Ttl(self.term()?)
+            } else if self.eat_word("TIMESTAMP") {
+                UsingOption::Timestamp(self.term()?)
+            } else if self.eat_word("TIMEOUT") {
+                UsingOption::Timeout(self.term()?)
+            } else {
+                return Err(self.error("TTL, TIMESTAMP or TIMEOUT"));
+            };
+            options.push(option);
+            if !self.eat_word("AND") {
+                return Ok(options);
+            }
+        }
+    }
+
+    fn ordering(&mut self) -> PResult<Ordering> {
+        let column = self.name("a column name")?;
+        let descending = if self.eat_word("DESC") {
+            true
+        } else {
+            self.eat_word("ASC");
+            false
+        };
+        Ok(Ordering { column, descending })
+    }
+
+    fn selection(&mut self) -> PResult<Selection> {
+        let start = self.start();
+        if self.eat_symbol("*") {
+            return Ok(Selection::All(self.finish(start)));
+        }
+        let selectors = self.comma_list(|p| {
+            let term = p.term()?;
+            let alias = if p.eat_word("AS") {
+                Some(p.name("an alias")?)
+            } else {
+                None
+            };
+            Ok(Selector { term, alias })
+        })?;
+        Ok(Selection::Columns(selectors))
+    }
+
+    fn property(&mut self) -> PResult<Property> {
+        let name = self.any_name("an option name")?;
+        self.expect_symbol("=")?;
+        Ok(Property {
+            name,
+            value: self.term()?,
+        })
+    }
+
+    /// `name = value {AND name = value}`
+    fn properties(&mut self) -> PResult<Vec<Property>> {
+        let mut properties = vec![self.property()?];
+        while self.eat_word("AND") {
+            properties.push(self.property()?);
+        }
+        Ok(properties)
+    }
+
+    /// Table options after `WITH`
+    fn table_options(&mut self) -> PResult<Vec<TableOption>> {
+        let mut options = Vec::new();
+        loop {
+            let option = if self.eat_words(&["CLUSTERING", "ORDER", "BY"]) {
+                TableOption::ClusteringOrder(self.paren_list(Self::ordering)?)
+            } else if self.eat_words(&["COMPACT", "STORAGE"]) {
+                TableOption::CompactStorage
+            } else {
+                TableOption::Property(self.property()?)
+            };
+            options.push(option);
+            if !self.eat_word("AND") {
+                return Ok(options);
+            }
+        }
+    }
+
+    /// `( partition [, clustering...] )`, after `PRIMARY KEY`
+    fn primary_key(&mut self) -> PResult<PrimaryKey> {
+        let start = self.start();
+        self.expect_symbol("(")?;
+        let partition_key = if self.at_symbol("(") {
+            self.paren_list(|p| p.name("a partition key column"))?
+        } else {
+            vec![self.name("a partition key column")?]
+        };
+        let mut clustering = Vec::new();
+        while self.eat_symbol(",") {
+            clustering.push(self.name("a clustering column")?);
+        }
+        self.expect_symbol(")")?;
+        Ok(PrimaryKey {
+            partition_key,
+            clustering,
+            span: self.finish(start),
+        })
+    }
+
+    fn column_definition(&mut self) -> PResult<ColumnDefinition> {
+        let start = self.start();
+        let name = self.name("a column name")?;
+        let cql_type = self.cql_type()?;
+        let is_static = self.eat_word("STATIC");
+        let primary_key = self.eat_words(&["PRIMARY", "KEY"]);
+        Ok(ColumnDefinition {
+            name,
+            cql_type,
+            is_static,
+            primary_key,
+            span: self.finish(start),
+        })
+    }
+
+    /// `old TO new {AND old TO new}`
+    fn renames(&mut self) -> PResult<Vec<(Name, Name)>> {
+        let mut renames = Vec::new();
+        loop {
+            let from = self.name("a name")?;
+            self.expect_word("TO")?;
+            renames.push((from, self.name("a new name")?));
+            if !self.eat_word("AND") {
+                return Ok(renames);
+            }
+        }
+    }
+
+    fn if_not_exists(&mut self) -> PResult<bool> {
+        if self.eat_word("IF") {
+            self.expect_words(&["NOT", "EXISTS"])?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn if_exists(&mut self) -> PResult<bool> {
+        if self.eat_word("IF") {
+            self.expect_word("EXISTS")?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    // ── Statements ─────────────────────────────────────────────
+
+    fn statement(&mut self) -> PResult<Statement> {
+        let Some(token) = self.peek().cloned() else {
+            return Err(self.error("a statement"));
+        };
+        let keyword = if is_word(&token) {
+            token.text.to_ascii_uppercase()
+        } else {
+            String::new()
+        };
+        match keyword.as_str() {
+            "SELECT" => self.select().map(Statement::Select),
+            "INSERT" => self.insert().map(Statement::Insert),
+            "UPDATE" => self.update().map(Statement::Update),
+            "DELETE" => self.delete().map(Statement::Delete),
+            "BEGIN" => self.batch().map(Statement::Batch),
+            "USE" => {
+                self.pos += 1;
+                let keyspace = self.name("a keyspace name")?;
+                Ok(Statement::Use {
+                    keyspace,
+                    span: self.finish(token.start),
+                })
+            }
+            "TRUNCATE" => {
+                self.pos += 1;
+                if !self.eat_word("TABLE") {
+                    self.eat_word("COLUMNFAMILY");
+                }
+                let table = self.qualified_name("a table name")?;
+                Ok(Statement::Truncate {
+                    table,
+                    span: self.finish(token.start),
+                })
+            }
+            "CREATE" => self.create(),
+            "ALTER" => self.alter(),
+            "DROP" => self.drop(),
+            "GRANT" | "REVOKE" | "LIST" | "DESCRIBE" | "DESC" => {
+                Ok(self.other(token.start, keyword))
+            }
+            _ => Err(ParseError::new(
+                format!("Unknown statement `{}`", token.text),
+                span_of(&token),
+            )),
+        }
+    }
+
+    /// A statement kept as text, up to its end
+    fn other(&mut self, start: usize, keyword: String) -> Statement {
+        while !self.at_statement_end() {
+            self.pos += 1;
+        }
+        Statement::Other {
+            keyword,
+            span: self.finish(start),
+        }
+    }
+
+    fn select(&mut self) -> PResult<SelectStatement> {
+        let start = self.start();
+        self.expect_word("SELECT")?;
+        let json = self.eat_word("JSON");
+        let distinct = self.eat_word("DISTINCT");
+        let selection = self.recover(SELECT_CLAUSES, Self::selection);
+        self.expect_word("FROM")?;
+        let table = self.qualified_name("a table name")?;
+
+        let where_clause = if self.eat_word("WHERE") {
+            self.recover(&SELECT_CLAUSES[2..], Self::relations)
+        } else {
+            Vec::new()
+        };
+        let group_by = if self.eat_words(&["GROUP", "BY"]) {
+            self.recover(&SELECT_CLAUSES[3..], |p| {
+                p.comma_list(|p| p.name("a column name"))
+            })
+        } else {
+            Vec::new()
+        };
+        let order_by = if self.eat_words(&["ORDER", "BY"]) {
+            self.recover(&SELECT_CLAUSES[4..], |p| p.comma_list(Self::ordering))
+        } else {
+            Vec::new()
+        };
+        let per_partition_limit = if self.eat_words(&["PER", "PARTITION", "LIMIT"]) {
+            self.recover(&SELECT_CLAUSES[5..], |p| p.term().map(Some))
+        } else {
+            None
+        };
+        let limit = if self.eat_word("LIMIT") {
+            self.recover(&SELECT_CLAUSES[6..], |p| p.term().map(Some))
+        } else {
+            None
+        };
+        let allow_filtering = self.eat_word("ALLOW");
+        if allow_filtering {
+            self.expect_word("FILTERING")?;
+        }
+
+        Ok(SelectStatement {
+            json,
+            distinct,
+            selection,
+            table,
+            where_clause,
+            group_by,
+            order_by,
+            per_partition_limit,
+            limit,
+            allow_filtering,
+            span: self.finish(start),
+        })
+    }
+
+    fn insert(&mut self) -> PResult<InsertStatement> {
+        let start = self.start();
+        self.expect_words(&["INSERT", "INTO"])?;
+        let table = self.qualified_name("a table name")?;
+        let values = self.recover(&["IF", "USING"], |p| {
+            if p.eat_word("JSON") {
+                let value = p.term()?;
+                let default_unset = if p.eat_word("DEFAULT") {
+                    if p.eat_word("UNSET") {
+                        true
+                    } else {
+                        p.expect_word("NULL")?;
+                        false
+                    }
+                } else {
+                    false
+                };
+                return Ok(InsertValues::Json {
+                    value,
+                    default_unset,
+                });
+            }
+            let columns = p.paren_list(|p| p.name("a column name"))?;
+            p.expect_word("VALUES")?;
+            let values = p.paren_list(Self::term)?;
+            Ok(InsertValues::Columns { columns, values })
+        });
+
+        let mut if_not_exists = false;
+        let mut using = Vec::new();
+        loop {
+            if self.eat_word("IF") {
+                self.expect_words(&["NOT", "EXISTS"])?;
+                if_not_exists = true;
+            } else if self.eat_word("USING") {
+                using = self.using_options()?;
+            } else {
+                break;
+            }
+        }
+
+        Ok(InsertStatement {
+            table,
+            values,
+            if_not_exists,
+            using,
+            span: self.finish(start),
+        })
+    }
+
+    fn assignment(&mut self) -> PResult<Assignment> {
+        let start = self.start();
+        let column = self.name("a column name")?;
+        let mut key = None;
+        let mut field = None;
+        if self.eat_symbol("[") {
+            key = Some(self.term()?);
+            self.expect_symbol("]")?;
+        } else if self.eat_symbol(".") {
+            field = Some(self.any_name("a field name")?);
+        }
+
+        let compound = [("+", AssignOp::Add), ("-", AssignOp::Subtract)]
+            .into_iter()
+            .find(|(symbol, _)| self.at_symbol(symbol) && self.at_symbol_n(1, "="));
+        if let Some((_, op)) = compound {
+            self.pos += 2;
+            let value = self.term()?;
+            return Ok(Assignment {
+                column,
+                key,
+                field,
+                op,
+                value,
+                span: self.finish(start),
+            });
+        }
+
+        self.expect_symbol("=")?;
+        let value = self.term()?;
+        let same_column = |term: &Term| matches!(term, Term::Column(n) if n.ident == column.ident);
+        let (op, value) = match value {
+            Term::Binary { op, left, right } if key.is_none() && field.is_none() => match op {
+                ArithOp::Add if same_column(&left) => (AssignOp::Add, *right),
+                ArithOp::Sub if same_column(&left) => (AssignOp::Subtract, *right),
+                ArithOp::Add if same_column(&right) => (AssignOp::Prepend, *left),
+                _ => (AssignOp::Set, Term::Binary { op, left, right }),
+            },
+            value => (AssignOp::Set, value),
+        };
+        Ok(Assignment {
+            column,
+            key,
+            field,
+            op,
+            value,
+            span: self.finish(start),
+        })
+    }
+
+    fn update(&mut self) -> PResult<UpdateStatement> {
+        let start = self.start();
+        self.expect_word("UPDATE")?;
+        let table = self.qualified_name("a table name")?;
+        let using = if self.eat_word("USING") {
+            self.using_options()?
+        } else {
+            Vec::new()
+        };
+        self.expect_word("SET")?;
+        let assignments = self.recover(&["WHERE", "IF"], |p| p.comma_list(Self::assignment));
+        self.expect_word("WHERE")?;
+        let where_clause = self.recover(&["IF"], Self::relations);
+        let condition = if self.eat_word("IF") {
+            self.recover(&[], |p| p.condition().map(Some))
+        } else {
+            None
+        };
+        Ok(UpdateStatement {
+            table,
+            using,
+            assignments,
+            where_clause,
+            condition,
+            span: self.finish(start),
+        })
+    }
+
+    fn delete(&mut self) -> PResult<DeleteStatement> {
+        let start = self.start();
+        self.expect_word("DELETE")?;
+        let columns = if self.at_word("FROM") {
+            Vec::new()
+        } else {
+            self.recover(&["FROM"], |p| p.comma_list(|p| p.primary("a column name")))
+        };
+        self.expect_word("FROM")?;
+        let table = self.qualified_name("a table name")?;
+        let using = if self.eat_word("USING") {
+            self.using_options()?
+        } else {
+            Vec::new()
+        };
+        self.expect_word("WHERE")?;
+        let where_clause = self.recover(&["IF"], Self::relations);
+        let condition = if self.eat_word("IF") {
+            self.recover(&[], |p| p.condition().map(Some))
+        } else {
+            None
+        };
+        Ok(DeleteStatement {
+            columns,
+            table,
+            using,
+            where_clause,
+            condition,
+            span: self.finish(start),
+        })
+    }
+
+    fn batch(&mut self) -> PResult<BatchStatement> {
+        let start = self.start();
+        self.expect_word("BEGIN")?;
+        let kind = if self.eat_word("UNLOGGED") {
+            BatchKind::Unlogged
+        } else if self.eat_word("COUNTER") {
+            BatchKind::Counter
+        } else {
+            self.eat_word("LOGGED");
+            BatchKind::Logged
+        };
+        self.expect_word("BATCH")?;
+        let using = if self.eat_word("USING") {
+            self.using_options()?
+        } else {
+            Vec::new()
+        };
+
+        let mut statements = Vec::new();
+        loop {
+            while self.eat_symbol(";") {}
+            if self.eat_words(&["APPLY", "BATCH"]) {
+                break;
+            }
+            let Some(token) = self.peek().cloned() else {
+                return Err(self.error("APPLY BATCH"));
+            };
+            let inner = if self.at_word("INSERT") {
+                self.insert().map(Statement::Insert)
+            } else if self.at_word("UPDATE") {
+                self.update().map(Statement::Update)
+            } else if self.at_word("DELETE") {
+                self.delete().map(Statement::Delete)
+            } else {
+                Err(ParseError::new(
+                    format!(
+                        "Expected INSERT, UPDATE, DELETE or APPLY BATCH, found `{}`",
+                        token.text
+                    ),
+                    span_of(&token),
+                ))
+            };
+            // The `;` between the statements of a batch is optional
+            let inner = inner.and_then(|statement| {
+                if self.at_statement_end()
+                    || self.at_words(&["APPLY", "BATCH"])
+                    || ["INSERT", "UPDATE", "DELETE"]
+                        .iter()
+                        .any(|kw| self.at_word(kw))
+                {
+                    Ok(statement)
+                } else {
+                    Err(self.error("`;`"))
+                }
+            });
+            match inner {
+                Ok(statement) => statements.push(statement),
+                Err(error) => {
+                    // Resume at the next statement of the batch
+                    self.push_error(error);
+                    while self.peek().is_some()
+                        && !self.at_symbol(";")
+                        && !self.at_words(&["APPLY", "BATCH"])
+                    {
+                        self.pos += 1;
+                    }
+                }
+            }
+        }
+
+        Ok(BatchStatement {
+            kind,
+            using,
+            statements,
+            span: self.finish(start),
+        })
+    }
+
+    fn create(&mut self) -> PResult<Statement> {
+        let start = self.start();
+        self.expect_word("CREATE")?;
+        let or_replace = self.eat_words(&["OR", "REPLACE"]);
+        if self.eat_word("KEYSPACE") || self.eat_word("SCHEMA") {
+            let if_not_exists = self.if_not_exists()?;
+            let name = self.name("a keyspace name")?;
+            self.expect_word("WITH")?;
+            let options = self.properties()?;
+            return Ok(Statement::CreateKeyspace(CreateKeyspace {
+                name,
+                if_not_exists,
+                options,
+                span: self.finish(start),
+            }));
+        }
+        if self.eat_word("TABLE") || self.eat_word("COLUMNFAMILY") {
+            return self.create_table(start).map(Statement::CreateTable);
+        }
+        if self.eat_word("TYPE") {
+            let if_not_exists = self.if_not_exists()?;
+            let name = self.qualified_name("a type name")?;
+            let fields = self.paren_list(|p| {
+                let field = p.name("a field name")?;
+                Ok((field, p.cql_type()?))
+            })?;
+            return Ok(Statement::CreateType(CreateType {
+                name,
+                if_not_exists,
+                fields,
+                span: self.finish(start),
+            }));
+        }
+        let custom = self.eat_word("CUSTOM");
+        if self.eat_word("INDEX") {
+            return self.create_index(start, custom).map(Statement::CreateIndex);
+        }
+        if custom {
+            return Err(self.error("INDEX"));
+        }
+        if self.eat_words(&["MATERIALIZED", "VIEW"]) {
+            return self
+                .create_view(start)
+                .map(Statement::CreateMaterializedView);
+        }
+        if self.eat_word("FUNCTION") {
+            return self
+                .create_function(start, or_replace)
+                .map(Statement::CreateFunction);
+        }
+        if self.eat_word("AGGREGATE") {
+            return self
+                .create_aggregate(start, or_replace)
+                .map(Statement::CreateAggregate);
+        }
+        if self.eat_word("TRIGGER") {
+            let if_not_exists = self.if_not_exists()?;
+            let name = self.name("a trigger name")?;
+            self.expect_word("ON")?;
+            let table = self.qualified_name("a table name")?;
+            self.expect_word("USING")?;
+            let class = self.string("a trigger class")?;
+            return Ok(Statement::CreateTrigger(CreateTrigger {
+                name,
+                if_not_exists,
+                table,
+                class,
+                span: self.finish(start),
+            }));
+        }
+        if self.at_word("ROLE") || self.at_word("USER") {
+            return Ok(self.other(start, "CREATE".to_string()));
+        }
+        Err(self.error(
+            "KEYSPACE, TABLE, TYPE, INDEX, MATERIALIZED VIEW, FUNCTION, AGGREGATE, TRIGGER or ROLE",
+        ))
+    }
+
+    fn create_table(&mut self, start: usize) -> PResult<CreateTable> {
+        let if_not_exists = self.if_not_exists()?;
+        let name = self.qualified_name("a table name")?;
+        self.expect_symbol("(")?;
+        let mut columns = Vec::new();
+        let mut primary_key = None;
+        loop {
+            if self.eat_words(&["PRIMARY", "KEY"]) {
+                primary_key = Some(self.primary_key()?);
+            } else {
+                columns.push(self.column_definition()?);
+            }
+            if !self.eat_symbol(",") {
+                break;
+            }
+        }
+        self.expect_symbol(")")?;
+        let options = if self.eat_word("WITH") {
+            self.table_options()?
+        } else {
+            Vec::new()
+        };
+        Ok(CreateTable {
+            name,
+            if_not_exists,
+            columns,
+            primary_key,
+            options,
+            span: self.finish(start),
+        })
+    }
+
+    fn create_index(&mut self, start: usize, custom: bool) -> PResult<CreateIndex> {
+        let if_not_exists = self.if_not_exists()?;
+        let name = if self.at_word("ON") {
+            None
+        } else {
+            Some(self.name("an index name")?)
+        };
+        self.expect_word("ON")?;
+        let table = self.qualified_name("a table name")?;
+        self.expect_symbol("(")?;
+        let wrapped = [
+            ("KEYS", IndexTargetKind::Keys),
+            ("VALUES", IndexTargetKind::Values),
+            ("ENTRIES", IndexTargetKind::Entries),
+            ("FULL", IndexTargetKind::Full),
+        ]
+        .into_iter()
+        .find(|(kw, _)| self.at_word(kw) && self.at_symbol_n(1, "("));
+        let (column, target) = match wrapped {
+            Some((_, target)) => {
+                self.pos += 2;
+                let column = self.name("a column name")?;
+                self.expect_symbol(")")?;
+                (column, target)
+            }
+            None => (self.name("a column name")?, IndexTargetKind::Values),
+        };
+        self.expect_symbol(")")?;
+        let using = if self.eat_word("USING") {
+            Some(self.string("an index class")?)
+        } else {
+            None
+        };
+        let options = if self.eat_word("WITH") {
+            self.properties()?
+        } else {
+            Vec::new()
+        };
+        Ok(CreateIndex {
+            name,
+            if_not_exists,
+            custom,
+            table,
+            column,
+            target,
+            using,
+            options,
+            span: self.finish(start),
+        })
+    }
+
+    fn create_view(&mut self, start: usize) -> PResult<CreateMaterializedView> {
+        let if_not_exists = self.if_not_exists()?;
+        let name = self.qualified_name("a view name")?;
+        self.expect_words(&["AS", "SELECT"])?;
+        let selection = self.selection()?;
+        self.expect_word("FROM")?;
+        let base_table = self.qualified_name("a table name")?;
+        self.expect_word("WHERE")?;
+        let where_clause = self.relations()?;
+        self.expect_words(&["PRIMARY", "KEY"])?;
+        let primary_key = self.primary_key()?;
+        let options = if self.eat_word("WITH") {
+            self.table_options()?
+        } else {
+            Vec::new()
+        };
+        Ok(CreateMaterializedView {
+            name,
+            if_not_exists,
+            selection,
+            base_table,
+            where_clause,
+            primary_key,
+            options,
+            span: self.finish(start),
+        })
+    }
+
+    fn create_function(&mut self, start: usize, or_replace: bool) -> PResult<CreateFunction> {
+        let if_not_exists = self.if_not_exists()?;
+        let name = self.qualified_name("a function name")?;
+        let params = self.paren_list(|p| {
+            let param = p.name("a parameter name")?;
+            Ok((param, p.cql_type()?))
+        })?;
+        let called_on_null_input = if self.eat_word("CALLED") {
+            true
+        } else {
+            self.expect_words(&["RETURNS", "NULL"])?;
+            false
+        };
+        self.expect_words(&["ON", "NULL", "INPUT", "RETURNS"])?;
+        let return_type = self.cql_type()?;
+        self.expect_word("LANGUAGE")?;
+        let language = self.any_name("a language")?.as_str().to_string();
+        self.expect_word("AS")?;
+        let body = self.string("a function body")?;
+        Ok(CreateFunction {
+            or_replace,
+            if_not_exists,
+            name,
+            params,
+            called_on_null_input,
+            return_type,
+            language,
+            body,
+            span: self.finish(start),
+        })
+    }
+
+    fn create_aggregate(&mut self, start: usize, or_replace: bool) -> PResult<CreateAggregate> {
+        let if_not_exists = self.if_not_exists()?;
+        let name = self.qualified_name("an aggregate name")?;
+        let arg_types = self.paren_list(Self::cql_type)?;
+        self.expect_word("SFUNC")?;
+        let state_function = self.any_name("a function name")?;
+        self.expect_word("STYPE")?;
+        let state_type = self.cql_type()?;
+        let final_function = if self.eat_word("FINALFUNC") {
+            Some(self.any_name("a function name")?)
+        } else {
+            None
+        };
+        let initial_condition = if self.eat_word("INITCOND") {
+            Some(self.term()?)
+        } else {
+            None
+        };
+        Ok(CreateAggregate {
+            or_replace,
+            if_not_exists,
+            name,
+            arg_types,
+            state_function,
+            state_type,
+            final_function,
+            initial_condition,
+            span: self.finish(start),
+        })
+    }
+
+    fn alter(&mut self) -> PResult<Statement> {
+        let start = self.start();
+        self.expect_word("ALTER")?;
+        if self.eat_word("KEYSPACE") || self.eat_word("SCHEMA") {
+            let if_exists = self.if_exists()?;
+            let name = self.name("a keyspace name")?;
+            self.expect_word("WITH")?;
+            let options = self.properties()?;
+            return Ok(Statement::AlterKeyspace(AlterKeyspace {
+                name,
+                if_exists,
+                options,
+                span: self.finish(start),
+            }));
+        }
+        if self.eat_word("TABLE") || self.eat_word("COLUMNFAMILY") {
+            let if_exists = self.if_exists()?;
+            let name = self.qualified_name("a table name")?;
+            let action = self.alter_table_action()?;
+            return Ok(Statement::AlterTable(AlterTable {
+                name,
+                if_exists,
+                action,
+                span: self.finish(start),
+            }));
+        }
+        if self.eat_word("TYPE") {
+            let if_exists = self.if_exists()?;
+            let name = self.qualified_name("a type name")?;
+            let action = if self.eat_word("ADD") {
+                self.if_not_exists()?;
+                let field = self.name("a field name")?;
+                AlterTypeAction::Add(field, self.cql_type()?)
+            } else if self.eat_word("RENAME") {
+                self.if_exists()?;
+                AlterTypeAction::Rename(self.renames()?)
+            } else if self.eat_word("ALTER") {
+                let field = self.name("a field name")?;
+                self.expect_word("TYPE")?;
+                AlterTypeAction::AlterType(field, self.cql_type()?)
+            } else {
+                return Err(self.error("ADD, RENAME or ALTER"));
+            };
+            return Ok(Statement::AlterType(AlterType {
+                name,
+                if_exists,
+                action,
+                span: self.finish(start),
+            }));
+        }
+        if self.eat_words(&["MATERIALIZED", "VIEW"]) {
+            let if_exists = self.if_exists()?;
+            let name = self.qualified_name("a view name")?;
+            self.expect_word("WITH")?;
+            let options = self.table_options()?;
+            return Ok(Statement::AlterMaterializedView(AlterMaterializedView {
+                name,
+                if_exists,
+                options,
+                span: self.finish(start),
+            }));
+        }
+        if self.at_word("ROLE") || self.at_word("USER") {
+            return Ok(self.other(start, "ALTER".to_string()));
+        }
+        Err(self.error("KEYSPACE, TABLE, TYPE, MATERIALIZED VIEW or ROLE"))
+    }
+
+    fn alter_table_action(&mut self) -> PResult<AlterTableAction> {
+        if self.eat_word("ADD") {
+            self.if_not_exists()?;
+            let columns = if self.at_symbol("(") {
+                self.paren_list(Self::column_definition)?
+            } else {
+                vec![self.column_definition()?]
+            };
+            return Ok(AlterTableAction::Add(columns));
+        }
+        if self.eat_word("DROP") {
+            self.if_exists()?;
+            let columns = if self.at_symbol("(") {
+                self.paren_list(|p| p.name("a column name"))?
+            } else {
+                vec![self.name("a column name")?]
+            };
+            return Ok(AlterTableAction::Drop(columns));
+        }
+        if self.eat_word("RENAME") {
+            self.if_exists()?;
+            return self.renames().map(AlterTableAction::Rename);
+        }
+        if self.eat_word("ALTER") {
+            let column = self.name("a column name")?;
+            self.expect_word("TYPE")?;
+            return Ok(AlterTableAction::AlterType(column, self.cql_type()?));
+        }
+        if self.eat_word("WITH") {
+            return self.table_options().map(AlterTableAction::With);
+        }
+        Err(self.error("ADD, DROP, RENAME, ALTER or WITH"))
+    }
+
+    fn drop(&mut self) -> PResult<Statement> {
+        let start = self.start();
+        self.expect_word("DROP")?;
+        let kind = if self.eat_word("KEYSPACE") || self.eat_word("SCHEMA") {
+            ObjectKind::Keyspace
+        } else if self.eat_word("TABLE") || self.eat_word("COLUMNFAMILY") {
+            ObjectKind::Table
+        } else if self.eat_word("TYPE") {
+            ObjectKind::Type
+        } else if self.eat_word("INDEX") {
+            ObjectKind::Index
+        } else if self.eat_words(&["MATERIALIZED", "VIEW"]) {
+            ObjectKind::MaterializedView
+        } else if self.eat_word("FUNCTION") {
+            ObjectKind::Function
+        } else if self.eat_word("AGGREGATE") {
+            ObjectKind::Aggregate
+        } else if self.eat_word("TRIGGER") {
+            ObjectKind::Trigger
+        } else if self.at_word("ROLE") || self.at_word("USER") {
+            return Ok(self.other(start, "DROP".to_string()));
+        } else {
+            return Err(self.error(
+                "KEYSPACE, TABLE, TYPE, INDEX, MATERIALIZED VIEW, FUNCTION, AGGREGATE, TRIGGER or ROLE",
+            ));
+        };
+        let if_exists = self.if_exists()?;
+        let what = format!("a {} name", kind.label());
+        let name = if kind == ObjectKind::Keyspace {
+            QualifiedName {
+                keyspace: None,
+                name: self.name(&what)?,
+            }
+        } else {
+            self.qualified_name(&what)?
+        };
+        let signature = if matches!(kind, ObjectKind::Function | ObjectKind::Aggregate)
+            && self.at_symbol("(")
+        {
+            Some(self.paren_list(Self::cql_type)?)
+        } else {
+            None
+        };
+        let table = if kind == ObjectKind::Trigger {
+            self.expect_word("ON")?;
+            Some(self.qualified_name("a table name")?)
+        } else {
+            None
+        };
+        Ok(Statement::Drop(DropStatement {
+            kind,
+            name,
+            if_exists,
+            signature,
+            table,
+            span: self.finish(start),
+        }))
+    }
+}
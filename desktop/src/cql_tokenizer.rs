@@ -196,6 +196,56 @@ fn is_function(word: &str) -> bool {
 
 // ── Tokenizer ──────────────────────────────────────────────────
 
+/// Length of a UUID constant (`8-4-4-4-12` hex digits) starting at `i`
+fn uuid_len(bytes: &[u8], i: usize) -> Option<usize> {
+    const GROUPS: [usize; 5] = [8, 4, 4, 4, 12];
+    let mut j = i;
+    for (n, group) in GROUPS.iter().enumerate() {
+        if n > 0 {
+            if bytes.get(j) != Some(&b'-') {
+                return None;
+            }
+            j += 1;
+        }
+        let digits = bytes.get(j..j + group)?;
+        if !digits.iter().all(u8::is_ascii_hexdigit) {
+            return None;
+        }
+        j += group;
+    }
+    if bytes
+        .get(j)
+        .is_some_and(|b| b.is_ascii_alphanumeric() || *b == b'_')
+    {
+        return None;
+    }
+    Some(j - i)
+}
+
+/// Length of a duration constant such as `1h30m` or `250ms` starting at `i`
+fn duration_len(bytes: &[u8], i: usize) -> Option<usize> {
+    const UNITS: [&[u8]; 10] = [
+        b"mo", b"ms", b"us", b"ns", b"y", b"w", b"d", b"h", b"m", b"s",
+    ];
+    let mut j = i;
+    let mut units = 0;
+    while bytes.get(j).is_some_and(u8::is_ascii_digit) {
+        while bytes.get(j).is_some_and(u8::is_ascii_digit) {
+            j += 1;
+        }
+        let rest = &bytes[j..];
+        let unit = UNITS
+            .iter()
+            .find(|u| rest.len() >= u.len() && rest[..u.len()].eq_ignore_ascii_case(u))?;
+        j += unit.len();
+        units += 1;
+    }
+    let ends_word = !bytes
+        .get(j)
+        .is_some_and(|b| b.is_ascii_alphanumeric() || *b == b'_');
+    (units > 0 && ends_word).then_some(j - i)
+}
+
 pub fn tokenize(source: &str) -> Vec<Token> {
     let bytes = source.as_bytes();
     let len = bytes.len();
@@ -280,13 +330,33 @@ pub fn tokenize(source: &str) -> Vec<Token> {
             continue;
         }
 
-        // 5. Quoted identifier: "..."
-        if b == b'"' {
-            i += 1;
-            while i < len && bytes[i] != b'"' {
+        // Dollar-quoted string: $$...$$ (function bodies)
+        if b == b'$' && i + 1 < len && bytes[i + 1] == b'$' {
+            i += 2;
+            while i + 1 < len && !(bytes[i] == b'$' && bytes[i + 1] == b'$') {
                 i += 1;
             }
-            if i < len {
+            i = (i + 2).min(len);
+            tokens.push(Token {
+                kind: TokenKind::String,
+                text: source[start..i].to_string(),
+                start,
+            });
+            continue;
+        }
+
+        // 5. Quoted identifier: "..." (escapes "" inside)
+        if b == b'"' {
+            i += 1;
+            while i < len {
+                if bytes[i] == b'"' {
+                    i += 1;
+                    if i < len && bytes[i] == b'"' {
+                        i += 1;
+                        continue;
+                    }
+                    break;
+                }
                 i += 1;
             }
             tokens.push(Token {
@@ -297,16 +367,47 @@ pub fn tokenize(source: &str) -> Vec<Token> {
             continue;
         }
 
-        // 6. Number (integer or decimal)
+        // 6. Constants spelled with letters: UUIDs, blobs, durations, and
+        //    numbers (integer, decimal, exponent)
+        if let Some(n) = uuid_len(bytes, i) {
+            i += n;
+            tokens.push(Token {
+                kind: TokenKind::Number,
+                text: source[start..i].to_string(),
+                start,
+            });
+            continue;
+        }
         if b.is_ascii_digit() {
-            while i < len && bytes[i].is_ascii_digit() {
-                i += 1;
-            }
-            if i < len && bytes[i] == b'.' && i + 1 < len && bytes[i + 1].is_ascii_digit() {
-                i += 1;
+            if b == b'0' && i + 1 < len && matches!(bytes[i + 1], b'x' | b'X') {
+                i += 2;
+                while i < len && bytes[i].is_ascii_hexdigit() {
+                    i += 1;
+                }
+            } else if let Some(n) = duration_len(bytes, i) {
+                i += n;
+            } else {
                 while i < len && bytes[i].is_ascii_digit() {
                     i += 1;
                 }
+                if i < len && bytes[i] == b'.' && i + 1 < len && bytes[i + 1].is_ascii_digit() {
+                    i += 1;
+                    while i < len && bytes[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+                if i < len && matches!(bytes[i], b'e' | b'E') {
+                    let mut j = i + 1;
+                    if j < len && matches!(bytes[j], b'+' | b'-') {
+                        j += 1;
+                    }
+                    if j < len && bytes[j].is_ascii_digit() {
+                        i = j;
+                        while i < len && bytes[i].is_ascii_digit() {
+                            i += 1;
+                        }
+                    }
+                }
             }
             tokens.push(Token {
                 kind: TokenKind::Number,
@@ -400,7 +501,7 @@ pub fn tokenize(source: &str) -> Vec<Token> {
 
 // ── HTML output ────────────────────────────────────────────────
 
-fn html_escape(s: &str) -> String {
+pub(crate) fn html_escape(s: &str) -> String {
     let mut out = String::with_capacity(s.len());
     for ch in s.chars() {
         match ch {
@@ -429,17 +530,62 @@ fn css_class(kind: TokenKind) -> Option<&'static str> {
 }
 
 pub fn to_highlighted_html(tokens: &[Token]) -> String {
+    to_highlighted_html_marked(tokens, &[])
+}
+
+/// A byte range to underline in the highlighted HTML, e.g. an error
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Mark<'a> {
+    pub start: usize,
+    pub end: usize,
+    /// Extra CSS class of the mark, e.g. `cql-mark-error`
+    pub class: &'a str,
+    /// Hover text, kept in the `data-message` attribute
+    pub message: &'a str,
+}
+
+/// Like [`to_highlighted_html`], wrapping the text under each mark in a
+/// `span.cql-mark`. Tokens crossing a mark boundary are split there; where
+/// marks overlap, the first one wins.
+pub fn to_highlighted_html_marked(tokens: &[Token], marks: &[Mark]) -> String {
     let mut html = String::with_capacity(tokens.iter().map(|t| t.text.len() + 30).sum());
     for token in tokens {
-        let escaped = html_escape(&token.text);
-        if let Some(class) = css_class(token.kind) {
-            html.push_str("<span class=\"");
-            html.push_str(class);
-            html.push_str("\">");
-            html.push_str(&escaped);
-            html.push_str("</span>");
-        } else {
-            html.push_str(&escaped);
+        let end = token.start + token.text.len();
+        let mut cuts: Vec<usize> = marks
+            .iter()
+            .flat_map(|m| [m.start, m.end])
+            .filter(|&c| c > token.start && c < end && token.text.is_char_boundary(c - token.start))
+            .collect();
+        cuts.sort_unstable();
+        cuts.dedup();
+        cuts.push(end);
+
+        let mut from = token.start;
+        for to in cuts {
+            let mark = marks
+                .iter()
+                .find(|m| m.start < m.end && m.start <= from && to <= m.end);
+            if let Some(mark) = mark {
+                html.push_str("<span class=\"cql-mark ");
+                html.push_str(mark.class);
+                html.push_str("\" data-message=\"");
+                html.push_str(&html_escape(mark.message));
+                html.push_str("\">");
+            }
+            let escaped = html_escape(&token.text[from - token.start..to - token.start]);
+            if let Some(class) = css_class(token.kind) {
+                html.push_str("<span class=\"");
+                html.push_str(class);
+                html.push_str("\">");
+                html.push_str(&escaped);
+                html.push_str("</span>");
+            } else {
+                html.push_str(&escaped);
+            }
+            if mark.is_some() {
+                html.push_str("</span>");
+            }
+            from = to;
         }
     }
     html
@@ -447,16 +593,68 @@ pub fn to_highlighted_html(tokens: &[Token]) -> String {
 
 // ── Autocomplete helpers ───────────────────────────────────────
 
-/// Extract the partial word being typed at `cursor` position.
-/// Returns (partial_word, start_offset).
-pub fn word_at_cursor(source: &str, cursor: usize) -> (&str, usize) {
+/// The word being typed at the cursor, with the name before a dot when the
+/// word is qualified, e.g. `shop` and `ord` for `FROM shop.ord|`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CursorWord<'a> {
+    /// Keyspace (or other) name before the dot, as written; may be quoted
+    pub qualifier: Option<&'a str>,
+    /// The partial word after the dot, possibly empty
+    pub partial: &'a str,
+    /// Byte offset where `partial` starts
+    pub start: usize,
+}
+
+fn is_word_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_'
+}
+
+/// Extract the partial word being typed at `cursor` position, along with
+/// the identifier before it when the two are joined by a dot.
+pub fn word_at_cursor(source: &str, cursor: usize) -> CursorWord<'_> {
     let bytes = source.as_bytes();
     let end = cursor.min(bytes.len());
     let mut start = end;
-    while start > 0 && (bytes[start - 1].is_ascii_alphanumeric() || bytes[start - 1] == b'_') {
+    while start > 0 && is_word_byte(bytes[start - 1]) {
         start -= 1;
     }
-    (&source[start..end], start)
+    CursorWord {
+        qualifier: qualifier_before(source, start),
+        partial: &source[start..end],
+        start,
+    }
+}
+
+/// The identifier ending in a dot right before `start`: either a bare word
+/// not starting with a digit, or a double-quoted name
+fn qualifier_before(source: &str, start: usize) -> Option<&str> {
+    let bytes = source.as_bytes();
+    if start == 0 || bytes[start - 1] != b'.' {
+        return None;
+    }
+    let end = start - 1;
+    let mut i = end;
+    if i > 0 && bytes[i - 1] == b'"' {
+        // Walk back to the opening quote, stepping over doubled quotes
+        i -= 1;
+        loop {
+            let open = source[..i].rfind('"')?;
+            if open > 0 && bytes[open - 1] == b'"' {
+                i = open - 1;
+            } else {
+                i = open;
+                break;
+            }
+        }
+    } else {
+        while i > 0 && is_word_byte(bytes[i - 1]) {
+            i -= 1;
+        }
+        if i == end || bytes[i].is_ascii_digit() {
+            return None;
+        }
+    }
+    Some(&source[i..end])
 }
 
 /// Returns the keyword immediately before `cursor` that is not part of the
@@ -506,6 +704,101 @@ pub fn suggest_completions(partial: &str, limit: usize) -> Vec<&'static str> {
     results
 }
 
+// ── Statement helpers ──────────────────────────────────────────
+
+/// If `source` is a lone `USE <keyspace>` statement, return the keyspace name
+/// as Cassandra stores it: unquoted names fold to lowercase, quoted names are
+/// kept verbatim.
+pub fn parse_use_statement(source: &str) -> Option<String> {
+    let tokens = tokenize(source);
+    let mut significant = tokens
+        .iter()
+        .filter(|t| !matches!(t.kind, TokenKind::Whitespace | TokenKind::Comment));
+
+    let first = significant.next()?;
+    if !first.text.eq_ignore_ascii_case("USE") {
+        return None;
+    }
+
+    let name = significant.next()?;
+    let keyspace = match name.kind {
+        TokenKind::Identifier if name.text.starts_with('"') => {
+            let inner = name.text.strip_prefix('"')?.strip_suffix('"')?;
+            inner.replace("\"\"", "\"")
+        }
+        TokenKind::Identifier | TokenKind::Keyword | TokenKind::Type | TokenKind::Function => {
+            name.text.to_ascii_lowercase()
+        }
+        _ => return None,
+    };
+
+    match significant.next() {
+        None => {}
+        Some(t) if t.text == ";" && significant.next().is_none() => {}
+        Some(_) => return None,
+    }
+
+    if keyspace.is_empty() {
+        None
+    } else {
+        Some(keyspace)
+    }
+}
+
+/// Whether `source` starts with a statement that changes the schema
+/// (`CREATE`, `ALTER` or `DROP`), ignoring leading comments
+pub fn is_schema_change(source: &str) -> bool {
+    tokenize(source)
+        .iter()
+        .find(|t| !matches!(t.kind, TokenKind::Whitespace | TokenKind::Comment))
+        .is_some_and(|t| {
+            ["CREATE", "ALTER", "DROP"]
+                .iter()
+                .any(|kw| t.text.eq_ignore_ascii_case(kw))
+        })
+}
+
+/// Split a script into its statements, without the terminating `;`.
+///
+/// Semicolons inside strings, comments and `$$` function bodies do not end a
+/// statement, and a `BEGIN BATCH ... APPLY BATCH` block stays one statement.
+/// Segments holding only comments are dropped.
+pub fn split_statements(source: &str) -> Vec<String> {
+    let tokens = tokenize(source);
+    let mut statements = Vec::new();
+    // Significant tokens of the current statement: byte range and keyword state
+    let mut start: Option<usize> = None;
+    let mut end = 0;
+    let mut in_batch = false;
+    let mut previous_word = String::new();
+
+    for token in &tokens {
+        if matches!(token.kind, TokenKind::Whitespace | TokenKind::Comment) {
+            continue;
+        }
+        if token.text == ";" && !in_batch {
+            if let Some(from) = start.take() {
+                statements.push(source[from..end].to_string());
+            }
+            previous_word.clear();
+            continue;
+        }
+        let word = token.text.to_ascii_uppercase();
+        if start.is_none() {
+            start = Some(token.start);
+            in_batch = word == "BEGIN";
+        } else if in_batch && word == "BATCH" && previous_word == "APPLY" {
+            in_batch = false;
+        }
+        end = token.start + token.text.len();
+        previous_word = word;
+    }
+    if let Some(from) = start {
+        statements.push(source[from..end].to_string());
+    }
+    statements
+}
+
 // ── Tests ──────────────────────────────────────────────────────
 
 #[cfg(test)]
@@ -540,6 +833,40 @@ mod tests {
             .any(|t| t.kind == TokenKind::Variable && t.text == "{{table_name}}"));
     }
 
+    #[test]
+    fn tokenize_constants() {
+        let texts = |source: &str| -> Vec<(TokenKind, String)> {
+            tokenize(source)
+                .into_iter()
+                .filter(|t| t.kind != TokenKind::Whitespace)
+                .map(|t| (t.kind, t.text))
+                .collect()
+        };
+        for constant in [
+            "123e4567-e89b-12d3-a456-426614174000",
+            "ca11ab1e-0000-4000-8000-000000000001",
+            "0xCAFE",
+            "1h30m",
+            "250ms",
+            "1.5e-3",
+            "2E10",
+        ] {
+            assert_eq!(
+                texts(constant),
+                vec![(TokenKind::Number, constant.to_string())]
+            );
+        }
+        assert_eq!(
+            texts("\"a\"\"b\" 12abc"),
+            vec![
+                (TokenKind::Identifier, "\"a\"\"b\"".to_string()),
+                (TokenKind::Number, "12".to_string()),
+                (TokenKind::Identifier, "abc".to_string()),
+            ]
+        );
+        assert_eq!(texts("deadbeef")[0].0, TokenKind::Identifier);
+    }
+
     #[test]
     fn tokenize_types() {
         let tokens = tokenize("CREATE TABLE t (id uuid, name text)");
@@ -553,9 +880,30 @@ mod tests {
 
     #[test]
     fn word_at_cursor_mid() {
-        let (word, start) = word_at_cursor("SELECT * FRO", 12);
-        assert_eq!(word, "FRO");
-        assert_eq!(start, 9);
+        let word = word_at_cursor("SELECT * FRO", 12);
+        assert_eq!(word.partial, "FRO");
+        assert_eq!(word.start, 9);
+        assert_eq!(word.qualifier, None);
+    }
+
+    #[test]
+    fn word_at_cursor_qualified() {
+        let word = word_at_cursor("SELECT * FROM orders_ks.ite", 27);
+        assert_eq!(word.qualifier, Some("orders_ks"));
+        assert_eq!(word.partial, "ite");
+        assert_eq!(word.start, 24);
+
+        let word = word_at_cursor("SELECT * FROM orders_ks.", 24);
+        assert_eq!(word.qualifier, Some("orders_ks"));
+        assert_eq!(word.partial, "");
+
+        let source = "FROM \"My\"\"Ks\".t";
+        assert_eq!(
+            word_at_cursor(source, source.len()).qualifier,
+            Some("\"My\"\"Ks\"")
+        );
+        assert_eq!(word_at_cursor("SELECT 1.5", 10).qualifier, None);
+        assert_eq!(word_at_cursor("SELECT .5", 9).qualifier, None);
     }
 
     #[test]
@@ -571,9 +919,80 @@ mod tests {
         assert!(html.contains("&lt;"));
     }
 
+    #[test]
+    fn marks_split_tokens() {
+        let tokens = tokenize("SELECT nme FROM t");
+        let marks = [Mark {
+            start: 8,
+            end: 10,
+            class: "cql-mark-error",
+            message: "Unknown column \"nme\"",
+        }];
+        let html = to_highlighted_html_marked(&tokens, &marks);
+        assert!(html.contains(
+            ">n</span><span class=\"cql-mark cql-mark-error\" \
+             data-message=\"Unknown column &quot;nme&quot;\"><span class=\"cql-identifier\">me</span></span> "
+        ));
+        assert_eq!(
+            to_highlighted_html_marked(&tokens, &[]),
+            to_highlighted_html(&tokens)
+        );
+    }
+
+    #[test]
+    fn use_statement_detection() {
+        assert_eq!(parse_use_statement("USE shop;").as_deref(), Some("shop"));
+        assert_eq!(parse_use_statement("  use Shop  ").as_deref(), Some("shop"));
+        assert_eq!(
+            parse_use_statement("-- switch\nUSE \"MyKs\";").as_deref(),
+            Some("MyKs")
+        );
+        assert_eq!(parse_use_statement("USE users").as_deref(), Some("users"));
+        assert_eq!(parse_use_statement("SELECT * FROM shop.items"), None);
+        assert_eq!(parse_use_statement("USE shop; SELECT 1"), None);
+        assert_eq!(parse_use_statement("USE"), None);
+    }
+
     #[test]
     fn keyword_before_from() {
         let kw = keyword_before_cursor("SELECT * FROM tab", 14);
         assert_eq!(kw.as_deref(), Some("FROM"));
     }
+
+    #[test]
+    fn schema_change_detection() {
+        assert!(is_schema_change(
+            "CREATE TABLE shop.items (id int PRIMARY KEY)"
+        ));
+        assert!(is_schema_change("-- cleanup\n  drop table shop.items;"));
+        assert!(is_schema_change(
+            "ALTER TABLE shop.items WITH gc_grace_seconds = 0"
+        ));
+        assert!(!is_schema_change("SELECT * FROM shop.items"));
+        assert!(!is_schema_change("INSERT INTO shop.items (id) VALUES (1)"));
+    }
+
+    #[test]
+    fn split_statements_respects_literals_and_batches() {
+        let script = "-- setup\n\
+            CREATE TABLE t (id int PRIMARY KEY, note text);\n\
+            INSERT INTO t (id, note) VALUES (1, 'a; b');\n\
+            /* one; comment */\n\
+            BEGIN BATCH\n  INSERT INTO t (id) VALUES (2);\n  INSERT INTO t (id) VALUES (3);\nAPPLY BATCH;\n\
+            CREATE FUNCTION f(x int) RETURNS NULL ON NULL INPUT RETURNS int LANGUAGE java AS $$ return x; $$;\n\
+            -- trailing comment only;\n\
+            SELECT * FROM t";
+        let statements = split_statements(script);
+        assert_eq!(
+            statements,
+            vec![
+                "CREATE TABLE t (id int PRIMARY KEY, note text)",
+                "INSERT INTO t (id, note) VALUES (1, 'a; b')",
+                "BEGIN BATCH\n  INSERT INTO t (id) VALUES (2);\n  INSERT INTO t (id) VALUES (3);\nAPPLY BATCH",
+                "CREATE FUNCTION f(x int) RETURNS NULL ON NULL INPUT RETURNS int LANGUAGE java AS $$ return x; $$",
+                "SELECT * FROM t",
+            ]
+        );
+        assert!(split_statements("-- nothing here;\n;").is_empty());
+    }
 }
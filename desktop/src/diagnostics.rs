@@ -0,0 +1,910 @@
+//! Problems found in the editor's script while typing: syntax errors from
+//! [`cql_parser`], and names, literal types and key restrictions checked
+//! against the cached schema of the keyspaces the script uses.
+//!
+//! Checks only report what they can tell for sure: names in keyspaces whose
+//! schema isn't loaded, bind markers, function results and `{{variables}}`
+//! are taken as valid.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::cassandra::SchemaColumn;
+use crate::cql_parser::{
+    self, AssignOp, CqlType, InsertValues, Literal, Name, ObjectKind, Operator, ParsedScript,
+    QualifiedName, Relation, Selection, Span, Statement, Term,
+};
+use crate::identifier::Identifier;
+use crate::schema::{quote_identifier, KeyspaceSchema};
+use crate::schema_lint::Severity;
+use crate::virtual_tables::is_virtual_keyspace;
+
+/// One problem at a byte range of the script
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub span: Span,
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// What the checks know of the cluster
+pub struct SchemaContext<'a> {
+    /// Keyspace of unqualified names, until a `USE` in the script
+    pub default_keyspace: Option<&'a str>,
+    /// Every keyspace of the cluster; `None` when not known
+    pub keyspaces: Option<&'a [String]>,
+    /// Schemas loaded so far, by keyspace name
+    pub schemas: &'a HashMap<String, Arc<KeyspaceSchema>>,
+}
+
+/// Keyspaces whose schema the checks of `parsed` use, following `USE`
+/// statements through the script
+pub fn referenced_keyspaces(parsed: &ParsedScript, default_keyspace: Option<&str>) -> Vec<String> {
+    fn visit(statements: &[Statement], current: &mut Option<String>, found: &mut BTreeSet<String>) {
+        for statement in statements {
+            match statement {
+                Statement::Use { keyspace, .. } => *current = Some(keyspace.as_str().to_string()),
+                Statement::Batch(batch) => visit(&batch.statements, current, found),
+                _ => {
+                    let keyspace = statement.table().and_then(|t| match &t.keyspace {
+                        Some(ks) => Some(ks.as_str().to_string()),
+                        None => current.clone(),
+                    });
+                    found.extend(keyspace);
+                }
+            }
+        }
+    }
+
+    let mut current = default_keyspace.map(str::to_string);
+    let mut found = BTreeSet::new();
+    visit(&parsed.statements, &mut current, &mut found);
+    found.into_iter().collect()
+}
+
+/// Every problem of `source`, parsed as `parsed`, sorted by position
+pub fn diagnose(source: &str, parsed: &ParsedScript, context: &SchemaContext) -> Vec<Diagnostic> {
+    let mut checker = Checker {
+        context,
+        keyspace: context.default_keyspace.map(str::to_string),
+        created_keyspaces: HashSet::new(),
+        created_tables: HashSet::new(),
+        diagnostics: parsed
+            .errors
+            .iter()
+            .map(|e| Diagnostic {
+                span: visible_span(source, e.span),
+                severity: Severity::Error,
+                message: e.message.clone(),
+            })
+            .collect(),
+    };
+    for statement in &parsed.statements {
+        let span = statement.span();
+        // Clauses lost to a syntax error would show as missing restrictions
+        let broken = parsed.errors.iter().any(|e| span.contains(e.span.start));
+        checker.statement(statement, broken);
+    }
+    let mut diagnostics = checker.diagnostics;
+    diagnostics.sort_by_key(|d| d.span.start);
+    diagnostics
+}
+
+/// Empty spans, e.g. of a missing token, are widened to the character
+/// before them so there is something to underline
+fn visible_span(source: &str, span: Span) -> Span {
+    if span.start < span.end {
+        return span;
+    }
+    let start = span.start.min(source.len());
+    let before = source[..start].trim_end();
+    match before.chars().next_back() {
+        Some(c) => Span::new(before.len() - c.len_utf8(), before.len()),
+        None => Span::new(
+            start,
+            start + source[start..].chars().next().map_or(0, char::len_utf8),
+        ),
+    }
+}
+
+/// A table or materialized view the script refers to
+struct Table<'a> {
+    schema: &'a KeyspaceSchema,
+    name: &'a str,
+    columns: &'a [SchemaColumn],
+}
+
+impl<'a> Table<'a> {
+    fn column(&self, name: &Name) -> Option<&'a SchemaColumn> {
+        self.columns.iter().find(|c| c.name == name.as_str())
+    }
+
+    fn column_type(&self, name: &Name) -> Option<CqlType> {
+        self.column(name)
+            .and_then(|c| cql_parser::parse_type(&c.data_type).ok())
+    }
+
+    /// Partition key columns in key order
+    fn partition_key(&self) -> Vec<&'a SchemaColumn> {
+        let mut key: Vec<&SchemaColumn> = self
+            .columns
+            .iter()
+            .filter(|c| c.kind == "partition_key")
+            .collect();
+        key.sort_by_key(|c| c.position);
+        key
+    }
+
+    fn is_indexed(&self, column: &str) -> bool {
+        self.schema
+            .indexes
+            .iter()
+            .filter(|i| i.table == self.name)
+            .filter_map(|i| i.options.get("target"))
+            .any(|target| index_target(target) == column)
+    }
+
+    /// Type of `field` of the user type `cql_type`
+    fn field_type(&self, cql_type: &CqlType, field: &Name) -> Option<CqlType> {
+        let CqlType::UserDefined(name) = unfrozen(cql_type) else {
+            return None;
+        };
+        let user_type = self.schema.user_type(name.name.as_str())?;
+        let (_, field_type) = user_type.fields.iter().find(|(f, _)| f == field.as_str())?;
+        cql_parser::parse_type(field_type).ok()
+    }
+}
+
+/// Column of an index `target` option, e.g. `tags` for `values(tags)`
+fn index_target(target: &str) -> String {
+    let column = ["keys(", "values(", "entries(", "full("]
+        .iter()
+        .find_map(|prefix| target.strip_prefix(prefix)?.strip_suffix(')'))
+        .unwrap_or(target);
+    Identifier::parse(column)
+        .map(|id| id.as_str().to_string())
+        .unwrap_or_else(|_| column.to_string())
+}
+
+fn unfrozen(cql_type: &CqlType) -> &CqlType {
+    match cql_type {
+        CqlType::Frozen(inner) => unfrozen(inner),
+        other => other,
+    }
+}
+
+/// Type of the elements a `CONTAINS` or `c[key]` reaches in a collection
+fn element_type(cql_type: &CqlType) -> Option<&CqlType> {
+    match unfrozen(cql_type) {
+        CqlType::List(item) | CqlType::Set(item) | CqlType::Map(_, item) => Some(item),
+        _ => None,
+    }
+}
+
+/// What a literal term is, for messages; `None` for terms whose type is
+/// only known at execution, such as bind markers and function calls
+fn describe(term: &Term) -> Option<&'static str> {
+    Some(match term {
+        Term::Literal { value, .. } => match value {
+            Literal::String(_) => "a string",
+            Literal::Integer(_) => "an integer",
+            Literal::Float(_) | Literal::NaN | Literal::Infinity => "a decimal number",
+            Literal::Boolean(_) => "a boolean",
+            Literal::Uuid(_) => "a UUID",
+            Literal::Blob(_) => "a blob",
+            Literal::Duration(_) => "a duration",
+            Literal::Null => "null",
+        },
+        Term::List { .. } => "a list",
+        Term::Set { .. } => "a set",
+        Term::Map { .. } => "a map",
+        Term::Tuple { .. } => "a tuple",
+        Term::UserType { .. } => "a user type value",
+        _ => return None,
+    })
+}
+
+fn native_accepts(type_name: &str, literal: &Literal) -> bool {
+    match type_name {
+        "ascii" | "text" | "varchar" | "inet" => matches!(literal, Literal::String(_)),
+        "bigint" | "counter" | "int" | "smallint" | "tinyint" | "varint" => {
+            matches!(literal, Literal::Integer(_))
+        }
+        "decimal" | "double" | "float" => matches!(
+            literal,
+            Literal::Integer(_) | Literal::Float(_) | Literal::NaN | Literal::Infinity
+        ),
+        "boolean" => matches!(literal, Literal::Boolean(_)),
+        "uuid" | "timeuuid" => matches!(literal, Literal::Uuid(_)),
+        "blob" => matches!(literal, Literal::Blob(_)),
+        "date" | "time" | "timestamp" => {
+            matches!(literal, Literal::String(_) | Literal::Integer(_))
+        }
+        "duration" => matches!(literal, Literal::Duration(_) | Literal::String(_)),
+        _ => true,
+    }
+}
+
+/// The innermost part of `term` that can't be a `cql_type` value: its span,
+/// what it is and the type expected there
+fn mismatch(cql_type: &CqlType, term: &Term) -> Option<(Span, &'static str, String)> {
+    let cql_type = unfrozen(cql_type);
+    let found = describe(term)?;
+    let fits = match (cql_type, term) {
+        (
+            _,
+            Term::Literal {
+                value: Literal::Null,
+                ..
+            },
+        )
+        | (CqlType::Custom(_), _)
+        | (CqlType::UserDefined(_), Term::UserType { .. }) => true,
+        (CqlType::Native(name), Term::Literal { value, .. }) => native_accepts(name, value),
+        (CqlType::List(item), Term::List { items, .. })
+        | (CqlType::Set(item), Term::Set { items, .. }) => {
+            return items.iter().find_map(|i| mismatch(item, i));
+        }
+        (CqlType::Vector(item, size), Term::List { items, .. }) if items.len() == *size => {
+            return items.iter().find_map(|i| mismatch(item, i));
+        }
+        (CqlType::Map(key, value), Term::Map { entries, .. }) => {
+            return entries
+                .iter()
+                .find_map(|(k, v)| mismatch(key, k).or_else(|| mismatch(value, v)));
+        }
+        // `{}` is the empty set as well as the empty map
+        (CqlType::Set(_) | CqlType::UserDefined(_), Term::Map { entries, .. }) => {
+            entries.is_empty()
+        }
+        (CqlType::Map(..), Term::Set { items, .. }) => items.is_empty(),
+        (CqlType::Tuple(types), Term::Tuple { items, .. }) if types.len() == items.len() => {
+            return types.iter().zip(items).find_map(|(t, i)| mismatch(t, i));
+        }
+        _ => false,
+    };
+    (!fits).then(|| (term.span(), found, cql_type.to_string()))
+}
+
+fn quoted_list(names: &[&str]) -> String {
+    names
+        .iter()
+        .map(|n| format!("`{}`", quote_identifier(n)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+struct Checker<'a> {
+    context: &'a SchemaContext<'a>,
+    /// Keyspace of unqualified names at the current statement
+    keyspace: Option<String>,
+    /// Keyspaces and tables created earlier in the script
+    created_keyspaces: HashSet<String>,
+    created_tables: HashSet<(String, String)>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Checker<'a> {
+    fn report(&mut self, span: Span, severity: Severity, message: String) {
+        self.diagnostics.push(Diagnostic {
+            span,
+            severity,
+            message,
+        });
+    }
+
+    fn statement(&mut self, statement: &Statement, broken: bool) {
+        match statement {
+            Statement::Use { keyspace, .. } => {
+                self.keyspace_exists(keyspace);
+                self.keyspace = Some(keyspace.as_str().to_string());
+            }
+            Statement::Select(select) => {
+                let Some(table) = self.table(&select.table, true) else {
+                    return;
+                };
+                if let Selection::Columns(selectors) = &select.selection {
+                    for selector in selectors {
+                        self.term_columns(&table, &selector.term);
+                    }
+                }
+                self.relations(&table, &select.where_clause);
+                for name in select
+                    .group_by
+                    .iter()
+                    .chain(select.order_by.iter().map(|o| &o.column))
+                {
+                    self.column(&table, name);
+                }
+                let needs_key = !select.where_clause.is_empty()
+                    && !select.allow_filtering
+                    && !select.where_clause.iter().any(|r| {
+                        r.left
+                            .column()
+                            .is_some_and(|c| table.is_indexed(c.as_str()))
+                    });
+                if needs_key && !broken {
+                    self.partition_key(
+                        &table,
+                        &select.where_clause,
+                        &select.table,
+                        Severity::Warning,
+                    );
+                }
+            }
+            Statement::Insert(insert) => {
+                let Some(table) = self.table(&insert.table, false) else {
+                    return;
+                };
+                let InsertValues::Columns { columns, values } = &insert.values else {
+                    return;
+                };
+                for (i, name) in columns.iter().enumerate() {
+                    if let Some(column) = self.column(&table, name) {
+                        if let (Some(value), Ok(cql_type)) =
+                            (values.get(i), cql_parser::parse_type(&column.data_type))
+                        {
+                            self.check_value(name, &cql_type, value);
+                        }
+                    }
+                }
+                if broken {
+                    return;
+                }
+                let missing: Vec<&str> = table
+                    .columns
+                    .iter()
+                    .filter(|c| c.kind == "partition_key" || c.kind == "clustering")
+                    .filter(|c| !columns.iter().any(|n| n.as_str() == c.name))
+                    .map(|c| c.name.as_str())
+                    .collect();
+                if !missing.is_empty() {
+                    let message = match missing[..] {
+                        [_] => format!("Missing primary key column {}", quoted_list(&missing)),
+                        _ => format!("Missing primary key columns {}", quoted_list(&missing)),
+                    };
+                    self.report(insert.table.span(), Severity::Error, message);
+                }
+            }
+            Statement::Update(update) => {
+                let Some(table) = self.table(&update.table, false) else {
+                    return;
+                };
+                for assignment in &update.assignments {
+                    self.term_columns(&table, &assignment.value);
+                    let Some(column) = self.column(&table, &assignment.column) else {
+                        continue;
+                    };
+                    let Ok(cql_type) = cql_parser::parse_type(&column.data_type) else {
+                        continue;
+                    };
+                    let expected = match (&assignment.key, &assignment.field, assignment.op) {
+                        (Some(key), _, _) => {
+                            match unfrozen(&cql_type) {
+                                CqlType::Map(key_type, _) => {
+                                    self.check_value(&assignment.column, key_type, key)
+                                }
+                                CqlType::List(_) => self.check_value(
+                                    &assignment.column,
+                                    &CqlType::Native("int".to_string()),
+                                    key,
+                                ),
+                                _ => {}
+                            }
+                            element_type(&cql_type).cloned()
+                        }
+                        (None, Some(field), _) => table.field_type(&cql_type, field),
+                        (None, None, AssignOp::Set) => Some(cql_type),
+                        // `c = c + v` adds to a counter or appends to a collection
+                        (None, None, AssignOp::Add | AssignOp::Prepend) => {
+                            match unfrozen(&cql_type) {
+                                CqlType::Native(name) if name == "counter" => Some(cql_type),
+                                CqlType::List(_) | CqlType::Set(_) | CqlType::Map(..) => {
+                                    Some(cql_type)
+                                }
+                                _ => None,
+                            }
+                        }
+                        // Keys are removed from a map by a set of keys
+                        (None, None, AssignOp::Subtract) => match unfrozen(&cql_type) {
+                            CqlType::Map(key, _) => Some(CqlType::Set(key.clone())),
+                            CqlType::Native(name) if name == "counter" => Some(cql_type),
+                            CqlType::List(_) | CqlType::Set(_) => Some(cql_type),
+                            _ => None,
+                        },
+                    };
+                    if let Some(expected) = expected {
+                        self.check_value(&assignment.column, &expected, &assignment.value);
+                    }
+                }
+                self.relations(&table, &update.where_clause);
+                if !broken {
+                    self.partition_key(
+                        &table,
+                        &update.where_clause,
+                        &update.table,
+                        Severity::Error,
+                    );
+                }
+            }
+            Statement::Delete(delete) => {
+                let Some(table) = self.table(&delete.table, false) else {
+                    return;
+                };
+                for term in &delete.columns {
+                    self.term_columns(&table, term);
+                }
+                self.relations(&table, &delete.where_clause);
+                if !broken {
+                    self.partition_key(
+                        &table,
+                        &delete.where_clause,
+                        &delete.table,
+                        Severity::Error,
+                    );
+                }
+            }
+            Statement::Batch(batch) => {
+                for statement in &batch.statements {
+                    self.statement(statement, broken);
+                }
+            }
+            Statement::Truncate { table, .. } => {
+                self.table(table, false);
+            }
+            Statement::CreateKeyspace(create) => {
+                self.created_keyspaces
+                    .insert(create.name.as_str().to_string());
+            }
+            Statement::CreateTable(create) => self.created(&create.name),
+            Statement::CreateMaterializedView(create) => {
+                self.table(&create.base_table, false);
+                self.created(&create.name);
+            }
+            Statement::AlterTable(alter) if !alter.if_exists => {
+                self.table(&alter.name, false);
+            }
+            Statement::CreateIndex(create) => {
+                if let Some(table) = self.table(&create.table, false) {
+                    self.column(&table, &create.column);
+                }
+            }
+            Statement::Drop(drop) if !drop.if_exists => match drop.kind {
+                ObjectKind::Keyspace => {
+                    self.keyspace_exists(&drop.name.name);
+                }
+                ObjectKind::Table | ObjectKind::MaterializedView => {
+                    self.table(&drop.name, true);
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    fn created(&mut self, name: &QualifiedName) {
+        let keyspace = match &name.keyspace {
+            Some(ks) => Some(ks.as_str().to_string()),
+            None => self.keyspace.clone(),
+        };
+        if let Some(keyspace) = keyspace {
+            self.created_tables
+                .insert((keyspace, name.name.as_str().to_string()));
+        }
+    }
+
+    /// Whether `name` may be a keyspace, reporting it when it surely isn't
+    fn keyspace_exists(&mut self, name: &Name) -> bool {
+        let Some(keyspaces) = self.context.keyspaces else {
+            return true;
+        };
+        let keyspace = name.as_str();
+        if name.is_template()
+            || is_virtual_keyspace(keyspace)
+            || self.created_keyspaces.contains(keyspace)
+            || keyspaces.iter().any(|k| k == keyspace)
+        {
+            return true;
+        }
+        self.report(
+            name.span,
+            Severity::Error,
+            format!("Unknown keyspace `{}`", name.ident),
+        );
+        false
+    }
+
+    /// The table or view `name`, when its keyspace's schema is loaded.
+    /// Materialized views are read-only, so only found when `views`.
+    fn table(&mut self, name: &QualifiedName, views: bool) -> Option<Table<'a>> {
+        if name.name.is_template() {
+            return None;
+        }
+        let keyspace = match &name.keyspace {
+            Some(ks) => {
+                if !self.keyspace_exists(ks) {
+                    return None;
+                }
+                ks.as_str().to_string()
+            }
+            None => self.keyspace.clone()?,
+        };
+        let table_name = name.name.as_str();
+        if is_virtual_keyspace(&keyspace)
+            || self
+                .created_tables
+                .contains(&(keyspace.clone(), table_name.to_string()))
+        {
+            return None;
+        }
+        let schema: &'a KeyspaceSchema = self.context.schemas.get(&keyspace)?;
+        if let Some(table) = schema.table(table_name) {
+            return Some(Table {
+                schema,
+                name: &table.name,
+                columns: &table.columns,
+            });
+        }
+        let message = match schema.view(table_name) {
+            Some(view) if views => {
+                return Some(Table {
+                    schema,
+                    name: &view.name,
+                    columns: &view.columns,
+                })
+            }
+            Some(_) => format!(
+                "`{}` is a materialized view, which can't be written to",
+                name.name.ident
+            ),
+            None => format!(
+                "Unknown table `{}` in keyspace `{}`",
+                name.name.ident,
+                quote_identifier(&keyspace)
+            ),
+        };
+        self.report(name.name.span, Severity::Error, message);
+        None
+    }
+
+    /// The column `name` of `table`, reporting it when missing
+    fn column(&mut self, table: &Table<'a>, name: &Name) -> Option<&'a SchemaColumn> {
+        if name.is_template() {
+            return None;
+        }
+        let column = table.column(name);
+        if column.is_none() {
+            self.report(
+                name.span,
+                Severity::Error,
+                format!(
+                    "Unknown column `{}` in `{}`",
+                    name.ident,
+                    quote_identifier(table.name)
+                ),
+            );
+        }
+        column
+    }
+
+    /// Checks the columns a term refers to
+    fn term_columns(&mut self, table: &Table<'a>, term: &Term) {
+        match term {
+            Term::Column(name) => {
+                self.column(table, name);
+            }
+            Term::FunctionCall { args: items, .. }
+            | Term::List { items, .. }
+            | Term::Set { items, .. }
+            | Term::Tuple { items, .. } => {
+                for item in items {
+                    self.term_columns(table, item);
+                }
+            }
+            Term::Map { entries, .. } => {
+                for (key, value) in entries {
+                    self.term_columns(table, key);
+                    self.term_columns(table, value);
+                }
+            }
+            Term::UserType { fields, .. } => {
+                for (_, value) in fields {
+                    self.term_columns(table, value);
+                }
+            }
+            Term::Cast { term, .. } | Term::TypeHint { term, .. } => self.term_columns(table, term),
+            Term::Binary { left, right, .. }
+            | Term::Element {
+                base: left,
+                key: right,
+                ..
+            } => {
+                self.term_columns(table, left);
+                self.term_columns(table, right);
+            }
+            Term::Field { base, .. } => self.term_columns(table, base),
+            Term::Literal { .. }
+            | Term::BindMarker { .. }
+            | Term::Variable { .. }
+            | Term::Wildcard(_) => {}
+        }
+    }
+
+    fn relations(&mut self, table: &Table<'a>, relations: &[Relation]) {
+        for relation in relations {
+            self.term_columns(table, &relation.left);
+            let Some(right) = &relation.right else {
+                continue;
+            };
+            self.term_columns(table, right);
+
+            let (name, cql_type) = match &relation.left {
+                Term::Column(name) => (name, table.column_type(name)),
+                Term::Element { base, .. } => match base.as_ref() {
+                    Term::Column(name) => (
+                        name,
+                        table
+                            .column_type(name)
+                            .and_then(|t| element_type(&t).cloned()),
+                    ),
+                    _ => continue,
+                },
+                _ => continue,
+            };
+            let Some(cql_type) = cql_type else {
+                continue;
+            };
+            match relation.op {
+                Operator::In => {
+                    if let Term::Tuple { items, .. } = right {
+                        for item in items {
+                            self.check_value(name, &cql_type, item);
+                        }
+                    }
+                }
+                Operator::Contains => {
+                    if let Some(item) = element_type(&cql_type) {
+                        self.check_value(name, item, right);
+                    }
+                }
+                Operator::ContainsKey => {
+                    if let CqlType::Map(key, _) = unfrozen(&cql_type) {
+                        self.check_value(name, key, right);
+                    }
+                }
+                Operator::Like | Operator::IsNotNull => {}
+                _ => self.check_value(name, &cql_type, right),
+            }
+        }
+    }
+
+    fn check_value(&mut self, column: &Name, cql_type: &CqlType, value: &Term) {
+        if let Some((span, found, expected)) = mismatch(cql_type, value) {
+            self.report(
+                span,
+                Severity::Error,
+                format!(
+                    "Expected {} for `{}`, found {}",
+                    expected, column.ident, found
+                ),
+            );
+        }
+    }
+
+    /// Reports partition key columns not restricted by `=` or `IN`, unless
+    /// the statement selects by token range
+    fn partition_key(
+        &mut self,
+        table: &Table<'a>,
+        where_clause: &[Relation],
+        table_name: &QualifiedName,
+        severity: Severity,
+    ) {
+        let by_token = where_clause.iter().any(
+            |r| matches!(&r.left, Term::FunctionCall { name, .. } if name.name.as_str() == "token"),
+        );
+        if by_token {
+            return;
+        }
+        let missing: Vec<&str> = table
+            .partition_key()
+            .into_iter()
+            .filter(|c| {
+                !where_clause.iter().any(|r| {
+                    matches!(r.op, Operator::Eq | Operator::In)
+                        && matches!(&r.left, Term::Column(n) if n.as_str() == c.name)
+                })
+            })
+            .map(|c| c.name.as_str())
+            .collect();
+        if missing.is_empty() {
+            return;
+        }
+        let subject = match missing[..] {
+            [_] => format!("Partition key column {} is", quoted_list(&missing)),
+            _ => format!("Partition key columns {} are", quoted_list(&missing)),
+        };
+        let message = match severity {
+            Severity::Error => format!("{} not restricted by = or IN", subject),
+            _ => format!(
+                "{} not restricted, so the query scans every partition and needs ALLOW FILTERING",
+                subject
+            ),
+        };
+        let span = match (where_clause.first(), where_clause.last()) {
+            (Some(first), Some(last)) => first.span.to(last.span),
+            _ => table_name.span(),
+        };
+        self.report(span, severity, message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::{IndexDef, TableOptions, ViewDef};
+    use crate::test_fixtures::{column, keyspace, table};
+
+    fn shop() -> KeyspaceSchema {
+        let orders = vec![
+            column("tenant", "uuid", "partition_key", 0),
+            column("day", "text", "partition_key", 1),
+            column("id", "int", "clustering", 0),
+            column("total", "decimal", "regular", -1),
+            column("status", "text", "regular", -1),
+            column("tags", "set<text>", "regular", -1),
+            column("attrs", "map<text, int>", "regular", -1),
+        ];
+        let mut schema = keyspace("shop", vec![table("shop", "orders", orders.clone())]);
+        schema.indexes.push(IndexDef {
+            keyspace: "shop".to_string(),
+            table: "orders".to_string(),
+            name: "orders_status".to_string(),
+            kind: "COMPOSITES".to_string(),
+            options: [("target".to_string(), "status".to_string())].into(),
+        });
+        schema.views.push(ViewDef {
+            keyspace: "shop".to_string(),
+            name: "orders_by_status".to_string(),
+            base_table: "orders".to_string(),
+            include_all_columns: true,
+            where_clause: String::new(),
+            columns: orders,
+            options: TableOptions::default(),
+        });
+        schema
+    }
+
+    /// Messages of the diagnostics of `source`, with the text they underline
+    fn check(source: &str) -> Vec<(String, String)> {
+        let schemas = HashMap::from([("shop".to_string(), Arc::new(shop()))]);
+        let keyspaces = vec!["shop".to_string(), "system".to_string()];
+        let context = SchemaContext {
+            default_keyspace: Some("shop"),
+            keyspaces: Some(&keyspaces),
+            schemas: &schemas,
+        };
+        diagnose(source, &cql_parser::parse(source), &context)
+            .into_iter()
+            .map(|d| (source[d.span.start..d.span.end].to_string(), d.message))
+            .collect()
+    }
+
+    const KEY: &str = "tenant = ? AND day = 'mon' AND id = 1";
+
+    #[test]
+    fn test_syntax_errors_are_underlined() {
+        let source = "SELECT * FROM orders WHERE  ";
+        let diagnostics = check(source);
+        assert_eq!(
+            diagnostics,
+            vec![(
+                "E".to_string(),
+                "Expected a column name, found end of statement".to_string()
+            )]
+        );
+        assert_eq!(visible_span("", Span::new(0, 0)), Span::new(0, 0));
+    }
+
+    #[test]
+    fn test_unknown_keyspaces_tables_and_columns() {
+        let source = format!(
+            "SELECT id, totl, writetime(stat) FROM orders WHERE {KEY} ORDER BY idd;\n\
+             SELECT * FROM shop.refunds;\n\
+             SELECT * FROM shopp.orders;\n\
+             SELECT * FROM system.local;\n\
+             INSERT INTO orders_by_status (tenant) VALUES (?);\n\
+             CREATE TABLE drafts (id int PRIMARY KEY);\n\
+             INSERT INTO drafts (id, x) VALUES (1, 2);\n\
+             SELECT {{{{col}}}} FROM {{{{table}}}};"
+        );
+        let found: Vec<(String, String)> = check(&source);
+        let expected = [
+            ("totl", "Unknown column `totl` in `orders`"),
+            ("stat", "Unknown column `stat` in `orders`"),
+            ("idd", "Unknown column `idd` in `orders`"),
+            ("refunds", "Unknown table `refunds` in keyspace `shop`"),
+            ("shopp", "Unknown keyspace `shopp`"),
+            (
+                "orders_by_status",
+                "`orders_by_status` is a materialized view, which can't be written to",
+            ),
+        ];
+        assert_eq!(
+            found,
+            expected
+                .iter()
+                .map(|(t, m)| (t.to_string(), m.to_string()))
+                .collect::<Vec<_>>()
+        );
+
+        // Names in keyspaces without a loaded schema go unchecked
+        let parsed =
+            cql_parser::parse("USE other; SELECT nothing FROM anywhere; SELECT * FROM shop.orders");
+        assert_eq!(
+            referenced_keyspaces(&parsed, Some("shop")),
+            vec!["other".to_string(), "shop".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_literal_type_mismatches() {
+        let source = format!(
+            "UPDATE orders SET total = 'abc', tags = tags + {{1}}, attrs['k'] = 'v', \
+             status = null, attrs = attrs - {{'k'}} WHERE {KEY};\n\
+             SELECT * FROM orders WHERE tenant = 5 AND day IN ('mon', 2) AND id = ? \
+             AND tags CONTAINS 'x' AND attrs CONTAINS KEY 3;\n\
+             INSERT INTO orders (tenant, day, id, total) VALUES (now(), 'tue', 1.5, 1e3)"
+        );
+        let found: Vec<String> = check(&source)
+            .into_iter()
+            .map(|(t, m)| format!("{t}: {m}"))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                "'abc': Expected decimal for `total`, found a string",
+                "1: Expected text for `tags`, found an integer",
+                "'v': Expected int for `attrs`, found a string",
+                "5: Expected uuid for `tenant`, found an integer",
+                "2: Expected text for `day`, found an integer",
+                "3: Expected text for `attrs`, found an integer",
+                "1.5: Expected int for `id`, found a decimal number",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_partition_key_restrictions() {
+        let found = check(
+            "SELECT * FROM orders WHERE tenant = ? AND id > 1;\n\
+             SELECT * FROM orders WHERE tenant = ? ALLOW FILTERING;\n\
+             SELECT * FROM orders WHERE token(tenant, day) > 0;\n\
+             SELECT * FROM orders WHERE status = 'open';\n\
+             SELECT * FROM orders;\n\
+             DELETE FROM orders WHERE id = 1;\n\
+             INSERT INTO orders (tenant, total) VALUES (?, 1)",
+        );
+        let messages: Vec<(&str, &str)> = found
+            .iter()
+            .map(|(t, m)| (t.as_str(), m.as_str()))
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                (
+                    "tenant = ? AND id > 1",
+                    "Partition key column `day` is not restricted, so the query scans every \
+                     partition and needs ALLOW FILTERING"
+                ),
+                (
+                    "id = 1",
+                    "Partition key columns `tenant`, `day` are not restricted by = or IN"
+                ),
+                ("orders", "Missing primary key columns `day`, `id`"),
+            ]
+        );
+        // A statement with syntax errors isn't checked for lost restrictions
+        assert_eq!(check("DELETE FROM orders WHERE tenant = ? AND").len(), 1);
+    }
+}
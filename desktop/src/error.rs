@@ -1,5 +1,18 @@
+use cdrs_tokio::frame::message_error::{ErrorBody, ErrorType};
 use thiserror::Error;
 
+/// Native protocol error codes surfaced by Cassandra (see `native_protocol_v4.spec`, section 9)
+pub mod codes {
+    pub const SERVER_ERROR: i32 = 0x0000;
+    pub const UNAVAILABLE: i32 = 0x1000;
+    pub const WRITE_TIMEOUT: i32 = 0x1100;
+    pub const READ_TIMEOUT: i32 = 0x1200;
+    pub const SYNTAX_ERROR: i32 = 0x2000;
+    pub const UNAUTHORIZED: i32 = 0x2100;
+    pub const INVALID: i32 = 0x2200;
+    pub const ALREADY_EXISTS: i32 = 0x2400;
+}
+
 /// Main error type for the Magda application
 #[derive(Debug, Error)]
 pub enum MagdaError {
@@ -30,13 +43,160 @@ pub enum MagdaError {
     #[error("Database driver error: {0}")]
     DriverError(String),
 
+    /// Not enough live replicas to satisfy the requested consistency level
+    #[error("Unavailable at {consistency}: {required} replicas required but only {alive} alive")]
+    Unavailable {
+        message: String,
+        consistency: String,
+        required: i32,
+        alive: i32,
+    },
+
+    /// Coordinator timed out waiting for replica read responses
+    #[error("Read timeout at {consistency}: {received} of {block_for} replicas responded")]
+    ReadTimeout {
+        message: String,
+        consistency: String,
+        received: i32,
+        block_for: i32,
+        data_present: bool,
+    },
+
+    /// Coordinator timed out waiting for replica write acknowledgements
+    #[error("Write timeout at {consistency} ({write_type}): {received} of {block_for} replicas acknowledged")]
+    WriteTimeout {
+        message: String,
+        consistency: String,
+        received: i32,
+        block_for: i32,
+        write_type: String,
+    },
+
+    #[error("Syntax error: {0}")]
+    SyntaxError(String),
+
+    #[error("Invalid query: {0}")]
+    InvalidQuery(String),
+
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+
+    /// Keyspace or table creation collided with an existing one.
+    /// `table` is empty when the conflict is on the keyspace itself.
+    #[error("Already exists: {message}")]
+    AlreadyExists {
+        message: String,
+        keyspace: String,
+        table: String,
+    },
+
+    /// Any other error returned by the server, with its native protocol code
+    #[error("Server error 0x{code:04X}: {message}")]
+    ServerError { code: i32, message: String },
+
     #[error("Unknown error occurred")]
     Unknown,
 }
 
 impl From<cdrs_tokio::error::Error> for MagdaError {
     fn from(error: cdrs_tokio::error::Error) -> Self {
-        MagdaError::QueryError(error.to_string())
+        match error {
+            cdrs_tokio::error::Error::Server { body, .. } => MagdaError::from(body),
+            other => MagdaError::QueryError(other.to_string()),
+        }
+    }
+}
+
+impl From<ErrorBody> for MagdaError {
+    fn from(body: ErrorBody) -> Self {
+        let ErrorBody { message, ty } = body;
+        match ty {
+            ErrorType::Unavailable(e) => Self::Unavailable {
+                message,
+                consistency: protocol_name(&format!("{:?}", e.cl)),
+                required: e.required,
+                alive: e.alive,
+            },
+            ErrorType::ReadTimeout(e) => Self::ReadTimeout {
+                message,
+                consistency: protocol_name(&format!("{:?}", e.cl)),
+                received: e.received,
+                block_for: e.block_for,
+                data_present: e.replica_has_responded(),
+            },
+            ErrorType::WriteTimeout(e) => Self::WriteTimeout {
+                message,
+                consistency: protocol_name(&format!("{:?}", e.cl)),
+                received: e.received,
+                block_for: e.block_for,
+                write_type: match e.write_type {
+                    cdrs_tokio::frame::message_error::WriteType::Unknown(wt) => wt,
+                    wt => protocol_name(&format!("{:?}", wt)),
+                },
+            },
+            ErrorType::Syntax => Self::SyntaxError(message),
+            ErrorType::Invalid => Self::InvalidQuery(message),
+            ErrorType::Unauthorized => Self::Unauthorized(message),
+            ErrorType::Authentication => Self::AuthError(message),
+            ErrorType::AlreadyExists(e) => Self::AlreadyExists {
+                message,
+                keyspace: e.ks,
+                table: e.table,
+            },
+            other => Self::ServerError {
+                code: other.to_error_code(),
+                message,
+            },
+        }
+    }
+}
+
+/// Convert a driver enum name (`LocalQuorum`, `UnloggedBatch`) to its
+/// protocol spelling (`LOCAL_QUORUM`, `UNLOGGED_BATCH`).
+fn protocol_name(debug_name: &str) -> String {
+    let mut out = String::with_capacity(debug_name.len() + 4);
+    for (i, ch) in debug_name.chars().enumerate() {
+        if ch.is_ascii_uppercase() && i > 0 {
+            out.push('_');
+        }
+        out.push(ch.to_ascii_uppercase());
+    }
+    out
+}
+
+/// Display-ready snapshot of an error, used by the UI error panels
+#[derive(Clone, Debug, PartialEq)]
+pub struct ErrorReport {
+    pub title: String,
+    pub explanation: String,
+    pub message: String,
+    pub details: Vec<(String, String)>,
+}
+
+impl ErrorReport {
+    /// Build a report for a client-side condition that has no `MagdaError`
+    pub fn plain(title: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            explanation: String::new(),
+            message: message.into(),
+            details: Vec::new(),
+        }
+    }
+}
+
+impl From<&MagdaError> for ErrorReport {
+    fn from(error: &MagdaError) -> Self {
+        Self {
+            title: error.title().to_string(),
+            explanation: error.user_message(),
+            message: error.to_string(),
+            details: error
+                .details()
+                .into_iter()
+                .map(|(label, value)| (label.to_string(), value))
+                .collect(),
+        }
     }
 }
 
@@ -63,10 +223,102 @@ impl MagdaError {
     pub fn is_recoverable(&self) -> bool {
         matches!(
             self,
-            Self::ConnectionError(_) | Self::QueryError(_) | Self::AuthError(_)
+            Self::ConnectionError(_)
+                | Self::QueryError(_)
+                | Self::AuthError(_)
+                | Self::Unavailable { .. }
+                | Self::ReadTimeout { .. }
+                | Self::WriteTimeout { .. }
         )
     }
 
+    /// Native protocol error code, for errors reported by the server
+    pub fn error_code(&self) -> Option<i32> {
+        match self {
+            Self::Unavailable { .. } => Some(codes::UNAVAILABLE),
+            Self::ReadTimeout { .. } => Some(codes::READ_TIMEOUT),
+            Self::WriteTimeout { .. } => Some(codes::WRITE_TIMEOUT),
+            Self::SyntaxError(_) => Some(codes::SYNTAX_ERROR),
+            Self::InvalidQuery(_) => Some(codes::INVALID),
+            Self::Unauthorized(_) => Some(codes::UNAUTHORIZED),
+            Self::AlreadyExists { .. } => Some(codes::ALREADY_EXISTS),
+            Self::ServerError { code, .. } => Some(*code),
+            _ => None,
+        }
+    }
+
+    /// Short title for error panels
+    pub fn title(&self) -> &'static str {
+        match self {
+            Self::ConnectionError(_) => "Connection error",
+            Self::AuthError(_) => "Authentication failed",
+            Self::Unavailable { .. } => "Replicas unavailable",
+            Self::ReadTimeout { .. } => "Read timeout",
+            Self::WriteTimeout { .. } => "Write timeout",
+            Self::SyntaxError(_) => "Syntax error",
+            Self::InvalidQuery(_) => "Invalid query",
+            Self::Unauthorized(_) => "Unauthorized",
+            Self::AlreadyExists { .. } => "Already exists",
+            Self::ServerError { .. } => "Server error",
+            Self::ValidationError(_) => "Invalid input",
+            _ => "Error",
+        }
+    }
+
+    /// Structured fields worth showing alongside the message, as (label, value) pairs
+    pub fn details(&self) -> Vec<(&'static str, String)> {
+        let mut details = Vec::new();
+        if let Some(code) = self.error_code() {
+            details.push(("Code", format!("0x{:04X}", code)));
+        }
+        match self {
+            Self::Unavailable {
+                consistency,
+                required,
+                alive,
+                ..
+            } => {
+                details.push(("Consistency", consistency.clone()));
+                details.push(("Required", required.to_string()));
+                details.push(("Alive", alive.to_string()));
+            }
+            Self::ReadTimeout {
+                consistency,
+                received,
+                block_for,
+                data_present,
+                ..
+            } => {
+                details.push(("Consistency", consistency.clone()));
+                details.push(("Received", received.to_string()));
+                details.push(("Block for", block_for.to_string()));
+                details.push(("Data present", data_present.to_string()));
+            }
+            Self::WriteTimeout {
+                consistency,
+                received,
+                block_for,
+                write_type,
+                ..
+            } => {
+                details.push(("Consistency", consistency.clone()));
+                details.push(("Received", received.to_string()));
+                details.push(("Block for", block_for.to_string()));
+                details.push(("Write type", write_type.clone()));
+            }
+            Self::AlreadyExists {
+                keyspace, table, ..
+            } => {
+                details.push(("Keyspace", keyspace.clone()));
+                if !table.is_empty() {
+                    details.push(("Table", table.clone()));
+                }
+            }
+            _ => {}
+        }
+        details
+    }
+
     /// Get a user-friendly error message
     pub fn user_message(&self) -> String {
         match self {
@@ -74,14 +326,149 @@ impl MagdaError {
                 "Unable to connect to the database. Please check your connection settings."
                     .to_string()
             }
-            Self::QueryError(_) => {
-                "Query execution failed. Please check your CQL syntax.".to_string()
-            }
+            Self::QueryError(_) => "Query execution failed.".to_string(),
             Self::AuthError(_) => {
                 "Authentication failed. Please verify your credentials.".to_string()
             }
             Self::ValidationError(msg) => format!("Invalid input: {}", msg),
+            Self::Unavailable {
+                consistency,
+                required,
+                alive,
+                ..
+            } => format!(
+                "The coordinator knows only {} of the {} replicas needed for {} are up, so it did not attempt the request. \
+                 Bring the down nodes back or retry with a lower consistency level.",
+                alive, required, consistency
+            ),
+            Self::ReadTimeout {
+                consistency,
+                received,
+                block_for,
+                data_present,
+                ..
+            } => {
+                let data = if *data_present {
+                    "The replica asked for data did respond"
+                } else {
+                    "The replica asked for data did not respond"
+                };
+                format!(
+                    "Only {} of {} replicas answered within the read timeout at {}. {}. \
+                     The cluster may be overloaded, or the query may scan too much data (large partitions, tombstones, ALLOW FILTERING).",
+                    received, block_for, consistency, data
+                )
+            }
+            Self::WriteTimeout {
+                consistency,
+                received,
+                block_for,
+                write_type,
+                ..
+            } => format!(
+                "Only {} of {} replicas acknowledged the {} write within the write timeout at {}. \
+                 The write may still have been applied on some replicas; retrying is only safe for idempotent statements.",
+                received, block_for, write_type, consistency
+            ),
+            Self::SyntaxError(_) => {
+                "Cassandra could not parse the statement. Check the CQL near the position reported below."
+                    .to_string()
+            }
+            Self::InvalidQuery(_) => {
+                "The statement is syntactically valid but was rejected, e.g. unknown table or column, \
+                 missing partition key restriction, or a type mismatch."
+                    .to_string()
+            }
+            Self::Unauthorized(_) => {
+                "The current role lacks the permission required for this statement. Ask an administrator for a GRANT."
+                    .to_string()
+            }
+            Self::AlreadyExists {
+                keyspace, table, ..
+            } => {
+                if table.is_empty() {
+                    format!(
+                        "Keyspace '{}' already exists. Use IF NOT EXISTS to make the statement idempotent.",
+                        keyspace
+                    )
+                } else {
+                    format!(
+                        "Table '{}.{}' already exists. Use IF NOT EXISTS to make the statement idempotent.",
+                        keyspace, table
+                    )
+                }
+            }
+            Self::ServerError { .. } => {
+                "The server reported an error while executing the request.".to_string()
+            }
             _ => "An unexpected error occurred. Please try again.".to_string(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cdrs_tokio::consistency::Consistency;
+    use cdrs_tokio::frame::message_error::{AlreadyExistsError, UnavailableError};
+
+    fn server_error(message: &str, ty: ErrorType) -> cdrs_tokio::error::Error {
+        cdrs_tokio::error::Error::Server {
+            body: ErrorBody {
+                message: message.to_string(),
+                ty,
+            },
+            addr: "127.0.0.1:9042".parse().unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_unavailable_keeps_payload() {
+        let err = MagdaError::from(server_error(
+            "Cannot achieve consistency level LOCAL_QUORUM",
+            ErrorType::Unavailable(UnavailableError {
+                cl: Consistency::LocalQuorum,
+                required: 2,
+                alive: 1,
+            }),
+        ));
+
+        match &err {
+            MagdaError::Unavailable {
+                consistency,
+                required,
+                alive,
+                ..
+            } => {
+                assert_eq!(consistency, "LOCAL_QUORUM");
+                assert_eq!(*required, 2);
+                assert_eq!(*alive, 1);
+            }
+            other => panic!("unexpected variant: {:?}", other),
+        }
+        assert_eq!(err.error_code(), Some(codes::UNAVAILABLE));
+        assert!(err.is_recoverable());
+    }
+
+    #[test]
+    fn test_simple_server_errors() {
+        let err = MagdaError::from(server_error(
+            "line 1:0 no viable alternative",
+            ErrorType::Syntax,
+        ));
+        assert!(matches!(err, MagdaError::SyntaxError(_)));
+        assert_eq!(err.error_code(), Some(codes::SYNTAX_ERROR));
+
+        let err = MagdaError::from(server_error(
+            "ks.users already exists",
+            ErrorType::AlreadyExists(AlreadyExistsError {
+                ks: "ks".to_string(),
+                table: "users".to_string(),
+            }),
+        ));
+        assert!(err.user_message().contains("ks.users"));
+
+        let err = MagdaError::from(server_error("overloaded", ErrorType::Overloaded));
+        assert_eq!(err.error_code(), Some(0x1001));
+    }
+}
@@ -0,0 +1,112 @@
+//! Keyspace, table, column and type names, and how they are written in CQL.
+//!
+//! Cassandra stores names verbatim: `"UserEvents"` is kept with its case,
+//! while an unquoted `UserEvents` folds to `userevents`. An [`Identifier`]
+//! is a name read from CQL text, resolved to the stored form and rendered
+//! back quoted whenever plain text would not round-trip. Stored names held
+//! as strings (e.g. from `system_schema`) are written into statements with
+//! [`quote_identifier`] and [`qualified_name`](crate::schema::qualified_name).
+
+use std::fmt;
+
+use crate::error::{MagdaError, Result};
+use crate::schema::quote_identifier;
+
+/// A name as Cassandra stores it. `Display` writes it as CQL, quoted and
+/// escaped (`"a""b"`) when needed.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Identifier(String);
+
+impl Identifier {
+    /// A name as stored, e.g. read from `system_schema`
+    pub fn new(name: impl Into<String>) -> Self {
+        Self(name.into())
+    }
+
+    /// A name as typed in CQL: unquoted names fold to lowercase, quoted
+    /// names keep their case and unescape `""`
+    pub fn parse(text: &str) -> Result<Self> {
+        let text = text.trim();
+        if let Some(inner) = text.strip_prefix('"') {
+            let Some(inner) = inner.strip_suffix('"') else {
+                return Err(MagdaError::validation(format!(
+                    "Invalid CQL identifier {}: missing closing quote",
+                    text
+                )));
+            };
+            if inner.replace("\"\"", "").contains('"') {
+                return Err(MagdaError::validation(format!(
+                    "Invalid CQL identifier {}: quotes inside a quoted name must be doubled",
+                    text
+                )));
+            }
+            if inner.is_empty() {
+                return Err(MagdaError::validation("CQL identifier cannot be empty"));
+            }
+            return Ok(Self(inner.replace("\"\"", "\"")));
+        }
+
+        let Some(first) = text.chars().next() else {
+            return Err(MagdaError::validation("CQL identifier cannot be empty"));
+        };
+        if !first.is_ascii_alphabetic() && first != '_' {
+            return Err(MagdaError::validation(format!(
+                "Invalid CQL identifier '{}': must start with a letter or underscore, or be quoted",
+                text
+            )));
+        }
+        if !text.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(MagdaError::validation(format!(
+                "Invalid CQL identifier '{}': only letters, digits and underscores are allowed unless quoted",
+                text
+            )));
+        }
+        Ok(Self(text.to_ascii_lowercase()))
+    }
+
+    /// The stored name, as compared against `system_schema` values
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Identifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&quote_identifier(&self.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_identifiers() {
+        assert_eq!(Identifier::parse("users").unwrap().as_str(), "users");
+        assert_eq!(
+            Identifier::parse("UserEvents").unwrap().as_str(),
+            "userevents"
+        );
+        assert_eq!(
+            Identifier::parse("\"UserEvents\"").unwrap().as_str(),
+            "UserEvents"
+        );
+        assert_eq!(Identifier::parse("\"a\"\"b\"").unwrap().as_str(), "a\"b");
+        assert_eq!(Identifier::parse(" \"my ks\" ").unwrap().as_str(), "my ks");
+
+        assert!(Identifier::parse("").is_err());
+        assert!(Identifier::parse("\"\"").is_err());
+        assert!(Identifier::parse("\"open").is_err());
+        assert!(Identifier::parse("\"a\"b\"").is_err());
+        assert!(Identifier::parse("2fa").is_err());
+        assert!(Identifier::parse("users; DROP TABLE x").is_err());
+    }
+
+    #[test]
+    fn test_display_round_trips() {
+        for name in ["users", "UserEvents", "a\"b", "select", "my ks", "_tmp"] {
+            let id = Identifier::new(name);
+            assert_eq!(Identifier::parse(&id.to_string()).unwrap(), id);
+        }
+    }
+}
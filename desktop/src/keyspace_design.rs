@@ -0,0 +1,379 @@
+//! Keyspace replication settings edited in the keyspace wizard, and the
+//! `CREATE KEYSPACE` / `ALTER KEYSPACE` statements they generate.
+
+use std::collections::BTreeMap;
+
+use crate::schema::{map_literal, quote_identifier, KeyspaceDef};
+
+const SIMPLE_STRATEGY: &str = "SimpleStrategy";
+const NETWORK_TOPOLOGY_STRATEGY: &str = "NetworkTopologyStrategy";
+
+/// Longest keyspace name Cassandra accepts
+const MAX_NAME_LENGTH: usize = 48;
+
+/// Data centers of the cluster and how many nodes each has, as read from
+/// `system.local` and `system.peers`
+pub type Topology = BTreeMap<String, usize>;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Replication {
+    /// One replication factor for the whole cluster; fine for development
+    Simple(u32),
+    /// Replication factor per data center
+    NetworkTopology(BTreeMap<String, u32>),
+}
+
+impl Replication {
+    /// Replication of an existing keyspace. Strategies the wizard does not
+    /// edit (e.g. `LocalStrategy`) are `None`.
+    pub fn from_map(map: &BTreeMap<String, String>) -> Option<Self> {
+        let class = map.get("class")?;
+        let short = class.rsplit('.').next().unwrap_or(class);
+        match short {
+            SIMPLE_STRATEGY => map
+                .get("replication_factor")
+                .and_then(|rf| rf.parse().ok())
+                .map(Replication::Simple),
+            NETWORK_TOPOLOGY_STRATEGY => Some(Replication::NetworkTopology(
+                map.iter()
+                    .filter(|(k, _)| k.as_str() != "class")
+                    .filter_map(|(dc, rf)| Some((dc.clone(), rf.parse().ok()?)))
+                    .collect(),
+            )),
+            _ => None,
+        }
+    }
+
+    /// The `replication` map, with short class names as cqlsh accepts them
+    pub fn to_map(&self) -> BTreeMap<String, String> {
+        let mut map = BTreeMap::new();
+        match self {
+            Replication::Simple(rf) => {
+                map.insert("class".to_string(), SIMPLE_STRATEGY.to_string());
+                map.insert("replication_factor".to_string(), rf.to_string());
+            }
+            Replication::NetworkTopology(factors) => {
+                map.insert("class".to_string(), NETWORK_TOPOLOGY_STRATEGY.to_string());
+                for (dc, rf) in factors {
+                    map.insert(dc.clone(), rf.to_string());
+                }
+            }
+        }
+        map
+    }
+
+    /// Replication factor per data center of `topology`
+    fn factors(&self, topology: &Topology) -> BTreeMap<String, u32> {
+        match self {
+            Replication::Simple(rf) => topology.keys().map(|dc| (dc.clone(), *rf)).collect(),
+            Replication::NetworkTopology(factors) => factors.clone(),
+        }
+    }
+}
+
+/// A keyspace being created or altered
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyspaceDesign {
+    pub name: String,
+    pub replication: Replication,
+    pub durable_writes: bool,
+}
+
+impl KeyspaceDesign {
+    /// A new keyspace replicated up to three times in every data center, or once
+    /// with `SimpleStrategy` when the topology is unknown
+    pub fn new(topology: &Topology) -> Self {
+        let replication = if topology.is_empty() {
+            Replication::Simple(1)
+        } else {
+            Replication::NetworkTopology(
+                topology
+                    .iter()
+                    .map(|(dc, nodes)| (dc.clone(), (*nodes as u32).min(3)))
+                    .collect(),
+            )
+        };
+        Self {
+            name: String::new(),
+            replication,
+            durable_writes: true,
+        }
+    }
+
+    /// The design of an existing keyspace, `None` for strategies the wizard
+    /// cannot edit
+    pub fn from_def(keyspace: &KeyspaceDef) -> Option<Self> {
+        Some(Self {
+            name: keyspace.name.clone(),
+            replication: Replication::from_map(&keyspace.replication)?,
+            durable_writes: keyspace.durable_writes,
+        })
+    }
+
+    /// Why the keyspace cannot be created as designed; empty when it can
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let name = self.name.trim();
+        if name.is_empty() {
+            problems.push("The keyspace needs a name".to_string());
+        } else if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            problems.push(
+                "Keyspace names may only contain letters, digits and underscores".to_string(),
+            );
+        } else if name.len() > MAX_NAME_LENGTH {
+            problems.push(format!(
+                "Keyspace names are limited to {} characters",
+                MAX_NAME_LENGTH
+            ));
+        }
+        match &self.replication {
+            Replication::Simple(0) => {
+                problems.push("The replication factor must be at least 1".to_string());
+            }
+            Replication::NetworkTopology(factors) if factors.values().all(|rf| *rf == 0) => {
+                problems.push("Replicate to at least one data center".to_string());
+            }
+            _ => {}
+        }
+        problems
+    }
+
+    /// Settings that work but are likely mistakes on this `topology`
+    pub fn warnings(&self, topology: &Topology) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if topology.is_empty() {
+            return warnings;
+        }
+        for (dc, rf) in self.replication.factors(topology) {
+            match topology.get(&dc) {
+                None if rf > 0 => warnings.push(format!(
+                    "Data center {} is not part of this cluster; its replicas will never be written",
+                    dc
+                )),
+                Some(&nodes) if rf as usize > nodes => {
+                    // Only `nodes` replicas can be placed, so ALL never succeeds;
+                    // QUORUM (RF / 2 + 1) may still fit on the nodes there are
+                    let quorum = rf as usize / 2 + 1;
+                    let consequence = if quorum > nodes {
+                        "writes at QUORUM or ALL will fail"
+                    } else if quorum == nodes {
+                        "writes at ALL will fail and QUORUM fails as soon as one node is down"
+                    } else {
+                        "writes at ALL will fail"
+                    };
+                    warnings.push(format!(
+                        "Replication factor {} in {} exceeds its {} node{}; {}",
+                        rf,
+                        dc,
+                        nodes,
+                        if nodes == 1 { "" } else { "s" },
+                        consequence
+                    ))
+                }
+                _ => {}
+            }
+        }
+        if matches!(self.replication, Replication::Simple(_)) && topology.len() > 1 {
+            warnings.push(
+                "SimpleStrategy ignores data centers; use NetworkTopologyStrategy on multi-DC clusters"
+                    .to_string(),
+            );
+        }
+        if !self.durable_writes {
+            warnings.push(
+                "Without durable writes, data not yet flushed is lost when a node restarts"
+                    .to_string(),
+            );
+        }
+        warnings
+    }
+
+    fn with_clause(&self) -> String {
+        format!(
+            "WITH replication = {} AND durable_writes = {};",
+            map_literal(&self.replication.to_map()),
+            self.durable_writes
+        )
+    }
+
+    pub fn create_cql(&self) -> String {
+        format!(
+            "CREATE KEYSPACE {} {}",
+            quote_identifier(self.name.trim()),
+            self.with_clause()
+        )
+    }
+
+    /// `ALTER KEYSPACE` turning `current` into this design, `None` when
+    /// nothing changed
+    pub fn alter_cql(&self, current: &KeyspaceDef) -> Option<String> {
+        let unchanged = KeyspaceDesign::from_def(current).is_some_and(|c| c == *self);
+        if unchanged {
+            return None;
+        }
+        Some(format!(
+            "ALTER KEYSPACE {} {}",
+            quote_identifier(&current.name),
+            self.with_clause()
+        ))
+    }
+
+    /// Whether altering `current` to this design adds replicas that only
+    /// receive existing data after a repair
+    pub fn needs_repair(&self, current: &KeyspaceDef, topology: &Topology) -> bool {
+        let Some(before) = Replication::from_map(&current.replication) else {
+            return true;
+        };
+        let before = before.factors(topology);
+        self.replication
+            .factors(topology)
+            .iter()
+            .any(|(dc, rf)| rf > before.get(dc).unwrap_or(&0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn topology() -> Topology {
+        BTreeMap::from([("dc1".to_string(), 3), ("dc2".to_string(), 2)])
+    }
+
+    fn existing() -> KeyspaceDef {
+        KeyspaceDef {
+            name: "shop".to_string(),
+            replication: BTreeMap::from([
+                (
+                    "class".to_string(),
+                    "org.apache.cassandra.locator.NetworkTopologyStrategy".to_string(),
+                ),
+                ("dc1".to_string(), "3".to_string()),
+                ("dc2".to_string(), "2".to_string()),
+            ]),
+            durable_writes: true,
+        }
+    }
+
+    #[test]
+    fn test_new_keyspace_defaults_to_topology() {
+        let mut design = KeyspaceDesign::new(&topology());
+        assert_eq!(
+            design.replication,
+            Replication::NetworkTopology(BTreeMap::from([
+                ("dc1".to_string(), 3),
+                ("dc2".to_string(), 2)
+            ]))
+        );
+        design.name = "metrics".to_string();
+        assert!(design.validate().is_empty());
+        assert!(design.warnings(&topology()).is_empty());
+        assert_eq!(
+            design.create_cql(),
+            "CREATE KEYSPACE metrics WITH replication = {'class': 'NetworkTopologyStrategy', 'dc1': '3', 'dc2': '2'} AND durable_writes = true;"
+        );
+
+        let dev = KeyspaceDesign::new(&Topology::new());
+        assert_eq!(dev.replication, Replication::Simple(1));
+    }
+
+    #[test]
+    fn test_validation() {
+        let mut design = KeyspaceDesign::new(&topology());
+        assert_eq!(design.validate(), vec!["The keyspace needs a name"]);
+        design.name = "my-keyspace".to_string();
+        design.replication = Replication::Simple(0);
+        assert_eq!(
+            design.validate(),
+            vec![
+                "Keyspace names may only contain letters, digits and underscores",
+                "The replication factor must be at least 1"
+            ]
+        );
+        design.name = "k".repeat(49);
+        design.replication = Replication::NetworkTopology(BTreeMap::from([("dc1".to_string(), 0)]));
+        assert_eq!(
+            design.validate(),
+            vec![
+                "Keyspace names are limited to 48 characters",
+                "Replicate to at least one data center"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_warnings() {
+        let design = KeyspaceDesign {
+            name: "shop".to_string(),
+            replication: Replication::NetworkTopology(BTreeMap::from([
+                ("dc1".to_string(), 3),
+                ("dc2".to_string(), 3),
+                ("dc3".to_string(), 1),
+            ])),
+            durable_writes: false,
+        };
+        assert_eq!(
+            design.warnings(&topology()),
+            vec![
+                "Replication factor 3 in dc2 exceeds its 2 nodes; writes at ALL will fail and QUORUM fails as soon as one node is down",
+                "Data center dc3 is not part of this cluster; its replicas will never be written",
+                "Without durable writes, data not yet flushed is lost when a node restarts",
+            ]
+        );
+
+        let simple = KeyspaceDesign {
+            name: "dev".to_string(),
+            replication: Replication::Simple(3),
+            durable_writes: true,
+        };
+        assert_eq!(
+            simple.warnings(&topology()),
+            vec![
+                "Replication factor 3 in dc2 exceeds its 2 nodes; writes at ALL will fail and QUORUM fails as soon as one node is down",
+                "SimpleStrategy ignores data centers; use NetworkTopologyStrategy on multi-DC clusters",
+            ]
+        );
+
+        let wide = KeyspaceDesign {
+            name: "wide".to_string(),
+            replication: Replication::NetworkTopology(BTreeMap::from([
+                ("dc1".to_string(), 7),
+                ("dc2".to_string(), 1),
+            ])),
+            durable_writes: true,
+        };
+        assert_eq!(
+            wide.warnings(&topology()),
+            vec!["Replication factor 7 in dc1 exceeds its 3 nodes; writes at QUORUM or ALL will fail"]
+        );
+    }
+
+    #[test]
+    fn test_alter_existing_keyspace() {
+        let current = existing();
+        let mut design = KeyspaceDesign::from_def(&current).unwrap();
+        assert_eq!(design.alter_cql(&current), None);
+
+        design.replication = Replication::NetworkTopology(BTreeMap::from([
+            ("dc1".to_string(), 3),
+            ("dc2".to_string(), 1),
+        ]));
+        assert_eq!(
+            design.alter_cql(&current).as_deref(),
+            Some("ALTER KEYSPACE shop WITH replication = {'class': 'NetworkTopologyStrategy', 'dc1': '3', 'dc2': '1'} AND durable_writes = true;")
+        );
+        assert!(!design.needs_repair(&current, &topology()));
+
+        design.replication = Replication::Simple(3);
+        assert!(design.needs_repair(&current, &topology()));
+
+        let local = KeyspaceDef {
+            replication: BTreeMap::from([(
+                "class".to_string(),
+                "org.apache.cassandra.locator.LocalStrategy".to_string(),
+            )]),
+            ..existing()
+        };
+        assert_eq!(KeyspaceDesign::from_def(&local), None);
+    }
+}
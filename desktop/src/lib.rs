@@ -1,18 +1,49 @@
 pub mod cassandra;
+pub mod completion;
 pub mod config;
 pub mod connection;
+pub mod cql_parser;
 pub mod cql_tokenizer;
+pub mod diagnostics;
 pub mod error;
+pub mod identifier;
+pub mod keyspace_design;
+pub mod metrics;
+pub mod migrations;
+pub mod roles;
+pub mod schema;
+pub mod schema_diagram;
+pub mod schema_diff;
+pub mod schema_lint;
+pub mod schema_search;
+pub mod schema_snapshots;
 pub mod state;
+pub mod table_design;
+#[cfg(test)]
+mod test_fixtures;
+pub mod virtual_tables;
 pub mod components {
     pub mod code_editor;
     pub mod connection_dialog;
     pub mod console_panel;
     pub mod data_grid;
+    pub mod editor_tabs;
+    pub mod error_panel;
+    pub mod keyspace_editor;
+    pub mod metrics_panel;
+    pub mod migration_runner;
     pub mod query_editor;
+    pub mod schema_compare;
+    pub mod schema_export;
+    pub mod schema_history;
+    pub mod schema_search;
     pub mod schema_viewer;
+    pub mod script_results;
+    pub mod security_panel;
     pub mod sidebar;
     pub mod statusbar;
+    pub mod table_designer;
+    pub mod table_options;
     pub mod variables_panel;
     pub mod workspace;
 }
@@ -0,0 +1,473 @@
+//! Driver-level query metrics.
+//!
+//! Every request sent through a `CassandraSession` produces a [`QuerySample`]
+//! (latency, bytes, rows, retries and the coordinator node). Samples are folded
+//! into latency histograms per connection and per node, so the UI can show
+//! p50/p95/p99 without keeping every sample around.
+
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+
+use cdrs_tokio::cluster::{ClusterMetadata, ConnectionManager};
+use cdrs_tokio::load_balancing::{LoadBalancingStrategy, QueryPlan, Request};
+use cdrs_tokio::retry::{DefaultRetryPolicy, QueryInfo, RetryDecision, RetryPolicy, RetrySession};
+use cdrs_tokio::transport::CdrsTransport;
+use chrono::{DateTime, Utc};
+
+/// Number of recent samples kept for the metrics panel
+const RECENT_SAMPLES: usize = 50;
+
+/// Linear sub-buckets per power of two; bounds the percentile error to 12.5%
+const SUB_BUCKETS: u64 = 8;
+const SUB_BUCKET_BITS: u64 = 3;
+const BUCKET_COUNT: usize = ((64 - SUB_BUCKET_BITS + 1) * SUB_BUCKETS) as usize;
+
+tokio::task_local! {
+    static QUERY_TRACE: RefCell<QueryTrace>;
+}
+
+/// What the driver did while sending one request: the nodes it planned to
+/// try and the retry decisions it took along the way.
+#[derive(Clone, Debug, Default)]
+pub struct QueryTrace {
+    plan: Vec<NodeId>,
+    retries: u32,
+    next_node_retries: usize,
+}
+
+impl QueryTrace {
+    /// Node that answered the request, as far as the retry decisions tell
+    pub fn coordinator(&self) -> Option<NodeId> {
+        self.plan.get(self.next_node_retries).cloned()
+    }
+
+    pub fn retries(&self) -> u32 {
+        self.retries
+    }
+}
+
+/// Run `future` while recording the query plan and retries the driver uses
+pub async fn traced<F: Future>(future: F) -> (F::Output, QueryTrace) {
+    QUERY_TRACE
+        .scope(RefCell::new(QueryTrace::default()), async move {
+            let output = future.await;
+            let trace = QUERY_TRACE.with(|trace| trace.take());
+            (output, trace)
+        })
+        .await
+}
+
+/// Address and datacenter of a cluster node
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct NodeId {
+    pub address: SocketAddr,
+    pub datacenter: String,
+}
+
+impl std::fmt::Display for NodeId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.datacenter.is_empty() {
+            write!(f, "{}", self.address)
+        } else {
+            write!(f, "{} ({})", self.address, self.datacenter)
+        }
+    }
+}
+
+/// Load balancing wrapper that reports each query plan to the running trace
+pub struct TracedLoadBalancing<LB> {
+    inner: LB,
+}
+
+impl<LB> TracedLoadBalancing<LB> {
+    pub fn new(inner: LB) -> Self {
+        Self { inner }
+    }
+}
+
+impl<T, CM, LB> LoadBalancingStrategy<T, CM> for TracedLoadBalancing<LB>
+where
+    T: CdrsTransport,
+    CM: ConnectionManager<T>,
+    LB: LoadBalancingStrategy<T, CM>,
+{
+    fn query_plan(
+        &self,
+        request: Option<Request>,
+        cluster: &ClusterMetadata<T, CM>,
+    ) -> QueryPlan<T, CM> {
+        let plan = self.inner.query_plan(request, cluster);
+        // Outside of `traced` (e.g. control connection) there is nothing to record
+        let _ = QUERY_TRACE.try_with(|trace| {
+            trace.borrow_mut().plan = plan
+                .iter()
+                .map(|node| NodeId {
+                    address: node.broadcast_rpc_address(),
+                    datacenter: node.datacenter().to_string(),
+                })
+                .collect();
+        });
+        plan
+    }
+}
+
+/// The driver's default retry policy, with retries counted in the running trace
+#[derive(Default)]
+pub struct TracedRetryPolicy;
+
+impl RetryPolicy for TracedRetryPolicy {
+    fn new_session(&self) -> Box<dyn RetrySession + Send + Sync> {
+        Box::new(TracedRetrySession {
+            inner: DefaultRetryPolicy.new_session(),
+        })
+    }
+}
+
+struct TracedRetrySession {
+    inner: Box<dyn RetrySession + Send + Sync>,
+}
+
+impl RetrySession for TracedRetrySession {
+    fn decide(&mut self, query_info: QueryInfo) -> RetryDecision {
+        let decision = self.inner.decide(query_info);
+        let _ = QUERY_TRACE.try_with(|trace| {
+            let mut trace = trace.borrow_mut();
+            match decision {
+                RetryDecision::RetrySameNode => trace.retries += 1,
+                RetryDecision::RetryNextNode => {
+                    trace.retries += 1;
+                    trace.next_node_retries += 1;
+                }
+                RetryDecision::DontRetry => {}
+            }
+        });
+        decision
+    }
+}
+
+/// One request sent to the cluster
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuerySample {
+    pub at: DateTime<Utc>,
+    pub query: String,
+    pub latency_us: u64,
+    /// Size of the statement text sent
+    pub request_bytes: usize,
+    /// Size of the response frame body
+    pub response_bytes: usize,
+    /// Rows returned, when the result was decoded
+    pub rows: Option<usize>,
+    pub retries: u32,
+    pub coordinator: Option<NodeId>,
+    pub success: bool,
+}
+
+/// Log-linear latency histogram over microseconds.
+///
+/// Values below 8 µs get their own bucket; above that every power of two is
+/// split into 8 equal buckets, so percentiles are accurate to within 12.5%.
+#[derive(Clone, Debug)]
+pub struct LatencyHistogram {
+    buckets: Vec<u64>,
+    count: u64,
+    sum_us: u64,
+    max_us: u64,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self {
+            buckets: vec![0; BUCKET_COUNT],
+            count: 0,
+            sum_us: 0,
+            max_us: 0,
+        }
+    }
+}
+
+fn bucket_index(us: u64) -> usize {
+    if us < SUB_BUCKETS {
+        return us as usize;
+    }
+    let exp = 63 - us.leading_zeros() as u64;
+    let sub = (us >> (exp - SUB_BUCKET_BITS)) - SUB_BUCKETS;
+    ((exp - SUB_BUCKET_BITS + 1) * SUB_BUCKETS + sub) as usize
+}
+
+/// Largest value that falls into bucket `index`
+fn bucket_upper_bound(index: usize) -> u64 {
+    let index = index as u64;
+    if index < SUB_BUCKETS {
+        return index;
+    }
+    let shift = index / SUB_BUCKETS - 1;
+    let lower = (SUB_BUCKETS + index % SUB_BUCKETS) << shift;
+    lower.saturating_add((1u64 << shift) - 1)
+}
+
+impl LatencyHistogram {
+    pub fn record(&mut self, us: u64) {
+        self.buckets[bucket_index(us)] += 1;
+        self.count += 1;
+        self.sum_us = self.sum_us.saturating_add(us);
+        self.max_us = self.max_us.max(us);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn mean_us(&self) -> u64 {
+        self.sum_us.checked_div(self.count).unwrap_or(0)
+    }
+
+    pub fn max_us(&self) -> u64 {
+        self.max_us
+    }
+
+    /// Latency below which `quantile` (0.0–1.0) of the samples fall
+    pub fn percentile(&self, quantile: f64) -> u64 {
+        if self.count == 0 {
+            return 0;
+        }
+        let target = ((quantile * self.count as f64).ceil() as u64).clamp(1, self.count);
+        let mut seen = 0;
+        for (index, &n) in self.buckets.iter().enumerate() {
+            seen += n;
+            if seen >= target {
+                return bucket_upper_bound(index).min(self.max_us);
+            }
+        }
+        self.max_us
+    }
+}
+
+/// Running totals for a connection or a node
+#[derive(Clone, Debug, Default)]
+struct Aggregate {
+    latency: LatencyHistogram,
+    errors: u64,
+    retries: u64,
+    rows: u64,
+    request_bytes: u64,
+    response_bytes: u64,
+}
+
+impl Aggregate {
+    fn record(&mut self, sample: &QuerySample) {
+        self.latency.record(sample.latency_us);
+        if !sample.success {
+            self.errors += 1;
+        }
+        self.retries += sample.retries as u64;
+        self.rows += sample.rows.unwrap_or(0) as u64;
+        self.request_bytes += sample.request_bytes as u64;
+        self.response_bytes += sample.response_bytes as u64;
+    }
+
+    fn summary(&self) -> MetricsSummary {
+        MetricsSummary {
+            queries: self.latency.count(),
+            errors: self.errors,
+            retries: self.retries,
+            rows: self.rows,
+            request_bytes: self.request_bytes,
+            response_bytes: self.response_bytes,
+            mean_us: self.latency.mean_us(),
+            p50_us: self.latency.percentile(0.50),
+            p95_us: self.latency.percentile(0.95),
+            p99_us: self.latency.percentile(0.99),
+            max_us: self.latency.max_us(),
+        }
+    }
+}
+
+/// Point-in-time statistics for a connection or node
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MetricsSummary {
+    pub queries: u64,
+    pub errors: u64,
+    pub retries: u64,
+    pub rows: u64,
+    pub request_bytes: u64,
+    pub response_bytes: u64,
+    pub mean_us: u64,
+    pub p50_us: u64,
+    pub p95_us: u64,
+    pub p99_us: u64,
+    pub max_us: u64,
+}
+
+/// Copy of a session's metrics for display
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MetricsSnapshot {
+    pub overall: MetricsSummary,
+    pub nodes: Vec<(NodeId, MetricsSummary)>,
+    /// Most recent samples, newest first
+    pub recent: Vec<QuerySample>,
+}
+
+#[derive(Default)]
+struct MetricsState {
+    overall: Aggregate,
+    nodes: HashMap<NodeId, Aggregate>,
+    recent: VecDeque<QuerySample>,
+}
+
+/// Metrics collected by one session, shared between concurrent queries
+#[derive(Default)]
+pub struct SessionMetrics {
+    state: Mutex<MetricsState>,
+}
+
+impl SessionMetrics {
+    pub fn record(&self, sample: QuerySample) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        state.overall.record(&sample);
+        if let Some(node) = &sample.coordinator {
+            state.nodes.entry(node.clone()).or_default().record(&sample);
+        }
+        if state.recent.len() == RECENT_SAMPLES {
+            state.recent.pop_back();
+        }
+        state.recent.push_front(sample);
+    }
+
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        let Ok(state) = self.state.lock() else {
+            return MetricsSnapshot::default();
+        };
+        let mut nodes: Vec<_> = state
+            .nodes
+            .iter()
+            .map(|(node, aggregate)| (node.clone(), aggregate.summary()))
+            .collect();
+        nodes.sort_by_key(|(node, _)| node.address);
+        MetricsSnapshot {
+            overall: state.overall.summary(),
+            nodes,
+            recent: state.recent.iter().cloned().collect(),
+        }
+    }
+
+    pub fn reset(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            *state = MetricsState::default();
+        }
+    }
+}
+
+/// Human-readable latency: µs below 1 ms, ms below 1 s, seconds above
+pub fn format_latency(us: u64) -> String {
+    if us < 1_000 {
+        format!("{} µs", us)
+    } else if us < 1_000_000 {
+        format!("{:.1} ms", us as f64 / 1_000.0)
+    } else {
+        format!("{:.2} s", us as f64 / 1_000_000.0)
+    }
+}
+
+/// Human-readable byte count
+pub fn format_bytes(bytes: u64) -> String {
+    if bytes < 1024 {
+        format!("{} B", bytes)
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KiB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(latency_us: u64, node: Option<&str>, success: bool) -> QuerySample {
+        QuerySample {
+            at: Utc::now(),
+            query: "SELECT * FROM t".to_string(),
+            latency_us,
+            request_bytes: 15,
+            response_bytes: 100,
+            rows: Some(2),
+            retries: 0,
+            coordinator: node.map(|addr| NodeId {
+                address: addr.parse().unwrap(),
+                datacenter: "dc1".to_string(),
+            }),
+            success,
+        }
+    }
+
+    #[test]
+    fn test_bucket_bounds_are_contiguous() {
+        for us in [0u64, 1, 7, 8, 15, 16, 17, 1_000, 123_456, u64::MAX / 2] {
+            let index = bucket_index(us);
+            assert!(us <= bucket_upper_bound(index), "{} above its bucket", us);
+            if index > 0 {
+                assert!(
+                    us > bucket_upper_bound(index - 1),
+                    "{} below its bucket",
+                    us
+                );
+            }
+        }
+        assert!(bucket_index(u64::MAX) < BUCKET_COUNT);
+    }
+
+    #[test]
+    fn test_percentiles() {
+        let mut histogram = LatencyHistogram::default();
+        assert_eq!(histogram.percentile(0.99), 0);
+
+        for us in 1..=1_000 {
+            histogram.record(us);
+        }
+        let within = |actual: u64, expected: u64| {
+            let error = (actual as f64 - expected as f64).abs() / expected as f64;
+            assert!(
+                error <= 0.125,
+                "{} not within 12.5% of {}",
+                actual,
+                expected
+            );
+        };
+        within(histogram.percentile(0.50), 500);
+        within(histogram.percentile(0.95), 950);
+        within(histogram.percentile(0.99), 990);
+        assert_eq!(histogram.percentile(1.0), 1_000);
+        assert_eq!(histogram.mean_us(), 500);
+    }
+
+    #[test]
+    fn test_session_metrics_per_node() {
+        let metrics = SessionMetrics::default();
+        metrics.record(sample(100, Some("10.0.0.1:9042"), true));
+        metrics.record(sample(300, Some("10.0.0.2:9042"), false));
+        metrics.record(sample(200, None, true));
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.overall.queries, 3);
+        assert_eq!(snapshot.overall.errors, 1);
+        assert_eq!(snapshot.overall.rows, 6);
+        assert_eq!(snapshot.nodes.len(), 2);
+        assert_eq!(snapshot.nodes[1].1.errors, 1);
+        assert_eq!(snapshot.recent[0].latency_us, 200);
+
+        metrics.reset();
+        assert_eq!(metrics.snapshot().overall.queries, 0);
+    }
+
+    #[test]
+    fn test_format_latency() {
+        assert_eq!(format_latency(850), "850 µs");
+        assert_eq!(format_latency(12_345), "12.3 ms");
+        assert_eq!(format_latency(1_200_000), "1.20 s");
+    }
+}
@@ -0,0 +1,518 @@
+//! Numbered `.cql` migration files applied in order to a keyspace, with the
+//! applied versions and checksums recorded in a tracking table there.
+//!
+//! Files are named `<version>_<description>.cql`, e.g. `0003_add_orders.cql`
+//! (`V3__add_orders.cql` works too). Their statements run with the target
+//! keyspace as the default, which must already exist. A migration that fails
+//! part way is not recorded, so statements should be written to be re-run
+//! (`IF NOT EXISTS`, `IF EXISTS`).
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use crate::cassandra::{self, CassandraSession};
+use crate::cql_tokenizer::{is_schema_change, split_statements};
+use crate::error::{MagdaError, Result};
+use crate::schema::{qualified_name, quote_identifier, quote_literal};
+
+/// Table recording applied migrations in the target keyspace
+pub const TRACKING_TABLE: &str = "schema_migrations";
+
+/// How long to wait for the nodes to agree on the schema after each DDL
+const AGREEMENT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// One migration file
+#[derive(Clone, Debug, PartialEq)]
+pub struct Migration {
+    pub version: u64,
+    pub description: String,
+    pub file_name: String,
+    pub checksum: String,
+    pub statements: Vec<String>,
+}
+
+impl Migration {
+    pub fn parse(file_name: &str, source: &str) -> Result<Self> {
+        let (version, description) = parse_file_name(file_name).ok_or_else(|| {
+            MagdaError::validation(format!(
+                "{} is not named <version>_<description>.cql",
+                file_name
+            ))
+        })?;
+        let statements = split_statements(source);
+        if statements.is_empty() {
+            return Err(MagdaError::validation(format!(
+                "{} contains no statements",
+                file_name
+            )));
+        }
+        Ok(Self {
+            version,
+            description,
+            file_name: file_name.to_string(),
+            checksum: checksum(source),
+            statements,
+        })
+    }
+}
+
+/// Version and description from a file name like `0003_add_orders.cql`
+fn parse_file_name(file_name: &str) -> Option<(u64, String)> {
+    let stem = file_name.strip_suffix(".cql")?;
+    let stem = stem.strip_prefix(['V', 'v']).unwrap_or(stem);
+    let digits = stem
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(stem.len());
+    let version = stem[..digits].parse().ok()?;
+    let description = stem[digits..]
+        .trim_start_matches(['_', '-', '.'])
+        .replace('_', " ");
+    Some((version, description.trim().to_string()))
+}
+
+/// FNV-1a hash of a file's content, stable across platforms and line endings
+pub fn checksum(source: &str) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in source.replace("\r\n", "\n").bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{:016x}", hash)
+}
+
+/// Every `.cql` file in `dir`, ordered by version
+pub fn load_dir(dir: &Path) -> Result<Vec<Migration>> {
+    let mut migrations = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("cql") {
+            continue;
+        }
+        let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        let source = fs::read_to_string(&path)?;
+        migrations.push(Migration::parse(file_name, &source)?);
+    }
+    migrations.sort_by_key(|m| m.version);
+    for pair in migrations.windows(2) {
+        if pair[0].version == pair[1].version {
+            return Err(MagdaError::validation(format!(
+                "{} and {} share version {}",
+                pair[0].file_name, pair[1].file_name, pair[0].version
+            )));
+        }
+    }
+    Ok(migrations)
+}
+
+/// A row of the tracking table
+#[derive(Clone, Debug, PartialEq)]
+pub struct AppliedMigration {
+    pub version: u64,
+    pub description: String,
+    pub checksum: String,
+    pub applied_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MigrationState {
+    Applied,
+    Pending,
+    /// Applied, but the file changed since
+    Modified,
+    /// Not applied, yet older than the latest applied migration
+    OutOfOrder,
+    /// Recorded as applied, but no longer in the directory
+    Missing,
+}
+
+impl MigrationState {
+    pub fn label(&self) -> &'static str {
+        match self {
+            MigrationState::Applied => "applied",
+            MigrationState::Pending => "pending",
+            MigrationState::Modified => "modified",
+            MigrationState::OutOfOrder => "out of order",
+            MigrationState::Missing => "missing",
+        }
+    }
+}
+
+/// Where one migration stands on the cluster
+#[derive(Clone, Debug, PartialEq)]
+pub struct MigrationStatus {
+    pub version: u64,
+    pub description: String,
+    pub state: MigrationState,
+    pub applied_at: Option<DateTime<Utc>>,
+}
+
+/// Status of every migration on disk or in the tracking table, by version
+pub fn status(local: &[Migration], applied: &[AppliedMigration]) -> Vec<MigrationStatus> {
+    let latest_applied = applied.iter().map(|a| a.version).max();
+    let mut statuses: BTreeMap<u64, MigrationStatus> = BTreeMap::new();
+    for migration in local {
+        let record = applied.iter().find(|a| a.version == migration.version);
+        let state = match record {
+            Some(a) if a.checksum == migration.checksum => MigrationState::Applied,
+            Some(_) => MigrationState::Modified,
+            None if latest_applied.is_some_and(|v| v > migration.version) => {
+                MigrationState::OutOfOrder
+            }
+            None => MigrationState::Pending,
+        };
+        statuses.insert(
+            migration.version,
+            MigrationStatus {
+                version: migration.version,
+                description: migration.description.clone(),
+                state,
+                applied_at: record.and_then(|a| a.applied_at),
+            },
+        );
+    }
+    for record in applied {
+        statuses
+            .entry(record.version)
+            .or_insert_with(|| MigrationStatus {
+                version: record.version,
+                description: record.description.clone(),
+                state: MigrationState::Missing,
+                applied_at: record.applied_at,
+            });
+    }
+    statuses.into_values().collect()
+}
+
+/// Why pending migrations cannot be applied; empty when they can
+pub fn problems(statuses: &[MigrationStatus]) -> Vec<String> {
+    statuses
+        .iter()
+        .filter_map(|s| match s.state {
+            MigrationState::Modified => Some(format!(
+                "Migration {} ({}) changed after it was applied",
+                s.version, s.description
+            )),
+            MigrationState::OutOfOrder => Some(format!(
+                "Migration {} ({}) is older than the latest applied migration",
+                s.version, s.description
+            )),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Migrations still to apply, in order
+pub fn pending<'a>(local: &'a [Migration], statuses: &[MigrationStatus]) -> Vec<&'a Migration> {
+    let versions: HashSet<u64> = statuses
+        .iter()
+        .filter(|s| s.state == MigrationState::Pending)
+        .map(|s| s.version)
+        .collect();
+    local
+        .iter()
+        .filter(|m| versions.contains(&m.version))
+        .collect()
+}
+
+pub fn tracking_table_cql(keyspace: &str) -> String {
+    format!(
+        "CREATE TABLE IF NOT EXISTS {} (version bigint PRIMARY KEY, description text, checksum text, applied_at timestamp)",
+        qualified_name(keyspace, TRACKING_TABLE)
+    )
+}
+
+fn record_cql(keyspace: &str, migration: &Migration) -> String {
+    format!(
+        "INSERT INTO {} (version, description, checksum, applied_at) VALUES ({}, {}, {}, toTimestamp(now()))",
+        qualified_name(keyspace, TRACKING_TABLE),
+        migration.version,
+        quote_literal(&migration.description),
+        quote_literal(&migration.checksum)
+    )
+}
+
+/// The statements applying `pending` would run, as a cqlsh script
+pub fn dry_run_script(keyspace: &str, pending: &[&Migration]) -> String {
+    let mut script = format!(
+        "USE {};\n\n{};\n",
+        quote_identifier(keyspace),
+        tracking_table_cql(keyspace)
+    );
+    for migration in pending {
+        script.push_str(&format!(
+            "\n-- {}: {} ({})\n",
+            migration.version, migration.description, migration.file_name
+        ));
+        for statement in &migration.statements {
+            script.push_str(statement);
+            script.push_str(";\n");
+        }
+        script.push_str(&record_cql(keyspace, migration));
+        script.push_str(";\n");
+    }
+    script
+}
+
+/// Rows of the tracking table; empty when it does not exist yet
+pub async fn applied_migrations(
+    session: &CassandraSession,
+    keyspace: &str,
+) -> Result<Vec<AppliedMigration>> {
+    let exists = cassandra::schema_rows(
+        session,
+        &format!(
+            "SELECT table_name FROM system_schema.tables WHERE keyspace_name = {} AND table_name = {}",
+            quote_literal(keyspace),
+            quote_literal(TRACKING_TABLE)
+        ),
+    )
+    .await?;
+    if exists.is_empty() {
+        return Ok(Vec::new());
+    }
+    let rows = cassandra::schema_rows(
+        session,
+        &format!("SELECT * FROM {}", qualified_name(keyspace, TRACKING_TABLE)),
+    )
+    .await?;
+    Ok(rows
+        .iter()
+        .filter_map(|row| {
+            Some(AppliedMigration {
+                version: u64::try_from(row.bigint("version")?).ok()?,
+                description: row.text("description").unwrap_or_default(),
+                checksum: row.text("checksum").unwrap_or_default(),
+                applied_at: row
+                    .bigint("applied_at")
+                    .and_then(DateTime::from_timestamp_millis),
+            })
+        })
+        .collect())
+}
+
+/// What `apply_pending` did
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MigrationReport {
+    /// Versions applied, in order
+    pub applied: Vec<u64>,
+    /// DDL statements after which the nodes did not agree on the schema in time
+    pub agreement_timeouts: usize,
+}
+
+/// Run a statement, waiting for schema agreement after DDL
+async fn run_statement(
+    session: &CassandraSession,
+    keyspace: &str,
+    statement: &str,
+    report: &mut MigrationReport,
+) -> Result<()> {
+    cassandra::execute_query_in(session, statement, Some(keyspace)).await?;
+    if is_schema_change(statement)
+        && !cassandra::wait_for_schema_agreement(session, AGREEMENT_TIMEOUT).await?
+    {
+        report.agreement_timeouts += 1;
+    }
+    Ok(())
+}
+
+/// Apply the pending migrations of `local` to `keyspace`, in order, recording
+/// each in the tracking table once all of its statements succeeded
+pub async fn apply_pending(
+    session: &CassandraSession,
+    keyspace: &str,
+    local: &[Migration],
+) -> Result<MigrationReport> {
+    let statuses = status(local, &applied_migrations(session, keyspace).await?);
+    let blocking = problems(&statuses);
+    if !blocking.is_empty() {
+        return Err(MagdaError::validation(blocking.join("; ")));
+    }
+    let pending = pending(local, &statuses);
+    let mut report = MigrationReport::default();
+    if pending.is_empty() {
+        return Ok(report);
+    }
+
+    run_statement(
+        session,
+        keyspace,
+        &tracking_table_cql(keyspace),
+        &mut report,
+    )
+    .await?;
+    for migration in pending {
+        tracing::info!(
+            "Applying migration {} ({}) to {}",
+            migration.version,
+            migration.file_name,
+            keyspace
+        );
+        for (i, statement) in migration.statements.iter().enumerate() {
+            if let Err(e) = run_statement(session, keyspace, statement, &mut report).await {
+                return Err(MagdaError::QueryError(format!(
+                    "{} statement {} failed after {} migration(s) were applied: {}",
+                    migration.file_name,
+                    i + 1,
+                    report.applied.len(),
+                    e
+                )));
+            }
+        }
+        cassandra::execute_query(session, &record_cql(keyspace, migration)).await?;
+        report.applied.push(migration.version);
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn migration(version: u64, source: &str) -> Migration {
+        Migration::parse(&format!("{:04}_step_{}.cql", version, version), source).unwrap()
+    }
+
+    fn applied(migration: &Migration) -> AppliedMigration {
+        AppliedMigration {
+            version: migration.version,
+            description: migration.description.clone(),
+            checksum: migration.checksum.clone(),
+            applied_at: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_file_names() {
+        assert_eq!(
+            parse_file_name("0003_add_orders.cql"),
+            Some((3, "add orders".to_string()))
+        );
+        assert_eq!(
+            parse_file_name("V12__backfill.cql"),
+            Some((12, "backfill".to_string()))
+        );
+        assert_eq!(parse_file_name("7.cql"), Some((7, String::new())));
+        assert_eq!(parse_file_name("add_orders.cql"), None);
+        assert_eq!(parse_file_name("0003_add_orders.sql"), None);
+
+        let parsed = Migration::parse(
+            "0001_init.cql",
+            "CREATE TABLE a (id int PRIMARY KEY);\r\n-- index\nCREATE INDEX ON a (id);\n",
+        )
+        .unwrap();
+        assert_eq!(parsed.statements.len(), 2);
+        assert_eq!(
+            parsed.checksum,
+            checksum("CREATE TABLE a (id int PRIMARY KEY);\n-- index\nCREATE INDEX ON a (id);\n")
+        );
+        assert!(Migration::parse("0002_empty.cql", "-- nothing yet\n").is_err());
+    }
+
+    #[test]
+    fn test_load_dir() {
+        let dir = std::env::temp_dir().join(format!("magda-migrations-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("0002_orders.cql"),
+            "CREATE TABLE orders (id int PRIMARY KEY);",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("0001_users.cql"),
+            "CREATE TABLE users (id int PRIMARY KEY);",
+        )
+        .unwrap();
+        fs::write(dir.join("README.md"), "not a migration").unwrap();
+        let versions: Vec<u64> = load_dir(&dir).unwrap().iter().map(|m| m.version).collect();
+        assert_eq!(versions, vec![1, 2]);
+
+        fs::write(
+            dir.join("002_carts.cql"),
+            "CREATE TABLE carts (id int PRIMARY KEY);",
+        )
+        .unwrap();
+        assert!(load_dir(&dir).is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_status_and_problems() {
+        let first = migration(1, "CREATE TABLE a (id int PRIMARY KEY);");
+        let second = migration(2, "CREATE TABLE b (id int PRIMARY KEY);");
+        let third = migration(3, "CREATE TABLE c (id int PRIMARY KEY);");
+        let local = vec![first.clone(), second.clone(), third.clone()];
+
+        let statuses = status(&local, &[applied(&first)]);
+        let states: Vec<MigrationState> = statuses.iter().map(|s| s.state).collect();
+        assert_eq!(
+            states,
+            vec![
+                MigrationState::Applied,
+                MigrationState::Pending,
+                MigrationState::Pending
+            ]
+        );
+        assert!(problems(&statuses).is_empty());
+        let versions: Vec<u64> = pending(&local, &statuses)
+            .iter()
+            .map(|m| m.version)
+            .collect();
+        assert_eq!(versions, vec![2, 3]);
+
+        let mut edited = applied(&first);
+        edited.checksum = "0000000000000000".to_string();
+        let gone = AppliedMigration {
+            version: 9,
+            description: "dropped file".to_string(),
+            checksum: "0000000000000000".to_string(),
+            applied_at: None,
+        };
+        let statuses = status(&local, &[edited, applied(&third), gone]);
+        let states: Vec<MigrationState> = statuses.iter().map(|s| s.state).collect();
+        assert_eq!(
+            states,
+            vec![
+                MigrationState::Modified,
+                MigrationState::OutOfOrder,
+                MigrationState::Applied,
+                MigrationState::Missing
+            ]
+        );
+        assert_eq!(
+            problems(&statuses),
+            vec![
+                "Migration 1 (step 1) changed after it was applied",
+                "Migration 2 (step 2) is older than the latest applied migration"
+            ]
+        );
+        assert!(pending(&local, &statuses).is_empty());
+    }
+
+    #[test]
+    fn test_dry_run_script() {
+        let add = Migration::parse(
+            "0004_add_carts.cql",
+            "CREATE TABLE carts (id uuid PRIMARY KEY);\nALTER TABLE carts ADD note text;",
+        )
+        .unwrap();
+        let script = dry_run_script("shop", &[&add]);
+        assert_eq!(
+            script,
+            format!(
+                "USE shop;\n\n\
+                CREATE TABLE IF NOT EXISTS shop.schema_migrations (version bigint PRIMARY KEY, description text, checksum text, applied_at timestamp);\n\n\
+                -- 4: add carts (0004_add_carts.cql)\n\
+                CREATE TABLE carts (id uuid PRIMARY KEY);\n\
+                ALTER TABLE carts ADD note text;\n\
+                INSERT INTO shop.schema_migrations (version, description, checksum, applied_at) VALUES (4, 'add carts', '{}', toTimestamp(now()));\n",
+                add.checksum
+            )
+        );
+    }
+}
@@ -0,0 +1,472 @@
+//! Roles and permissions as read from `LIST ROLES`, `LIST ALL PERMISSIONS`
+//! and `system_auth`, and the `CREATE ROLE` / `GRANT` / `REVOKE` statements
+//! the security view generates.
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use crate::cql_tokenizer::{tokenize, TokenKind};
+use crate::schema::{quote_identifier, quote_literal, SchemaRow};
+
+/// Permissions that can be granted, `ALL` standing for every one applicable
+pub const PERMISSIONS: [&str; 9] = [
+    "ALL",
+    "CREATE",
+    "ALTER",
+    "DROP",
+    "SELECT",
+    "MODIFY",
+    "AUTHORIZE",
+    "DESCRIBE",
+    "EXECUTE",
+];
+
+/// A role of the cluster
+#[derive(Clone, Debug, PartialEq)]
+pub struct Role {
+    pub name: String,
+    pub can_login: bool,
+    pub is_superuser: bool,
+    /// Roles this role is granted, and so inherits permissions from
+    pub member_of: Vec<String>,
+}
+
+impl Role {
+    /// A row of `LIST ROLES`
+    pub fn from_list_row(row: &SchemaRow) -> Self {
+        Self {
+            name: row.text("role").unwrap_or_default(),
+            can_login: row.boolean("login").unwrap_or(false),
+            is_superuser: row.boolean("super").unwrap_or(false),
+            member_of: Vec::new(),
+        }
+    }
+}
+
+/// One permission held by a role on a resource, as `LIST ALL PERMISSIONS`
+/// reports it
+#[derive(Clone, Debug, PartialEq)]
+pub struct Grant {
+    pub role: String,
+    /// Resource as Cassandra prints it, e.g. `<table shop.orders>`
+    pub resource: String,
+    pub permission: String,
+}
+
+impl Grant {
+    pub fn from_row(row: &SchemaRow) -> Self {
+        Self {
+            role: row.text("role").unwrap_or_default(),
+            resource: row.text("resource").unwrap_or_default(),
+            permission: row.text("permission").unwrap_or_default(),
+        }
+    }
+
+    /// `REVOKE` taking this permission away again, when the resource is one
+    /// the security view understands
+    pub fn revoke_cql(&self) -> Option<String> {
+        let resource = Resource::from_label(&self.resource)?;
+        Some(revoke_cql(&self.permission, &resource, &self.role))
+    }
+}
+
+/// Something permissions are granted on
+#[derive(Clone, Debug, PartialEq)]
+pub enum Resource {
+    AllKeyspaces,
+    Keyspace(String),
+    Table(String, String),
+    AllRoles,
+    Role(String),
+    AllFunctions,
+    KeyspaceFunctions(String),
+}
+
+impl Resource {
+    /// The resource in a `GRANT ... ON <resource>` statement
+    pub fn to_cql(&self) -> String {
+        match self {
+            Resource::AllKeyspaces => "ALL KEYSPACES".to_string(),
+            Resource::Keyspace(ks) => format!("KEYSPACE {}", quote_identifier(ks)),
+            Resource::Table(ks, table) => {
+                format!("TABLE {}.{}", quote_identifier(ks), quote_identifier(table))
+            }
+            Resource::AllRoles => "ALL ROLES".to_string(),
+            Resource::Role(role) => format!("ROLE {}", quote_identifier(role)),
+            Resource::AllFunctions => "ALL FUNCTIONS".to_string(),
+            Resource::KeyspaceFunctions(ks) => {
+                format!("ALL FUNCTIONS IN KEYSPACE {}", quote_identifier(ks))
+            }
+        }
+    }
+
+    /// The resource as `LIST ALL PERMISSIONS` prints it
+    pub fn label(&self) -> String {
+        match self {
+            Resource::AllKeyspaces => "<all keyspaces>".to_string(),
+            Resource::Keyspace(ks) => format!("<keyspace {}>", ks),
+            Resource::Table(ks, table) => format!("<table {}.{}>", ks, table),
+            Resource::AllRoles => "<all roles>".to_string(),
+            Resource::Role(role) => format!("<role {}>", role),
+            Resource::AllFunctions => "<all functions>".to_string(),
+            Resource::KeyspaceFunctions(ks) => format!("<all functions in {}>", ks),
+        }
+    }
+
+    /// Parse a resource printed by `LIST ALL PERMISSIONS`. Single functions
+    /// and MBeans are `None`.
+    pub fn from_label(label: &str) -> Option<Self> {
+        let inner = label.strip_prefix('<')?.strip_suffix('>')?;
+        match inner {
+            "all keyspaces" => return Some(Resource::AllKeyspaces),
+            "all roles" => return Some(Resource::AllRoles),
+            "all functions" => return Some(Resource::AllFunctions),
+            _ => {}
+        }
+        if let Some(ks) = inner.strip_prefix("all functions in ") {
+            return Some(Resource::KeyspaceFunctions(ks.to_string()));
+        }
+        let (kind, name) = inner.split_once(' ')?;
+        match kind {
+            "keyspace" => Some(Resource::Keyspace(name.to_string())),
+            "table" => {
+                let (ks, table) = name.split_once('.')?;
+                Some(Resource::Table(ks.to_string(), table.to_string()))
+            }
+            "role" => Some(Resource::Role(name.to_string())),
+            _ => None,
+        }
+    }
+
+    /// Permissions Cassandra accepts on this resource, besides `ALL`
+    pub fn applicable_permissions(&self) -> &'static [&'static str] {
+        match self {
+            Resource::AllKeyspaces | Resource::Keyspace(_) => {
+                &["CREATE", "ALTER", "DROP", "SELECT", "MODIFY", "AUTHORIZE"]
+            }
+            Resource::Table(_, _) => &["ALTER", "DROP", "SELECT", "MODIFY", "AUTHORIZE"],
+            Resource::AllRoles => &["CREATE", "ALTER", "DROP", "AUTHORIZE", "DESCRIBE"],
+            Resource::Role(_) => &["ALTER", "DROP", "AUTHORIZE"],
+            Resource::AllFunctions | Resource::KeyspaceFunctions(_) => {
+                &["CREATE", "ALTER", "DROP", "AUTHORIZE", "EXECUTE"]
+            }
+        }
+    }
+
+    /// Why `permission` cannot be granted on this resource, if it cannot
+    pub fn check_permission(&self, permission: &str) -> Option<String> {
+        if permission == "ALL" || self.applicable_permissions().contains(&permission) {
+            None
+        } else {
+            Some(format!(
+                "{} cannot be granted on {}; use one of {}",
+                permission,
+                self.label(),
+                self.applicable_permissions().join(", ")
+            ))
+        }
+    }
+}
+
+fn permission_cql(permission: &str) -> &str {
+    if permission == "ALL" {
+        "ALL PERMISSIONS"
+    } else {
+        permission
+    }
+}
+
+pub fn grant_cql(permission: &str, resource: &Resource, role: &str) -> String {
+    format!(
+        "GRANT {} ON {} TO {};",
+        permission_cql(permission),
+        resource.to_cql(),
+        quote_identifier(role)
+    )
+}
+
+pub fn revoke_cql(permission: &str, resource: &Resource, role: &str) -> String {
+    format!(
+        "REVOKE {} ON {} FROM {};",
+        permission_cql(permission),
+        resource.to_cql(),
+        quote_identifier(role)
+    )
+}
+
+pub fn drop_role_cql(role: &str) -> String {
+    format!("DROP ROLE {};", quote_identifier(role))
+}
+
+/// `statement` with the literal after each `PASSWORD` (`= '...'` or `'...'`)
+/// masked, for statements that are logged or kept in query metrics
+pub fn redact_passwords(statement: &str) -> Cow<'_, str> {
+    let tokens: Vec<_> = tokenize(statement)
+        .into_iter()
+        .filter(|t| !matches!(t.kind, TokenKind::Whitespace | TokenKind::Comment))
+        .collect();
+    let mut secrets = Vec::new();
+    for (i, token) in tokens.iter().enumerate() {
+        if token.kind == TokenKind::String || !token.text.eq_ignore_ascii_case("PASSWORD") {
+            continue;
+        }
+        let value = match tokens.get(i + 1) {
+            Some(next) if next.text == "=" => tokens.get(i + 2),
+            next => next,
+        };
+        if let Some(value) = value.filter(|t| t.kind == TokenKind::String) {
+            secrets.push(value.start..value.start + value.text.len());
+        }
+    }
+    if secrets.is_empty() {
+        return Cow::Borrowed(statement);
+    }
+
+    let mut redacted = String::with_capacity(statement.len());
+    let mut copied = 0;
+    for secret in secrets {
+        redacted.push_str(&statement[copied..secret.start]);
+        redacted.push_str("'********'");
+        copied = secret.end;
+    }
+    redacted.push_str(&statement[copied..]);
+    Cow::Owned(redacted)
+}
+
+/// Permissions of each role, by resource
+pub fn grants_by_role(grants: &[Grant]) -> BTreeMap<String, BTreeMap<String, Vec<String>>> {
+    let mut by_role: BTreeMap<String, BTreeMap<String, Vec<String>>> = BTreeMap::new();
+    for grant in grants {
+        by_role
+            .entry(grant.role.clone())
+            .or_default()
+            .entry(grant.resource.clone())
+            .or_default()
+            .push(grant.permission.clone());
+    }
+    by_role
+}
+
+/// Permissions on each resource, by role
+pub fn grants_by_resource(grants: &[Grant]) -> BTreeMap<String, BTreeMap<String, Vec<String>>> {
+    let mut by_resource: BTreeMap<String, BTreeMap<String, Vec<String>>> = BTreeMap::new();
+    for grant in grants {
+        by_resource
+            .entry(grant.resource.clone())
+            .or_default()
+            .entry(grant.role.clone())
+            .or_default()
+            .push(grant.permission.clone());
+    }
+    by_resource
+}
+
+/// A role being created or altered
+#[derive(Clone, Debug, PartialEq)]
+pub struct RoleDesign {
+    pub name: String,
+    /// New password; empty leaves it unset (or unchanged when altering)
+    pub password: String,
+    pub login: bool,
+    pub superuser: bool,
+}
+
+impl RoleDesign {
+    pub fn new() -> Self {
+        Self {
+            name: String::new(),
+            password: String::new(),
+            login: true,
+            superuser: false,
+        }
+    }
+
+    pub fn from_role(role: &Role) -> Self {
+        Self {
+            name: role.name.clone(),
+            password: String::new(),
+            login: role.can_login,
+            superuser: role.is_superuser,
+        }
+    }
+
+    /// Why the role cannot be created, or `current` altered, as designed;
+    /// empty when it can
+    pub fn validate(&self, current: Option<&Role>) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.name.trim().is_empty() {
+            problems.push("The role needs a name".to_string());
+        }
+        let had_login = current.is_some_and(|r| r.can_login);
+        if self.login && !had_login && self.password.is_empty() {
+            problems.push("Roles that can log in need a password".to_string());
+        }
+        problems
+    }
+
+    fn options(&self, password: bool, login: bool, superuser: bool) -> Vec<String> {
+        let mut options = Vec::new();
+        if password && !self.password.is_empty() {
+            options.push(format!("PASSWORD = {}", quote_literal(&self.password)));
+        }
+        if login {
+            options.push(format!("LOGIN = {}", self.login));
+        }
+        if superuser {
+            options.push(format!("SUPERUSER = {}", self.superuser));
+        }
+        options
+    }
+
+    pub fn create_cql(&self) -> String {
+        format!(
+            "CREATE ROLE {} WITH {};",
+            quote_identifier(self.name.trim()),
+            self.options(true, true, true).join(" AND ")
+        )
+    }
+
+    /// `ALTER ROLE` turning `current` into this design, `None` when nothing
+    /// changed
+    pub fn alter_cql(&self, current: &Role) -> Option<String> {
+        let options = self.options(
+            true,
+            self.login != current.can_login,
+            self.superuser != current.is_superuser,
+        );
+        if options.is_empty() {
+            return None;
+        }
+        Some(format!(
+            "ALTER ROLE {} WITH {};",
+            quote_identifier(&current.name),
+            options.join(" AND ")
+        ))
+    }
+}
+
+impl Default for RoleDesign {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grant(role: &str, resource: &str, permission: &str) -> Grant {
+        Grant {
+            role: role.to_string(),
+            resource: resource.to_string(),
+            permission: permission.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_redact_passwords() {
+        assert_eq!(
+            redact_passwords(
+                "CREATE ROLE analyst WITH PASSWORD = 'it''s secret' AND LOGIN = true;"
+            ),
+            "CREATE ROLE analyst WITH PASSWORD = '********' AND LOGIN = true;"
+        );
+        assert_eq!(
+            redact_passwords("create user bob with password 'a'; alter role x with password='b'"),
+            "create user bob with password '********'; alter role x with password='********'"
+        );
+        assert!(matches!(
+            redact_passwords("SELECT password FROM creds WHERE id = 'x'"),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn test_grant_and_revoke_statements() {
+        let table = Resource::Table("Shop".to_string(), "orders".to_string());
+        assert_eq!(
+            grant_cql("SELECT", &table, "analyst"),
+            "GRANT SELECT ON TABLE \"Shop\".orders TO analyst;"
+        );
+        assert_eq!(
+            revoke_cql("ALL", &Resource::AllKeyspaces, "Ops Team"),
+            "REVOKE ALL PERMISSIONS ON ALL KEYSPACES FROM \"Ops Team\";"
+        );
+        assert!(table.check_permission("MODIFY").is_none());
+        assert!(table.check_permission("ALL").is_none());
+        assert!(table.check_permission("CREATE").is_some());
+        assert!(Resource::Role("app".to_string())
+            .check_permission("DESCRIBE")
+            .is_some());
+
+        assert_eq!(
+            grant("analyst", "<table shop.orders>", "SELECT").revoke_cql(),
+            Some("REVOKE SELECT ON TABLE shop.orders FROM analyst;".to_string())
+        );
+        assert_eq!(
+            grant("app", "<function shop.f(int)>", "EXECUTE").revoke_cql(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_resource_labels_round_trip() {
+        for resource in [
+            Resource::AllKeyspaces,
+            Resource::Keyspace("shop".to_string()),
+            Resource::Table("shop".to_string(), "orders".to_string()),
+            Resource::AllRoles,
+            Resource::Role("admin".to_string()),
+            Resource::AllFunctions,
+            Resource::KeyspaceFunctions("shop".to_string()),
+        ] {
+            assert_eq!(Resource::from_label(&resource.label()), Some(resource));
+        }
+        assert_eq!(Resource::from_label("<all mbeans>"), None);
+    }
+
+    #[test]
+    fn test_role_statements() {
+        let mut design = RoleDesign::new();
+        assert_eq!(design.validate(None).len(), 2);
+        design.name = "analyst".to_string();
+        design.password = "it's secret".to_string();
+        assert!(design.validate(None).is_empty());
+        assert_eq!(
+            design.create_cql(),
+            "CREATE ROLE analyst WITH PASSWORD = 'it''s secret' AND LOGIN = true AND SUPERUSER = false;"
+        );
+
+        let current = Role {
+            name: "analyst".to_string(),
+            can_login: true,
+            is_superuser: false,
+            member_of: Vec::new(),
+        };
+        let mut design = RoleDesign::from_role(&current);
+        assert!(design.validate(Some(&current)).is_empty());
+        assert_eq!(design.alter_cql(&current), None);
+        design.superuser = true;
+        assert_eq!(
+            design.alter_cql(&current),
+            Some("ALTER ROLE analyst WITH SUPERUSER = true;".to_string())
+        );
+    }
+
+    #[test]
+    fn test_grants_grouping() {
+        let grants = vec![
+            grant("analyst", "<keyspace shop>", "SELECT"),
+            grant("analyst", "<keyspace shop>", "DESCRIBE"),
+            grant("app", "<keyspace shop>", "MODIFY"),
+        ];
+        let by_role = grants_by_role(&grants);
+        assert_eq!(
+            by_role["analyst"]["<keyspace shop>"],
+            vec!["SELECT", "DESCRIBE"]
+        );
+        let by_resource = grants_by_resource(&grants);
+        assert_eq!(by_resource["<keyspace shop>"].len(), 2);
+        assert_eq!(by_resource["<keyspace shop>"]["app"], vec!["MODIFY"]);
+    }
+}
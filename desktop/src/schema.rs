@@ -0,0 +1,1345 @@
+//! Schema model read from `system_schema`, and the CQL DDL that recreates it.
+//!
+//! The definitions here mirror the `system_schema` tables closely enough to
+//! regenerate the `CREATE` statements `cqlsh`'s `DESCRIBE` prints: keyspaces,
+//! user-defined types, tables with their `WITH` options, indexes, materialized
+//! views, functions and aggregates.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::cassandra::SchemaColumn;
+
+/// Reserved CQL keywords that must be quoted when used as identifiers
+static RESERVED_KEYWORDS: &[&str] = &[
+    "add",
+    "allow",
+    "alter",
+    "and",
+    "apply",
+    "asc",
+    "authorize",
+    "batch",
+    "begin",
+    "by",
+    "columnfamily",
+    "create",
+    "default",
+    "delete",
+    "desc",
+    "describe",
+    "drop",
+    "entries",
+    "execute",
+    "from",
+    "full",
+    "grant",
+    "if",
+    "in",
+    "index",
+    "infinity",
+    "insert",
+    "into",
+    "is",
+    "keyspace",
+    "limit",
+    "materialized",
+    "mbean",
+    "mbeans",
+    "modify",
+    "nan",
+    "norecursive",
+    "not",
+    "null",
+    "of",
+    "on",
+    "or",
+    "order",
+    "primary",
+    "rename",
+    "replace",
+    "revoke",
+    "schema",
+    "select",
+    "set",
+    "table",
+    "to",
+    "token",
+    "truncate",
+    "unlogged",
+    "unset",
+    "update",
+    "use",
+    "using",
+    "view",
+    "where",
+    "with",
+];
+
+/// Whether `word`, in any case, is a reserved keyword that only works as a
+/// name when quoted
+pub fn is_reserved_keyword(word: &str) -> bool {
+    RESERVED_KEYWORDS
+        .binary_search(&word.to_ascii_lowercase().as_str())
+        .is_ok()
+}
+
+/// Quote an identifier unless it is a plain lowercase, non-reserved name
+pub fn quote_identifier(name: &str) -> String {
+    let plain = name.chars().next().is_some_and(|c| c.is_ascii_lowercase())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        && RESERVED_KEYWORDS.binary_search(&name).is_err();
+    if plain {
+        name.to_string()
+    } else {
+        format!("\"{}\"", name.replace('"', "\"\""))
+    }
+}
+
+/// `keyspace.name`, each part quoted as needed
+pub fn qualified_name(keyspace: &str, name: &str) -> String {
+    format!("{}.{}", quote_identifier(keyspace), quote_identifier(name))
+}
+
+/// A CQL string literal
+pub fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// A `{'key': 'value', ...}` map literal, with `class` first as cqlsh prints it
+pub fn map_literal(map: &BTreeMap<String, String>) -> String {
+    let class = map.get("class").map(|v| ("class", v));
+    let entries: Vec<String> = class
+        .into_iter()
+        .chain(
+            map.iter()
+                .filter(|(k, _)| k.as_str() != "class")
+                .map(|(k, v)| (k.as_str(), v)),
+        )
+        .map(|(k, v)| format!("{}: {}", quote_literal(k), quote_literal(v)))
+        .collect();
+    format!("{{{}}}", entries.join(", "))
+}
+
+/// Doubles keep a fractional part (`1.0`, not `1`), as Cassandra prints them
+fn double_literal(value: f64) -> String {
+    if value.fract() == 0.0 && value.is_finite() {
+        format!("{:.1}", value)
+    } else {
+        value.to_string()
+    }
+}
+
+// ── Raw rows ───────────────────────────────────────────────────
+
+/// One row of a `system_schema` table, addressed by column name.
+/// Null cells are simply absent.
+#[derive(Clone, Debug, Default)]
+pub struct SchemaRow {
+    cells: HashMap<String, Vec<u8>>,
+}
+
+impl SchemaRow {
+    pub fn new(cells: HashMap<String, Vec<u8>>) -> Self {
+        Self { cells }
+    }
+
+    fn bytes(&self, column: &str) -> Option<&[u8]> {
+        self.cells.get(column).map(Vec::as_slice)
+    }
+
+    pub fn text(&self, column: &str) -> Option<String> {
+        self.bytes(column)
+            .and_then(|b| String::from_utf8(b.to_vec()).ok())
+    }
+
+    pub fn int(&self, column: &str) -> Option<i32> {
+        self.bytes(column)
+            .and_then(|b| b.try_into().ok())
+            .map(i32::from_be_bytes)
+    }
+
+    pub fn bigint(&self, column: &str) -> Option<i64> {
+        self.bytes(column)
+            .and_then(|b| b.try_into().ok())
+            .map(i64::from_be_bytes)
+    }
+
+    pub fn double(&self, column: &str) -> Option<f64> {
+        self.bytes(column)
+            .and_then(|b| b.try_into().ok())
+            .map(f64::from_be_bytes)
+    }
+
+    pub fn boolean(&self, column: &str) -> Option<bool> {
+        self.bytes(column).and_then(|b| b.first()).map(|&b| b != 0)
+    }
+
+    /// Raw bytes of a column, for comparing values of types without a decoder
+    pub fn raw(&self, column: &str) -> Option<&[u8]> {
+        self.bytes(column)
+    }
+
+    /// A `list<text>` or `set<text>` column
+    pub fn text_list(&self, column: &str) -> Vec<String> {
+        self.bytes(column)
+            .and_then(decode_collection)
+            .unwrap_or_default()
+    }
+
+    /// A `map<text, text>` column
+    pub fn text_map(&self, column: &str) -> BTreeMap<String, String> {
+        let items = self
+            .bytes(column)
+            .and_then(decode_collection)
+            .unwrap_or_default();
+        let mut items = items.into_iter();
+        let mut map = BTreeMap::new();
+        while let (Some(key), Some(value)) = (items.next(), items.next()) {
+            map.insert(key, value);
+        }
+        map
+    }
+}
+
+/// Decode a serialized collection of text elements. Maps come back as a flat
+/// key, value, key, value... sequence.
+fn decode_collection(bytes: &[u8]) -> Option<Vec<String>> {
+    fn read_i32(bytes: &[u8], at: &mut usize) -> Option<i32> {
+        let value = i32::from_be_bytes(bytes.get(*at..*at + 4)?.try_into().ok()?);
+        *at += 4;
+        Some(value)
+    }
+
+    let mut at = 0;
+    let count = read_i32(bytes, &mut at)?;
+    let mut items = Vec::new();
+    // Maps serialize `count` pairs; the loop reads until the bytes run out
+    while at < bytes.len() {
+        let len = read_i32(bytes, &mut at)?;
+        if len < 0 {
+            items.push(String::new());
+            continue;
+        }
+        let end = at + len as usize;
+        items.push(String::from_utf8(bytes.get(at..end)?.to_vec()).ok()?);
+        at = end;
+    }
+    if items.len() < count as usize {
+        return None;
+    }
+    Some(items)
+}
+
+// ── Schema model ───────────────────────────────────────────────
+
+/// `WITH` options of a table or materialized view. Options that only exist
+/// on some Cassandra versions are `None` when the cluster does not have them.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TableOptions {
+    pub additional_write_policy: Option<String>,
+    pub bloom_filter_fp_chance: Option<f64>,
+    pub caching: BTreeMap<String, String>,
+    pub cdc: Option<bool>,
+    pub comment: String,
+    pub compaction: BTreeMap<String, String>,
+    pub compression: BTreeMap<String, String>,
+    pub crc_check_chance: Option<f64>,
+    pub dclocal_read_repair_chance: Option<f64>,
+    pub default_time_to_live: Option<i32>,
+    pub gc_grace_seconds: Option<i32>,
+    pub max_index_interval: Option<i32>,
+    pub memtable_flush_period_in_ms: Option<i32>,
+    pub min_index_interval: Option<i32>,
+    pub read_repair: Option<String>,
+    pub read_repair_chance: Option<f64>,
+    pub speculative_retry: Option<String>,
+}
+
+impl TableOptions {
+    pub fn from_row(row: &SchemaRow) -> Self {
+        Self {
+            additional_write_policy: row.text("additional_write_policy"),
+            bloom_filter_fp_chance: row.double("bloom_filter_fp_chance"),
+            caching: row.text_map("caching"),
+            cdc: row.boolean("cdc"),
+            comment: row.text("comment").unwrap_or_default(),
+            compaction: row.text_map("compaction"),
+            compression: row.text_map("compression"),
+            crc_check_chance: row.double("crc_check_chance"),
+            dclocal_read_repair_chance: row.double("dclocal_read_repair_chance"),
+            default_time_to_live: row.int("default_time_to_live"),
+            gc_grace_seconds: row.int("gc_grace_seconds"),
+            max_index_interval: row.int("max_index_interval"),
+            memtable_flush_period_in_ms: row.int("memtable_flush_period_in_ms"),
+            min_index_interval: row.int("min_index_interval"),
+            read_repair: row.text("read_repair"),
+            read_repair_chance: row.double("read_repair_chance"),
+            speculative_retry: row.text("speculative_retry"),
+        }
+    }
+
+    /// `name = value` pairs in the order `DESCRIBE` prints them
+    pub fn to_cql_pairs(&self) -> Vec<(&'static str, String)> {
+        let mut pairs = Vec::new();
+        if let Some(v) = &self.additional_write_policy {
+            pairs.push(("additional_write_policy", quote_literal(v)));
+        }
+        if let Some(v) = self.bloom_filter_fp_chance {
+            pairs.push(("bloom_filter_fp_chance", double_literal(v)));
+        }
+        if !self.caching.is_empty() {
+            pairs.push(("caching", map_literal(&self.caching)));
+        }
+        if let Some(v) = self.cdc {
+            pairs.push(("cdc", v.to_string()));
+        }
+        pairs.push(("comment", quote_literal(&self.comment)));
+        if !self.compaction.is_empty() {
+            pairs.push(("compaction", map_literal(&self.compaction)));
+        }
+        if !self.compression.is_empty() {
+            pairs.push(("compression", map_literal(&self.compression)));
+        }
+        if let Some(v) = self.crc_check_chance {
+            pairs.push(("crc_check_chance", double_literal(v)));
+        }
+        if let Some(v) = self.dclocal_read_repair_chance {
+            pairs.push(("dclocal_read_repair_chance", double_literal(v)));
+        }
+        let ints = [
+            ("default_time_to_live", self.default_time_to_live),
+            ("gc_grace_seconds", self.gc_grace_seconds),
+            ("max_index_interval", self.max_index_interval),
+            (
+                "memtable_flush_period_in_ms",
+                self.memtable_flush_period_in_ms,
+            ),
+            ("min_index_interval", self.min_index_interval),
+        ];
+        for (name, value) in ints {
+            if let Some(v) = value {
+                pairs.push((name, v.to_string()));
+            }
+        }
+        if let Some(v) = &self.read_repair {
+            pairs.push(("read_repair", quote_literal(v)));
+        }
+        if let Some(v) = self.read_repair_chance {
+            pairs.push(("read_repair_chance", double_literal(v)));
+        }
+        if let Some(v) = &self.speculative_retry {
+            pairs.push(("speculative_retry", quote_literal(v)));
+        }
+        pairs
+    }
+
+    /// Options set differently in `desired`, as `(name, current, desired)`
+    /// CQL literals. Options this table does not report are left out, since
+    /// the cluster's Cassandra version would reject them.
+    pub fn changes_to(&self, desired: &TableOptions) -> Vec<(&'static str, String, String)> {
+        let current: BTreeMap<&str, String> = self.to_cql_pairs().into_iter().collect();
+        desired
+            .to_cql_pairs()
+            .into_iter()
+            .filter_map(|(option, value)| {
+                let existing = current.get(option)?;
+                (existing != &value).then(|| (option, existing.clone(), value))
+            })
+            .collect()
+    }
+
+    /// `<alter> WITH ...` setting the options that differ in `desired`,
+    /// where `alter` is e.g. `ALTER TABLE ks.t`
+    pub fn alter_cql(&self, alter: &str, desired: &TableOptions) -> Option<String> {
+        let changes = self.changes_to(desired);
+        if changes.is_empty() {
+            return None;
+        }
+        let assignments: Vec<String> = changes
+            .iter()
+            .map(|(option, _, value)| format!("{} = {}", option, value))
+            .collect();
+        Some(format!(
+            "{} WITH {};",
+            alter,
+            assignments.join("\n    AND ")
+        ))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct KeyspaceDef {
+    pub name: String,
+    pub replication: BTreeMap<String, String>,
+    pub durable_writes: bool,
+}
+
+impl KeyspaceDef {
+    pub fn from_row(row: &SchemaRow) -> Self {
+        Self {
+            name: row.text("keyspace_name").unwrap_or_default(),
+            replication: row.text_map("replication"),
+            durable_writes: row.boolean("durable_writes").unwrap_or(true),
+        }
+    }
+
+    pub fn to_cql(&self) -> String {
+        format!(
+            "CREATE KEYSPACE {} WITH replication = {} AND durable_writes = {};",
+            quote_identifier(&self.name),
+            map_literal(&self.replication),
+            self.durable_writes
+        )
+    }
+}
+
+/// A user-defined type and its fields in declaration order
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UserTypeDef {
+    pub keyspace: String,
+    pub name: String,
+    pub fields: Vec<(String, String)>,
+}
+
+impl UserTypeDef {
+    pub fn from_row(row: &SchemaRow) -> Self {
+        let names = row.text_list("field_names");
+        let types = row.text_list("field_types");
+        Self {
+            keyspace: row.text("keyspace_name").unwrap_or_default(),
+            name: row.text("type_name").unwrap_or_default(),
+            fields: names.into_iter().zip(types).collect(),
+        }
+    }
+
+    pub fn to_cql(&self) -> String {
+        let fields: Vec<String> = self
+            .fields
+            .iter()
+            .map(|(name, ty)| format!("    {} {}", quote_identifier(name), ty))
+            .collect();
+        format!(
+            "CREATE TYPE {} (\n{}\n);",
+            qualified_name(&self.keyspace, &self.name),
+            fields.join(",\n")
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TableDef {
+    pub keyspace: String,
+    pub name: String,
+    /// Sorted partition key, clustering, static, regular (see `describe_table`)
+    pub columns: Vec<SchemaColumn>,
+    pub options: TableOptions,
+}
+
+impl TableDef {
+    pub fn to_cql(&self) -> String {
+        let mut lines: Vec<String> = self
+            .columns
+            .iter()
+            .map(|c| {
+                let mut line = format!("    {} {}", quote_identifier(&c.name), c.data_type);
+                if c.kind == "static" {
+                    line.push_str(" static");
+                }
+                line
+            })
+            .collect();
+        lines.push(format!("    {}", primary_key_clause(&self.columns)));
+
+        format!(
+            "CREATE TABLE {} (\n{}\n){};",
+            qualified_name(&self.keyspace, &self.name),
+            lines.join(",\n"),
+            with_clause(&self.columns, &self.options)
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IndexDef {
+    pub keyspace: String,
+    pub table: String,
+    pub name: String,
+    /// `COMPOSITES`, `KEYS` or `CUSTOM`
+    pub kind: String,
+    pub options: BTreeMap<String, String>,
+}
+
+impl IndexDef {
+    pub fn from_row(row: &SchemaRow) -> Self {
+        Self {
+            keyspace: row.text("keyspace_name").unwrap_or_default(),
+            table: row.text("table_name").unwrap_or_default(),
+            name: row.text("index_name").unwrap_or_default(),
+            kind: row.text("kind").unwrap_or_default(),
+            options: row.text_map("options"),
+        }
+    }
+
+    /// Indexed column expression, e.g. `email` or `values(tags)`
+    pub fn target(&self) -> &str {
+        self.options.get("target").map(String::as_str).unwrap_or("")
+    }
+
+    pub fn class_name(&self) -> Option<&str> {
+        self.options.get("class_name").map(String::as_str)
+    }
+
+    pub fn to_cql(&self) -> String {
+        let on = format!(
+            "{} ON {} ({})",
+            quote_identifier(&self.name),
+            qualified_name(&self.keyspace, &self.table),
+            self.target()
+        );
+        match self.class_name() {
+            Some(class) if self.kind == "CUSTOM" => {
+                let extra: BTreeMap<String, String> = self
+                    .options
+                    .iter()
+                    .filter(|(k, _)| k.as_str() != "target" && k.as_str() != "class_name")
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect();
+                let mut cql = format!("CREATE CUSTOM INDEX {} USING {}", on, quote_literal(class));
+                if !extra.is_empty() {
+                    cql.push_str(&format!(" WITH OPTIONS = {}", map_literal(&extra)));
+                }
+                cql.push(';');
+                cql
+            }
+            _ => format!("CREATE INDEX {};", on),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ViewDef {
+    pub keyspace: String,
+    pub name: String,
+    pub base_table: String,
+    pub include_all_columns: bool,
+    pub where_clause: String,
+    pub columns: Vec<SchemaColumn>,
+    pub options: TableOptions,
+}
+
+impl ViewDef {
+    pub fn to_cql(&self) -> String {
+        let selection = if self.include_all_columns {
+            "*".to_string()
+        } else {
+            self.columns
+                .iter()
+                .map(|c| quote_identifier(&c.name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        format!(
+            "CREATE MATERIALIZED VIEW {} AS\n    SELECT {}\n    FROM {}\n    WHERE {}\n    {}\n{};",
+            qualified_name(&self.keyspace, &self.name),
+            selection,
+            qualified_name(&self.keyspace, &self.base_table),
+            self.where_clause,
+            primary_key_clause(&self.columns),
+            with_clause(&self.columns, &self.options)
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FunctionDef {
+    pub keyspace: String,
+    pub name: String,
+    pub argument_names: Vec<String>,
+    pub argument_types: Vec<String>,
+    pub return_type: String,
+    pub language: String,
+    pub body: String,
+    pub called_on_null_input: bool,
+}
+
+impl FunctionDef {
+    pub fn from_row(row: &SchemaRow) -> Self {
+        Self {
+            keyspace: row.text("keyspace_name").unwrap_or_default(),
+            name: row.text("function_name").unwrap_or_default(),
+            argument_names: row.text_list("argument_names"),
+            argument_types: row.text_list("argument_types"),
+            return_type: row.text("return_type").unwrap_or_default(),
+            language: row.text("language").unwrap_or_default(),
+            body: row.text("body").unwrap_or_default(),
+            called_on_null_input: row.boolean("called_on_null_input").unwrap_or(false),
+        }
+    }
+
+    /// `name(type, ...)`, which identifies an overload
+    pub fn signature(&self) -> String {
+        format!("{}({})", self.name, self.argument_types.join(", "))
+    }
+
+    pub fn to_cql(&self) -> String {
+        let arguments: Vec<String> = self
+            .argument_names
+            .iter()
+            .zip(&self.argument_types)
+            .map(|(name, ty)| format!("{} {}", quote_identifier(name), ty))
+            .collect();
+        format!(
+            "CREATE FUNCTION {}({})\n    {} ON NULL INPUT\n    RETURNS {}\n    LANGUAGE {}\n    AS $${}$$;",
+            qualified_name(&self.keyspace, &self.name),
+            arguments.join(", "),
+            if self.called_on_null_input {
+                "CALLED"
+            } else {
+                "RETURNS NULL"
+            },
+            self.return_type,
+            self.language,
+            self.body
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AggregateDef {
+    pub keyspace: String,
+    pub name: String,
+    pub argument_types: Vec<String>,
+    pub state_func: String,
+    pub state_type: String,
+    pub final_func: Option<String>,
+    /// Initial state as a CQL literal
+    pub initcond: Option<String>,
+    pub return_type: String,
+}
+
+impl AggregateDef {
+    pub fn from_row(row: &SchemaRow) -> Self {
+        Self {
+            keyspace: row.text("keyspace_name").unwrap_or_default(),
+            name: row.text("aggregate_name").unwrap_or_default(),
+            argument_types: row.text_list("argument_types"),
+            state_func: row.text("state_func").unwrap_or_default(),
+            state_type: row.text("state_type").unwrap_or_default(),
+            final_func: row.text("final_func").filter(|f| !f.is_empty()),
+            initcond: row.text("initcond").filter(|c| !c.is_empty()),
+            return_type: row.text("return_type").unwrap_or_default(),
+        }
+    }
+
+    pub fn signature(&self) -> String {
+        format!("{}({})", self.name, self.argument_types.join(", "))
+    }
+
+    pub fn to_cql(&self) -> String {
+        let mut cql = format!(
+            "CREATE AGGREGATE {}({})\n    SFUNC {}\n    STYPE {}",
+            qualified_name(&self.keyspace, &self.name),
+            self.argument_types.join(", "),
+            quote_identifier(&self.state_func),
+            self.state_type
+        );
+        if let Some(final_func) = &self.final_func {
+            cql.push_str(&format!("\n    FINALFUNC {}", quote_identifier(final_func)));
+        }
+        if let Some(initcond) = &self.initcond {
+            cql.push_str(&format!("\n    INITCOND {}", initcond));
+        }
+        cql.push(';');
+        cql
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TriggerDef {
+    pub keyspace: String,
+    pub table: String,
+    pub name: String,
+    pub options: BTreeMap<String, String>,
+}
+
+impl TriggerDef {
+    pub fn from_row(row: &SchemaRow) -> Self {
+        Self {
+            keyspace: row.text("keyspace_name").unwrap_or_default(),
+            table: row.text("table_name").unwrap_or_default(),
+            name: row.text("trigger_name").unwrap_or_default(),
+            options: row.text_map("options"),
+        }
+    }
+
+    /// Java class implementing `ITrigger`
+    pub fn class_name(&self) -> &str {
+        self.options.get("class").map(String::as_str).unwrap_or("")
+    }
+
+    pub fn to_cql(&self) -> String {
+        format!(
+            "CREATE TRIGGER {} ON {} USING {};",
+            quote_identifier(&self.name),
+            qualified_name(&self.keyspace, &self.table),
+            quote_literal(self.class_name())
+        )
+    }
+}
+
+/// A named object inside a keyspace, as browsed in the schema viewer
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SchemaObject {
+    Table(String),
+    View(String),
+    Type(String),
+    Index(String),
+    /// Identified by signature, since overloads share a name
+    Function(String),
+    Aggregate(String),
+    /// Trigger names are only unique within their table
+    Trigger {
+        table: String,
+        name: String,
+    },
+}
+
+impl SchemaObject {
+    pub fn kind_label(&self) -> &'static str {
+        match self {
+            SchemaObject::Table(_) => "Table",
+            SchemaObject::View(_) => "Materialized view",
+            SchemaObject::Type(_) => "Type",
+            SchemaObject::Index(_) => "Index",
+            SchemaObject::Function(_) => "Function",
+            SchemaObject::Aggregate(_) => "Aggregate",
+            SchemaObject::Trigger { .. } => "Trigger",
+        }
+    }
+
+    /// Heading for a list of objects of this kind
+    pub fn group_label(&self) -> &'static str {
+        match self {
+            SchemaObject::Table(_) => "Tables",
+            SchemaObject::View(_) => "Materialized views",
+            SchemaObject::Type(_) => "Types",
+            SchemaObject::Index(_) => "Indexes",
+            SchemaObject::Function(_) => "Functions",
+            SchemaObject::Aggregate(_) => "Aggregates",
+            SchemaObject::Trigger { .. } => "Triggers",
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            SchemaObject::Table(name)
+            | SchemaObject::View(name)
+            | SchemaObject::Type(name)
+            | SchemaObject::Index(name)
+            | SchemaObject::Function(name)
+            | SchemaObject::Aggregate(name)
+            | SchemaObject::Trigger { name, .. } => name,
+        }
+    }
+
+    /// Stable string form, e.g. `view:by_email` or `trigger:users.audit`
+    pub fn key(&self) -> String {
+        match self {
+            SchemaObject::Table(name) => format!("table:{}", name),
+            SchemaObject::View(name) => format!("view:{}", name),
+            SchemaObject::Type(name) => format!("type:{}", name),
+            SchemaObject::Index(name) => format!("index:{}", name),
+            SchemaObject::Function(signature) => format!("function:{}", signature),
+            SchemaObject::Aggregate(signature) => format!("aggregate:{}", signature),
+            SchemaObject::Trigger { table, name } => format!("trigger:{}.{}", table, name),
+        }
+    }
+
+    /// Inverse of [`SchemaObject::key`]
+    pub fn from_key(key: &str) -> Option<Self> {
+        let (kind, name) = key.split_once(':')?;
+        let name = name.to_string();
+        Some(match kind {
+            "table" => SchemaObject::Table(name),
+            "view" => SchemaObject::View(name),
+            "type" => SchemaObject::Type(name),
+            "index" => SchemaObject::Index(name),
+            "function" => SchemaObject::Function(name),
+            "aggregate" => SchemaObject::Aggregate(name),
+            "trigger" => {
+                let (table, name) = name.split_once('.')?;
+                SchemaObject::Trigger {
+                    table: table.to_string(),
+                    name: name.to_string(),
+                }
+            }
+            _ => return None,
+        })
+    }
+}
+
+/// Everything `system_schema` knows about one keyspace
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct KeyspaceSchema {
+    pub keyspace: KeyspaceDef,
+    pub types: Vec<UserTypeDef>,
+    pub tables: Vec<TableDef>,
+    pub indexes: Vec<IndexDef>,
+    pub views: Vec<ViewDef>,
+    pub functions: Vec<FunctionDef>,
+    pub aggregates: Vec<AggregateDef>,
+    pub triggers: Vec<TriggerDef>,
+}
+
+impl KeyspaceSchema {
+    pub fn table(&self, name: &str) -> Option<&TableDef> {
+        self.tables.iter().find(|t| t.name == name)
+    }
+
+    pub fn view(&self, name: &str) -> Option<&ViewDef> {
+        self.views.iter().find(|v| v.name == name)
+    }
+
+    pub fn user_type(&self, name: &str) -> Option<&UserTypeDef> {
+        self.types.iter().find(|t| t.name == name)
+    }
+
+    pub fn index(&self, name: &str) -> Option<&IndexDef> {
+        self.indexes.iter().find(|i| i.name == name)
+    }
+
+    pub fn function(&self, signature: &str) -> Option<&FunctionDef> {
+        self.functions.iter().find(|f| f.signature() == signature)
+    }
+
+    pub fn aggregate(&self, signature: &str) -> Option<&AggregateDef> {
+        self.aggregates.iter().find(|a| a.signature() == signature)
+    }
+
+    pub fn trigger(&self, table: &str, name: &str) -> Option<&TriggerDef> {
+        self.triggers
+            .iter()
+            .find(|t| t.table == table && t.name == name)
+    }
+
+    /// Every object in the keyspace, grouped by kind
+    pub fn objects(&self) -> Vec<SchemaObject> {
+        let mut objects: Vec<SchemaObject> = self
+            .tables
+            .iter()
+            .map(|t| SchemaObject::Table(t.name.clone()))
+            .collect();
+        objects.extend(
+            self.views
+                .iter()
+                .map(|v| SchemaObject::View(v.name.clone())),
+        );
+        objects.extend(
+            self.types
+                .iter()
+                .map(|t| SchemaObject::Type(t.name.clone())),
+        );
+        objects.extend(
+            self.indexes
+                .iter()
+                .map(|i| SchemaObject::Index(i.name.clone())),
+        );
+        objects.extend(
+            self.functions
+                .iter()
+                .map(|f| SchemaObject::Function(f.signature())),
+        );
+        objects.extend(
+            self.aggregates
+                .iter()
+                .map(|a| SchemaObject::Aggregate(a.signature())),
+        );
+        objects.extend(self.triggers.iter().map(|t| SchemaObject::Trigger {
+            table: t.table.clone(),
+            name: t.name.clone(),
+        }));
+        objects
+    }
+
+    /// `DESCRIBE` for one object; tables include their dependents
+    pub fn describe(&self, object: &SchemaObject) -> Option<String> {
+        match object {
+            SchemaObject::Table(name) => self.describe_table(name),
+            SchemaObject::View(name) => self.view(name).map(ViewDef::to_cql),
+            SchemaObject::Type(name) => self.user_type(name).map(UserTypeDef::to_cql),
+            SchemaObject::Index(name) => self.index(name).map(IndexDef::to_cql),
+            SchemaObject::Function(signature) => self.function(signature).map(FunctionDef::to_cql),
+            SchemaObject::Aggregate(signature) => {
+                self.aggregate(signature).map(AggregateDef::to_cql)
+            }
+            SchemaObject::Trigger { table, name } => {
+                self.trigger(table, name).map(TriggerDef::to_cql)
+            }
+        }
+    }
+
+    /// Tables and types whose columns or fields use the type `name`
+    pub fn type_usages(&self, name: &str) -> Vec<SchemaObject> {
+        let mut usages: Vec<SchemaObject> = self
+            .tables
+            .iter()
+            .filter(|t| {
+                t.columns
+                    .iter()
+                    .any(|c| type_references(&c.data_type, name))
+            })
+            .map(|t| SchemaObject::Table(t.name.clone()))
+            .collect();
+        usages.extend(
+            self.types
+                .iter()
+                .filter(|t| {
+                    t.name != name && t.fields.iter().any(|(_, ty)| type_references(ty, name))
+                })
+                .map(|t| SchemaObject::Type(t.name.clone())),
+        );
+        usages
+    }
+
+    /// `DESCRIBE TABLE`: the table followed by its indexes, triggers and views
+    pub fn describe_table(&self, name: &str) -> Option<String> {
+        let table = self.table(name)?;
+        let mut statements = vec![table.to_cql()];
+        statements.extend(
+            self.indexes
+                .iter()
+                .filter(|i| i.table == name)
+                .map(IndexDef::to_cql),
+        );
+        statements.extend(
+            self.triggers
+                .iter()
+                .filter(|t| t.table == name)
+                .map(TriggerDef::to_cql),
+        );
+        statements.extend(
+            self.views
+                .iter()
+                .filter(|v| v.base_table == name)
+                .map(ViewDef::to_cql),
+        );
+        Some(statements.join("\n\n"))
+    }
+
+    /// User types ordered so that every type comes after the types its
+    /// fields use. Types caught in a cycle (not creatable anyway) keep
+    /// their original order at the end.
+    pub fn types_in_dependency_order(&self) -> Vec<&UserTypeDef> {
+        let mut remaining: Vec<&UserTypeDef> = self.types.iter().collect();
+        let mut ordered: Vec<&UserTypeDef> = Vec::with_capacity(remaining.len());
+        while !remaining.is_empty() {
+            let ready = remaining.iter().position(|candidate| {
+                remaining.iter().all(|other| {
+                    other.name == candidate.name
+                        || !candidate
+                            .fields
+                            .iter()
+                            .any(|(_, ty)| type_references(ty, &other.name))
+                })
+            });
+            match ready {
+                Some(index) => ordered.push(remaining.remove(index)),
+                None => {
+                    ordered.append(&mut remaining);
+                }
+            }
+        }
+        ordered
+    }
+
+    /// `DESCRIBE KEYSPACE`: every object, each after the ones it depends on —
+    /// types, then functions and aggregates, tables, indexes, triggers and
+    /// finally views
+    pub fn to_cql(&self) -> String {
+        let mut statements = vec![self.keyspace.to_cql()];
+        statements.extend(
+            self.types_in_dependency_order()
+                .into_iter()
+                .map(UserTypeDef::to_cql),
+        );
+        statements.extend(self.functions.iter().map(FunctionDef::to_cql));
+        statements.extend(self.aggregates.iter().map(AggregateDef::to_cql));
+        statements.extend(self.tables.iter().map(TableDef::to_cql));
+        statements.extend(self.indexes.iter().map(IndexDef::to_cql));
+        statements.extend(self.triggers.iter().map(TriggerDef::to_cql));
+        statements.extend(self.views.iter().map(ViewDef::to_cql));
+        statements.join("\n\n")
+    }
+}
+
+/// Whether a CQL type string (e.g. `frozen<list<address>>`) uses the type `name`
+pub(crate) fn type_references(ty: &str, name: &str) -> bool {
+    ty.split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '"'))
+        .any(|part| part.trim_matches('"') == name)
+}
+
+/// Keyspaces Cassandra creates and manages itself
+pub fn is_system_keyspace(name: &str) -> bool {
+    matches!(
+        name,
+        "system"
+            | "system_auth"
+            | "system_distributed"
+            | "system_schema"
+            | "system_traces"
+            | "system_views"
+            | "system_virtual_schema"
+    )
+}
+
+/// A `.cql` script recreating `keyspaces`, with a short provenance header
+pub fn export_script(source: &str, keyspaces: &[KeyspaceSchema]) -> String {
+    let mut script = format!(
+        "-- Schema of {}\n-- Exported {} ({} keyspace{})\n",
+        source,
+        chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC"),
+        keyspaces.len(),
+        if keyspaces.len() == 1 { "" } else { "s" }
+    );
+    for keyspace in keyspaces {
+        script.push_str(&format!("\n-- Keyspace {}\n\n", keyspace.keyspace.name));
+        script.push_str(&keyspace.to_cql());
+        script.push('\n');
+    }
+    script
+}
+
+// ── Shared clauses ─────────────────────────────────────────────
+
+fn key_columns<'a>(columns: &'a [SchemaColumn], kind: &str) -> Vec<&'a SchemaColumn> {
+    let mut keys: Vec<_> = columns.iter().filter(|c| c.kind == kind).collect();
+    keys.sort_by_key(|c| c.position);
+    keys
+}
+
+/// `PRIMARY KEY ((a, b), c)`
+fn primary_key_clause(columns: &[SchemaColumn]) -> String {
+    let names = |cols: Vec<&SchemaColumn>| -> Vec<String> {
+        cols.iter().map(|c| quote_identifier(&c.name)).collect()
+    };
+    let partition = names(key_columns(columns, "partition_key"));
+    let clustering = names(key_columns(columns, "clustering"));
+
+    let partition = if partition.len() == 1 {
+        partition[0].clone()
+    } else {
+        format!("({})", partition.join(", "))
+    };
+    if clustering.is_empty() {
+        format!("PRIMARY KEY ({})", partition)
+    } else {
+        format!("PRIMARY KEY ({}, {})", partition, clustering.join(", "))
+    }
+}
+
+/// ` WITH CLUSTERING ORDER BY (...)\n    AND option = value...`
+fn with_clause(columns: &[SchemaColumn], options: &TableOptions) -> String {
+    let mut clauses = Vec::new();
+    let clustering = key_columns(columns, "clustering");
+    if !clustering.is_empty() {
+        let order: Vec<String> = clustering
+            .iter()
+            .map(|c| {
+                format!(
+                    "{} {}",
+                    quote_identifier(&c.name),
+                    c.clustering_order.to_uppercase()
+                )
+            })
+            .collect();
+        clauses.push(format!("CLUSTERING ORDER BY ({})", order.join(", ")));
+    }
+    clauses.extend(
+        options
+            .to_cql_pairs()
+            .into_iter()
+            .map(|(name, value)| format!("{} = {}", name, value)),
+    );
+    if clauses.is_empty() {
+        String::new()
+    } else {
+        format!(" WITH {}", clauses.join("\n    AND "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{column, descending};
+
+    fn encode_collection(items: &[&str], count: i32) -> Vec<u8> {
+        let mut bytes = count.to_be_bytes().to_vec();
+        for item in items {
+            bytes.extend((item.len() as i32).to_be_bytes());
+            bytes.extend(item.as_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_reserved_keywords_sorted() {
+        let mut sorted = RESERVED_KEYWORDS.to_vec();
+        sorted.sort();
+        assert_eq!(sorted, RESERVED_KEYWORDS);
+    }
+
+    #[test]
+    fn test_quote_identifier() {
+        assert_eq!(quote_identifier("users"), "users");
+        assert_eq!(quote_identifier("user_id2"), "user_id2");
+        assert_eq!(quote_identifier("UserId"), "\"UserId\"");
+        assert_eq!(quote_identifier("select"), "\"select\"");
+        assert_eq!(quote_identifier("a\"b"), "\"a\"\"b\"");
+        assert_eq!(quote_identifier("2fa"), "\"2fa\"");
+    }
+
+    #[test]
+    fn test_decode_collections() {
+        let mut cells = HashMap::new();
+        cells.insert(
+            "replication".to_string(),
+            encode_collection(&["class", "SimpleStrategy", "replication_factor", "3"], 2),
+        );
+        cells.insert(
+            "field_names".to_string(),
+            encode_collection(&["street", "city"], 2),
+        );
+        let row = SchemaRow::new(cells);
+
+        let map = row.text_map("replication");
+        assert_eq!(map.get("class").map(String::as_str), Some("SimpleStrategy"));
+        assert_eq!(map.get("replication_factor").map(String::as_str), Some("3"));
+        assert_eq!(row.text_list("field_names"), vec!["street", "city"]);
+        assert!(row.text_list("missing").is_empty());
+    }
+
+    #[test]
+    fn test_keyspace_ddl() {
+        let keyspace = KeyspaceDef {
+            name: "shop".to_string(),
+            replication: BTreeMap::from([
+                ("DC1".to_string(), "3".to_string()),
+                (
+                    "class".to_string(),
+                    "org.apache.cassandra.locator.NetworkTopologyStrategy".to_string(),
+                ),
+            ]),
+            durable_writes: true,
+        };
+        assert_eq!(
+            keyspace.to_cql(),
+            "CREATE KEYSPACE shop WITH replication = {'class': 'org.apache.cassandra.locator.NetworkTopologyStrategy', 'DC1': '3'} AND durable_writes = true;"
+        );
+    }
+
+    #[test]
+    fn test_table_ddl() {
+        let table = TableDef {
+            keyspace: "shop".to_string(),
+            name: "orders".to_string(),
+            columns: vec![
+                column("customer_id", "uuid", "partition_key", 0),
+                column("region", "text", "partition_key", 1),
+                descending(column("ordered_at", "timestamp", "clustering", 0)),
+                column("note", "text", "static", -1),
+                column("Total", "decimal", "regular", -1),
+            ],
+            options: TableOptions {
+                bloom_filter_fp_chance: Some(0.01),
+                crc_check_chance: Some(1.0),
+                gc_grace_seconds: Some(864000),
+                comment: "it's here".to_string(),
+                compaction: BTreeMap::from([(
+                    "class".to_string(),
+                    "org.apache.cassandra.db.compaction.SizeTieredCompactionStrategy".to_string(),
+                )]),
+                ..Default::default()
+            },
+        };
+        assert_eq!(
+            table.to_cql(),
+            "CREATE TABLE shop.orders (\n    customer_id uuid,\n    region text,\n    ordered_at timestamp,\n    note text static,\n    \"Total\" decimal,\n    PRIMARY KEY ((customer_id, region), ordered_at)\n) WITH CLUSTERING ORDER BY (ordered_at DESC)\n    AND bloom_filter_fp_chance = 0.01\n    AND comment = 'it''s here'\n    AND compaction = {'class': 'org.apache.cassandra.db.compaction.SizeTieredCompactionStrategy'}\n    AND crc_check_chance = 1.0\n    AND gc_grace_seconds = 864000;"
+        );
+    }
+
+    #[test]
+    fn test_index_ddl() {
+        let mut index = IndexDef {
+            keyspace: "shop".to_string(),
+            table: "orders".to_string(),
+            name: "orders_tags_idx".to_string(),
+            kind: "COMPOSITES".to_string(),
+            options: BTreeMap::from([("target".to_string(), "values(tags)".to_string())]),
+        };
+        assert_eq!(
+            index.to_cql(),
+            "CREATE INDEX orders_tags_idx ON shop.orders (values(tags));"
+        );
+
+        index.kind = "CUSTOM".to_string();
+        index.options.insert(
+            "class_name".to_string(),
+            "org.apache.cassandra.index.sai.StorageAttachedIndex".to_string(),
+        );
+        index
+            .options
+            .insert("case_sensitive".to_string(), "false".to_string());
+        assert_eq!(
+            index.to_cql(),
+            "CREATE CUSTOM INDEX orders_tags_idx ON shop.orders (values(tags)) USING 'org.apache.cassandra.index.sai.StorageAttachedIndex' WITH OPTIONS = {'case_sensitive': 'false'};"
+        );
+    }
+
+    #[test]
+    fn test_types_in_dependency_order() {
+        let udt = |name: &str, fields: &[(&str, &str)]| UserTypeDef {
+            keyspace: "shop".to_string(),
+            name: name.to_string(),
+            fields: fields
+                .iter()
+                .map(|(n, t)| (n.to_string(), t.to_string()))
+                .collect(),
+        };
+        let schema = KeyspaceSchema {
+            keyspace: KeyspaceDef {
+                name: "shop".to_string(),
+                replication: BTreeMap::new(),
+                durable_writes: true,
+            },
+            types: vec![
+                udt(
+                    "customer",
+                    &[("addresses", "frozen<list<frozen<address>>>")],
+                ),
+                udt("address", &[("street", "text"), ("geo", "frozen<point>")]),
+                udt("point", &[("lat", "double"), ("lon", "double")]),
+                udt("addressbook", &[("name", "text")]),
+            ],
+            tables: Vec::new(),
+            indexes: Vec::new(),
+            views: Vec::new(),
+            functions: Vec::new(),
+            aggregates: Vec::new(),
+            triggers: Vec::new(),
+        };
+        let names: Vec<&str> = schema
+            .types_in_dependency_order()
+            .iter()
+            .map(|t| t.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["point", "address", "customer", "addressbook"]);
+        assert_eq!(
+            schema.type_usages("address"),
+            vec![SchemaObject::Type("customer".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_function_and_aggregate_ddl() {
+        let function = FunctionDef {
+            keyspace: "shop".to_string(),
+            name: "plus".to_string(),
+            argument_names: vec!["a".to_string(), "b".to_string()],
+            argument_types: vec!["int".to_string(), "int".to_string()],
+            return_type: "int".to_string(),
+            language: "java".to_string(),
+            body: "return a + b;".to_string(),
+            called_on_null_input: false,
+        };
+        assert_eq!(
+            function.to_cql(),
+            "CREATE FUNCTION shop.plus(a int, b int)\n    RETURNS NULL ON NULL INPUT\n    RETURNS int\n    LANGUAGE java\n    AS $$return a + b;$$;"
+        );
+
+        let aggregate = AggregateDef {
+            keyspace: "shop".to_string(),
+            name: "total".to_string(),
+            argument_types: vec!["int".to_string()],
+            state_func: "plus".to_string(),
+            state_type: "int".to_string(),
+            final_func: None,
+            initcond: Some("0".to_string()),
+            return_type: "int".to_string(),
+        };
+        assert_eq!(
+            aggregate.to_cql(),
+            "CREATE AGGREGATE shop.total(int)\n    SFUNC plus\n    STYPE int\n    INITCOND 0;"
+        );
+    }
+
+    #[test]
+    fn test_trigger_ddl() {
+        let trigger = TriggerDef {
+            keyspace: "shop".to_string(),
+            table: "orders".to_string(),
+            name: "audit".to_string(),
+            options: BTreeMap::from([(
+                "class".to_string(),
+                "org.example.AuditTrigger".to_string(),
+            )]),
+        };
+        assert_eq!(
+            trigger.to_cql(),
+            "CREATE TRIGGER audit ON shop.orders USING 'org.example.AuditTrigger';"
+        );
+    }
+
+    #[test]
+    fn test_schema_object_key_roundtrip() {
+        let objects = [
+            SchemaObject::Table("orders".to_string()),
+            SchemaObject::Function("total(int, int)".to_string()),
+            SchemaObject::Trigger {
+                table: "orders".to_string(),
+                name: "audit".to_string(),
+            },
+        ];
+        for object in objects {
+            assert_eq!(SchemaObject::from_key(&object.key()), Some(object));
+        }
+        assert_eq!(SchemaObject::from_key("orders"), None);
+        assert_eq!(SchemaObject::from_key("trigger:audit"), None);
+    }
+
+    #[test]
+    fn test_alter_options() {
+        let current = TableOptions {
+            gc_grace_seconds: Some(864000),
+            default_time_to_live: Some(0),
+            compaction: BTreeMap::from([(
+                "class".to_string(),
+                "org.apache.cassandra.db.compaction.SizeTieredCompactionStrategy".to_string(),
+            )]),
+            ..Default::default()
+        };
+        assert_eq!(current.alter_cql("ALTER TABLE shop.orders", &current), None);
+
+        let desired = TableOptions {
+            gc_grace_seconds: Some(3600),
+            compaction: BTreeMap::from([
+                (
+                    "class".to_string(),
+                    "org.apache.cassandra.db.compaction.TimeWindowCompactionStrategy".to_string(),
+                ),
+                ("compaction_window_size".to_string(), "1".to_string()),
+            ]),
+            // Not reported by the table, so never altered
+            cdc: Some(true),
+            ..current.clone()
+        };
+        assert_eq!(
+            current.alter_cql("ALTER TABLE shop.orders", &desired).as_deref(),
+            Some("ALTER TABLE shop.orders WITH compaction = {'class': 'org.apache.cassandra.db.compaction.TimeWindowCompactionStrategy', 'compaction_window_size': '1'}\n    AND gc_grace_seconds = 3600;")
+        );
+    }
+}
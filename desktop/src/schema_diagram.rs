@@ -0,0 +1,604 @@
+//! Chebotko-style diagram of a keyspace, rendered as standalone SVG.
+//!
+//! Tables and materialized views are drawn as boxes listing their columns,
+//! marked `K` (partition key), `C↑`/`C↓` (clustering, by order) and `S`
+//! (static). User types sit in a column on the left, linked to the tables and
+//! types that use them; views sit on the right, linked to their base table.
+
+use crate::cassandra::SchemaColumn;
+use crate::cql_tokenizer::html_escape;
+use crate::schema::{type_references, KeyspaceSchema};
+
+/// Approximate advance of one character at `FONT_SIZE` in a monospace font
+const CHAR_WIDTH: f64 = 7.3;
+const FONT_SIZE: f64 = 12.0;
+const ROW_HEIGHT: f64 = 20.0;
+const HEADER_HEIGHT: f64 = 28.0;
+const MIN_NODE_WIDTH: f64 = 160.0;
+/// Room for the widest marker (`C↑`) before the column name
+const MARKER_WIDTH: f64 = 28.0;
+const PADDING: f64 = 10.0;
+/// Space around the diagram and between stacked boxes
+const MARGIN: f64 = 24.0;
+/// Horizontal space between the type, table and view columns, where edges run
+const LANE_GAP: f64 = 80.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NodeKind {
+    Table,
+    View,
+    Type,
+}
+
+impl NodeKind {
+    fn label(&self) -> &'static str {
+        match self {
+            NodeKind::Table => "table",
+            NodeKind::View => "view",
+            NodeKind::Type => "type",
+        }
+    }
+}
+
+/// One line of a box: a column or a type field
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeRow {
+    /// `K`, `C↑`, `C↓`, `S` or empty for regular columns and fields
+    pub marker: &'static str,
+    pub name: String,
+    pub data_type: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Node {
+    pub kind: NodeKind,
+    pub name: String,
+    pub rows: Vec<NodeRow>,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+}
+
+impl Node {
+    fn new(kind: NodeKind, name: &str, rows: Vec<NodeRow>) -> Self {
+        let widest = rows
+            .iter()
+            .map(|r| r.name.chars().count() + r.data_type.chars().count() + 2)
+            .chain(std::iter::once(
+                name.chars().count() + kind.label().chars().count() + 3,
+            ))
+            .max()
+            .unwrap_or(0);
+        Node {
+            kind,
+            name: name.to_string(),
+            rows,
+            x: 0.0,
+            y: 0.0,
+            width: (MARKER_WIDTH + widest as f64 * CHAR_WIDTH + 2.0 * PADDING).max(MIN_NODE_WIDTH),
+        }
+    }
+
+    pub fn height(&self) -> f64 {
+        HEADER_HEIGHT + ROW_HEIGHT * self.rows.len().max(1) as f64
+    }
+
+    fn center_y(&self) -> f64 {
+        self.y + self.height() / 2.0
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EdgeKind {
+    /// From a user type to a table or type with a column or field of that type
+    UsesType,
+    /// From a base table to a materialized view selecting from it
+    ViewOf,
+}
+
+/// A link between two nodes, by index into [`Diagram::nodes`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Edge {
+    pub from: usize,
+    pub to: usize,
+    pub kind: EdgeKind,
+}
+
+/// Laid-out diagram of one keyspace
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagram {
+    pub keyspace: String,
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// Chebotko marker for a column, from its kind and clustering order
+fn column_marker(column: &SchemaColumn) -> &'static str {
+    match column.kind.as_str() {
+        "partition_key" => "K",
+        "clustering" if column.clustering_order.eq_ignore_ascii_case("desc") => "C↓",
+        "clustering" => "C↑",
+        "static" => "S",
+        _ => "",
+    }
+}
+
+/// Key columns first, by position, then static and regular columns by name
+fn column_rows(columns: &[SchemaColumn]) -> Vec<NodeRow> {
+    let rank = |c: &SchemaColumn| match c.kind.as_str() {
+        "partition_key" => 0,
+        "clustering" => 1,
+        "static" => 2,
+        _ => 3,
+    };
+    let mut sorted: Vec<&SchemaColumn> = columns.iter().collect();
+    sorted.sort_by(|a, b| {
+        rank(a)
+            .cmp(&rank(b))
+            .then(a.position.cmp(&b.position))
+            .then(a.name.cmp(&b.name))
+    });
+    sorted
+        .into_iter()
+        .map(|c| NodeRow {
+            marker: column_marker(c),
+            name: c.name.clone(),
+            data_type: c.data_type.clone(),
+        })
+        .collect()
+}
+
+/// Place the keyspace's types, tables and views in three columns. Tables are
+/// stacked in schema order; each view and type is placed level with the first
+/// table it relates to, or below the previous box in its column.
+pub fn layout(schema: &KeyspaceSchema) -> Diagram {
+    let mut nodes: Vec<Node> = Vec::new();
+    let mut edges: Vec<Edge> = Vec::new();
+
+    let tables: Vec<usize> = schema
+        .tables
+        .iter()
+        .map(|t| {
+            nodes.push(Node::new(NodeKind::Table, &t.name, column_rows(&t.columns)));
+            nodes.len() - 1
+        })
+        .collect();
+    let views: Vec<usize> = schema
+        .views
+        .iter()
+        .map(|v| {
+            nodes.push(Node::new(NodeKind::View, &v.name, column_rows(&v.columns)));
+            nodes.len() - 1
+        })
+        .collect();
+    let types: Vec<usize> = schema
+        .types_in_dependency_order()
+        .into_iter()
+        .map(|t| {
+            let rows = t
+                .fields
+                .iter()
+                .map(|(name, ty)| NodeRow {
+                    marker: "",
+                    name: name.clone(),
+                    data_type: ty.clone(),
+                })
+                .collect();
+            nodes.push(Node::new(NodeKind::Type, &t.name, rows));
+            nodes.len() - 1
+        })
+        .collect();
+
+    let find = |indexes: &[usize], nodes: &[Node], name: &str| {
+        indexes.iter().copied().find(|&i| nodes[i].name == name)
+    };
+
+    for (&view, def) in views.iter().zip(&schema.views) {
+        if let Some(base) = find(&tables, &nodes, &def.base_table) {
+            edges.push(Edge {
+                from: base,
+                to: view,
+                kind: EdgeKind::ViewOf,
+            });
+        }
+    }
+    for &ty in &types {
+        let name = nodes[ty].name.clone();
+        for &user in tables.iter().chain(&types) {
+            if user == ty {
+                continue;
+            }
+            let node = &nodes[user];
+            if node
+                .rows
+                .iter()
+                .any(|r| type_references(&r.data_type, &name))
+            {
+                edges.push(Edge {
+                    from: ty,
+                    to: user,
+                    kind: EdgeKind::UsesType,
+                });
+            }
+        }
+    }
+
+    // Column x positions: types | tables | views
+    let column_width = |indexes: &[usize], nodes: &[Node]| {
+        indexes.iter().map(|&i| nodes[i].width).fold(0.0, f64::max)
+    };
+    let types_x = MARGIN;
+    let types_width = column_width(&types, &nodes);
+    let tables_x = if types.is_empty() {
+        MARGIN
+    } else {
+        types_x + types_width + LANE_GAP
+    };
+    let tables_width = column_width(&tables, &nodes);
+    let views_x = if tables.is_empty() {
+        tables_x
+    } else {
+        tables_x + tables_width + LANE_GAP
+    };
+    let views_width = column_width(&views, &nodes);
+
+    let mut y = MARGIN;
+    for &i in &tables {
+        nodes[i].x = tables_x;
+        nodes[i].y = y;
+        y += nodes[i].height() + MARGIN;
+    }
+
+    // Level with the first related table, without overlapping the box above
+    let place_beside = |column: &[usize], x: f64, nodes: &mut [Node]| {
+        let mut next_free = MARGIN;
+        for &i in column {
+            let anchor = edges
+                .iter()
+                .filter_map(|e| match e.kind {
+                    EdgeKind::ViewOf if e.to == i => Some(e.from),
+                    EdgeKind::UsesType if e.from == i && tables.contains(&e.to) => Some(e.to),
+                    _ => None,
+                })
+                .map(|t| nodes[t].y)
+                .fold(f64::INFINITY, f64::min);
+            let y = if anchor.is_finite() {
+                anchor.max(next_free)
+            } else {
+                next_free
+            };
+            nodes[i].x = x;
+            nodes[i].y = y;
+            next_free = y + nodes[i].height() + MARGIN;
+        }
+    };
+    place_beside(&views, views_x, &mut nodes);
+    place_beside(&types, types_x, &mut nodes);
+
+    let right = if !views.is_empty() {
+        views_x + views_width
+    } else if !tables.is_empty() {
+        tables_x + tables_width
+    } else {
+        types_x + types_width
+    };
+    let bottom = nodes
+        .iter()
+        .map(|n| n.y + n.height())
+        .fold(MARGIN, f64::max);
+
+    Diagram {
+        keyspace: schema.keyspace.name.clone(),
+        nodes,
+        edges,
+        width: (right + MARGIN).max(MIN_NODE_WIDTH + 2.0 * MARGIN),
+        height: bottom + MARGIN,
+    }
+}
+
+const STYLE: &str = "\
+.background { fill: #09090b; }
+.node-box { fill: #18181b; stroke: #3f3f46; stroke-width: 1; }
+.node-header { fill: #27272a; }
+.node-view .node-header { fill: #1e3a5f; }
+.node-type .node-header { fill: #3b2f4a; }
+.node-title { fill: #fafafa; font-weight: bold; }
+.node-kind { fill: #a1a1aa; }
+.row-marker { fill: #f59e0b; font-weight: bold; }
+.row-name { fill: #e4e4e7; }
+.row-key { fill: #fbbf24; }
+.row-type { fill: #a1a1aa; }
+.row-separator { stroke: #27272a; stroke-width: 1; }
+.edge { fill: none; stroke-width: 1.5; }
+.edge-view { stroke: #60a5fa; }
+.edge-type { stroke: #c084fc; stroke-dasharray: 5 4; }
+.empty { fill: #a1a1aa; }
+";
+
+/// A cubic curve between two points, leaving and entering horizontally
+fn curve(x1: f64, y1: f64, x2: f64, y2: f64) -> String {
+    let bend = ((x2 - x1).abs() / 2.0).max(40.0);
+    let (c1, c2) = if x2 >= x1 {
+        (x1 + bend, x2 - bend)
+    } else {
+        (x1 - bend, x2 + bend)
+    };
+    format!(
+        "M {:.1} {:.1} C {:.1} {:.1}, {:.1} {:.1}, {:.1} {:.1}",
+        x1, y1, c1, y1, c2, y2, x2, y2
+    )
+}
+
+/// Path for an edge: between facing sides of boxes in different columns, or
+/// looping around the left side of boxes stacked in the same column
+fn edge_path(from: &Node, to: &Node) -> String {
+    if (from.x - to.x).abs() < f64::EPSILON {
+        let (y1, y2) = (from.center_y(), to.center_y());
+        let reach = LANE_GAP.min(MARGIN * 2.0) - 8.0;
+        format!(
+            "M {:.1} {:.1} C {:.1} {:.1}, {:.1} {:.1}, {:.1} {:.1}",
+            from.x,
+            y1,
+            from.x - reach,
+            y1,
+            to.x - reach,
+            y2,
+            to.x,
+            y2
+        )
+    } else if from.x < to.x {
+        curve(from.x + from.width, from.center_y(), to.x, to.center_y())
+    } else {
+        curve(from.x, from.center_y(), to.x + to.width, to.center_y())
+    }
+}
+
+impl Diagram {
+    /// Standalone SVG document with embedded styles, suitable for export
+    pub fn to_svg(&self) -> String {
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w:.0}\" height=\"{h:.0}\" viewBox=\"0 0 {w:.0} {h:.0}\" font-family=\"'JetBrains Mono', 'Fira Code', monospace\" font-size=\"{fs}\">\n",
+            w = self.width,
+            h = self.height,
+            fs = FONT_SIZE
+        );
+        svg.push_str(&format!(
+            "<title>Keyspace {}</title>\n",
+            html_escape(&self.keyspace)
+        ));
+        svg.push_str("<defs>\n<style>\n");
+        svg.push_str(STYLE);
+        svg.push_str("</style>\n");
+        svg.push_str("<marker id=\"arrow\" viewBox=\"0 0 10 10\" refX=\"10\" refY=\"5\" markerWidth=\"8\" markerHeight=\"8\" orient=\"auto-start-reverse\"><path d=\"M 0 0 L 10 5 L 0 10 z\" fill=\"#60a5fa\"/></marker>\n");
+        svg.push_str("</defs>\n");
+        svg.push_str("<rect class=\"background\" width=\"100%\" height=\"100%\"/>\n");
+
+        if self.nodes.is_empty() {
+            svg.push_str(&format!(
+                "<text class=\"empty\" x=\"{:.0}\" y=\"{:.0}\">No tables, views or types</text>\n",
+                MARGIN,
+                MARGIN + FONT_SIZE
+            ));
+        }
+
+        for edge in &self.edges {
+            let (from, to) = (&self.nodes[edge.from], &self.nodes[edge.to]);
+            let (class, marker) = match edge.kind {
+                EdgeKind::ViewOf => ("edge edge-view", " marker-end=\"url(#arrow)\""),
+                EdgeKind::UsesType => ("edge edge-type", ""),
+            };
+            svg.push_str(&format!(
+                "<path class=\"{}\" d=\"{}\"{}/>\n",
+                class,
+                edge_path(from, to),
+                marker
+            ));
+        }
+
+        for node in &self.nodes {
+            self.push_node(&mut svg, node);
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    fn push_node(&self, svg: &mut String, node: &Node) {
+        let kind = node.kind.label();
+        svg.push_str(&format!(
+            "<g class=\"node node-{}\" data-name=\"{}\" transform=\"translate({:.1} {:.1})\">\n",
+            kind,
+            html_escape(&node.name),
+            node.x,
+            node.y
+        ));
+        svg.push_str(&format!(
+            "<rect class=\"node-box\" width=\"{:.1}\" height=\"{:.1}\" rx=\"4\"/>\n",
+            node.width,
+            node.height()
+        ));
+        svg.push_str(&format!(
+            "<rect class=\"node-header\" x=\"0.5\" y=\"0.5\" width=\"{:.1}\" height=\"{:.1}\" rx=\"4\"/>\n",
+            node.width - 1.0,
+            HEADER_HEIGHT - 1.0
+        ));
+        let baseline = HEADER_HEIGHT / 2.0 + FONT_SIZE / 2.0 - 2.0;
+        svg.push_str(&format!(
+            "<text class=\"node-title\" x=\"{:.1}\" y=\"{:.1}\">{}</text>\n",
+            PADDING,
+            baseline,
+            html_escape(&node.name)
+        ));
+        svg.push_str(&format!(
+            "<text class=\"node-kind\" x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\">{}</text>\n",
+            node.width - PADDING,
+            baseline,
+            kind
+        ));
+
+        for (i, row) in node.rows.iter().enumerate() {
+            let top = HEADER_HEIGHT + ROW_HEIGHT * i as f64;
+            let baseline = top + ROW_HEIGHT / 2.0 + FONT_SIZE / 2.0 - 2.0;
+            if i > 0 {
+                svg.push_str(&format!(
+                    "<line class=\"row-separator\" x1=\"1\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\"/>\n",
+                    top,
+                    node.width - 1.0,
+                    top
+                ));
+            }
+            if !row.marker.is_empty() {
+                svg.push_str(&format!(
+                    "<text class=\"row-marker\" x=\"{:.1}\" y=\"{:.1}\">{}</text>\n",
+                    PADDING, baseline, row.marker
+                ));
+            }
+            let name_class = if matches!(row.marker, "K" | "C↑" | "C↓") {
+                "row-name row-key"
+            } else {
+                "row-name"
+            };
+            svg.push_str(&format!(
+                "<text class=\"{}\" x=\"{:.1}\" y=\"{:.1}\">{}</text>\n",
+                name_class,
+                PADDING + MARKER_WIDTH,
+                baseline,
+                html_escape(&row.name)
+            ));
+            svg.push_str(&format!(
+                "<text class=\"row-type\" x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\">{}</text>\n",
+                node.width - PADDING,
+                baseline,
+                html_escape(&row.data_type)
+            ));
+        }
+        svg.push_str("</g>\n");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::{TableOptions, UserTypeDef, ViewDef};
+    use crate::test_fixtures::{column, descending, keyspace, table};
+
+    fn shop() -> KeyspaceSchema {
+        let orders = table(
+            "shop",
+            "orders",
+            vec![
+                column("customer_id", "uuid", "partition_key", 0),
+                descending(column("ordered_at", "timestamp", "clustering", 0)),
+                column("order_id", "uuid", "clustering", 1),
+                column("customer_name", "text", "static", -1),
+                column("ship_to", "frozen<address>", "regular", -1),
+                column("total", "decimal", "regular", -1),
+            ],
+        );
+        let mut schema = keyspace("shop", vec![orders]);
+        schema.types.push(UserTypeDef {
+            keyspace: "shop".to_string(),
+            name: "address".to_string(),
+            fields: vec![
+                ("street".to_string(), "text".to_string()),
+                ("city".to_string(), "text".to_string()),
+            ],
+        });
+        schema.views.push(ViewDef {
+            keyspace: "shop".to_string(),
+            name: "orders_by_id".to_string(),
+            base_table: "orders".to_string(),
+            include_all_columns: false,
+            where_clause: "order_id IS NOT NULL".to_string(),
+            columns: vec![
+                column("order_id", "uuid", "partition_key", 0),
+                column("customer_id", "uuid", "clustering", 0),
+                descending(column("ordered_at", "timestamp", "clustering", 1)),
+            ],
+            options: TableOptions::default(),
+        });
+        schema
+    }
+
+    #[test]
+    fn test_chebotko_markers() {
+        let diagram = layout(&shop());
+        let orders = &diagram.nodes[0];
+        let markers: Vec<(&str, &str)> = orders
+            .rows
+            .iter()
+            .map(|r| (r.marker, r.name.as_str()))
+            .collect();
+        assert_eq!(
+            markers,
+            vec![
+                ("K", "customer_id"),
+                ("C↓", "ordered_at"),
+                ("C↑", "order_id"),
+                ("S", "customer_name"),
+                ("", "ship_to"),
+                ("", "total"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_edges_and_columns() {
+        let diagram = layout(&shop());
+        let index = |name: &str| diagram.nodes.iter().position(|n| n.name == name).unwrap();
+        let (orders, view, address) = (index("orders"), index("orders_by_id"), index("address"));
+        assert!(diagram.edges.contains(&Edge {
+            from: orders,
+            to: view,
+            kind: EdgeKind::ViewOf
+        }));
+        assert!(diagram.edges.contains(&Edge {
+            from: address,
+            to: orders,
+            kind: EdgeKind::UsesType
+        }));
+        assert_eq!(diagram.edges.len(), 2);
+
+        let nodes = &diagram.nodes;
+        assert!(nodes[address].x + nodes[address].width < nodes[orders].x);
+        assert!(nodes[orders].x + nodes[orders].width < nodes[view].x);
+        assert_eq!(nodes[view].y, nodes[orders].y);
+        assert!(diagram.width >= nodes[view].x + nodes[view].width);
+    }
+
+    #[test]
+    fn test_stacked_boxes_do_not_overlap() {
+        let mut schema = shop();
+        let mut second = schema.tables[0].clone();
+        second.name = "orders_archive".to_string();
+        schema.tables.push(second);
+        let mut view = schema.views[0].clone();
+        view.name = "orders_by_total".to_string();
+        schema.views.push(view);
+
+        let diagram = layout(&schema);
+        for (i, a) in diagram.nodes.iter().enumerate() {
+            for b in &diagram.nodes[i + 1..] {
+                let apart = a.x + a.width <= b.x
+                    || b.x + b.width <= a.x
+                    || a.y + a.height() <= b.y
+                    || b.y + b.height() <= a.y;
+                assert!(apart, "{} overlaps {}", a.name, b.name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_svg_escapes_names() {
+        let mut schema = shop();
+        schema.tables[0].columns[4].data_type = "map<text, frozen<address>>".to_string();
+        let svg = layout(&schema).to_svg();
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.contains("map&lt;text, frozen&lt;address&gt;&gt;"));
+        assert!(svg.contains(">C↓</text>"));
+        assert!(svg.contains("marker-end=\"url(#arrow)\""));
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+}
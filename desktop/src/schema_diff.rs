@@ -0,0 +1,775 @@
+//! Comparison of two keyspace schemas.
+//!
+//! The *source* schema is the reference (e.g. dev) and the *target* the one
+//! to bring in line with it (e.g. prod). Every difference comes with the CQL
+//! that applies it to the target; changes Cassandra cannot apply in place
+//! (primary key or column type changes) and replication differences, which
+//! are usually deliberate per environment, are reported as `--` comments.
+
+use crate::cassandra::SchemaColumn;
+use crate::schema::{
+    map_literal, qualified_name, quote_identifier, AggregateDef, FunctionDef, IndexDef,
+    KeyspaceSchema, TableDef, TableOptions, TriggerDef, UserTypeDef, ViewDef,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// Only in the source; created on the target
+    Added,
+    /// Only in the target; dropped from it
+    Removed,
+    Changed,
+}
+
+impl ChangeKind {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            ChangeKind::Added => "+",
+            ChangeKind::Removed => "−",
+            ChangeKind::Changed => "~",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ObjectKind {
+    Keyspace,
+    Type,
+    Function,
+    Aggregate,
+    Table,
+    Column,
+    Options,
+    Index,
+    Trigger,
+    View,
+}
+
+impl ObjectKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ObjectKind::Keyspace => "keyspace",
+            ObjectKind::Type => "type",
+            ObjectKind::Function => "function",
+            ObjectKind::Aggregate => "aggregate",
+            ObjectKind::Table => "table",
+            ObjectKind::Column => "column",
+            ObjectKind::Options => "options",
+            ObjectKind::Index => "index",
+            ObjectKind::Trigger => "trigger",
+            ObjectKind::View => "view",
+        }
+    }
+}
+
+/// One difference between source and target
+#[derive(Clone, Debug, PartialEq)]
+pub struct SchemaChange {
+    pub kind: ChangeKind,
+    pub object: ObjectKind,
+    /// Object name, `table.column` for columns
+    pub name: String,
+    /// What differs, in `target → source` terms for changed values
+    pub detail: String,
+    /// CQL applying the change to the target; `--` lines need manual work
+    pub statements: Vec<String>,
+}
+
+impl SchemaChange {
+    fn new(kind: ChangeKind, object: ObjectKind, name: impl Into<String>) -> Self {
+        Self {
+            kind,
+            object,
+            name: name.into(),
+            detail: String::new(),
+            statements: Vec::new(),
+        }
+    }
+
+    fn detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = detail.into();
+        self
+    }
+
+    fn statement(mut self, statement: impl Into<String>) -> Self {
+        self.statements.push(statement.into());
+        self
+    }
+
+    /// Whether applying this change deletes data or schema objects
+    pub fn is_destructive(&self) -> bool {
+        self.kind == ChangeKind::Removed
+    }
+}
+
+/// Differences that bring `target` in line with `source`, in the order
+/// their statements can run: creations and alterations first (types before
+/// tables before indexes, triggers and views), then drops in reverse
+/// dependency order.
+pub fn diff_keyspaces(source: &KeyspaceSchema, target: &KeyspaceSchema) -> Vec<SchemaChange> {
+    let ks = target.keyspace.name.as_str();
+    let mut changes = Vec::new();
+    let mut drops = Vec::new();
+
+    let source_ks = &source.keyspace;
+    let target_ks = &target.keyspace;
+    let mut keyspace_details = Vec::new();
+    let mut keyspace_statements = Vec::new();
+    // Replication is per environment (dev and prod rarely share a factor),
+    // so it is reported but never applied
+    if source_ks.replication != target_ks.replication {
+        keyspace_details.push(format!(
+            "replication {} → {}",
+            map_literal(&target_ks.replication),
+            map_literal(&source_ks.replication)
+        ));
+        keyspace_statements.push(format!(
+            "-- {}: replication differs ({} → {}); review before altering",
+            ks,
+            map_literal(&target_ks.replication),
+            map_literal(&source_ks.replication)
+        ));
+    }
+    if source_ks.durable_writes != target_ks.durable_writes {
+        keyspace_details.push(format!(
+            "durable_writes {} → {}",
+            target_ks.durable_writes, source_ks.durable_writes
+        ));
+        keyspace_statements.push(format!(
+            "ALTER KEYSPACE {} WITH durable_writes = {};",
+            quote_identifier(ks),
+            source_ks.durable_writes
+        ));
+    }
+    if !keyspace_details.is_empty() {
+        let mut change = SchemaChange::new(ChangeKind::Changed, ObjectKind::Keyspace, ks)
+            .detail(keyspace_details.join(", "));
+        change.statements = keyspace_statements;
+        changes.push(change);
+    }
+
+    // Types, in dependency order so new types can use each other
+    for ty in source.types_in_dependency_order() {
+        match target.types.iter().find(|t| t.name == ty.name) {
+            None => changes.push(
+                SchemaChange::new(ChangeKind::Added, ObjectKind::Type, &ty.name).statement(
+                    UserTypeDef {
+                        keyspace: ks.to_string(),
+                        ..ty.clone()
+                    }
+                    .to_cql(),
+                ),
+            ),
+            Some(existing) => changes.extend(diff_type(ks, ty, existing)),
+        }
+    }
+    for ty in target.types_in_dependency_order().into_iter().rev() {
+        if !source.types.iter().any(|t| t.name == ty.name) {
+            drops.push(
+                SchemaChange::new(ChangeKind::Removed, ObjectKind::Type, &ty.name)
+                    .statement(format!("DROP TYPE {};", qualified_name(ks, &ty.name))),
+            );
+        }
+    }
+
+    diff_routines(
+        ks,
+        &source.functions,
+        &target.functions,
+        &mut changes,
+        &mut drops,
+    );
+    diff_routines(
+        ks,
+        &source.aggregates,
+        &target.aggregates,
+        &mut changes,
+        &mut drops,
+    );
+
+    for table in &source.tables {
+        match target.table(&table.name) {
+            None => changes.push(
+                SchemaChange::new(ChangeKind::Added, ObjectKind::Table, &table.name).statement(
+                    TableDef {
+                        keyspace: ks.to_string(),
+                        ..table.clone()
+                    }
+                    .to_cql(),
+                ),
+            ),
+            Some(existing) => changes.extend(diff_table(ks, table, existing)),
+        }
+    }
+
+    for index in &source.indexes {
+        let create = IndexDef {
+            keyspace: ks.to_string(),
+            ..index.clone()
+        }
+        .to_cql();
+        match target.indexes.iter().find(|i| i.name == index.name) {
+            None => changes.push(
+                SchemaChange::new(ChangeKind::Added, ObjectKind::Index, &index.name)
+                    .detail(format!("on {} ({})", index.table, index.target()))
+                    .statement(create),
+            ),
+            Some(existing)
+                if existing.table != index.table || existing.options != index.options =>
+            {
+                changes.push(
+                    SchemaChange::new(ChangeKind::Changed, ObjectKind::Index, &index.name)
+                        .detail(format!(
+                            "{} ({}) → {} ({})",
+                            existing.table,
+                            existing.target(),
+                            index.table,
+                            index.target()
+                        ))
+                        .statement(format!(
+                            "DROP INDEX {};",
+                            qualified_name(ks, &existing.name)
+                        ))
+                        .statement(create),
+                )
+            }
+            Some(_) => {}
+        }
+    }
+
+    for trigger in &source.triggers {
+        let create = TriggerDef {
+            keyspace: ks.to_string(),
+            ..trigger.clone()
+        }
+        .to_cql();
+        let name = format!("{}.{}", trigger.table, trigger.name);
+        match target.trigger(&trigger.table, &trigger.name) {
+            None => changes.push(
+                SchemaChange::new(ChangeKind::Added, ObjectKind::Trigger, name)
+                    .detail(trigger.class_name())
+                    .statement(create),
+            ),
+            Some(existing) if existing.options != trigger.options => changes.push(
+                SchemaChange::new(ChangeKind::Changed, ObjectKind::Trigger, name)
+                    .detail(format!(
+                        "{} → {}",
+                        existing.class_name(),
+                        trigger.class_name()
+                    ))
+                    .statement(format!(
+                        "DROP TRIGGER {} ON {};",
+                        quote_identifier(&existing.name),
+                        qualified_name(ks, &existing.table)
+                    ))
+                    .statement(create),
+            ),
+            Some(_) => {}
+        }
+    }
+
+    for view in &source.views {
+        match target.views.iter().find(|v| v.name == view.name) {
+            None => changes.push(
+                SchemaChange::new(ChangeKind::Added, ObjectKind::View, &view.name)
+                    .detail(format!("on {}", view.base_table))
+                    .statement(
+                        ViewDef {
+                            keyspace: ks.to_string(),
+                            ..view.clone()
+                        }
+                        .to_cql(),
+                    ),
+            ),
+            Some(existing) => changes.extend(diff_view(ks, view, existing)),
+        }
+    }
+
+    // Drops run last, dependents first: views, indexes, triggers, tables,
+    // routines, types
+    let mut table_drops = Vec::new();
+    for view in &target.views {
+        if !source.views.iter().any(|v| v.name == view.name) {
+            table_drops.push(
+                SchemaChange::new(ChangeKind::Removed, ObjectKind::View, &view.name).statement(
+                    format!("DROP MATERIALIZED VIEW {};", qualified_name(ks, &view.name)),
+                ),
+            );
+        }
+    }
+    for index in &target.indexes {
+        let table_dropped = source.table(&index.table).is_none();
+        if !table_dropped && !source.indexes.iter().any(|i| i.name == index.name) {
+            table_drops.push(
+                SchemaChange::new(ChangeKind::Removed, ObjectKind::Index, &index.name)
+                    .statement(format!("DROP INDEX {};", qualified_name(ks, &index.name))),
+            );
+        }
+    }
+    for trigger in &target.triggers {
+        let table_dropped = source.table(&trigger.table).is_none();
+        if !table_dropped && source.trigger(&trigger.table, &trigger.name).is_none() {
+            table_drops.push(
+                SchemaChange::new(
+                    ChangeKind::Removed,
+                    ObjectKind::Trigger,
+                    format!("{}.{}", trigger.table, trigger.name),
+                )
+                .statement(format!(
+                    "DROP TRIGGER {} ON {};",
+                    quote_identifier(&trigger.name),
+                    qualified_name(ks, &trigger.table)
+                )),
+            );
+        }
+    }
+    for table in &target.tables {
+        if source.table(&table.name).is_none() {
+            table_drops.push(
+                SchemaChange::new(ChangeKind::Removed, ObjectKind::Table, &table.name)
+                    .statement(format!("DROP TABLE {};", qualified_name(ks, &table.name))),
+            );
+        }
+    }
+    table_drops.extend(drops);
+
+    changes.extend(table_drops);
+    changes
+}
+
+/// The CQL script for `changes`, optionally leaving out drops
+pub fn migration_script(changes: &[SchemaChange], include_drops: bool) -> String {
+    let mut lines = Vec::new();
+    for change in changes {
+        if change.is_destructive() && !include_drops {
+            continue;
+        }
+        lines.extend(change.statements.iter().cloned());
+    }
+    lines.join("\n\n")
+}
+
+fn diff_type(ks: &str, source: &UserTypeDef, target: &UserTypeDef) -> Vec<SchemaChange> {
+    let mut changes = Vec::new();
+    let name = &source.name;
+    for (field, ty) in &source.fields {
+        match target.fields.iter().find(|(f, _)| f == field) {
+            None => changes.push(
+                SchemaChange::new(
+                    ChangeKind::Added,
+                    ObjectKind::Type,
+                    format!("{}.{}", name, field),
+                )
+                .detail(ty.clone())
+                .statement(format!(
+                    "ALTER TYPE {} ADD {} {};",
+                    qualified_name(ks, name),
+                    quote_identifier(field),
+                    ty
+                )),
+            ),
+            Some((_, existing)) if existing != ty => changes.push(
+                SchemaChange::new(
+                    ChangeKind::Changed,
+                    ObjectKind::Type,
+                    format!("{}.{}", name, field),
+                )
+                .detail(format!("{} → {}", existing, ty))
+                .statement(format!(
+                    "-- {}.{}: field type cannot be changed in place ({} → {})",
+                    name, field, existing, ty
+                )),
+            ),
+            Some(_) => {}
+        }
+    }
+    for (field, ty) in &target.fields {
+        if !source.fields.iter().any(|(f, _)| f == field) {
+            changes.push(
+                SchemaChange::new(
+                    ChangeKind::Removed,
+                    ObjectKind::Type,
+                    format!("{}.{}", name, field),
+                )
+                .detail(ty.clone())
+                .statement(format!(
+                    "-- {}.{}: fields cannot be removed from a type",
+                    name, field
+                )),
+            );
+        }
+    }
+    changes
+}
+
+/// The primary key as compared between schemas: key columns with their order
+fn primary_key(columns: &[SchemaColumn]) -> Vec<(String, String, i32, String)> {
+    columns
+        .iter()
+        .filter(|c| c.kind == "partition_key" || c.kind == "clustering")
+        .map(|c| {
+            (
+                c.name.clone(),
+                c.kind.clone(),
+                c.position,
+                c.clustering_order.clone(),
+            )
+        })
+        .collect()
+}
+
+fn diff_table(ks: &str, source: &TableDef, target: &TableDef) -> Vec<SchemaChange> {
+    let mut changes = Vec::new();
+    let table = qualified_name(ks, &source.name);
+
+    if primary_key(&source.columns) != primary_key(&target.columns) {
+        changes.push(
+            SchemaChange::new(ChangeKind::Changed, ObjectKind::Table, &source.name)
+                .detail("primary key differs")
+                .statement(format!(
+                    "-- {}: the primary key cannot be altered; the table must be recreated",
+                    source.name
+                )),
+        );
+    }
+
+    for column in &source.columns {
+        let name = format!("{}.{}", source.name, column.name);
+        match target.columns.iter().find(|c| c.name == column.name) {
+            None if column.kind == "regular" || column.kind == "static" => changes.push(
+                SchemaChange::new(ChangeKind::Added, ObjectKind::Column, name)
+                    .detail(column.data_type.clone())
+                    .statement(format!(
+                        "ALTER TABLE {} ADD {} {}{};",
+                        table,
+                        quote_identifier(&column.name),
+                        column.data_type,
+                        if column.kind == "static" {
+                            " static"
+                        } else {
+                            ""
+                        }
+                    )),
+            ),
+            // Key columns are covered by the primary key change above
+            None => {}
+            Some(existing) if existing.data_type != column.data_type => changes.push(
+                SchemaChange::new(ChangeKind::Changed, ObjectKind::Column, name)
+                    .detail(format!("{} → {}", existing.data_type, column.data_type))
+                    .statement(format!(
+                        "-- {}.{}: column type cannot be changed in place ({} → {})",
+                        source.name, column.name, existing.data_type, column.data_type
+                    )),
+            ),
+            Some(_) => {}
+        }
+    }
+    for column in &target.columns {
+        let is_key = column.kind == "partition_key" || column.kind == "clustering";
+        if !is_key && !source.columns.iter().any(|c| c.name == column.name) {
+            changes.push(
+                SchemaChange::new(
+                    ChangeKind::Removed,
+                    ObjectKind::Column,
+                    format!("{}.{}", source.name, column.name),
+                )
+                .detail(column.data_type.clone())
+                .statement(format!(
+                    "ALTER TABLE {} DROP {};",
+                    table,
+                    quote_identifier(&column.name)
+                )),
+            );
+        }
+    }
+
+    if let Some(change) = diff_options(
+        &source.name,
+        &format!("ALTER TABLE {}", table),
+        &source.options,
+        &target.options,
+    ) {
+        changes.push(change);
+    }
+    changes
+}
+
+fn diff_view(ks: &str, source: &ViewDef, target: &ViewDef) -> Vec<SchemaChange> {
+    let definition_changed = source.base_table != target.base_table
+        || source.where_clause != target.where_clause
+        || source.include_all_columns != target.include_all_columns
+        || source.columns != target.columns;
+    if definition_changed {
+        return vec![
+            SchemaChange::new(ChangeKind::Changed, ObjectKind::View, &source.name)
+                .detail("definition differs")
+                .statement(format!(
+                    "DROP MATERIALIZED VIEW {};",
+                    qualified_name(ks, &source.name)
+                ))
+                .statement(
+                    ViewDef {
+                        keyspace: ks.to_string(),
+                        ..source.clone()
+                    }
+                    .to_cql(),
+                ),
+        ];
+    }
+    diff_options(
+        &source.name,
+        &format!(
+            "ALTER MATERIALIZED VIEW {}",
+            qualified_name(ks, &source.name)
+        ),
+        &source.options,
+        &target.options,
+    )
+    .into_iter()
+    .collect()
+}
+
+/// Options set differently in the source. Options the target's Cassandra
+/// version does not report are skipped rather than treated as changes.
+fn diff_options(
+    name: &str,
+    alter: &str,
+    source: &TableOptions,
+    target: &TableOptions,
+) -> Option<SchemaChange> {
+    let statement = target.alter_cql(alter, source)?;
+    let detail: Vec<String> = target
+        .changes_to(source)
+        .iter()
+        .map(|(option, from, to)| format!("{}: {} → {}", option, from, to))
+        .collect();
+    Some(
+        SchemaChange::new(ChangeKind::Changed, ObjectKind::Options, name)
+            .detail(detail.join("; "))
+            .statement(statement),
+    )
+}
+
+/// Functions and aggregates are matched by signature, since overloads share a name
+trait Routine: Clone {
+    const KIND: ObjectKind;
+    const KEYWORD: &'static str;
+    fn signature(&self) -> String;
+    fn name(&self) -> &str;
+    fn argument_types(&self) -> &[String];
+    fn in_keyspace(&self, keyspace: &str) -> String;
+}
+
+impl Routine for FunctionDef {
+    const KIND: ObjectKind = ObjectKind::Function;
+    const KEYWORD: &'static str = "FUNCTION";
+    fn signature(&self) -> String {
+        FunctionDef::signature(self)
+    }
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn argument_types(&self) -> &[String] {
+        &self.argument_types
+    }
+    fn in_keyspace(&self, keyspace: &str) -> String {
+        FunctionDef {
+            keyspace: keyspace.to_string(),
+            ..self.clone()
+        }
+        .to_cql()
+    }
+}
+
+impl Routine for AggregateDef {
+    const KIND: ObjectKind = ObjectKind::Aggregate;
+    const KEYWORD: &'static str = "AGGREGATE";
+    fn signature(&self) -> String {
+        AggregateDef::signature(self)
+    }
+    fn name(&self) -> &str {
+        &self.name
+    }
+    fn argument_types(&self) -> &[String] {
+        &self.argument_types
+    }
+    fn in_keyspace(&self, keyspace: &str) -> String {
+        AggregateDef {
+            keyspace: keyspace.to_string(),
+            ..self.clone()
+        }
+        .to_cql()
+    }
+}
+
+fn diff_routines<R: Routine + PartialEq>(
+    ks: &str,
+    source: &[R],
+    target: &[R],
+    changes: &mut Vec<SchemaChange>,
+    drops: &mut Vec<SchemaChange>,
+) {
+    let create = format!("CREATE {} ", R::KEYWORD);
+    for routine in source {
+        let signature = routine.signature();
+        let cql = routine.in_keyspace(ks);
+        match target.iter().find(|r| r.signature() == signature) {
+            None => changes
+                .push(SchemaChange::new(ChangeKind::Added, R::KIND, signature).statement(cql)),
+            Some(existing) if existing.in_keyspace(ks) != cql => changes.push(
+                SchemaChange::new(ChangeKind::Changed, R::KIND, signature)
+                    .detail("definition differs")
+                    .statement(cql.replacen(
+                        &create,
+                        &format!("CREATE OR REPLACE {} ", R::KEYWORD),
+                        1,
+                    )),
+            ),
+            Some(_) => {}
+        }
+    }
+    for routine in target {
+        let signature = routine.signature();
+        if !source.iter().any(|r| r.signature() == signature) {
+            drops.insert(
+                0,
+                SchemaChange::new(ChangeKind::Removed, R::KIND, &signature).statement(format!(
+                    "DROP {} {}({});",
+                    R::KEYWORD,
+                    qualified_name(ks, routine.name()),
+                    routine.argument_types().join(", ")
+                )),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{column, keyspace, table};
+
+    fn users_table(keyspace: &str, extra: Vec<SchemaColumn>, gc_grace: i32) -> TableDef {
+        let mut columns = vec![
+            column("id", "uuid", "partition_key", 0),
+            column("email", "text", "regular", -1),
+        ];
+        columns.extend(extra);
+        let mut users = table(keyspace, "users", columns);
+        users.options.gc_grace_seconds = Some(gc_grace);
+        users
+    }
+
+    #[test]
+    fn test_identical_schemas_have_no_changes() {
+        let dev = keyspace("app", vec![users_table("app", Vec::new(), 864000)]);
+        let prod = keyspace(
+            "app_prod",
+            vec![users_table("app_prod", Vec::new(), 864000)],
+        );
+        assert!(diff_keyspaces(&dev, &prod).is_empty());
+    }
+
+    #[test]
+    fn test_table_changes_target_the_target_keyspace() {
+        let dev = keyspace(
+            "app",
+            vec![users_table(
+                "app",
+                vec![column("name", "text", "regular", -1)],
+                3600,
+            )],
+        );
+        let prod = keyspace(
+            "app_prod",
+            vec![
+                users_table(
+                    "app_prod",
+                    vec![column("legacy", "int", "regular", -1)],
+                    864000,
+                ),
+                TableDef {
+                    name: "old_events".to_string(),
+                    ..users_table("app_prod", Vec::new(), 864000)
+                },
+            ],
+        );
+
+        let changes = diff_keyspaces(&dev, &prod);
+        let summary: Vec<(ChangeKind, ObjectKind, &str)> = changes
+            .iter()
+            .map(|c| (c.kind, c.object, c.name.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (ChangeKind::Added, ObjectKind::Column, "users.name"),
+                (ChangeKind::Removed, ObjectKind::Column, "users.legacy"),
+                (ChangeKind::Changed, ObjectKind::Options, "users"),
+                (ChangeKind::Removed, ObjectKind::Table, "old_events"),
+            ]
+        );
+
+        let script = migration_script(&changes, false);
+        assert_eq!(
+            script,
+            "ALTER TABLE app_prod.users ADD name text;\n\nALTER TABLE app_prod.users WITH gc_grace_seconds = 3600;"
+        );
+        let with_drops = migration_script(&changes, true);
+        assert!(with_drops.contains("ALTER TABLE app_prod.users DROP legacy;"));
+        assert!(with_drops.ends_with("DROP TABLE app_prod.old_events;"));
+    }
+
+    #[test]
+    fn test_primary_key_change_is_reported_not_applied() {
+        let mut source_table = users_table("app", Vec::new(), 864000);
+        source_table
+            .columns
+            .push(column("created_at", "timestamp", "clustering", 0));
+        let dev = keyspace("app", vec![source_table]);
+        let prod = keyspace("app", vec![users_table("app", Vec::new(), 864000)]);
+
+        let changes = diff_keyspaces(&dev, &prod);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].object, ObjectKind::Table);
+        assert!(changes[0].statements[0].starts_with("--"));
+    }
+
+    #[test]
+    fn test_replication_difference_is_reported_not_applied() {
+        let dev = keyspace("app", Vec::new());
+        let mut prod = keyspace("app", Vec::new());
+        prod.keyspace
+            .replication
+            .insert("replication_factor".to_string(), "3".to_string());
+        prod.keyspace.durable_writes = false;
+
+        let changes = diff_keyspaces(&dev, &prod);
+        assert_eq!(changes.len(), 1);
+        assert!(changes[0].detail.contains("durable_writes false → true"));
+        assert_eq!(
+            migration_script(&changes, false),
+            "-- app: replication differs ({'class': 'SimpleStrategy', 'replication_factor': '3'} → {'class': 'SimpleStrategy', 'replication_factor': '1'}); review before altering\n\nALTER KEYSPACE app WITH durable_writes = true;"
+        );
+    }
+
+    #[test]
+    fn test_new_type_is_created_before_tables() {
+        let mut dev = keyspace("app", vec![users_table("app", Vec::new(), 864000)]);
+        dev.types.push(UserTypeDef {
+            keyspace: "app".to_string(),
+            name: "address".to_string(),
+            fields: vec![("street".to_string(), "text".to_string())],
+        });
+        dev.tables.push(TableDef {
+            name: "shops".to_string(),
+            ..users_table("app", Vec::new(), 864000)
+        });
+        let prod = keyspace("app", vec![users_table("app", Vec::new(), 864000)]);
+
+        let changes = diff_keyspaces(&dev, &prod);
+        assert_eq!(changes[0].object, ObjectKind::Type);
+        assert_eq!(changes[1].object, ObjectKind::Table);
+        assert!(changes[1].statements[0].starts_with("CREATE TABLE app.shops"));
+    }
+}
@@ -0,0 +1,506 @@
+//! Data model checks over keyspace schema.
+//!
+//! Each rule looks at one table (with its indexes, views and options) and
+//! reports access patterns or settings that tend to hurt in production.
+//! Rules are heuristics: findings explain the concern rather than assert a
+//! bug, and `Info` findings are often fine on purpose.
+
+use crate::cql_tokenizer::{tokenize, TokenKind};
+use crate::schema::{KeyspaceSchema, TableDef};
+
+/// Secondary indexes on one table above which writes pay noticeably
+const MAX_SECONDARY_INDEXES: usize = 3;
+
+/// Three days; shorter windows are easily outrun by repairs and hint replay
+const LOW_GC_GRACE_SECONDS: i32 = 3 * 86_400;
+
+/// A lint rule: findings for one table of a keyspace
+type Rule = fn(&KeyspaceSchema, &TableDef, &[String]) -> Vec<Finding>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+/// One rule violation on one table
+#[derive(Clone, Debug, PartialEq)]
+pub struct Finding {
+    /// Stable rule identifier, e.g. `low-gc-grace`
+    pub rule: &'static str,
+    pub severity: Severity,
+    pub table: String,
+    /// What was found on this table
+    pub message: String,
+    /// Why it matters and what to do instead
+    pub explanation: &'static str,
+}
+
+/// Findings for every table of `schema`, most severe first
+pub fn lint_keyspace(schema: &KeyspaceSchema, queries: &[String]) -> Vec<Finding> {
+    let mut findings: Vec<Finding> = schema
+        .tables
+        .iter()
+        .flat_map(|table| lint_table(schema, table, queries))
+        .collect();
+    findings.sort_by(|a, b| b.severity.cmp(&a.severity).then(a.table.cmp(&b.table)));
+    findings
+}
+
+/// Findings for one table, most severe first. `queries` are statements run
+/// against the cluster (e.g. the query history), used by access-pattern rules.
+pub fn lint_table(schema: &KeyspaceSchema, table: &TableDef, queries: &[String]) -> Vec<Finding> {
+    let rules: [Rule; 10] = [
+        collection_in_primary_key,
+        low_cardinality_partition_key,
+        collection_without_clustering,
+        unbounded_time_series,
+        unbounded_partition,
+        too_many_indexes,
+        allow_filtering_queries,
+        low_gc_grace,
+        ttl_without_time_window,
+        materialized_views,
+    ];
+    let mut findings: Vec<Finding> = rules
+        .iter()
+        .flat_map(|rule| rule(schema, table, queries))
+        .collect();
+    findings.sort_by_key(|f| std::cmp::Reverse(f.severity));
+    findings
+}
+
+fn finding(
+    rule: &'static str,
+    severity: Severity,
+    table: &TableDef,
+    message: String,
+    explanation: &'static str,
+) -> Finding {
+    Finding {
+        rule,
+        severity,
+        table: table.name.clone(),
+        message,
+        explanation,
+    }
+}
+
+// ── Type helpers ───────────────────────────────────────────────
+
+/// `list<..>`, `set<..>` or `map<..>`, frozen or not
+fn is_collection(data_type: &str) -> bool {
+    let inner = unfreeze(data_type);
+    ["list<", "set<", "map<"]
+        .iter()
+        .any(|prefix| inner.starts_with(prefix))
+}
+
+fn unfreeze(data_type: &str) -> &str {
+    data_type
+        .strip_prefix("frozen<")
+        .and_then(|t| t.strip_suffix('>'))
+        .unwrap_or(data_type)
+}
+
+fn is_temporal(data_type: &str) -> bool {
+    matches!(data_type, "timestamp" | "timeuuid" | "date" | "time")
+}
+
+/// Whether the partition key bounds partitions by time or an explicit bucket
+fn has_bucket(table: &TableDef) -> bool {
+    table.columns.iter().any(|c| {
+        c.kind == "partition_key" && (is_temporal(&c.data_type) || c.name.contains("bucket"))
+    })
+}
+
+// ── Rules ──────────────────────────────────────────────────────
+
+fn collection_in_primary_key(_: &KeyspaceSchema, table: &TableDef, _: &[String]) -> Vec<Finding> {
+    table
+        .columns
+        .iter()
+        .filter(|c| (c.kind == "partition_key" || c.kind == "clustering") && is_collection(&c.data_type))
+        .map(|c| {
+            finding(
+                "collection-in-primary-key",
+                Severity::Warning,
+                table,
+                format!("Key column {} is a collection ({})", c.name, c.data_type),
+                "Frozen collections in the primary key are compared as a whole blob: queries must supply the exact collection and clustering order follows serialized bytes. Model the elements as separate key columns instead.",
+            )
+        })
+        .collect()
+}
+
+fn low_cardinality_partition_key(
+    _: &KeyspaceSchema,
+    table: &TableDef,
+    _: &[String],
+) -> Vec<Finding> {
+    let partition: Vec<_> = table
+        .columns
+        .iter()
+        .filter(|c| c.kind == "partition_key")
+        .collect();
+    match partition.as_slice() {
+        [column] if matches!(column.data_type.as_str(), "boolean" | "tinyint") => vec![finding(
+            "low-cardinality-partition-key",
+            Severity::Warning,
+            table,
+            format!(
+                "Partition key {} ({}) can only take a handful of values",
+                column.name, column.data_type
+            ),
+            "All rows end up in a few huge partitions owned by a few replicas, which become hot spots and grow without bound. Add a higher-cardinality column (or a bucket) to the partition key.",
+        )],
+        _ => Vec::new(),
+    }
+}
+
+fn collection_without_clustering(
+    _: &KeyspaceSchema,
+    table: &TableDef,
+    _: &[String],
+) -> Vec<Finding> {
+    if table.columns.iter().any(|c| c.kind == "clustering") {
+        return Vec::new();
+    }
+    table
+        .columns
+        .iter()
+        .filter(|c| c.kind == "regular" && is_collection(&c.data_type) && !c.data_type.starts_with("frozen<"))
+        .map(|c| {
+            finding(
+                "collection-without-clustering",
+                Severity::Info,
+                table,
+                format!(
+                    "{} ({}) grows inside a single-row partition",
+                    c.name, c.data_type
+                ),
+                "Without a clustering key each partition is one row, so an ever-growing collection makes the row wide: it is read whole and every element carries its own tombstones when replaced. Prefer a clustering column with one row per element.",
+            )
+        })
+        .collect()
+}
+
+fn unbounded_time_series(_: &KeyspaceSchema, table: &TableDef, _: &[String]) -> Vec<Finding> {
+    let time_clustered = table
+        .columns
+        .iter()
+        .find(|c| c.kind == "clustering" && is_temporal(&c.data_type));
+    match time_clustered {
+        Some(column) if !has_bucket(table) => vec![finding(
+            "unbounded-time-series",
+            Severity::Info,
+            table,
+            format!(
+                "Rows are ordered by {} but the partition key has no time bucket",
+                column.name
+            ),
+            "A time series partitioned only by entity keeps growing for the entity's lifetime. Partitions beyond ~100 MB slow down reads, repairs and compaction. Add a time bucket (day, month) to the partition key.",
+        )],
+        _ => Vec::new(),
+    }
+}
+
+fn unbounded_partition(_: &KeyspaceSchema, table: &TableDef, _: &[String]) -> Vec<Finding> {
+    let clustering: Vec<&str> = table
+        .columns
+        .iter()
+        .filter(|c| c.kind == "clustering")
+        .map(|c| c.name.as_str())
+        .collect();
+    let partition: Vec<&str> = table
+        .columns
+        .iter()
+        .filter(|c| c.kind == "partition_key")
+        .map(|c| c.name.as_str())
+        .collect();
+    // Time-ordered partitions are covered by `unbounded_time_series`
+    let time_clustered = table
+        .columns
+        .iter()
+        .any(|c| c.kind == "clustering" && is_temporal(&c.data_type));
+    if clustering.is_empty() || partition.len() > 1 || time_clustered || has_bucket(table) {
+        return Vec::new();
+    }
+    vec![finding(
+        "unbounded-partition",
+        Severity::Info,
+        table,
+        format!(
+            "Each {} partition holds one row per {} with no upper bound",
+            partition.join(", "),
+            clustering.join(", ")
+        ),
+        "A partition keyed by a single entity keeps collecting rows for as long as the entity lives, so popular keys become wide partitions (beyond ~100 MB reads, repairs and compaction slow down). If rows per key are not naturally small, add a bucket column to the partition key.",
+    )]
+}
+
+fn too_many_indexes(schema: &KeyspaceSchema, table: &TableDef, _: &[String]) -> Vec<Finding> {
+    let indexes = schema
+        .indexes
+        .iter()
+        .filter(|i| i.table == table.name)
+        .count();
+    if indexes <= MAX_SECONDARY_INDEXES {
+        return Vec::new();
+    }
+    vec![finding(
+        "too-many-indexes",
+        Severity::Warning,
+        table,
+        format!("{} secondary indexes", indexes),
+        "Every write updates each index, and index queries without the partition key fan out to the whole cluster. Denormalize the main access patterns into query tables instead.",
+    )]
+}
+
+fn allow_filtering_queries(
+    schema: &KeyspaceSchema,
+    table: &TableDef,
+    queries: &[String],
+) -> Vec<Finding> {
+    let count = queries
+        .iter()
+        .filter(|q| {
+            filtered_table(q).is_some_and(|(keyspace, name)| {
+                name == table.name && keyspace.is_none_or(|ks| ks == schema.keyspace.name)
+            })
+        })
+        .count();
+    if count == 0 {
+        return Vec::new();
+    }
+    vec![finding(
+        "allow-filtering",
+        Severity::Warning,
+        table,
+        format!("{} recent quer{} rely on ALLOW FILTERING", count, if count == 1 { "y" } else { "ies" }),
+        "ALLOW FILTERING scans partitions and discards non-matching rows; cost grows with the data, not the result. Create a table keyed by the filtered columns for this access pattern.",
+    )]
+}
+
+fn low_gc_grace(_: &KeyspaceSchema, table: &TableDef, _: &[String]) -> Vec<Finding> {
+    match table.options.gc_grace_seconds {
+        Some(seconds) if seconds < LOW_GC_GRACE_SECONDS => vec![finding(
+            "low-gc-grace",
+            Severity::Warning,
+            table,
+            format!("gc_grace_seconds is {}", seconds),
+            "Tombstones are purged after gc_grace_seconds. A replica that was down longer than that, or not repaired within it, can bring deleted data back. Keep it above the repair interval (default 10 days).",
+        )],
+        _ => Vec::new(),
+    }
+}
+
+fn ttl_without_time_window(_: &KeyspaceSchema, table: &TableDef, _: &[String]) -> Vec<Finding> {
+    let ttl = table.options.default_time_to_live.unwrap_or(0);
+    let strategy = table
+        .options
+        .compaction
+        .get("class")
+        .map(String::as_str)
+        .unwrap_or("");
+    if ttl == 0
+        || strategy.ends_with("TimeWindowCompactionStrategy")
+        || strategy.ends_with("UnifiedCompactionStrategy")
+    {
+        return Vec::new();
+    }
+    vec![finding(
+        "ttl-without-time-window",
+        Severity::Info,
+        table,
+        format!(
+            "default_time_to_live is {} with {}",
+            ttl,
+            strategy.rsplit('.').next().unwrap_or(strategy)
+        ),
+        "Expiring data mixed across SSTables is only dropped when compaction happens to merge it. TimeWindowCompactionStrategy groups data by write time so whole SSTables expire at once.",
+    )]
+}
+
+fn materialized_views(schema: &KeyspaceSchema, table: &TableDef, _: &[String]) -> Vec<Finding> {
+    let views: Vec<&str> = schema
+        .views
+        .iter()
+        .filter(|v| v.base_table == table.name)
+        .map(|v| v.name.as_str())
+        .collect();
+    if views.is_empty() {
+        return Vec::new();
+    }
+    vec![finding(
+        "materialized-views",
+        Severity::Info,
+        table,
+        format!("Base table of {}", views.join(", ")),
+        "Materialized views are experimental: views can silently diverge from the base table and there is no built-in way to detect or repair it. Consider maintaining query tables from the application.",
+    )]
+}
+
+/// The table a `SELECT ... ALLOW FILTERING` reads, as `(keyspace, table)`
+fn filtered_table(query: &str) -> Option<(Option<String>, String)> {
+    let tokens = tokenize(query);
+    let words: Vec<&str> = tokens
+        .iter()
+        .filter(|t| !matches!(t.kind, TokenKind::Whitespace | TokenKind::Comment))
+        .map(|t| t.text.as_str())
+        .collect();
+    let filtering = words
+        .windows(2)
+        .any(|w| w[0].eq_ignore_ascii_case("ALLOW") && w[1].eq_ignore_ascii_case("FILTERING"));
+    if !filtering {
+        return None;
+    }
+
+    let from = words.iter().position(|w| w.eq_ignore_ascii_case("FROM"))?;
+    let name = |word: &str| match word.strip_prefix('"').and_then(|w| w.strip_suffix('"')) {
+        Some(quoted) => quoted.replace("\"\"", "\""),
+        None => word.to_ascii_lowercase(),
+    };
+    match words.get(from + 1..from + 4) {
+        Some([keyspace, ".", table]) => Some((Some(name(keyspace)), name(table))),
+        _ => Some((None, name(words.get(from + 1)?))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::cassandra::SchemaColumn;
+    use crate::schema::IndexDef;
+    use crate::test_fixtures::{self, column};
+
+    fn table(name: &str, columns: Vec<SchemaColumn>) -> TableDef {
+        test_fixtures::table("shop", name, columns)
+    }
+
+    fn keyspace(tables: Vec<TableDef>) -> KeyspaceSchema {
+        test_fixtures::keyspace("shop", tables)
+    }
+
+    fn rules(findings: &[Finding]) -> Vec<&'static str> {
+        findings.iter().map(|f| f.rule).collect()
+    }
+
+    #[test]
+    fn test_clean_table_has_no_findings() {
+        let orders = table(
+            "orders",
+            vec![
+                column("customer_id", "uuid", "partition_key", 0),
+                column("order_id", "timeuuid", "clustering", 0),
+                column("total", "decimal", "regular", -1),
+            ],
+        );
+        let schema = keyspace(vec![orders.clone()]);
+        // Time clustering without a bucket is the only (informational) finding
+        assert_eq!(
+            rules(&lint_table(&schema, &orders, &[])),
+            vec!["unbounded-time-series"]
+        );
+    }
+
+    #[test]
+    fn test_table_rules() {
+        let mut events = table(
+            "events",
+            vec![
+                column("active", "boolean", "partition_key", 0),
+                column("views", "int", "regular", -1),
+                column("tags", "set<text>", "regular", -1),
+            ],
+        );
+        events.options.gc_grace_seconds = Some(0);
+        let mut schema = keyspace(vec![events.clone()]);
+        for i in 0..4 {
+            schema.indexes.push(IndexDef {
+                keyspace: "shop".to_string(),
+                table: "events".to_string(),
+                name: format!("idx_{}", i),
+                kind: "COMPOSITES".to_string(),
+                options: BTreeMap::new(),
+            });
+        }
+
+        let findings = lint_table(&schema, &events, &[]);
+        assert_eq!(findings[0].severity, Severity::Warning);
+        let mut found = rules(&findings);
+        found.sort();
+        assert_eq!(
+            found,
+            vec![
+                "collection-without-clustering",
+                "low-cardinality-partition-key",
+                "low-gc-grace",
+                "too-many-indexes",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unbounded_partition() {
+        let followers = table(
+            "followers",
+            vec![
+                column("user_id", "uuid", "partition_key", 0),
+                column("follower_id", "uuid", "clustering", 0),
+            ],
+        );
+        let schema = keyspace(vec![followers.clone()]);
+        assert_eq!(
+            rules(&lint_table(&schema, &followers, &[])),
+            vec!["unbounded-partition"]
+        );
+
+        let mut bucketed = followers.clone();
+        bucketed
+            .columns
+            .push(column("bucket", "int", "partition_key", 1));
+        assert!(lint_table(&schema, &bucketed, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_allow_filtering_matches_table() {
+        let users = table("users", vec![column("id", "uuid", "partition_key", 0)]);
+        let schema = keyspace(vec![users.clone()]);
+        let queries = vec![
+            "SELECT * FROM users WHERE email = 'a' ALLOW FILTERING".to_string(),
+            "select * from shop.users where age > 3 allow filtering;".to_string(),
+            "SELECT * FROM other.users WHERE x = 1 ALLOW FILTERING".to_string(),
+            "SELECT * FROM users WHERE id = 1".to_string(),
+        ];
+        let findings = lint_table(&schema, &users, &queries);
+        assert_eq!(rules(&findings), vec!["allow-filtering"]);
+        assert!(findings[0].message.starts_with("2 recent queries"));
+    }
+
+    #[test]
+    fn test_frozen_collection_in_key() {
+        let t = table(
+            "by_tags",
+            vec![column("tags", "frozen<set<text>>", "partition_key", 0)],
+        );
+        let schema = keyspace(vec![t.clone()]);
+        assert_eq!(
+            rules(&lint_table(&schema, &t, &[])),
+            vec!["collection-in-primary-key"]
+        );
+    }
+}
@@ -0,0 +1,303 @@
+//! Finding keyspaces, tables, columns, type fields and indexes by name across
+//! every keyspace of a cluster, e.g. "which tables have a `tenant_id`".
+
+use crate::schema::{KeyspaceSchema, SchemaObject};
+
+/// Most hits returned for one search; broad terms match thousands of columns
+pub const MAX_HITS: usize = 200;
+
+/// What a search hit names
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HitKind {
+    Keyspace,
+    Table,
+    View,
+    Type,
+    Index,
+    Column,
+    Field,
+}
+
+impl HitKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            HitKind::Keyspace => "Keyspace",
+            HitKind::Table => "Table",
+            HitKind::View => "Materialized view",
+            HitKind::Type => "Type",
+            HitKind::Index => "Index",
+            HitKind::Column => "Column",
+            HitKind::Field => "Type field",
+        }
+    }
+}
+
+/// How closely a name matches the search term; lower sorts first
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Closeness {
+    Exact,
+    Prefix,
+    Contains,
+}
+
+fn closeness(name: &str, term: &str) -> Option<Closeness> {
+    let name = name.to_lowercase();
+    if name == term {
+        Some(Closeness::Exact)
+    } else if name.starts_with(term) {
+        Some(Closeness::Prefix)
+    } else if name.contains(term) {
+        Some(Closeness::Contains)
+    } else {
+        None
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchHit {
+    pub keyspace: String,
+    pub kind: HitKind,
+    /// Name shown for the hit, qualified by its table or type for columns
+    /// and fields, e.g. `orders.tenant_id`
+    pub label: String,
+    /// Column or field type, or the indexed table and target
+    pub detail: String,
+    /// Object the schema viewer opens; `None` for keyspaces
+    pub object: Option<SchemaObject>,
+    closeness: Closeness,
+}
+
+impl SearchHit {
+    /// The hit with its keyspace, e.g. `shop.orders.tenant_id`
+    pub fn path(&self) -> String {
+        match self.kind {
+            HitKind::Keyspace => self.keyspace.clone(),
+            _ => format!("{}.{}", self.keyspace, self.label),
+        }
+    }
+}
+
+/// Names in `schemas` containing `term`, case-insensitively. Exact matches
+/// come first, then prefixes, then the rest; at most [`MAX_HITS`].
+pub fn search<'a>(
+    schemas: impl IntoIterator<Item = &'a KeyspaceSchema>,
+    term: &str,
+) -> Vec<SearchHit> {
+    let term = term.trim().to_lowercase();
+    if term.is_empty() {
+        return Vec::new();
+    }
+
+    let mut hits = Vec::new();
+    let mut push = |keyspace: &str,
+                    kind: HitKind,
+                    name: &str,
+                    label: String,
+                    detail: String,
+                    object: Option<SchemaObject>| {
+        if let Some(closeness) = closeness(name, &term) {
+            hits.push(SearchHit {
+                keyspace: keyspace.to_string(),
+                kind,
+                label,
+                detail,
+                object,
+                closeness,
+            });
+        }
+    };
+
+    for schema in schemas {
+        let ks = schema.keyspace.name.as_str();
+        push(
+            ks,
+            HitKind::Keyspace,
+            ks,
+            ks.to_string(),
+            String::new(),
+            None,
+        );
+
+        for table in &schema.tables {
+            let object = SchemaObject::Table(table.name.clone());
+            push(
+                ks,
+                HitKind::Table,
+                &table.name,
+                table.name.clone(),
+                String::new(),
+                Some(object.clone()),
+            );
+            for column in &table.columns {
+                push(
+                    ks,
+                    HitKind::Column,
+                    &column.name,
+                    format!("{}.{}", table.name, column.name),
+                    column.data_type.clone(),
+                    Some(object.clone()),
+                );
+            }
+        }
+
+        for view in &schema.views {
+            let object = SchemaObject::View(view.name.clone());
+            push(
+                ks,
+                HitKind::View,
+                &view.name,
+                view.name.clone(),
+                format!("on {}", view.base_table),
+                Some(object.clone()),
+            );
+            for column in &view.columns {
+                push(
+                    ks,
+                    HitKind::Column,
+                    &column.name,
+                    format!("{}.{}", view.name, column.name),
+                    column.data_type.clone(),
+                    Some(object.clone()),
+                );
+            }
+        }
+
+        for user_type in &schema.types {
+            let object = SchemaObject::Type(user_type.name.clone());
+            push(
+                ks,
+                HitKind::Type,
+                &user_type.name,
+                user_type.name.clone(),
+                String::new(),
+                Some(object.clone()),
+            );
+            for (field, field_type) in &user_type.fields {
+                push(
+                    ks,
+                    HitKind::Field,
+                    field,
+                    format!("{}.{}", user_type.name, field),
+                    field_type.clone(),
+                    Some(object.clone()),
+                );
+            }
+        }
+
+        for index in &schema.indexes {
+            let target = index.options.get("target").cloned().unwrap_or_default();
+            push(
+                ks,
+                HitKind::Index,
+                &index.name,
+                index.name.clone(),
+                format!("on {} ({})", index.table, target),
+                Some(SchemaObject::Index(index.name.clone())),
+            );
+        }
+    }
+
+    hits.sort_by(|a, b| {
+        (a.closeness, a.kind, &a.keyspace, &a.label).cmp(&(
+            b.closeness,
+            b.kind,
+            &b.keyspace,
+            &b.label,
+        ))
+    });
+    hits.truncate(MAX_HITS);
+    hits
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::schema::{IndexDef, UserTypeDef};
+    use crate::test_fixtures::{self, column, table};
+
+    fn keyspace(name: &str) -> KeyspaceSchema {
+        let orders = table(
+            name,
+            "orders",
+            vec![
+                column("tenant_id", "uuid", "regular", -1),
+                column("order_tenant_id_hint", "text", "regular", -1),
+                column("total", "decimal", "regular", -1),
+            ],
+        );
+        let mut schema = test_fixtures::keyspace(name, vec![orders]);
+        schema.types.push(UserTypeDef {
+            keyspace: name.to_string(),
+            name: "tenant_ref".to_string(),
+            fields: vec![("id".to_string(), "uuid".to_string())],
+        });
+        schema.indexes.push(IndexDef {
+            keyspace: name.to_string(),
+            table: "orders".to_string(),
+            name: "orders_tenant_idx".to_string(),
+            kind: "COMPOSITES".to_string(),
+            options: BTreeMap::from([("target".to_string(), "tenant_id".to_string())]),
+        });
+        schema
+    }
+
+    #[test]
+    fn test_search_ranks_exact_matches_first() {
+        let schemas = [keyspace("shop"), keyspace("billing")];
+        let hits = search(&schemas, " Tenant_ID ");
+        let labels: Vec<(&str, &str)> = hits
+            .iter()
+            .map(|h| (h.keyspace.as_str(), h.label.as_str()))
+            .collect();
+        assert_eq!(
+            labels,
+            vec![
+                ("billing", "orders.tenant_id"),
+                ("shop", "orders.tenant_id"),
+                ("billing", "orders_tenant_idx"),
+                ("shop", "orders_tenant_idx"),
+                ("billing", "orders.order_tenant_id_hint"),
+                ("shop", "orders.order_tenant_id_hint"),
+            ]
+        );
+        assert_eq!(hits[0].kind, HitKind::Column);
+        assert_eq!(hits[0].detail, "uuid");
+        assert_eq!(
+            hits[0].object,
+            Some(SchemaObject::Table("orders".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_search_finds_types_fields_and_indexes() {
+        let schemas = [keyspace("shop")];
+        let kinds: Vec<HitKind> = search(&schemas, "tenant").iter().map(|h| h.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                HitKind::Type,
+                HitKind::Column,
+                HitKind::Index,
+                HitKind::Column
+            ]
+        );
+
+        let hits = search(&schemas, "id");
+        let field = hits.iter().find(|h| h.kind == HitKind::Field).unwrap();
+        assert_eq!(field.label, "tenant_ref.id");
+        assert_eq!(
+            field.object,
+            Some(SchemaObject::Type("tenant_ref".to_string()))
+        );
+
+        let index = &search(&schemas, "orders_tenant_idx")[0];
+        assert_eq!(index.detail, "on orders (tenant_id)");
+
+        assert_eq!(search(&schemas, "shop")[0].kind, HitKind::Keyspace);
+        assert_eq!(search(&schemas, "shop")[0].path(), "shop");
+        assert_eq!(field.path(), "shop.tenant_ref.id");
+        assert!(search(&schemas, "  ").is_empty());
+    }
+}
@@ -0,0 +1,284 @@
+//! Timestamped copies of a cluster's schema, kept per connection in the
+//! config directory, and the differences between two of them.
+//!
+//! Each snapshot is one JSON file under `snapshots/<connection id>/`, named
+//! after the UTC time it was taken. A snapshot identical to the latest one is
+//! not written again, so the history only grows when the schema changes.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::connection::CassandraConnection;
+use crate::error::{MagdaError, Result};
+use crate::schema::KeyspaceSchema;
+use crate::schema_diff::{diff_keyspaces, ChangeKind, SchemaChange};
+
+/// File name format of a snapshot, without the `.json` extension
+const FILE_TIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// The schema of every non-system keyspace of a cluster at one point in time
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SchemaSnapshot {
+    pub connection_id: Uuid,
+    pub connection_name: String,
+    pub taken_at: DateTime<Utc>,
+    pub keyspaces: Vec<KeyspaceSchema>,
+}
+
+/// A stored snapshot, listed without reading it
+#[derive(Clone, Debug, PartialEq)]
+pub struct SnapshotInfo {
+    pub path: PathBuf,
+    pub taken_at: DateTime<Utc>,
+}
+
+/// What happened to one keyspace between two snapshots
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyspaceChanges {
+    pub keyspace: String,
+    /// `Added` or `Removed` for whole keyspaces, `Changed` otherwise
+    pub kind: ChangeKind,
+    /// Object-level changes of a `Changed` keyspace
+    pub changes: Vec<SchemaChange>,
+}
+
+/// Directory holding the snapshots of every connection
+#[derive(Clone, Debug)]
+pub struct SnapshotStore {
+    root: PathBuf,
+}
+
+impl SnapshotStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// The store in the application's config directory
+    pub fn open() -> Option<Self> {
+        ProjectDirs::from("com", "magda", "Magda")
+            .map(|dirs| Self::new(dirs.config_dir().join("snapshots")))
+    }
+
+    fn connection_dir(&self, connection_id: Uuid) -> PathBuf {
+        self.root.join(connection_id.to_string())
+    }
+
+    /// Write `snapshot` unless it matches the latest one of its connection.
+    /// Returns the new file, or `None` when nothing changed.
+    pub fn save(&self, snapshot: &SchemaSnapshot) -> Result<Option<PathBuf>> {
+        if let Some(latest) = self.list(snapshot.connection_id).first() {
+            if self
+                .load(&latest.path)
+                .is_ok_and(|previous| previous.keyspaces == snapshot.keyspaces)
+            {
+                return Ok(None);
+            }
+        }
+
+        let dir = self.connection_dir(snapshot.connection_id);
+        fs::create_dir_all(&dir).map_err(|e| {
+            MagdaError::ConfigError(format!("Failed to create snapshot directory: {}", e))
+        })?;
+        let path = dir.join(format!(
+            "{}.json",
+            snapshot.taken_at.format(FILE_TIME_FORMAT)
+        ));
+        let content = serde_json::to_string(snapshot)
+            .map_err(|e| MagdaError::ConfigError(format!("Failed to serialize snapshot: {}", e)))?;
+        fs::write(&path, content)
+            .map_err(|e| MagdaError::ConfigError(format!("Failed to write snapshot: {}", e)))?;
+        tracing::debug!("Saved schema snapshot to {:?}", path);
+        Ok(Some(path))
+    }
+
+    /// Snapshots of a connection, newest first
+    pub fn list(&self, connection_id: Uuid) -> Vec<SnapshotInfo> {
+        let Ok(entries) = fs::read_dir(self.connection_dir(connection_id)) else {
+            return Vec::new();
+        };
+        let mut snapshots: Vec<SnapshotInfo> = entries
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                let stem = path
+                    .file_name()?
+                    .to_str()?
+                    .strip_suffix(".json")?
+                    .to_string();
+                let taken_at = NaiveDateTime::parse_from_str(&stem, FILE_TIME_FORMAT)
+                    .ok()?
+                    .and_utc();
+                Some(SnapshotInfo { path, taken_at })
+            })
+            .collect();
+        snapshots.sort_by_key(|s| std::cmp::Reverse(s.taken_at));
+        snapshots
+    }
+
+    pub fn load(&self, path: &Path) -> Result<SchemaSnapshot> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| MagdaError::ConfigError(format!("Failed to read snapshot: {}", e)))?;
+        serde_json::from_str(&content)
+            .map_err(|e| MagdaError::ConfigError(format!("Failed to parse snapshot: {}", e)))
+    }
+}
+
+/// Read the schema of `connection` and store it as a new snapshot
+pub async fn snapshot_connection(
+    store: &SnapshotStore,
+    connection: &CassandraConnection,
+) -> Result<Option<PathBuf>> {
+    let keyspaces = connection.describe_keyspaces(false).await?;
+    store.save(&SchemaSnapshot {
+        connection_id: connection.id,
+        connection_name: connection.config.name.clone(),
+        taken_at: Utc::now(),
+        keyspaces,
+    })
+}
+
+/// What changed from `older` to `newer`, keyspace by keyspace in name order.
+/// Object changes read as edits applied to `older`: `Added` objects exist
+/// only in `newer`.
+pub fn diff_snapshots(older: &SchemaSnapshot, newer: &SchemaSnapshot) -> Vec<KeyspaceChanges> {
+    let mut names: Vec<&str> = older
+        .keyspaces
+        .iter()
+        .chain(&newer.keyspaces)
+        .map(|k| k.keyspace.name.as_str())
+        .collect();
+    names.sort();
+    names.dedup();
+
+    let find = |snapshot: &'_ SchemaSnapshot, name: &str| {
+        snapshot
+            .keyspaces
+            .iter()
+            .find(|k| k.keyspace.name == name)
+            .cloned()
+    };
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let (kind, changes) = match (find(older, name), find(newer, name)) {
+                (Some(before), Some(after)) => {
+                    let changes = diff_keyspaces(&after, &before);
+                    if changes.is_empty() {
+                        return None;
+                    }
+                    (ChangeKind::Changed, changes)
+                }
+                (None, Some(_)) => (ChangeKind::Added, Vec::new()),
+                (Some(_), None) => (ChangeKind::Removed, Vec::new()),
+                (None, None) => return None,
+            };
+            Some(KeyspaceChanges {
+                keyspace: name.to_string(),
+                kind,
+                changes,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{self, column, table};
+
+    /// A keyspace of tables keyed by an `id` column
+    fn keyspace(name: &str, tables: &[&str]) -> KeyspaceSchema {
+        let tables = tables
+            .iter()
+            .map(|t| table(name, t, vec![column("id", "uuid", "partition_key", 0)]))
+            .collect();
+        test_fixtures::keyspace(name, tables)
+    }
+
+    fn snapshot(connection_id: Uuid, at: &str, keyspaces: Vec<KeyspaceSchema>) -> SchemaSnapshot {
+        SchemaSnapshot {
+            connection_id,
+            connection_name: "prod".to_string(),
+            taken_at: at.parse().unwrap(),
+            keyspaces,
+        }
+    }
+
+    #[test]
+    fn test_store_round_trip_and_dedup() {
+        let root = std::env::temp_dir().join(format!("magda-snapshots-{}", Uuid::new_v4()));
+        let store = SnapshotStore::new(&root);
+        let id = Uuid::new_v4();
+        assert!(store.list(id).is_empty());
+
+        let first = snapshot(
+            id,
+            "2026-10-01T08:00:00Z",
+            vec![keyspace("shop", &["orders"])],
+        );
+        let path = store
+            .save(&first)
+            .unwrap()
+            .expect("first snapshot is written");
+        assert_eq!(store.load(&path).unwrap(), first);
+
+        // Unchanged schema does not add to the history
+        let same = snapshot(id, "2026-10-02T08:00:00Z", first.keyspaces.clone());
+        assert_eq!(store.save(&same).unwrap(), None);
+
+        let changed = snapshot(
+            id,
+            "2026-10-03T08:00:00Z",
+            vec![keyspace("shop", &["orders", "carts"])],
+        );
+        assert!(store.save(&changed).unwrap().is_some());
+
+        let listed: Vec<DateTime<Utc>> = store.list(id).iter().map(|s| s.taken_at).collect();
+        assert_eq!(listed, vec![changed.taken_at, first.taken_at]);
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_diff_snapshots() {
+        let id = Uuid::new_v4();
+        let older = snapshot(
+            id,
+            "2026-10-01T08:00:00Z",
+            vec![
+                keyspace("legacy", &["events"]),
+                keyspace("shop", &["orders"]),
+                keyspace("users", &["profiles"]),
+            ],
+        );
+        let newer = snapshot(
+            id,
+            "2026-10-08T08:00:00Z",
+            vec![
+                keyspace("analytics", &[]),
+                keyspace("shop", &["orders", "carts"]),
+                keyspace("users", &["profiles"]),
+            ],
+        );
+
+        let diff = diff_snapshots(&older, &newer);
+        let summary: Vec<(&str, ChangeKind)> =
+            diff.iter().map(|k| (k.keyspace.as_str(), k.kind)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("analytics", ChangeKind::Added),
+                ("legacy", ChangeKind::Removed),
+                ("shop", ChangeKind::Changed),
+            ]
+        );
+        let shop = &diff[2].changes;
+        assert_eq!(shop.len(), 1);
+        assert_eq!(shop[0].kind, ChangeKind::Added);
+        assert_eq!(shop[0].name, "carts");
+    }
+}
@@ -1,9 +1,15 @@
+use dioxus::core::Task;
 use dioxus::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
 use uuid::Uuid;
 
+use crate::components::data_grid::QueryResult;
 use crate::connection::ConnectionManager;
+use crate::error::ErrorReport;
+use crate::roles::redact_passwords;
+use crate::schema::SchemaObject;
 
 /// Default number of rows per page in query results
 pub const DEFAULT_PAGE_SIZE: u32 = 100;
@@ -26,6 +32,34 @@ pub struct SavedQuery {
     pub query: String,
 }
 
+/// A query editor tab. Persisted between sessions; execution state lives in
+/// [`QueryExecution`] and is not persisted.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EditorTab {
+    pub id: Uuid,
+    pub title: String,
+    #[serde(default)]
+    pub query: String,
+    /// Connection this tab runs against. `None` follows the active connection.
+    #[serde(default)]
+    pub connection_id: Option<Uuid>,
+    #[serde(default)]
+    pub keyspace: Option<String>,
+}
+
+impl EditorTab {
+    /// Create an empty tab that follows the active connection
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            title: title.into(),
+            query: String::new(),
+            connection_id: None,
+            keyspace: None,
+        }
+    }
+}
+
 /// Global application state
 #[derive(Clone)]
 pub struct AppState {
@@ -35,14 +69,28 @@ pub struct AppState {
     pub sidebar_visible: Signal<bool>,
     pub theme: Signal<Theme>,
     pub selected_table: Signal<Option<String>>,
+    /// Keyspace browsed in the Data tab; `None` until resolved from the connection
+    pub data_keyspace: Signal<Option<String>>,
     pub pending_query: Signal<Option<String>>,
+    /// Keyspace object the schema viewer should reveal, set by other views
+    pub schema_target: Signal<Option<(String, SchemaObject)>>,
+    /// Bumped whenever cached schema is invalidated, so schema views reload
+    pub schema_version: Signal<u64>,
     /// Reactive connection status string for the statusbar.
     /// `None` means disconnected, `Some(description)` means connected.
     pub connection_status: Signal<Option<String>>,
     pub query_variables: Signal<Vec<QueryVariable>>,
     pub saved_queries: Signal<Vec<SavedQuery>>,
-    /// Current text in the query editor — persists across tab switches.
+    /// Text of the active editor tab — persists across workspace tab switches.
     pub query_text: Signal<String>,
+    /// Open query editor tabs, in display order
+    pub editor_tabs: Signal<Vec<EditorTab>>,
+    /// Id of the editor tab currently shown in the query editor
+    pub active_editor_tab: Signal<Uuid>,
+    /// Execution state per editor tab, keyed by tab id
+    pub tab_executions: Signal<HashMap<Uuid, QueryExecution>>,
+    /// Whether a multi-statement script stops at its first failing statement
+    pub stop_on_error: Signal<bool>,
     /// Transient status message shown in the status bar (connecting, errors, etc.).
     pub status_message: Signal<Option<StatusMessage>>,
     /// Console log entries (connection events, query results, errors)
@@ -60,6 +108,17 @@ impl Default for AppState {
 impl AppState {
     /// Create a new application state
     pub fn new() -> Self {
+        let (mut editor_tabs, active) = crate::config::load_editor_tabs();
+        if editor_tabs.is_empty() {
+            editor_tabs.push(EditorTab::new("Query 1"));
+        }
+        let active_tab = editor_tabs
+            .iter()
+            .find(|t| Some(t.id) == active)
+            .unwrap_or(&editor_tabs[0]);
+        let active_editor_tab = active_tab.id;
+        let query_text = active_tab.query.clone();
+
         Self {
             connection_manager: Arc::new(ConnectionManager::new()),
             query_history: Signal::new(Vec::new()),
@@ -67,11 +126,18 @@ impl AppState {
             sidebar_visible: Signal::new(true),
             theme: Signal::new(Theme::Dark),
             selected_table: Signal::new(None),
+            data_keyspace: Signal::new(None),
             pending_query: Signal::new(None),
+            schema_target: Signal::new(None),
+            schema_version: Signal::new(0),
             connection_status: Signal::new(None),
             query_variables: Signal::new(crate::config::load_variables()),
             saved_queries: Signal::new(crate::config::load_saved_queries()),
-            query_text: Signal::new(String::new()),
+            query_text: Signal::new(query_text),
+            editor_tabs: Signal::new(editor_tabs),
+            active_editor_tab: Signal::new(active_editor_tab),
+            tab_executions: Signal::new(HashMap::new()),
+            stop_on_error: Signal::new(true),
             status_message: Signal::new(None),
             console_log: Signal::new(Vec::new()),
             console_visible: Signal::new(false),
@@ -85,13 +151,7 @@ impl AppState {
         success: bool,
         execution_time_ms: u64,
     ) {
-        let item = QueryHistoryItem {
-            id: Uuid::new_v4(),
-            query,
-            success,
-            execution_time_ms,
-            executed_at: chrono::Utc::now(),
-        };
+        let item = QueryHistoryItem::new(&query, success, execution_time_ms);
 
         let mut history = query_history.write();
         history.push(item);
@@ -126,6 +186,29 @@ impl AppState {
         }
     }
 
+    /// Open `query` in a new editor tab and make it the active one
+    pub fn open_in_new_tab(
+        mut editor_tabs: Signal<Vec<EditorTab>>,
+        mut active_editor_tab: Signal<Uuid>,
+        mut query_text: Signal<String>,
+        mut tab: EditorTab,
+        query: String,
+    ) {
+        // Keep whatever is in the editor with the tab being left
+        let current = *active_editor_tab.peek();
+        let text = query_text.peek().clone();
+        if let Some(previous) = editor_tabs.write().iter_mut().find(|t| t.id == current) {
+            previous.query = text;
+        }
+
+        tab.query = query.clone();
+        let id = tab.id;
+        editor_tabs.write().push(tab);
+        active_editor_tab.set(id);
+        query_text.set(query);
+        crate::config::save_editor_tabs(&editor_tabs.peek(), id);
+    }
+
     /// Toggle sidebar visibility
     pub fn toggle_sidebar(mut sidebar_visible: Signal<bool>) {
         let current = *sidebar_visible.read();
@@ -153,14 +236,31 @@ pub struct QueryHistoryItem {
     pub executed_at: chrono::DateTime<chrono::Utc>,
 }
 
+impl QueryHistoryItem {
+    /// History entry for a statement run now. Role passwords are masked so
+    /// the History tab never shows them.
+    pub fn new(query: &str, success: bool, execution_time_ms: u64) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            query: redact_passwords(query).into_owned(),
+            success,
+            execution_time_ms,
+            executed_at: chrono::Utc::now(),
+        }
+    }
+}
+
 /// Active tab in the main workspace
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum ActiveTab {
     Query,
     Schema,
+    Compare,
     Data,
     History,
     Variables,
+    Metrics,
+    Security,
 }
 
 /// Application theme
@@ -257,10 +357,70 @@ pub enum ConsoleCategory {
     System,
 }
 
-/// Query execution state
+/// Query execution state of an editor tab
 #[derive(Clone, Debug, Default)]
 pub struct QueryExecution {
     pub is_running: bool,
     pub current_query: Option<String>,
     pub started_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Full result of the last successful run, paged client-side
+    pub result: Option<QueryResult>,
+    pub error: Option<ErrorReport>,
+    pub current_page: u32,
+    /// Handle of the in-flight query, used for cancellation
+    pub task: Option<Task>,
+    /// Statements of a multi-statement script, in order; empty for a
+    /// single statement
+    pub script: Vec<StatementRun>,
+    /// Script statement whose result or error is shown
+    pub selected_statement: Option<usize>,
+}
+
+/// Progress of one statement of a script
+#[derive(Clone, Debug, Default)]
+pub enum StatementState {
+    #[default]
+    Pending,
+    Running,
+    Done(QueryResult),
+    Failed(ErrorReport),
+    /// Not run: an earlier statement failed or the script was cancelled
+    Skipped,
+}
+
+/// One statement of a script run from an editor tab
+#[derive(Clone, Debug)]
+pub struct StatementRun {
+    pub statement: String,
+    pub state: StatementState,
+    pub execution_time_ms: u64,
+}
+
+impl StatementRun {
+    pub fn new(statement: String) -> Self {
+        Self {
+            statement,
+            state: StatementState::Pending,
+            execution_time_ms: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_masks_passwords() {
+        let item = QueryHistoryItem::new(
+            "CREATE ROLE alice WITH PASSWORD = 'secret' AND LOGIN = true",
+            true,
+            3,
+        );
+        assert!(!item.query.contains("secret"));
+        assert_eq!(
+            item.query,
+            "CREATE ROLE alice WITH PASSWORD = '********' AND LOGIN = true"
+        );
+    }
 }
@@ -0,0 +1,466 @@
+//! Table designs built in the create-table form, their validation and the
+//! `CREATE TABLE` they generate.
+//!
+//! Column types are read with the CQL parser and checked for what Cassandra
+//! would reject: unknown or misspelled types, user types missing from the
+//! keyspace, non-frozen nested collections and types that cannot be part of
+//! a key.
+
+use crate::cassandra::SchemaColumn;
+use crate::cql_parser::{self, CqlType};
+use crate::schema::{TableDef, TableOptions, UserTypeDef};
+
+/// Collection and tuple templates offered by the type picker
+pub const COLLECTION_TYPES: &[&str] = &[
+    "list<text>",
+    "set<text>",
+    "map<text, text>",
+    "tuple<text, int>",
+    "frozen<list<text>>",
+    "vector<float, 3>",
+];
+
+/// What a column is in the table
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColumnRole {
+    PartitionKey,
+    ClusteringAsc,
+    ClusteringDesc,
+    Static,
+    Regular,
+}
+
+impl ColumnRole {
+    pub const ALL: [ColumnRole; 5] = [
+        ColumnRole::PartitionKey,
+        ColumnRole::ClusteringAsc,
+        ColumnRole::ClusteringDesc,
+        ColumnRole::Static,
+        ColumnRole::Regular,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ColumnRole::PartitionKey => "Partition key",
+            ColumnRole::ClusteringAsc => "Clustering ASC",
+            ColumnRole::ClusteringDesc => "Clustering DESC",
+            ColumnRole::Static => "Static",
+            ColumnRole::Regular => "Regular",
+        }
+    }
+
+    /// Stable value for form fields
+    pub fn key(&self) -> &'static str {
+        match self {
+            ColumnRole::PartitionKey => "partition_key",
+            ColumnRole::ClusteringAsc => "clustering_asc",
+            ColumnRole::ClusteringDesc => "clustering_desc",
+            ColumnRole::Static => "static",
+            ColumnRole::Regular => "regular",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|r| r.key() == key)
+    }
+
+    fn is_key(&self) -> bool {
+        matches!(
+            self,
+            ColumnRole::PartitionKey | ColumnRole::ClusteringAsc | ColumnRole::ClusteringDesc
+        )
+    }
+
+    fn is_clustering(&self) -> bool {
+        matches!(self, ColumnRole::ClusteringAsc | ColumnRole::ClusteringDesc)
+    }
+}
+
+/// One column of a table design
+#[derive(Clone, Debug, PartialEq)]
+pub struct DesignColumn {
+    pub name: String,
+    pub data_type: String,
+    pub role: ColumnRole,
+}
+
+impl DesignColumn {
+    pub fn new(name: &str, data_type: &str, role: ColumnRole) -> Self {
+        Self {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            role,
+        }
+    }
+}
+
+/// A table being designed. Key columns keep the order they have in
+/// `columns`: the first partition key column is the first key component.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TableDesign {
+    pub keyspace: String,
+    pub name: String,
+    pub columns: Vec<DesignColumn>,
+    pub options: TableOptions,
+}
+
+/// Whether `ty` has cells of its own: a non-frozen collection or user type
+fn is_multi_cell(ty: &CqlType) -> bool {
+    matches!(
+        ty,
+        CqlType::List(_) | CqlType::Set(_) | CqlType::Map(..) | CqlType::UserDefined(_)
+    )
+}
+
+/// Types directly nested in `ty`
+fn children(ty: &CqlType) -> Vec<&CqlType> {
+    match ty {
+        CqlType::Map(key, value) => vec![key, value],
+        CqlType::Tuple(items) => items.iter().collect(),
+        CqlType::List(inner)
+        | CqlType::Set(inner)
+        | CqlType::Vector(inner, _)
+        | CqlType::Frozen(inner) => vec![inner],
+        CqlType::Native(_) | CqlType::UserDefined(_) | CqlType::Custom(_) => Vec::new(),
+    }
+}
+
+/// `ty` and every type nested in it
+fn walk(ty: &CqlType) -> Vec<&CqlType> {
+    let mut all = vec![ty];
+    all.extend(children(ty).into_iter().flat_map(walk));
+    all
+}
+
+/// Element types of a collection or vector, which must be frozen when
+/// they are collections or user types themselves
+fn elements(ty: &CqlType) -> Vec<&CqlType> {
+    match ty {
+        CqlType::List(_) | CqlType::Set(_) | CqlType::Vector(..) | CqlType::Map(..) => children(ty),
+        _ => Vec::new(),
+    }
+}
+
+/// Whether `ty` is the native type `name`
+fn is_native(ty: &CqlType, name: &str) -> bool {
+    matches!(ty, CqlType::Native(native) if native == name)
+}
+
+/// Problems nested anywhere in `ty`. Inside `frozen<>` everything is frozen,
+/// so nested collections only need an explicit `frozen<>` outside of it.
+fn nested_type_problems(
+    column: &str,
+    ty: &CqlType,
+    in_frozen: bool,
+    types: &[UserTypeDef],
+    problems: &mut Vec<String>,
+) {
+    match ty {
+        CqlType::UserDefined(user) if !types.iter().any(|t| t.name == user.name.as_str()) => {
+            problems.push(format!(
+                "Column {}: unknown type `{}` (not a native type or a user type of this keyspace)",
+                column, user
+            ));
+        }
+        CqlType::Frozen(inner) if matches!(inner.as_ref(), CqlType::Native(_)) => {
+            problems.push(format!(
+                "Column {}: frozen<> only applies to collections, tuples and user types",
+                column
+            ));
+        }
+        _ => {}
+    }
+    let elements = elements(ty);
+    if !in_frozen && elements.iter().any(|e| is_multi_cell(e)) {
+        problems.push(format!(
+            "Column {}: collections and user types nested in a collection must be frozen",
+            column
+        ));
+    }
+    if elements.iter().any(|e| is_native(e, "counter")) {
+        problems.push(format!(
+            "Column {}: collections cannot hold counters",
+            column
+        ));
+    }
+
+    let in_frozen = in_frozen || matches!(ty, CqlType::Frozen(_));
+    for child in children(ty) {
+        nested_type_problems(column, child, in_frozen, types, problems);
+    }
+}
+
+/// Problems in one column's type, given the keyspace's user types
+fn type_problems(column: &DesignColumn, ty: &CqlType, types: &[UserTypeDef]) -> Vec<String> {
+    let mut problems = Vec::new();
+    let name = &column.name;
+    nested_type_problems(name, ty, false, types, &mut problems);
+
+    if column.role.is_key() {
+        if is_multi_cell(ty) {
+            problems.push(format!(
+                "Column {}: key columns cannot be non-frozen collections or user types",
+                name
+            ));
+        }
+        for (native, what) in [("counter", "counters"), ("duration", "durations")] {
+            if walk(ty).iter().any(|t| is_native(t, native)) {
+                problems.push(format!("Column {}: key columns cannot hold {}", name, what));
+            }
+        }
+    }
+    problems
+}
+
+impl TableDesign {
+    pub fn new(keyspace: &str) -> Self {
+        Self {
+            keyspace: keyspace.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Why the design cannot be created, in form order; empty when it can.
+    /// `types` are the user types of the keyspace.
+    pub fn validate(&self, types: &[UserTypeDef]) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.name.trim().is_empty() {
+            problems.push("The table needs a name".to_string());
+        }
+        if self.columns.is_empty() {
+            problems.push("The table needs at least one column".to_string());
+        }
+
+        let mut seen: Vec<&str> = Vec::new();
+        for column in &self.columns {
+            let name = column.name.trim();
+            if name.is_empty() {
+                problems.push("Every column needs a name".to_string());
+                continue;
+            }
+            if seen.contains(&name) {
+                problems.push(format!("Column {} is defined more than once", name));
+            } else {
+                seen.push(name);
+            }
+            if column.data_type.trim().is_empty() {
+                problems.push(format!("Column {} needs a type", name));
+                continue;
+            }
+            match cql_parser::parse_type(&column.data_type) {
+                Ok(ty) => problems.extend(type_problems(column, &ty, types)),
+                Err(e) => problems.push(format!("Column {}: {}", name, e)),
+            }
+        }
+
+        if !self.columns.is_empty()
+            && !self
+                .columns
+                .iter()
+                .any(|c| c.role == ColumnRole::PartitionKey)
+        {
+            problems.push("Choose at least one partition key column".to_string());
+        }
+        if self.columns.iter().any(|c| c.role == ColumnRole::Static)
+            && !self.columns.iter().any(|c| c.role.is_clustering())
+        {
+            problems.push("Static columns need at least one clustering column".to_string());
+        }
+
+        let values: Vec<&DesignColumn> = self.columns.iter().filter(|c| !c.role.is_key()).collect();
+        let counters = values
+            .iter()
+            .filter(|c| c.data_type.trim().eq_ignore_ascii_case("counter"))
+            .count();
+        if counters > 0 && counters < values.len() {
+            problems.push(
+                "Counter tables can only have counter columns outside the primary key".to_string(),
+            );
+        }
+        problems
+    }
+
+    /// The design as a table definition, with key positions in column order
+    pub fn to_table_def(&self) -> TableDef {
+        let mut partition = 0;
+        let mut clustering = 0;
+        let columns = self
+            .columns
+            .iter()
+            .map(|c| {
+                let (kind, position, order) = match c.role {
+                    ColumnRole::PartitionKey => {
+                        partition += 1;
+                        ("partition_key", partition - 1, "none")
+                    }
+                    ColumnRole::ClusteringAsc | ColumnRole::ClusteringDesc => {
+                        clustering += 1;
+                        let order = if c.role == ColumnRole::ClusteringDesc {
+                            "desc"
+                        } else {
+                            "asc"
+                        };
+                        ("clustering", clustering - 1, order)
+                    }
+                    ColumnRole::Static => ("static", -1, "none"),
+                    ColumnRole::Regular => ("regular", -1, "none"),
+                };
+                SchemaColumn {
+                    name: c.name.trim().to_string(),
+                    data_type: c.data_type.trim().to_string(),
+                    kind: kind.to_string(),
+                    position,
+                    clustering_order: order.to_string(),
+                }
+            })
+            .collect();
+        TableDef {
+            keyspace: self.keyspace.clone(),
+            name: self.name.trim().to_string(),
+            columns,
+            options: self.options.clone(),
+        }
+    }
+
+    /// `CREATE TABLE` for the design
+    pub fn to_cql(&self) -> String {
+        self.to_table_def().to_cql()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn design(columns: Vec<DesignColumn>) -> TableDesign {
+        TableDesign {
+            name: "events".to_string(),
+            columns,
+            ..TableDesign::new("shop")
+        }
+    }
+
+    fn address() -> UserTypeDef {
+        UserTypeDef {
+            keyspace: "shop".to_string(),
+            name: "address".to_string(),
+            fields: vec![("city".to_string(), "text".to_string())],
+        }
+    }
+
+    #[test]
+    fn test_valid_design_generates_create_table() {
+        let mut events = design(vec![
+            DesignColumn::new("device_id", "uuid", ColumnRole::PartitionKey),
+            DesignColumn::new("day", "date", ColumnRole::PartitionKey),
+            DesignColumn::new("at", "timestamp", ColumnRole::ClusteringDesc),
+            DesignColumn::new("owner", "text", ColumnRole::Static),
+            DesignColumn::new("location", "frozen<address>", ColumnRole::Regular),
+            DesignColumn::new("tags", "set<text>", ColumnRole::Regular),
+        ]);
+        events.options.gc_grace_seconds = Some(86400);
+        assert!(events.validate(&[address()]).is_empty());
+        assert_eq!(
+            events.to_cql(),
+            "CREATE TABLE shop.events (\n    device_id uuid,\n    day date,\n    at timestamp,\n    owner text static,\n    location frozen<address>,\n    tags set<text>,\n    PRIMARY KEY ((device_id, day), at)\n) WITH CLUSTERING ORDER BY (at DESC)\n    AND comment = ''\n    AND gc_grace_seconds = 86400;"
+        );
+    }
+
+    #[test]
+    fn test_duplicate_and_missing_names() {
+        let problems = design(vec![
+            DesignColumn::new("id", "uuid", ColumnRole::PartitionKey),
+            DesignColumn::new("id", "text", ColumnRole::Regular),
+            DesignColumn::new(" ", "text", ColumnRole::Regular),
+        ])
+        .validate(&[]);
+        assert_eq!(
+            problems,
+            vec![
+                "Column id is defined more than once",
+                "Every column needs a name"
+            ]
+        );
+
+        let problems = TableDesign::new("shop").validate(&[]);
+        assert_eq!(
+            problems,
+            vec![
+                "The table needs a name",
+                "The table needs at least one column"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_key_types() {
+        let problems = design(vec![
+            DesignColumn::new("tags", "set<text>", ColumnRole::PartitionKey),
+            DesignColumn::new("hits", "counter", ColumnRole::ClusteringAsc),
+            DesignColumn::new("took", "duration", ColumnRole::ClusteringAsc),
+            DesignColumn::new(
+                "frozen_tags",
+                "frozen<set<text>>",
+                ColumnRole::ClusteringAsc,
+            ),
+        ])
+        .validate(&[]);
+        assert_eq!(
+            problems,
+            vec![
+                "Column tags: key columns cannot be non-frozen collections or user types",
+                "Column hits: key columns cannot hold counters",
+                "Column took: key columns cannot hold durations",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_type_rules() {
+        let problems = design(vec![
+            DesignColumn::new("id", "uuid", ColumnRole::PartitionKey),
+            DesignColumn::new("home", "adress", ColumnRole::Regular),
+            DesignColumn::new("nested", "list<list<int>>", ColumnRole::Regular),
+            DesignColumn::new("deep", "frozen<list<list<int>>>", ColumnRole::Regular),
+            DesignColumn::new("name", "frozen<text>", ColumnRole::Regular),
+            DesignColumn::new("bad", "map<text", ColumnRole::Regular),
+        ])
+        .validate(&[address()]);
+        assert_eq!(
+            problems,
+            vec![
+                "Column home: unknown type `adress` (not a native type or a user type of this keyspace)",
+                "Column nested: collections and user types nested in a collection must be frozen",
+                "Column name: frozen<> only applies to collections, tuples and user types",
+                "Column bad: Expected `,`, found end of statement",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_table_structure_rules() {
+        let problems = design(vec![
+            DesignColumn::new("id", "uuid", ColumnRole::Regular),
+            DesignColumn::new("owner", "text", ColumnRole::Static),
+        ])
+        .validate(&[]);
+        assert_eq!(
+            problems,
+            vec![
+                "Choose at least one partition key column",
+                "Static columns need at least one clustering column",
+            ]
+        );
+
+        let problems = design(vec![
+            DesignColumn::new("page", "text", ColumnRole::PartitionKey),
+            DesignColumn::new("views", "counter", ColumnRole::Regular),
+            DesignColumn::new("title", "text", ColumnRole::Regular),
+        ])
+        .validate(&[]);
+        assert_eq!(
+            problems,
+            vec!["Counter tables can only have counter columns outside the primary key"]
+        );
+    }
+}
@@ -0,0 +1,54 @@
+//! Schema values shared by unit tests.
+
+use std::collections::BTreeMap;
+
+use crate::cassandra::SchemaColumn;
+use crate::schema::{KeyspaceDef, KeyspaceSchema, TableDef, TableOptions};
+
+/// A column; clustering columns sort ascending
+pub fn column(name: &str, data_type: &str, kind: &str, position: i32) -> SchemaColumn {
+    SchemaColumn {
+        name: name.to_string(),
+        data_type: data_type.to_string(),
+        kind: kind.to_string(),
+        position,
+        clustering_order: if kind == "clustering" { "asc" } else { "none" }.to_string(),
+    }
+}
+
+/// `column` sorted descending
+pub fn descending(mut column: SchemaColumn) -> SchemaColumn {
+    column.clustering_order = "desc".to_string();
+    column
+}
+
+/// A table with default options
+pub fn table(keyspace: &str, name: &str, columns: Vec<SchemaColumn>) -> TableDef {
+    TableDef {
+        keyspace: keyspace.to_string(),
+        name: name.to_string(),
+        columns,
+        options: TableOptions::default(),
+    }
+}
+
+/// A keyspace replicated once with `SimpleStrategy`, holding only `tables`
+pub fn keyspace(name: &str, tables: Vec<TableDef>) -> KeyspaceSchema {
+    KeyspaceSchema {
+        keyspace: KeyspaceDef {
+            name: name.to_string(),
+            replication: BTreeMap::from([
+                ("class".to_string(), "SimpleStrategy".to_string()),
+                ("replication_factor".to_string(), "1".to_string()),
+            ]),
+            durable_writes: true,
+        },
+        types: Vec::new(),
+        tables,
+        indexes: Vec::new(),
+        views: Vec::new(),
+        functions: Vec::new(),
+        aggregates: Vec::new(),
+        triggers: Vec::new(),
+    }
+}
@@ -0,0 +1,391 @@
+//! Virtual tables of Cassandra 4.0+ (`system_views`, `system_virtual_schema`).
+//!
+//! Virtual tables are computed by each node on read and are not described in
+//! `system_schema`; they are listed from `system_virtual_schema` instead.
+//! They are small, local to the coordinator and do not support paging the
+//! way regular tables do, so they are read whole. Well-known tables come with
+//! a [`VirtualView`] selecting the columns worth looking at first.
+
+use serde::{Deserialize, Serialize};
+
+use crate::schema::{qualified_name, quote_identifier};
+
+/// Keyspaces holding virtual tables
+pub const VIRTUAL_KEYSPACES: [&str; 2] = ["system_views", "system_virtual_schema"];
+
+/// Whether `keyspace` holds virtual tables rather than stored ones
+pub fn is_virtual_keyspace(keyspace: &str) -> bool {
+    VIRTUAL_KEYSPACES.contains(&keyspace)
+}
+
+/// A virtual table as listed in `system_virtual_schema`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct VirtualTable {
+    pub keyspace: String,
+    pub name: String,
+    pub comment: String,
+    /// Column names in schema order (partition key, clustering, regular)
+    pub columns: Vec<String>,
+}
+
+impl VirtualTable {
+    /// The pre-built view for this table, if it has one
+    pub fn view(&self) -> Option<&'static VirtualView> {
+        view_for(&self.keyspace, &self.name)
+    }
+
+    /// Title shown in the sidebar and above the grid
+    pub fn title(&self) -> String {
+        self.view()
+            .map(|v| v.title.to_string())
+            .unwrap_or_else(|| self.name.clone())
+    }
+
+    /// What the table shows: the view's description, else the table comment
+    pub fn description(&self) -> &str {
+        self.view()
+            .map(|v| v.description)
+            .unwrap_or(self.comment.as_str())
+    }
+
+    /// Statement reading the whole table. Uses the view's columns that this
+    /// node actually has, as they vary between Cassandra releases, and falls
+    /// back to `*` when none of them do.
+    pub fn browse_query(&self) -> String {
+        let columns: Vec<String> = self
+            .view()
+            .map(|v| {
+                v.columns
+                    .iter()
+                    .filter(|c| self.columns.iter().any(|have| have == *c))
+                    .map(|c| quote_identifier(c))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let columns = if columns.is_empty() {
+            "*".to_string()
+        } else {
+            columns.join(", ")
+        };
+        format!(
+            "SELECT {} FROM {}",
+            columns,
+            qualified_name(&self.keyspace, &self.name)
+        )
+    }
+}
+
+/// A friendly presentation of a well-known virtual table
+#[derive(Clone, Debug, PartialEq)]
+pub struct VirtualView {
+    pub keyspace: &'static str,
+    pub table: &'static str,
+    pub title: &'static str,
+    pub description: &'static str,
+    /// Columns to select, in display order
+    pub columns: &'static [&'static str],
+}
+
+const LATENCY_COLUMNS: &[&str] = &[
+    "keyspace_name",
+    "table_name",
+    "count",
+    "per_second",
+    "p50th_ms",
+    "p99th_ms",
+    "max_ms",
+];
+
+const HISTOGRAM_COLUMNS: &[&str] = &[
+    "keyspace_name",
+    "table_name",
+    "count",
+    "p50th",
+    "p99th",
+    "max",
+];
+
+const VIEWS: &[VirtualView] = &[
+    VirtualView {
+        keyspace: "system_views",
+        table: "settings",
+        title: "Settings",
+        description: "Configuration in effect on this node",
+        columns: &["name", "value"],
+    },
+    VirtualView {
+        keyspace: "system_views",
+        table: "system_properties",
+        title: "System properties",
+        description: "JVM and Cassandra system properties of this node",
+        columns: &["name", "value"],
+    },
+    VirtualView {
+        keyspace: "system_views",
+        table: "clients",
+        title: "Connected clients",
+        description: "Client connections to this node, with driver and request counts",
+        columns: &[
+            "address",
+            "port",
+            "username",
+            "driver_name",
+            "driver_version",
+            "protocol_version",
+            "connection_stage",
+            "ssl_enabled",
+            "request_count",
+        ],
+    },
+    VirtualView {
+        keyspace: "system_views",
+        table: "thread_pools",
+        title: "Thread pools",
+        description: "Active, pending and blocked tasks per thread pool",
+        columns: &[
+            "name",
+            "active_tasks",
+            "active_tasks_limit",
+            "pending_tasks",
+            "completed_tasks",
+            "blocked_tasks",
+            "blocked_tasks_all_time",
+        ],
+    },
+    VirtualView {
+        keyspace: "system_views",
+        table: "caches",
+        title: "Caches",
+        description: "Size and hit ratio of the key, row, counter and chunk caches",
+        columns: &[
+            "name",
+            "entry_count",
+            "size_bytes",
+            "capacity_bytes",
+            "hit_ratio",
+            "request_count",
+            "recent_hit_rate_per_second",
+        ],
+    },
+    VirtualView {
+        keyspace: "system_views",
+        table: "sstable_tasks",
+        title: "SSTable tasks",
+        description: "Compactions, cleanups and other SSTable operations in progress",
+        columns: &[
+            "keyspace_name",
+            "table_name",
+            "kind",
+            "progress",
+            "total",
+            "unit",
+            "task_id",
+        ],
+    },
+    VirtualView {
+        keyspace: "system_views",
+        table: "queries",
+        title: "Running queries",
+        description: "Requests currently executing on this node",
+        columns: &["thread_id", "queued_micros", "running_micros", "task"],
+    },
+    VirtualView {
+        keyspace: "system_views",
+        table: "gossip_info",
+        title: "Gossip",
+        description: "Cluster members as seen through gossip",
+        columns: &[
+            "address",
+            "port",
+            "dc",
+            "rack",
+            "status",
+            "load",
+            "release_version",
+            "schema_version",
+            "host_id",
+        ],
+    },
+    VirtualView {
+        keyspace: "system_views",
+        table: "pending_hints",
+        title: "Pending hints",
+        description: "Hints waiting to be delivered, per target node",
+        columns: &[
+            "address", "port", "dc", "rack", "status", "files", "oldest", "newest",
+        ],
+    },
+    VirtualView {
+        keyspace: "system_views",
+        table: "disk_usage",
+        title: "Disk usage",
+        description: "Space used on disk per table, in MiB",
+        columns: &["keyspace_name", "table_name", "mebibytes"],
+    },
+    VirtualView {
+        keyspace: "system_views",
+        table: "max_partition_size",
+        title: "Largest partitions",
+        description: "Size of the largest partition per table, in MiB",
+        columns: &["keyspace_name", "table_name", "mebibytes"],
+    },
+    VirtualView {
+        keyspace: "system_views",
+        table: "local_read_latency",
+        title: "Local read latency",
+        description: "Replica read latency per table",
+        columns: LATENCY_COLUMNS,
+    },
+    VirtualView {
+        keyspace: "system_views",
+        table: "local_write_latency",
+        title: "Local write latency",
+        description: "Replica write latency per table",
+        columns: LATENCY_COLUMNS,
+    },
+    VirtualView {
+        keyspace: "system_views",
+        table: "local_scan_latency",
+        title: "Local scan latency",
+        description: "Replica range scan latency per table",
+        columns: LATENCY_COLUMNS,
+    },
+    VirtualView {
+        keyspace: "system_views",
+        table: "coordinator_read_latency",
+        title: "Coordinator read latency",
+        description: "Read latency seen by this node as coordinator, per table",
+        columns: LATENCY_COLUMNS,
+    },
+    VirtualView {
+        keyspace: "system_views",
+        table: "coordinator_write_latency",
+        title: "Coordinator write latency",
+        description: "Write latency seen by this node as coordinator, per table",
+        columns: LATENCY_COLUMNS,
+    },
+    VirtualView {
+        keyspace: "system_views",
+        table: "coordinator_scan_latency",
+        title: "Coordinator scan latency",
+        description: "Range scan latency seen by this node as coordinator, per table",
+        columns: LATENCY_COLUMNS,
+    },
+    VirtualView {
+        keyspace: "system_views",
+        table: "rows_per_read",
+        title: "Rows per read",
+        description: "Rows read per query, per table",
+        columns: HISTOGRAM_COLUMNS,
+    },
+    VirtualView {
+        keyspace: "system_views",
+        table: "tombstones_per_read",
+        title: "Tombstones per read",
+        description: "Tombstones scanned per query, per table",
+        columns: HISTOGRAM_COLUMNS,
+    },
+    VirtualView {
+        keyspace: "system_virtual_schema",
+        table: "keyspaces",
+        title: "Virtual keyspaces",
+        description: "Keyspaces holding virtual tables",
+        columns: &["keyspace_name"],
+    },
+    VirtualView {
+        keyspace: "system_virtual_schema",
+        table: "tables",
+        title: "Virtual tables",
+        description: "Every virtual table and what it shows",
+        columns: &["keyspace_name", "table_name", "comment"],
+    },
+    VirtualView {
+        keyspace: "system_virtual_schema",
+        table: "columns",
+        title: "Virtual table columns",
+        description: "Columns of every virtual table",
+        columns: &[
+            "keyspace_name",
+            "table_name",
+            "column_name",
+            "type",
+            "kind",
+            "position",
+        ],
+    },
+];
+
+/// The pre-built view of `keyspace.table`, if it is a well-known table
+pub fn view_for(keyspace: &str, table: &str) -> Option<&'static VirtualView> {
+    VIEWS
+        .iter()
+        .find(|v| v.keyspace == keyspace && v.table == table)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(keyspace: &str, name: &str, columns: &[&str]) -> VirtualTable {
+        VirtualTable {
+            keyspace: keyspace.to_string(),
+            name: name.to_string(),
+            comment: "from the server".to_string(),
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_virtual_keyspaces() {
+        assert!(is_virtual_keyspace("system_views"));
+        assert!(is_virtual_keyspace("system_virtual_schema"));
+        assert!(!is_virtual_keyspace("system_schema"));
+        assert!(!is_virtual_keyspace("shop"));
+    }
+
+    #[test]
+    fn test_browse_query_uses_available_view_columns() {
+        // 4.0 has no active_tasks_limit
+        let pools = table(
+            "system_views",
+            "thread_pools",
+            &[
+                "name",
+                "active_tasks",
+                "blocked_tasks",
+                "blocked_tasks_all_time",
+                "completed_tasks",
+                "pending_tasks",
+            ],
+        );
+        assert_eq!(pools.title(), "Thread pools");
+        assert_eq!(
+            pools.browse_query(),
+            "SELECT name, active_tasks, pending_tasks, completed_tasks, blocked_tasks, \
+             blocked_tasks_all_time FROM system_views.thread_pools"
+        );
+
+        let unknown = table("system_views", "streaming", &["id", "status"]);
+        assert!(unknown.view().is_none());
+        assert_eq!(unknown.title(), "streaming");
+        assert_eq!(unknown.description(), "from the server");
+        assert_eq!(
+            unknown.browse_query(),
+            "SELECT * FROM system_views.streaming"
+        );
+
+        let renamed = table("system_views", "caches", &["cache_name"]);
+        assert_eq!(renamed.browse_query(), "SELECT * FROM system_views.caches");
+    }
+
+    #[test]
+    fn test_view_columns_are_unique() {
+        for view in VIEWS {
+            let mut columns = view.columns.to_vec();
+            columns.sort();
+            columns.dedup();
+            assert_eq!(columns.len(), view.columns.len(), "{}", view.table);
+            assert!(is_virtual_keyspace(view.keyspace));
+        }
+    }
+}